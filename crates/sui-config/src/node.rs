@@ -9,6 +9,7 @@ use crate::verifier_signing_config::VerifierSigningConfig;
 use crate::Config;
 use anyhow::Result;
 use consensus_config::Parameters as ConsensusParameters;
+use move_core_types::language_storage::StructTag;
 use narwhal_config::Parameters as NarwhalParameters;
 use once_cell::sync::OnceCell;
 use rand::rngs::OsRng;
@@ -28,6 +29,7 @@ use sui_types::crypto::KeypairTraits;
 use sui_types::crypto::NetworkKeyPair;
 use sui_types::crypto::SuiKeyPair;
 use sui_types::messages_checkpoint::CheckpointSequenceNumber;
+use sui_types::sui_serde::SuiStructTag;
 use sui_types::supported_protocol_versions::{Chain, SupportedProtocolVersions};
 use sui_types::traffic_control::{PolicyConfig, RemoteFirewallConfig};
 
@@ -206,6 +208,12 @@ pub struct NodeConfig {
     /// By default, write stall is enabled on validators but not on fullnodes.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub enable_db_write_stall: Option<bool>,
+
+    /// If set, the fullnode still executes every checkpoint, but only retains the historical
+    /// versions and the owner and dynamic field indexes of objects matching this config.
+    /// Ignored on validators.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub selective_retention_config: Option<SelectiveRetentionConfig>,
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -656,6 +664,31 @@ impl Default for CheckpointExecutorConfig {
     }
 }
 
+/// Configures a fullnode that only keeps the data of a subset of objects. All checkpoints are
+/// still executed and verified against their contents, so the live version of every object is
+/// kept, since it may be an input of a later transaction. Old versions of objects that do not
+/// match are pruned aggressively by the objects pruner, regardless of
+/// `AuthorityStorePruningConfig::num_epochs_to_retain`, while old versions of objects that
+/// match are never pruned.
+#[serde_as]
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct SelectiveRetentionConfig {
+    /// Retain objects owned by these addresses. An object ID can be used to retain the
+    /// dynamic fields and other children of that object.
+    #[serde(default)]
+    pub owners: Vec<SuiAddress>,
+    /// Retain packages with these IDs, and objects whose type is defined in them. Types are
+    /// defined in the package at its original ID, before any upgrades.
+    #[serde(default)]
+    pub packages: Vec<ObjectID>,
+    /// Retain objects of these types, e.g. `0x2::coin::Coin`, which matches all coin types,
+    /// or `0x2::coin::Coin<0x2::sui::SUI>`.
+    #[serde(default)]
+    #[serde_as(as = "Vec<SuiStructTag>")]
+    pub types: Vec<StructTag>,
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(rename_all = "kebab-case")]
 pub struct AuthorityStorePruningConfig {
@@ -696,6 +729,22 @@ pub struct AuthorityStorePruningConfig {
     pub killswitch_tombstone_pruning: bool,
     #[serde(default = "default_smoothing", skip_serializing_if = "is_true")]
     pub smooth: bool,
+    /// Objects matching one of these rules are kept forever by the objects pruner, while other
+    /// objects are retained for `num_epochs_to_retain` epochs. The rules only apply when
+    /// `num_epochs_to_retain` enables the objects pruner, i.e. is not `u64::MAX`, and are
+    /// ignored on validators.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub retention_rules: Vec<ObjectRetentionRule>,
+}
+
+/// A set of objects whose history the objects pruner keeps forever.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct ObjectRetentionRule {
+    /// Name of the rule, used to label its metrics
+    pub name: String,
+    #[serde(flatten)]
+    pub objects: SelectiveRetentionConfig,
 }
 
 fn default_num_latest_epoch_dbs_to_retain() -> usize {
@@ -735,6 +784,7 @@ impl Default for AuthorityStorePruningConfig {
             num_epochs_to_retain_for_checkpoints: if cfg!(msim) { Some(2) } else { None },
             killswitch_tombstone_pruning: false,
             smooth: true,
+            retention_rules: vec![],
         }
     }
 }
@@ -788,6 +838,11 @@ pub struct DBCheckpointConfig {
     pub perform_index_db_checkpoints_at_epoch_end: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub prune_and_compact_before_upload: Option<bool>,
+    /// If true, db checkpoints are uploaded incrementally: SST files already uploaded by an
+    /// earlier db checkpoint are not uploaded again, and each db checkpoint is described by a
+    /// manifest of the files it is made of. Defaults to false.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub incremental_upload: Option<bool>,
 }
 
 #[derive(Debug, Clone)]
@@ -1141,8 +1196,9 @@ mod tests {
     use rand::{rngs::StdRng, SeedableRng};
     use sui_keys::keypair_file::{write_authority_keypair_to_file, write_keypair_to_file};
     use sui_types::crypto::{get_key_pair_from_rng, AuthorityKeyPair, NetworkKeyPair, SuiKeyPair};
+    use sui_types::parse_sui_struct_tag;
 
-    use super::Genesis;
+    use super::{AuthorityStorePruningConfig, Genesis};
     use crate::NodeConfig;
 
     #[test]
@@ -1162,6 +1218,22 @@ mod tests {
         let _template: NodeConfig = serde_yaml::from_str(TEMPLATE).unwrap();
     }
 
+    #[test]
+    fn retention_rule_types() {
+        const RULES: &str = "retention-rules:\n  - name: coins\n    types: [\"0x2::coin::Coin\"]\n";
+        let config: AuthorityStorePruningConfig = serde_yaml::from_str(RULES).unwrap();
+        let coin = parse_sui_struct_tag("0x2::coin::Coin").unwrap();
+        assert_eq!(config.retention_rules[0].objects.types, vec![coin.clone()]);
+
+        let s = serde_yaml::to_string(&config).unwrap();
+        let loaded_config: AuthorityStorePruningConfig = serde_yaml::from_str(&s).unwrap();
+        assert_eq!(loaded_config.retention_rules[0].objects.types, vec![coin]);
+
+        // invalid types are rejected when the config is loaded
+        let invalid = RULES.replace("0x2::coin::Coin", "not a type");
+        assert!(serde_yaml::from_str::<AuthorityStorePruningConfig>(&invalid).is_err());
+    }
+
     #[test]
     fn load_key_pairs_to_node_config() {
         let protocol_key_pair: AuthorityKeyPair =
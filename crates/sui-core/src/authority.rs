@@ -142,13 +142,14 @@ use crate::authority::authority_store_pruner::{
 };
 use crate::authority::epoch_start_configuration::EpochStartConfigTrait;
 use crate::authority::epoch_start_configuration::EpochStartConfiguration;
+use crate::authority::retention_filter::RetentionFilter;
 use crate::checkpoints::CheckpointStore;
 use crate::epoch::committee_store::CommitteeStore;
 use crate::execution_cache::{
     CheckpointCache, ExecutionCacheCommit, ExecutionCacheReconfigAPI, ExecutionCacheWrite,
     ObjectCacheRead, StateSyncAPI,
 };
-use crate::execution_driver::execution_process;
+use crate::execution_driver::{execution_process, InflightExecutions};
 use crate::metrics::LatencyObserver;
 use crate::metrics::RateTracker;
 use crate::module_cache_metrics::ResolverMetrics;
@@ -209,6 +210,7 @@ pub mod authority_store_pruner;
 pub mod authority_store_tables;
 pub mod authority_store_types;
 pub mod epoch_start_configuration;
+pub mod retention_filter;
 pub mod shared_object_congestion_tracker;
 pub mod shared_object_version_manager;
 #[cfg(any(test, feature = "test-utils"))]
@@ -820,6 +822,12 @@ pub struct AuthorityState {
     /// Current overload status in this authority. Updated periodically.
     pub overload_info: AuthorityOverloadInfo,
 
+    /// Certificates currently being executed by the execution driver.
+    pub execution_driver_inflight: InflightExecutions,
+
+    /// Objects whose history and indexes are kept, when running as a selective fullnode.
+    retention_filter: Option<Arc<RetentionFilter>>,
+
     pub validator_tx_finalizer: Option<Arc<ValidatorTxFinalizer<NetworkAuthorityClient>>>,
 }
 
@@ -1913,6 +1921,7 @@ impl AuthorityState {
                 )?,
                 object_changes,
                 balance_changes,
+                raw_written_objects: Vec::new(),
             },
             written_with_kind,
             effects,
@@ -2417,6 +2426,10 @@ impl AuthorityState {
                         || panic!("tx_digest={:?}, error processing object owner index, written does not contain object {:?}", tx_digest, id)
                     );
                     assert_eq!(new_object.version(), oref.1, "tx_digest={:?} error processing object owner index, object {:?} from written has mismatched version. Actual: {}, expected: {}", tx_digest, id, new_object.version(), oref.1);
+                    if !self.is_retained(new_object) {
+                        // The old index entry, if any, is still removed above.
+                        continue;
+                    }
 
                     let type_ = new_object
                         .type_()
@@ -2440,6 +2453,9 @@ impl AuthorityState {
                         || panic!("tx_digest={:?}, error processing object owner index, written does not contain object {:?}", tx_digest, id)
                     );
                     assert_eq!(new_object.version(), oref.1, "tx_digest={:?} error processing object owner index, object {:?} from written has mismatched version. Actual: {}, expected: {}", tx_digest, id, new_object.version(), oref.1);
+                    if !self.is_retained(new_object) {
+                        continue;
+                    }
 
                     let Some(df_info) = self
                         .try_create_dynamic_field_info(new_object, written, layout_resolver.as_mut())
@@ -2466,6 +2482,13 @@ impl AuthorityState {
         })
     }
 
+    /// Returns false if this is a selective fullnode and the object is not one it retains.
+    fn is_retained(&self, object: &Object) -> bool {
+        self.retention_filter
+            .as_ref()
+            .map_or(true, |filter| filter.matches(object))
+    }
+
     fn try_create_dynamic_field_info(
         &self,
         o: &Object,
@@ -2865,17 +2888,27 @@ impl AuthorityState {
             epoch_store.get_parent_path(),
             &config.authority_store_pruning_config,
         );
+        let is_validator = epoch_store.committee().authority_exists(&name);
+        let retention_filter = match &config.selective_retention_config {
+            Some(_) if is_validator => {
+                warn!("Ignoring selective retention config on a validator");
+                None
+            }
+            Some(retention_config) => Some(Arc::new(RetentionFilter::new(retention_config))),
+            None => None,
+        };
         let _pruner = AuthorityStorePruner::new(
             store.perpetual_tables.clone(),
             checkpoint_store.clone(),
             rest_index.clone(),
             store.objects_lock_table.clone(),
             config.authority_store_pruning_config.clone(),
-            epoch_store.committee().authority_exists(&name),
+            is_validator,
             epoch_store.epoch_start_state().epoch_duration_ms(),
             prometheus_registry,
             indirect_objects_threshold,
             archive_readers,
+            retention_filter.clone(),
         );
         let input_loader =
             TransactionInputLoader::new(execution_cache_trait_pointers.object_cache_reader.clone());
@@ -2900,6 +2933,8 @@ impl AuthorityState {
             db_checkpoint_config: db_checkpoint_config.clone(),
             config,
             overload_info: AuthorityOverloadInfo::default(),
+            execution_driver_inflight: InflightExecutions::default(),
+            retention_filter,
             validator_tx_finalizer,
         });
 
@@ -1806,6 +1806,7 @@ impl AuthorityStore {
             AuthorityStorePruningMetrics::new_for_test(),
             usize::MAX,
             EPOCH_DURATION_MS_FOR_TESTING,
+            None,
         )
         .await;
         let _ = AuthorityStorePruner::compact(&self.perpetual_tables);
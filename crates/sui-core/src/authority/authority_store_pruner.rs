@@ -2,14 +2,15 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::authority::authority_store_types::{ObjectContentDigest, StoreData, StoreObject};
+use crate::authority::retention_filter::RetentionFilter;
 use crate::checkpoints::{CheckpointStore, CheckpointWatermark};
 use crate::rest_index::RestIndexStore;
 use anyhow::anyhow;
 use mysten_metrics::{monitored_scope, spawn_monitored_task};
 use once_cell::sync::Lazy;
 use prometheus::{
-    register_int_counter_with_registry, register_int_gauge_with_registry, IntCounter, IntGauge,
-    Registry,
+    register_int_counter_vec_with_registry, register_int_counter_with_registry,
+    register_int_gauge_with_registry, IntCounter, IntCounterVec, IntGauge, Registry,
 };
 use std::cmp::{max, min};
 use std::collections::{BTreeSet, HashMap};
@@ -63,6 +64,8 @@ pub struct AuthorityStorePruningMetrics {
     pub last_pruned_checkpoint: IntGauge,
     pub num_pruned_objects: IntCounter,
     pub num_pruned_tombstones: IntCounter,
+    pub num_retained_objects: IntCounterVec,
+    pub num_retained_tombstones: IntCounterVec,
     pub last_pruned_effects_checkpoint: IntGauge,
     pub num_epochs_to_retain_for_objects: IntGauge,
     pub num_epochs_to_retain_for_checkpoints: IntGauge,
@@ -89,6 +92,20 @@ impl AuthorityStorePruningMetrics {
                 registry
             )
             .unwrap(),
+            num_retained_objects: register_int_counter_vec_with_registry!(
+                "num_retained_objects",
+                "Number of object versions eligible for pruning that were retained, grouped by retention rule",
+                &["rule"],
+                registry
+            )
+            .unwrap(),
+            num_retained_tombstones: register_int_counter_vec_with_registry!(
+                "num_retained_tombstones",
+                "Number of tombstones eligible for pruning that were retained, grouped by retention rule",
+                &["rule"],
+                registry
+            )
+            .unwrap(),
             last_pruned_effects_checkpoint: register_int_gauge_with_registry!(
                 "last_pruned_effects_checkpoint",
                 "Last pruned effects checkpoint",
@@ -132,6 +149,7 @@ impl AuthorityStorePruner {
         metrics: Arc<AuthorityStorePruningMetrics>,
         indirect_objects_threshold: usize,
         enable_pruning_tombstones: bool,
+        retention_filter: Option<&RetentionFilter>,
     ) -> anyhow::Result<()> {
         let _scope = monitored_scope("ObjectsLivePruner");
         let mut wb = perpetual_db.objects.batch();
@@ -152,6 +170,35 @@ impl AuthorityStorePruner {
             }
         }
 
+        if let Some(retention_filter) = retention_filter {
+            let retained = Self::retained_objects(
+                perpetual_db,
+                retention_filter,
+                &live_object_keys_to_prune,
+                &object_tombstones_to_prune,
+            )?;
+            live_object_keys_to_prune.retain(|key| match retained.get(&key.0) {
+                Some(rule) => {
+                    metrics
+                        .num_retained_objects
+                        .with_label_values(&[*rule])
+                        .inc();
+                    false
+                }
+                None => true,
+            });
+            object_tombstones_to_prune.retain(|key| match retained.get(&key.0) {
+                Some(rule) => {
+                    metrics
+                        .num_retained_tombstones
+                        .with_label_values(&[*rule])
+                        .inc();
+                    false
+                }
+                None => true,
+            });
+        }
+
         metrics
             .num_pruned_objects
             .inc_by(live_object_keys_to_prune.len() as u64);
@@ -230,6 +277,56 @@ impl AuthorityStorePruner {
         Ok(())
     }
 
+    /// Returns the IDs of objects that have a version about to be pruned that matches the
+    /// retention filter, with the name of the rule that matched. None of the versions of these
+    /// objects are pruned. Only the versions about to be pruned are checked, or for a tombstone
+    /// the versions before it, so the old versions of an object that only matches in its live
+    /// version, e.g. after being transferred to a retained owner, are still pruned.
+    fn retained_objects<'a>(
+        perpetual_db: &Arc<AuthorityPerpetualTables>,
+        retention_filter: &'a RetentionFilter,
+        live_object_keys_to_prune: &[ObjectKey],
+        object_tombstones_to_prune: &[ObjectKey],
+    ) -> anyhow::Result<HashMap<ObjectID, &'a str>> {
+        let mut retained = HashMap::new();
+        for (key, object) in live_object_keys_to_prune.iter().zip(
+            perpetual_db
+                .objects
+                .multi_get(live_object_keys_to_prune.iter())?,
+        ) {
+            if retained.contains_key(&key.0) {
+                continue;
+            }
+            let Some(object) = object else {
+                continue;
+            };
+            if let Some(object) = perpetual_db.object(key, object)? {
+                if let Some(rule) = retention_filter.matching_rule(&object) {
+                    retained.insert(key.0, rule);
+                }
+            }
+        }
+        // the tombstone itself has no content, so check the versions of the object before it
+        for ObjectKey(object_id, seq_number) in object_tombstones_to_prune {
+            if retained.contains_key(object_id) {
+                continue;
+            }
+            for result in perpetual_db.objects.safe_iter_with_bounds(
+                Some(ObjectKey(*object_id, VersionNumber::MIN)),
+                Some(ObjectKey(*object_id, *seq_number)),
+            ) {
+                let (key, object) = result?;
+                if let Some(object) = perpetual_db.object(&key, object)? {
+                    if let Some(rule) = retention_filter.matching_rule(&object) {
+                        retained.insert(*object_id, rule);
+                        break;
+                    }
+                }
+            }
+        }
+        Ok(retained)
+    }
+
     fn prune_checkpoints(
         perpetual_db: &Arc<AuthorityPerpetualTables>,
         checkpoint_db: &Arc<CheckpointStore>,
@@ -318,6 +415,7 @@ impl AuthorityStorePruner {
         metrics: Arc<AuthorityStorePruningMetrics>,
         indirect_objects_threshold: usize,
         epoch_duration_ms: u64,
+        retention_filter: Option<&RetentionFilter>,
     ) -> anyhow::Result<()> {
         let _scope = monitored_scope("PruneObjectsForEligibleEpochs");
         let (mut max_eligible_checkpoint_number, epoch_id) = checkpoint_store
@@ -347,6 +445,7 @@ impl AuthorityStorePruner {
             config,
             metrics.clone(),
             indirect_objects_threshold,
+            retention_filter,
         )
         .await
     }
@@ -407,6 +506,7 @@ impl AuthorityStorePruner {
             config,
             metrics.clone(),
             indirect_objects_threshold,
+            None,
         )
         .await
     }
@@ -424,6 +524,7 @@ impl AuthorityStorePruner {
         config: AuthorityStorePruningConfig,
         metrics: Arc<AuthorityStorePruningMetrics>,
         indirect_objects_threshold: usize,
+        retention_filter: Option<&RetentionFilter>,
     ) -> anyhow::Result<()> {
         let _scope = monitored_scope("PruneForEligibleEpochs");
 
@@ -486,6 +587,7 @@ impl AuthorityStorePruner {
                             metrics.clone(),
                             indirect_objects_threshold,
                             !config.killswitch_tombstone_pruning,
+                            retention_filter,
                         )
                         .await?
                     }
@@ -519,6 +621,7 @@ impl AuthorityStorePruner {
                         metrics.clone(),
                         indirect_objects_threshold,
                         !config.killswitch_tombstone_pruning,
+                        retention_filter,
                     )
                     .await?
                 }
@@ -628,6 +731,7 @@ impl AuthorityStorePruner {
         metrics: Arc<AuthorityStorePruningMetrics>,
         indirect_objects_threshold: usize,
         archive_readers: ArchiveReaderBalancer,
+        retention_filter: Option<Arc<RetentionFilter>>,
     ) -> Sender<()> {
         let (sender, mut recv) = tokio::sync::oneshot::channel();
         debug!(
@@ -685,7 +789,7 @@ impl AuthorityStorePruner {
             loop {
                 tokio::select! {
                     _ = objects_prune_interval.tick(), if config.num_epochs_to_retain != u64::MAX => {
-                        if let Err(err) = Self::prune_objects_for_eligible_epochs(&perpetual_db, &checkpoint_store, rest_index.as_deref(), &objects_lock_table, config.clone(), metrics.clone(), indirect_objects_threshold, epoch_duration_ms).await {
+                        if let Err(err) = Self::prune_objects_for_eligible_epochs(&perpetual_db, &checkpoint_store, rest_index.as_deref(), &objects_lock_table, config.clone(), metrics.clone(), indirect_objects_threshold, epoch_duration_ms, retention_filter.as_deref()).await {
                             error!("Failed to prune objects: {:?}", err);
                         }
                     },
@@ -712,8 +816,23 @@ impl AuthorityStorePruner {
         registry: &Registry,
         indirect_objects_threshold: usize,
         archive_readers: ArchiveReaderBalancer,
+        selective_retention_filter: Option<Arc<RetentionFilter>>,
     ) -> Self {
-        if pruning_config.num_epochs_to_retain > 0 && pruning_config.num_epochs_to_retain < u64::MAX
+        if is_validator && !pruning_config.retention_rules.is_empty() {
+            warn!("Ignoring object retention rules on a validator");
+            pruning_config.retention_rules.clear();
+        }
+        let retention_filter = RetentionFilter::for_pruning(
+            selective_retention_filter.as_deref(),
+            &pruning_config.retention_rules,
+        )
+        .map(Arc::new);
+        if selective_retention_filter.is_some() {
+            // objects that are not retained don't need their history, only the live version
+            info!("Selective retention is enabled, using aggressive pruner for objects");
+            pruning_config.num_epochs_to_retain = 0;
+        } else if pruning_config.num_epochs_to_retain > 0
+            && pruning_config.num_epochs_to_retain < u64::MAX
         {
             warn!("Using objects pruner with num_epochs_to_retain = {} can lead to performance issues", pruning_config.num_epochs_to_retain);
             if is_validator {
@@ -734,6 +853,7 @@ impl AuthorityStorePruner {
                 AuthorityStorePruningMetrics::new(registry),
                 indirect_objects_threshold,
                 archive_readers,
+                retention_filter,
             ),
         }
     }
@@ -760,9 +880,11 @@ mod tests {
         get_store_object_pair, ObjectContentDigest, StoreData, StoreObject, StoreObjectPair,
         StoreObjectWrapper,
     };
+    use crate::authority::retention_filter::{RetentionFilter, SELECTIVE_RETENTION_RULE};
     use prometheus::Registry;
+    use sui_config::node::{ObjectRetentionRule, SelectiveRetentionConfig};
     use sui_storage::mutex_table::RwLockTable;
-    use sui_types::base_types::ObjectDigest;
+    use sui_types::base_types::{ObjectDigest, SuiAddress};
     use sui_types::effects::TransactionEffects;
     use sui_types::effects::TransactionEffectsAPI;
     use sui_types::{
@@ -925,6 +1047,7 @@ mod tests {
                 metrics,
                 indirect_object_threshold,
                 true,
+                None,
             )
             .await
             .unwrap();
@@ -989,6 +1112,177 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_pruning_with_retention_filter() {
+        let path = tempfile::tempdir().unwrap().into_path();
+        let retained_owner = SuiAddress::random_for_testing_only();
+        let other_owner = SuiAddress::random_for_testing_only();
+        let (mut to_keep, mut to_delete) = (HashSet::new(), vec![]);
+        {
+            let db = Arc::new(AuthorityPerpetualTables::open(&path, None));
+            let mut batch = db.objects.batch();
+            for (i, id) in ObjectID::in_range(ObjectID::ZERO, 100)
+                .unwrap()
+                .into_iter()
+                .enumerate()
+            {
+                let owner = if i % 2 == 0 {
+                    retained_owner
+                } else {
+                    other_owner
+                };
+                for seq in 0..3 {
+                    let key = ObjectKey(id, SequenceNumber::from_u64(seq));
+                    let obj =
+                        get_store_object_pair(Object::with_id_owner_for_testing(id, owner), 0).0;
+                    batch.insert_batch(&db.objects, [(key, obj)]).unwrap();
+                    // the latest version is live, older versions are only kept for retained objects
+                    if seq == 2 || owner == retained_owner {
+                        to_keep.insert(key);
+                    } else {
+                        to_delete.push(key);
+                    }
+                }
+            }
+            batch.write().unwrap();
+
+            let mut effects = TransactionEffects::default();
+            for key in to_delete
+                .iter()
+                .chain(to_keep.iter())
+                .filter(|key| key.1.value() < 2)
+            {
+                effects.unsafe_add_deleted_live_object_for_testing((
+                    key.0,
+                    key.1,
+                    ObjectDigest::MIN,
+                ));
+            }
+            let retention_filter = RetentionFilter::new(&SelectiveRetentionConfig {
+                owners: vec![retained_owner],
+                ..Default::default()
+            });
+            let metrics = AuthorityStorePruningMetrics::new_for_test();
+            AuthorityStorePruner::prune_objects(
+                vec![effects],
+                &db,
+                &lock_table(),
+                0,
+                metrics.clone(),
+                0,
+                true,
+                Some(&retention_filter),
+            )
+            .await
+            .unwrap();
+            assert_eq!(metrics.num_pruned_objects.get(), 100);
+            assert_eq!(
+                metrics
+                    .num_retained_objects
+                    .with_label_values(&[SELECTIVE_RETENTION_RULE])
+                    .get(),
+                100
+            );
+        }
+        tokio::time::sleep(Duration::from_secs(3)).await;
+        assert_eq!(to_keep, get_keys_after_pruning(&path).unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_pruning_with_retention_rules() {
+        let alice = SuiAddress::random_for_testing_only();
+        let bob = SuiAddress::random_for_testing_only();
+        let other = SuiAddress::random_for_testing_only();
+        let rules = [("alice", alice), ("bob", bob)].map(|(name, owner)| ObjectRetentionRule {
+            name: name.to_string(),
+            objects: SelectiveRetentionConfig {
+                owners: vec![owner],
+                ..Default::default()
+            },
+        });
+        let retention_filter = RetentionFilter::for_pruning(None, &rules).unwrap();
+
+        for killswitch_tombstone_pruning in [false, true] {
+            let path = tempfile::tempdir().unwrap().into_path();
+            let mut to_keep = HashSet::new();
+            {
+                let db = Arc::new(AuthorityPerpetualTables::open(&path, None));
+                let mut batch = db.objects.batch();
+                let mut effects = TransactionEffects::default();
+                for (i, id) in ObjectID::in_range(ObjectID::ZERO, 30)
+                    .unwrap()
+                    .into_iter()
+                    .enumerate()
+                {
+                    let owner = [alice, bob, other][i % 3];
+                    // two versions of the object, followed by its tombstone
+                    for seq in 0..3 {
+                        let key = ObjectKey(id, SequenceNumber::from_u64(seq));
+                        let obj = if seq < 2 {
+                            get_store_object_pair(Object::with_id_owner_for_testing(id, owner), 0).0
+                        } else {
+                            StoreObjectWrapper::V1(StoreObject::Deleted)
+                        };
+                        batch.insert_batch(&db.objects, [(key, obj)]).unwrap();
+                        if owner != other {
+                            to_keep.insert(key);
+                        }
+                    }
+                    effects.unsafe_add_deleted_live_object_for_testing((
+                        id,
+                        SequenceNumber::from_u64(0),
+                        ObjectDigest::MIN,
+                    ));
+                    effects.unsafe_add_object_tombstone_for_testing((
+                        id,
+                        SequenceNumber::from_u64(2),
+                        ObjectDigest::MIN,
+                    ));
+                }
+                batch.write().unwrap();
+
+                let metrics = AuthorityStorePruningMetrics::new_for_test();
+                AuthorityStorePruner::prune_objects(
+                    vec![effects],
+                    &db,
+                    &lock_table(),
+                    0,
+                    metrics.clone(),
+                    0,
+                    !killswitch_tombstone_pruning,
+                    Some(&retention_filter),
+                )
+                .await
+                .unwrap();
+
+                // each retained object has two versions eligible for pruning: its first version
+                // and the key of its tombstone
+                let expected_tombstones = if killswitch_tombstone_pruning { 0 } else { 10 };
+                for rule in ["alice", "bob"] {
+                    assert_eq!(
+                        metrics
+                            .num_retained_objects
+                            .with_label_values(&[rule])
+                            .get(),
+                        20
+                    );
+                    assert_eq!(
+                        metrics
+                            .num_retained_tombstones
+                            .with_label_values(&[rule])
+                            .get(),
+                        expected_tombstones
+                    );
+                }
+                assert_eq!(metrics.num_pruned_objects.get(), 20);
+                assert_eq!(metrics.num_pruned_tombstones.get(), expected_tombstones);
+            }
+            tokio::time::sleep(Duration::from_secs(3)).await;
+            // all versions and tombstones of retained objects survive, the others are pruned
+            assert_eq!(to_keep, get_keys_after_pruning(&path).unwrap());
+        }
+    }
+
     #[cfg(not(target_env = "msvc"))]
     #[tokio::test]
     async fn test_db_size_after_compaction() -> Result<(), anyhow::Error> {
@@ -1051,6 +1345,7 @@ mod tests {
             metrics,
             0,
             true,
+            None,
         )
         .await;
         info!("Total pruned keys = {:?}", total_pruned);
@@ -1173,6 +1468,7 @@ mod pprof_tests {
             metrics,
             1,
             true,
+            None,
         )
         .await?;
         let guard = pprof::ProfilerGuardBuilder::default()
@@ -1211,6 +1507,7 @@ mod pprof_tests {
             metrics,
             1,
             true,
+            None,
         )
         .await?;
         if let Ok(()) = perpetual_db.objects.flush() {
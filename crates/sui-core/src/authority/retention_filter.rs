@@ -0,0 +1,186 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use move_core_types::language_storage::StructTag;
+use std::collections::HashSet;
+use sui_config::node::{ObjectRetentionRule, SelectiveRetentionConfig};
+use sui_types::base_types::{ObjectID, SuiAddress};
+use sui_types::object::{Object, Owner};
+
+/// Name of the rule built from the `SelectiveRetentionConfig` of a selective fullnode
+pub const SELECTIVE_RETENTION_RULE: &str = "selective-retention";
+
+/// Decides which objects the pruner keeps the history of, either because the fullnode runs
+/// with a `SelectiveRetentionConfig` or because they match one of the `retention_rules` of the
+/// pruning config. Selective fullnodes also only index the objects matching their config.
+#[derive(Clone, Debug)]
+pub struct RetentionFilter {
+    rules: Vec<RetentionRule>,
+}
+
+#[derive(Clone, Debug)]
+struct RetentionRule {
+    name: String,
+    owners: HashSet<SuiAddress>,
+    packages: HashSet<ObjectID>,
+    types: Vec<StructTag>,
+}
+
+impl RetentionFilter {
+    pub fn new(config: &SelectiveRetentionConfig) -> Self {
+        Self {
+            rules: vec![RetentionRule::new(SELECTIVE_RETENTION_RULE, config)],
+        }
+    }
+
+    /// Returns the filter used by the objects pruner, which keeps the objects retained by the
+    /// selective fullnode filter, if any, and the objects matching `rules`.
+    pub fn for_pruning(
+        selective_filter: Option<&RetentionFilter>,
+        rules: &[ObjectRetentionRule],
+    ) -> Option<Self> {
+        let mut filter = selective_filter.cloned().unwrap_or(Self { rules: vec![] });
+        for rule in rules {
+            filter
+                .rules
+                .push(RetentionRule::new(&rule.name, &rule.objects));
+        }
+        (!filter.rules.is_empty()).then_some(filter)
+    }
+
+    pub fn matches(&self, object: &Object) -> bool {
+        self.matching_rule(object).is_some()
+    }
+
+    /// Returns the name of the first rule matching the object
+    pub fn matching_rule(&self, object: &Object) -> Option<&str> {
+        self.rules
+            .iter()
+            .find(|rule| rule.matches(object))
+            .map(|rule| rule.name.as_str())
+    }
+}
+
+impl RetentionRule {
+    fn new(name: &str, config: &SelectiveRetentionConfig) -> Self {
+        Self {
+            name: name.to_string(),
+            owners: config.owners.iter().copied().collect(),
+            packages: config.packages.iter().copied().collect(),
+            types: config.types.clone(),
+        }
+    }
+
+    fn matches(&self, object: &Object) -> bool {
+        let owner = match object.owner {
+            Owner::AddressOwner(addr) => Some(addr),
+            Owner::ObjectOwner(addr) => Some(addr),
+            Owner::Shared { .. } | Owner::Immutable => None,
+        };
+        if owner.is_some_and(|owner| self.owners.contains(&owner)) {
+            return true;
+        }
+        if object.is_package() {
+            return self.packages.contains(&object.id());
+        }
+        let Some(struct_tag) = object.struct_tag() else {
+            return false;
+        };
+        self.packages.contains(&ObjectID::from(struct_tag.address))
+            || self
+                .types
+                .iter()
+                .any(|t| Self::type_matches(t, &struct_tag))
+    }
+
+    /// A type filter without type parameters matches every instantiation of the type.
+    fn type_matches(filter: &StructTag, struct_tag: &StructTag) -> bool {
+        filter.address == struct_tag.address
+            && filter.module == struct_tag.module
+            && filter.name == struct_tag.name
+            && (filter.type_params.is_empty() || filter.type_params == struct_tag.type_params)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sui_types::gas_coin::GAS;
+    use sui_types::parse_sui_struct_tag;
+    use sui_types::SUI_FRAMEWORK_ADDRESS;
+
+    #[test]
+    fn test_retention_filter() {
+        let owner = SuiAddress::random_for_testing_only();
+        let other = SuiAddress::random_for_testing_only();
+        let owned = Object::with_owner_for_testing(owner);
+        let coin = Object::with_owner_for_testing(other);
+        let immutable_coin = Object::immutable_with_id_for_testing(ObjectID::random());
+
+        let filter = RetentionFilter::new(&SelectiveRetentionConfig {
+            owners: vec![owner],
+            packages: vec![],
+            types: vec![parse_sui_struct_tag("0x2::coin::Coin<0x2::foo::BAR>").unwrap()],
+        });
+        assert!(filter.matches(&owned));
+        assert!(!filter.matches(&coin));
+        assert!(!filter.matches(&immutable_coin));
+
+        for type_ in [
+            "0x2::coin::Coin".to_string(),
+            format!("0x2::coin::Coin<{}>", GAS::type_()),
+        ] {
+            let filter = RetentionFilter::new(&SelectiveRetentionConfig {
+                owners: vec![],
+                packages: vec![],
+                types: vec![parse_sui_struct_tag(&type_).unwrap()],
+            });
+            assert!(filter.matches(&coin));
+            assert!(filter.matches(&immutable_coin));
+        }
+
+        let filter = RetentionFilter::new(&SelectiveRetentionConfig {
+            owners: vec![],
+            packages: vec![ObjectID::from(SUI_FRAMEWORK_ADDRESS)],
+            types: vec![],
+        });
+        assert!(filter.matches(&coin));
+    }
+
+    #[test]
+    fn test_retention_rules() {
+        let owner = SuiAddress::random_for_testing_only();
+        let owned = Object::with_owner_for_testing(owner);
+        let coin = Object::with_owner_for_testing(SuiAddress::random_for_testing_only());
+        let rules = vec![
+            ObjectRetentionRule {
+                name: "coins".to_string(),
+                objects: SelectiveRetentionConfig {
+                    types: vec![parse_sui_struct_tag("0x2::coin::Coin").unwrap()],
+                    ..Default::default()
+                },
+            },
+            ObjectRetentionRule {
+                name: "owner".to_string(),
+                objects: SelectiveRetentionConfig {
+                    owners: vec![owner],
+                    ..Default::default()
+                },
+            },
+        ];
+        assert!(RetentionFilter::for_pruning(None, &[]).is_none());
+
+        let filter = RetentionFilter::for_pruning(None, &rules).unwrap();
+        // the first matching rule wins
+        assert_eq!(filter.matching_rule(&owned), Some("coins"));
+        assert_eq!(filter.matching_rule(&coin), Some("coins"));
+
+        let selective_filter = RetentionFilter::new(&SelectiveRetentionConfig {
+            owners: vec![owner],
+            ..Default::default()
+        });
+        let filter = RetentionFilter::for_pruning(Some(&selective_filter), &rules[1..]).unwrap();
+        assert_eq!(filter.matching_rule(&owned), Some(SELECTIVE_RETENTION_RULE));
+        assert_eq!(filter.matching_rule(&coin), None);
+    }
+}
@@ -1283,6 +1283,7 @@ impl ValidatorService {
     fn handle_traffic_resp<T>(
         &self,
         client: Option<IpAddr>,
+        request_kind: &str,
         wrapped_response: WrappedServiceResponse<T>,
     ) -> Result<tonic::Response<T>, tonic::Status> {
         let (error, spam_weight, unwrapped_response) = match wrapped_response {
@@ -1304,6 +1305,7 @@ impl ValidatorService {
                     (error_weight, error_type)
                 }),
                 spam_weight,
+                request_kind: Some(request_kind.to_string()),
                 timestamp: SystemTime::now(),
             })
         }
@@ -1354,9 +1356,14 @@ macro_rules! handle_with_decoration {
         // check if either IP is blocked, in which case return early
         $self.handle_traffic_req(client.clone()).await?;
 
-        // handle traffic tallying
+        // handle traffic tallying, with requests identified by the endpoint
+        // they are served by, e.g. `transaction` for `transaction_impl`
         let wrapped_response = $self.$func_name($request).await;
-        $self.handle_traffic_resp(client, wrapped_response)
+        $self.handle_traffic_resp(
+            client,
+            stringify!($func_name).trim_end_matches("_impl"),
+            wrapped_response,
+        )
     }};
 }
 
@@ -5,6 +5,7 @@ use crate::authority::authority_store_pruner::{
     AuthorityStorePruner, AuthorityStorePruningMetrics, EPOCH_DURATION_MS_FOR_TESTING,
 };
 use crate::authority::authority_store_tables::AuthorityPerpetualTables;
+use crate::authority::retention_filter::RetentionFilter;
 use crate::checkpoints::CheckpointStore;
 use crate::rest_index::RestIndexStore;
 use anyhow::Result;
@@ -12,7 +13,10 @@ use bytes::Bytes;
 use futures::future::try_join_all;
 use object_store::path::Path;
 use object_store::DynObjectStore;
-use prometheus::{register_int_gauge_with_registry, IntGauge, Registry};
+use prometheus::{
+    register_int_counter_with_registry, register_int_gauge_with_registry, IntCounter, IntGauge,
+    Registry,
+};
 use std::fs;
 use std::num::NonZeroUsize;
 use std::path::PathBuf;
@@ -23,7 +27,8 @@ use sui_config::object_storage_config::{ObjectStoreConfig, ObjectStoreType};
 use sui_storage::mutex_table::RwLockTable;
 use sui_storage::object_store::util::{
     copy_recursively, find_all_dirs_with_epoch_prefix, find_missing_epochs_dirs,
-    path_to_filesystem, put, run_manifest_update_loop, write_snapshot_manifest,
+    path_to_filesystem, put, run_manifest_update_loop, upload_db_checkpoint_incrementally,
+    write_snapshot_manifest,
 };
 use tracing::{debug, error, info};
 use typed_store::rocks::MetricConf;
@@ -36,6 +41,8 @@ pub const STATE_SNAPSHOT_COMPLETED_MARKER: &str = "_STATE_SNAPSHOT_COMPLETED";
 pub struct DBCheckpointMetrics {
     pub first_missing_db_checkpoint_epoch: IntGauge,
     pub num_local_db_checkpoints: IntGauge,
+    pub db_checkpoint_uploaded_bytes: IntCounter,
+    pub db_checkpoint_reused_bytes: IntCounter,
 }
 
 impl DBCheckpointMetrics {
@@ -53,6 +60,18 @@ impl DBCheckpointMetrics {
                 registry
             )
             .unwrap(),
+            db_checkpoint_uploaded_bytes: register_int_counter_with_registry!(
+                "db_checkpoint_uploaded_bytes",
+                "Number of bytes of incremental db checkpoints uploaded to the remote store",
+                registry
+            )
+            .unwrap(),
+            db_checkpoint_reused_bytes: register_int_counter_with_registry!(
+                "db_checkpoint_reused_bytes",
+                "Number of bytes of incremental db checkpoints not uploaded because the remote store already had them",
+                registry
+            )
+            .unwrap(),
         };
         Arc::new(this)
     }
@@ -77,6 +96,10 @@ pub struct DBCheckpointHandler {
     state_snapshot_enabled: bool,
     /// Pruning objects
     pruning_config: AuthorityStorePruningConfig,
+    /// If true, only upload the SST files that are not already in the remote store
+    incremental_upload: bool,
+    /// Objects retained by a selective fullnode, whose history is kept when pruning
+    selective_retention_filter: Option<Arc<RetentionFilter>>,
     metrics: Arc<DBCheckpointMetrics>,
 }
 
@@ -90,6 +113,8 @@ impl DBCheckpointHandler {
         pruning_config: AuthorityStorePruningConfig,
         registry: &Registry,
         state_snapshot_enabled: bool,
+        incremental_upload: bool,
+        selective_retention_filter: Option<Arc<RetentionFilter>>,
     ) -> Result<Arc<Self>> {
         let input_store_config = ObjectStoreConfig {
             object_store: Some(ObjectStoreType::File),
@@ -111,6 +136,8 @@ impl DBCheckpointHandler {
             indirect_objects_threshold,
             state_snapshot_enabled,
             pruning_config,
+            incremental_upload,
+            selective_retention_filter,
             metrics: DBCheckpointMetrics::new(registry),
         }))
     }
@@ -120,6 +147,7 @@ impl DBCheckpointHandler {
         interval_s: u64,
         prune_and_compact_before_upload: bool,
         state_snapshot_enabled: bool,
+        incremental_upload: bool,
     ) -> Result<Arc<Self>> {
         Ok(Arc::new(DBCheckpointHandler {
             input_object_store: input_object_store_config.make()?,
@@ -136,6 +164,8 @@ impl DBCheckpointHandler {
             indirect_objects_threshold: 0,
             state_snapshot_enabled,
             pruning_config: AuthorityStorePruningConfig::default(),
+            incremental_upload,
+            selective_retention_filter: None,
             metrics: DBCheckpointMetrics::new(&Registry::default()),
         }))
     }
@@ -266,6 +296,10 @@ impl DBCheckpointHandler {
         let rest_index = RestIndexStore::new_without_init(db_path.join("rest_index"));
         let metrics = AuthorityStorePruningMetrics::new(&Registry::default());
         let lock_table = Arc::new(RwLockTable::new(1));
+        let retention_filter = RetentionFilter::for_pruning(
+            self.selective_retention_filter.as_deref(),
+            &self.pruning_config.retention_rules,
+        );
         info!(
             "Pruning db checkpoint in {:?} for epoch: {epoch}",
             db_path.display()
@@ -279,6 +313,7 @@ impl DBCheckpointHandler {
             metrics,
             self.indirect_objects_threshold,
             epoch_duration_ms,
+            retention_filter.as_ref(),
         )
         .await?;
         info!(
@@ -321,18 +356,41 @@ impl DBCheckpointHandler {
                         .await?;
                 }
 
-                info!("Copying db checkpoint for epoch: {epoch} to remote storage");
-                copy_recursively(
-                    db_path,
-                    &self.input_object_store,
-                    &object_store,
-                    NonZeroUsize::new(20).unwrap(),
-                )
-                .await?;
-
-                // This writes a single "MANIFEST" file which contains a list of all files that make up a db snapshot
-                write_snapshot_manifest(db_path, &object_store, format!("epoch_{}/", epoch))
+                if self.incremental_upload {
+                    info!("Uploading db checkpoint for epoch: {epoch} incrementally to remote storage");
+                    // This writes a "DB_CHECKPOINT_MANIFEST" file instead of the "MANIFEST" file,
+                    // since most SST files are not in the epoch directory
+                    let (_, stats) = upload_db_checkpoint_incrementally(
+                        db_path,
+                        &self.input_object_store,
+                        &object_store,
+                        NonZeroUsize::new(20).unwrap(),
+                    )
                     .await?;
+                    info!(
+                        "Uploaded {} files ({} bytes) of db checkpoint for epoch: {epoch}, reused {} files ({} bytes)",
+                        stats.uploaded_files, stats.uploaded_bytes, stats.reused_files, stats.reused_bytes
+                    );
+                    self.metrics
+                        .db_checkpoint_uploaded_bytes
+                        .inc_by(stats.uploaded_bytes);
+                    self.metrics
+                        .db_checkpoint_reused_bytes
+                        .inc_by(stats.reused_bytes);
+                } else {
+                    info!("Copying db checkpoint for epoch: {epoch} to remote storage");
+                    copy_recursively(
+                        db_path,
+                        &self.input_object_store,
+                        &object_store,
+                        NonZeroUsize::new(20).unwrap(),
+                    )
+                    .await?;
+
+                    // This writes a single "MANIFEST" file which contains a list of all files that make up a db snapshot
+                    write_snapshot_manifest(db_path, &object_store, format!("epoch_{}/", epoch))
+                        .await?;
+                }
                 // Drop marker in the output directory that upload completed successfully
                 let bytes = Bytes::from_static(b"success");
                 let success_marker = db_path.child(SUCCESS_MARKER);
@@ -394,6 +452,7 @@ mod tests {
     use sui_config::object_storage_config::{ObjectStoreConfig, ObjectStoreType};
     use sui_storage::object_store::util::{
         find_all_dirs_with_epoch_prefix, find_missing_epochs_dirs, path_to_filesystem,
+        DB_CHECKPOINT_MANIFEST_FILENAME, SST_FILES_DIR,
     };
     use tempfile::TempDir;
 
@@ -432,6 +491,7 @@ mod tests {
             10,
             false,
             false,
+            false,
         )?;
         let local_checkpoints_by_epoch =
             find_all_dirs_with_epoch_prefix(&db_checkpoint_handler.input_object_store, None)
@@ -477,6 +537,70 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_incremental_upload() -> anyhow::Result<()> {
+        let checkpoint_dir = TempDir::new()?;
+        let checkpoint_dir_path = checkpoint_dir.path();
+        for epoch in 0..2 {
+            let local_checkpoint = checkpoint_dir_path.join(format!("epoch_{epoch}"));
+            fs::create_dir(&local_checkpoint)?;
+            fs::write(local_checkpoint.join("000001.sst"), b"Lorem ipsum")?;
+            fs::write(local_checkpoint.join("CURRENT"), format!("epoch {epoch}"))?;
+        }
+
+        let remote_checkpoint_dir = TempDir::new()?;
+        let remote_checkpoint_dir_path = remote_checkpoint_dir.path();
+        let input_store_config = ObjectStoreConfig {
+            object_store: Some(ObjectStoreType::File),
+            directory: Some(checkpoint_dir_path.to_path_buf()),
+            ..Default::default()
+        };
+        let output_store_config = ObjectStoreConfig {
+            object_store: Some(ObjectStoreType::File),
+            directory: Some(remote_checkpoint_dir_path.to_path_buf()),
+            ..Default::default()
+        };
+        let db_checkpoint_handler = DBCheckpointHandler::new_for_test(
+            &input_store_config,
+            Some(&output_store_config),
+            10,
+            false,
+            false,
+            true,
+        )?;
+        let missing_epochs = find_missing_epochs_dirs(
+            db_checkpoint_handler.output_object_store.as_ref().unwrap(),
+            SUCCESS_MARKER,
+        )
+        .await?;
+        db_checkpoint_handler
+            .upload_db_checkpoints_to_object_store(missing_epochs)
+            .await?;
+
+        // the SST file shared by both db checkpoints is only uploaded once
+        assert_eq!(
+            fs::read_dir(remote_checkpoint_dir_path.join(SST_FILES_DIR))?.count(),
+            1
+        );
+        assert_eq!(
+            db_checkpoint_handler
+                .metrics
+                .db_checkpoint_reused_bytes
+                .get(),
+            "Lorem ipsum".len() as u64
+        );
+        for epoch in 0..2 {
+            let remote_checkpoint = remote_checkpoint_dir_path.join(format!("epoch_{epoch}"));
+            assert!(!remote_checkpoint.join("000001.sst").exists());
+            assert!(remote_checkpoint.join("CURRENT").exists());
+            assert!(remote_checkpoint
+                .join(DB_CHECKPOINT_MANIFEST_FILENAME)
+                .exists());
+            assert!(remote_checkpoint.join(SUCCESS_MARKER).exists());
+        }
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_upload_resumes() -> anyhow::Result<()> {
         let checkpoint_dir = TempDir::new()?;
@@ -503,6 +627,7 @@ mod tests {
             10,
             false,
             false,
+            false,
         )?;
 
         fs::create_dir(&local_epoch0_checkpoint)?;
@@ -625,6 +750,7 @@ mod tests {
             10,
             false,
             false,
+            false,
         )?;
 
         let missing_epochs = find_missing_epochs_dirs(
@@ -690,6 +816,7 @@ mod tests {
             10,
             false,
             false,
+            false,
         )?;
 
         let missing_epochs = find_missing_epochs_dirs(
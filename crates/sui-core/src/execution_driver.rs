@@ -2,20 +2,24 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use std::{
+    collections::HashMap,
     sync::{Arc, Weak},
     time::Duration,
 };
 
 use mysten_metrics::{monitored_scope, spawn_monitored_task};
+use parking_lot::Mutex;
 use rand::{
     rngs::{OsRng, StdRng},
     Rng, SeedableRng,
 };
+use serde::Serialize;
 use sui_macros::fail_point_async;
 use sui_protocol_config::Chain;
+use sui_types::digests::TransactionDigest;
 use tokio::{
     sync::{mpsc::UnboundedReceiver, oneshot, Semaphore},
-    time::sleep,
+    time::{sleep, Instant},
 };
 use tracing::{error, error_span, info, trace, Instrument};
 
@@ -32,6 +36,79 @@ pub const EXECUTION_MAX_ATTEMPTS: u32 = 10;
 const EXECUTION_FAILURE_RETRY_INTERVAL: Duration = Duration::from_secs(1);
 const QUEUEING_DELAY_SAMPLING_RATIO: f64 = 0.05;
 
+/// Certificates the execution driver is currently executing, i.e. that have been received from
+/// TransactionManager and hold an execution permit.
+#[derive(Clone, Default)]
+pub struct InflightExecutions {
+    executions: Arc<Mutex<HashMap<TransactionDigest, InflightExecution>>>,
+}
+
+struct InflightExecution {
+    start_time: Instant,
+    attempts: u32,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct InflightExecutionSnapshot {
+    pub digest: TransactionDigest,
+    /// Time since the execution driver started executing the certificate.
+    pub running_ms: u64,
+    /// Number of attempts to execute the certificate, including the current one.
+    pub attempts: u32,
+}
+
+impl InflightExecutions {
+    /// Returns the certificates being executed, longest running first.
+    pub fn snapshot(&self) -> Vec<InflightExecutionSnapshot> {
+        let mut snapshot: Vec<_> = self
+            .executions
+            .lock()
+            .iter()
+            .map(|(digest, execution)| InflightExecutionSnapshot {
+                digest: *digest,
+                running_ms: execution.start_time.elapsed().as_millis() as u64,
+                attempts: execution.attempts,
+            })
+            .collect();
+        snapshot.sort_by_key(|execution| std::cmp::Reverse(execution.running_ms));
+        snapshot
+    }
+
+    // Tracks the certificate until the returned guard is dropped.
+    fn start(&self, digest: TransactionDigest) -> InflightExecutionGuard {
+        self.executions.lock().insert(
+            digest,
+            InflightExecution {
+                start_time: Instant::now(),
+                attempts: 0,
+            },
+        );
+        InflightExecutionGuard {
+            executions: self.clone(),
+            digest,
+        }
+    }
+}
+
+struct InflightExecutionGuard {
+    executions: InflightExecutions,
+    digest: TransactionDigest,
+}
+
+impl InflightExecutionGuard {
+    fn set_attempts(&self, attempts: u32) {
+        if let Some(execution) = self.executions.executions.lock().get_mut(&self.digest) {
+            execution.attempts = attempts;
+        }
+    }
+}
+
+impl Drop for InflightExecutionGuard {
+    fn drop(&mut self) {
+        self.executions.executions.lock().remove(&self.digest);
+    }
+}
+
 /// When a notification that a new pending transaction is received we activate
 /// processing the transaction in a loop.
 pub async fn execution_process(
@@ -137,10 +214,14 @@ pub async fn execution_process(
             if let Ok(true) = authority.is_tx_already_executed(&digest) {
                 return;
             }
+            // Removes the certificate from the inflight set when execution finishes, or the task
+            // is cancelled at the end of the epoch.
+            let inflight = authority.execution_driver_inflight.start(digest);
             let mut attempts = 0;
             loop {
                 fail_point_async!("transaction_execution_delay");
                 attempts += 1;
+                inflight.set_attempts(attempts);
                 let res = authority
                     .try_execute_immediately(&certificate, expected_effects_digest, &epoch_store_clone)
                     .await;
@@ -3,6 +3,7 @@
 
 use crate::authority::AuthorityState;
 use mysten_metrics::monitored_scope;
+use serde::Serialize;
 use std::cmp::{max, min};
 use std::hash::Hasher;
 use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
@@ -40,6 +41,41 @@ impl AuthorityOverloadInfo {
     }
 }
 
+/// The load shedding state of an authority, along with the signals the overload monitor uses to
+/// decide it.
+#[derive(Clone, Debug, Serialize)]
+pub struct AuthorityOverloadSnapshot {
+    pub is_overload: bool,
+    pub load_shedding_percentage: u32,
+    pub execution_queueing_latency_ms: u64,
+    /// Rate of transactions becoming ready for execution, per second.
+    pub txn_ready_rate: f64,
+    /// Rate of transactions being executed, per second.
+    pub execution_rate: f64,
+    pub config: AuthorityOverloadConfig,
+}
+
+impl AuthorityOverloadSnapshot {
+    pub fn new(authority: &AuthorityState) -> Self {
+        Self {
+            is_overload: authority.overload_info.is_overload.load(Ordering::Relaxed),
+            load_shedding_percentage: authority
+                .overload_info
+                .load_shedding_percentage
+                .load(Ordering::Relaxed),
+            execution_queueing_latency_ms: authority
+                .metrics
+                .execution_queueing_latency
+                .latency()
+                .unwrap_or_default()
+                .as_millis() as u64,
+            txn_ready_rate: authority.metrics.txn_ready_rate_tracker.lock().rate(),
+            execution_rate: authority.metrics.execution_rate_tracker.lock().rate(),
+            config: authority.overload_config().clone(),
+        }
+    }
+}
+
 const STEADY_OVERLOAD_REDUCTION_PERCENTAGE: u32 = 10;
 const EXECUTION_RATE_RATIO_FOR_COMPARISON: f64 = 0.95;
 const ADDITIONAL_LOAD_SHEDDING: f64 = 0.02;
@@ -3,7 +3,8 @@
 
 use prometheus::{
     register_int_counter_vec_with_registry, register_int_counter_with_registry,
-    register_int_gauge_with_registry, IntCounter, IntCounterVec, IntGauge, Registry,
+    register_int_gauge_vec_with_registry, register_int_gauge_with_registry, IntCounter,
+    IntCounterVec, IntGauge, IntGaugeVec, Registry,
 };
 
 #[derive(Clone)]
@@ -24,6 +25,9 @@ pub struct TrafficControllerMetrics {
     pub highest_proxied_spam_rate: IntGauge,
     pub highest_direct_error_rate: IntGauge,
     pub highest_proxied_error_rate: IntGauge,
+    pub policy_decisions: IntCounterVec,
+    pub token_bucket_global_tokens: IntGaugeVec,
+    pub token_bucket_tracked_clients: IntGaugeVec,
 }
 
 impl TrafficControllerMetrics {
@@ -129,6 +133,27 @@ impl TrafficControllerMetrics {
                 registry
             )
             .unwrap(),
+            policy_decisions: register_int_counter_vec_with_registry!(
+                "traffic_control_policy_decisions",
+                "Number of tallies handled, grouped by tally type and the decision of the policy",
+                &["tally_type", "decision"],
+                registry
+            )
+            .unwrap(),
+            token_bucket_global_tokens: register_int_gauge_vec_with_registry!(
+                "traffic_control_token_bucket_global_tokens",
+                "Tokens remaining in the global bucket of the token bucket policy",
+                &["tally_type"],
+                registry
+            )
+            .unwrap(),
+            token_bucket_tracked_clients: register_int_gauge_vec_with_registry!(
+                "traffic_control_token_bucket_tracked_clients",
+                "Number of client buckets held by the token bucket policy",
+                &["tally_type"],
+                registry
+            )
+            .unwrap(),
         }
     }
 
@@ -410,11 +410,32 @@ async fn run_tally_loop(
                     );
                 }
             }
+            update_token_bucket_metrics(&mut spam_policy, "spam", &metrics);
+            update_token_bucket_metrics(&mut error_policy, "error", &metrics);
             metric_timer = Instant::now();
         }
     }
 }
 
+fn update_token_bucket_metrics(
+    policy: &mut TrafficControlPolicy,
+    tally_type: &str,
+    metrics: &TrafficControllerMetrics,
+) {
+    if let TrafficControlPolicy::TokenBucket(policy) = policy {
+        if let Some(global_tokens) = policy.global_tokens() {
+            metrics
+                .token_bucket_global_tokens
+                .with_label_values(&[tally_type])
+                .set(global_tokens as i64);
+        }
+        metrics
+            .token_bucket_tracked_clients
+            .with_label_values(&[tally_type])
+            .set(policy.tracked_clients() as i64);
+    }
+}
+
 async fn handle_error_tally(
     policy: &mut TrafficControlPolicy,
     policy_config: &PolicyConfig,
@@ -442,6 +463,12 @@ async fn handle_error_tally(
         .inc();
     let resp = policy.handle_tally(tally);
     metrics.error_tally_handled.inc();
+    if let Some(decision) = resp.decision {
+        metrics
+            .policy_decisions
+            .with_label_values(&["error", decision.as_str()])
+            .inc();
+    }
     if let Some(fw_config) = fw_config {
         if fw_config.delegate_error_blocking && !mem_drainfile_present {
             let client = nodefw_client
@@ -476,6 +503,12 @@ async fn handle_spam_tally(
     }
     let resp = policy.handle_tally(tally.clone());
     metrics.tally_handled.inc();
+    if let Some(decision) = resp.decision {
+        metrics
+            .policy_decisions
+            .with_label_values(&["spam", decision.as_str()])
+            .inc();
+    }
     if let Some(fw_config) = fw_config {
         if fw_config.delegate_spam_blocking && !mem_drainfile_present {
             let client = nodefw_client
@@ -504,6 +537,7 @@ async fn handle_policy_response(
     let PolicyResponse {
         block_client,
         block_proxied_client,
+        ..
     } = response;
     let PolicyConfig {
         connection_blocklist_ttl_sec,
@@ -552,6 +586,7 @@ async fn delegate_policy_response(
     let PolicyResponse {
         block_client,
         block_proxied_client,
+        ..
     } = response;
     let PolicyConfig {
         connection_blocklist_ttl_sec,
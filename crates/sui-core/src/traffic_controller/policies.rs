@@ -2,9 +2,11 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use std::{collections::HashMap, net::IpAddr, sync::Arc};
+use std::{collections::HashMap, net::IpAddr, num::NonZeroUsize, sync::Arc};
 
 use count_min_sketch::CountMinSketch32;
+use ipnetwork::IpNetwork;
+use lru::LruCache;
 use mysten_metrics::spawn_monitored_task;
 use parking_lot::RwLock;
 use std::cmp::Reverse;
@@ -13,7 +15,9 @@ use std::fmt::Debug;
 use std::hash::Hash;
 use std::time::Duration;
 use std::time::{Instant, SystemTime};
-use sui_types::traffic_control::{FreqThresholdConfig, PolicyConfig, PolicyType, Weight};
+use sui_types::traffic_control::{
+    FreqThresholdConfig, PolicyConfig, PolicyType, TokenBucketConfig, Weight,
+};
 use tracing::{info, trace};
 
 const HIGHEST_RATES_CAPACITY: usize = 20;
@@ -224,6 +228,9 @@ pub struct TrafficTally {
     pub through_fullnode: Option<IpAddr>,
     pub error_info: Option<(Weight, String)>,
     pub spam_weight: Weight,
+    /// The kind of request being tallied, e.g. the JSON-RPC method or
+    /// validator endpoint, used by policies that weigh requests by cost
+    pub request_kind: Option<String>,
     pub timestamp: SystemTime,
 }
 
@@ -239,6 +246,7 @@ impl TrafficTally {
             through_fullnode,
             error_info,
             spam_weight,
+            request_kind: None,
             timestamp: SystemTime::now(),
         }
     }
@@ -248,6 +256,34 @@ impl TrafficTally {
 pub struct PolicyResponse {
     pub block_client: Option<IpAddr>,
     pub block_proxied_client: Option<IpAddr>,
+    /// The reason for the response, for policies that report one
+    pub decision: Option<PolicyDecision>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PolicyDecision {
+    /// The request was within the limits of the policy
+    Allowed,
+    /// The clients of the request are exempt from the policy
+    Allowlisted,
+    /// The client's bucket could not pay for the request
+    ClientBucketExhausted,
+    /// The proxied client's bucket could not pay for the request
+    ProxiedClientBucketExhausted,
+    /// The bucket shared by all clients could not pay for the request
+    GlobalBucketExhausted,
+}
+
+impl PolicyDecision {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PolicyDecision::Allowed => "allowed",
+            PolicyDecision::Allowlisted => "allowlisted",
+            PolicyDecision::ClientBucketExhausted => "client_bucket_exhausted",
+            PolicyDecision::ProxiedClientBucketExhausted => "proxied_client_bucket_exhausted",
+            PolicyDecision::GlobalBucketExhausted => "global_bucket_exhausted",
+        }
+    }
 }
 
 pub trait Policy {
@@ -261,6 +297,7 @@ pub trait Policy {
 // not object safe, so we can't use a trait object instead
 pub enum TrafficControlPolicy {
     FreqThreshold(FreqThresholdPolicy),
+    TokenBucket(TokenBucketPolicy),
     NoOp(NoOpPolicy),
     // Test policies below this point
     TestNConnIP(TestNConnIPPolicy),
@@ -272,6 +309,7 @@ impl Policy for TrafficControlPolicy {
         match self {
             TrafficControlPolicy::NoOp(policy) => policy.handle_tally(tally),
             TrafficControlPolicy::FreqThreshold(policy) => policy.handle_tally(tally),
+            TrafficControlPolicy::TokenBucket(policy) => policy.handle_tally(tally),
             TrafficControlPolicy::TestNConnIP(policy) => policy.handle_tally(tally),
             TrafficControlPolicy::TestPanicOnInvocation(policy) => policy.handle_tally(tally),
         }
@@ -281,6 +319,7 @@ impl Policy for TrafficControlPolicy {
         match self {
             TrafficControlPolicy::NoOp(policy) => policy.policy_config(),
             TrafficControlPolicy::FreqThreshold(policy) => policy.policy_config(),
+            TrafficControlPolicy::TokenBucket(policy) => policy.policy_config(),
             TrafficControlPolicy::TestNConnIP(policy) => policy.policy_config(),
             TrafficControlPolicy::TestPanicOnInvocation(policy) => policy.policy_config(),
         }
@@ -300,6 +339,9 @@ impl TrafficControlPolicy {
             PolicyType::FreqThreshold(freq_threshold_config) => Self::FreqThreshold(
                 FreqThresholdPolicy::new(policy_config, freq_threshold_config),
             ),
+            PolicyType::TokenBucket(token_bucket_config) => {
+                Self::TokenBucket(TokenBucketPolicy::new(policy_config, token_bucket_config))
+            }
             PolicyType::TestNConnIP(n) => {
                 Self::TestNConnIP(TestNConnIPPolicy::new(policy_config, n).await)
             }
@@ -389,6 +431,223 @@ impl FreqThresholdPolicy {
         PolicyResponse {
             block_client,
             block_proxied_client,
+            decision: None,
+        }
+    }
+
+    fn policy_config(&self) -> &PolicyConfig {
+        &self.config
+    }
+}
+
+struct TokenBucket {
+    capacity: f64,
+    refill_rate: f64,
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    fn new(capacity: u64, refill_rate: u64, now: Instant) -> Self {
+        Self {
+            capacity: capacity as f64,
+            refill_rate: refill_rate as f64,
+            tokens: capacity as f64,
+            last_refill: now,
+        }
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.last_refill);
+        self.tokens = (self.tokens + elapsed.as_secs_f64() * self.refill_rate).min(self.capacity);
+        self.last_refill = now;
+    }
+
+    fn can_pay(&mut self, cost: f64, now: Instant) -> bool {
+        self.refill(now);
+        self.tokens >= cost
+    }
+}
+
+pub struct TokenBucketPolicy {
+    config: PolicyConfig,
+    bucket_config: TokenBucketConfig,
+    allow_list: Vec<IpNetwork>,
+    clients: LruCache<IpAddr, TokenBucket>,
+    proxied_clients: LruCache<IpAddr, TokenBucket>,
+    global: Option<TokenBucket>,
+}
+
+impl TokenBucketPolicy {
+    pub fn new(config: PolicyConfig, bucket_config: TokenBucketConfig) -> Self {
+        let allow_list = bucket_config
+            .allow_list
+            .iter()
+            .map(|network| {
+                network.parse::<IpNetwork>().unwrap_or_else(|_| {
+                    panic!(
+                        "Failed to parse token bucket allowlist entry: {:?}",
+                        network
+                    )
+                })
+            })
+            .collect();
+        let max_cost = bucket_config
+            .request_costs
+            .values()
+            .copied()
+            .chain(std::iter::once(bucket_config.default_cost))
+            .max()
+            .unwrap();
+        assert!(
+            max_cost <= bucket_config.client_capacity
+                && max_cost <= bucket_config.proxied_client_capacity,
+            "Request cost {max_cost} exceeds client bucket capacity, so such requests would always be blocked"
+        );
+        if let Some(global_capacity) = bucket_config.global_capacity {
+            assert!(
+                bucket_config.global_refill_rate > 0,
+                "Global bucket has a capacity but no refill rate, so it would block all clients once exhausted"
+            );
+            assert!(
+                max_cost <= global_capacity,
+                "Request cost {max_cost} exceeds global bucket capacity, so such requests would always be blocked"
+            );
+        }
+        let max_tracked_clients = NonZeroUsize::new(bucket_config.max_tracked_clients)
+            .expect("max_tracked_clients must be greater than 0");
+        let global = bucket_config.global_capacity.map(|capacity| {
+            TokenBucket::new(capacity, bucket_config.global_refill_rate, Instant::now())
+        });
+        Self {
+            config,
+            bucket_config,
+            allow_list,
+            clients: LruCache::new(max_tracked_clients),
+            proxied_clients: LruCache::new(max_tracked_clients),
+            global,
+        }
+    }
+
+    /// Tokens currently in the bucket shared by all clients, if there is one
+    pub fn global_tokens(&mut self) -> Option<u64> {
+        let now = Instant::now();
+        self.global.as_mut().map(|bucket| {
+            bucket.refill(now);
+            bucket.tokens as u64
+        })
+    }
+
+    /// Number of client and proxied client buckets held in memory
+    pub fn tracked_clients(&self) -> usize {
+        self.clients.len() + self.proxied_clients.len()
+    }
+
+    fn is_allowlisted(&self, ip: &IpAddr) -> bool {
+        self.allow_list.iter().any(|network| network.contains(*ip))
+    }
+
+    /// Returns the bucket of `ip`. Once `max_tracked_clients` buckets are held, the bucket
+    /// of the least recently seen client is dropped to make room for a new one. That client
+    /// has been idle the longest, so its bucket is the most likely to have refilled.
+    fn bucket(
+        buckets: &mut LruCache<IpAddr, TokenBucket>,
+        ip: IpAddr,
+        capacity: u64,
+        refill_rate: u64,
+        now: Instant,
+    ) -> &mut TokenBucket {
+        buckets.get_or_insert_mut(ip, || TokenBucket::new(capacity, refill_rate, now))
+    }
+
+    pub fn handle_tally(&mut self, tally: TrafficTally) -> PolicyResponse {
+        let client = tally.direct.filter(|ip| !self.is_allowlisted(ip));
+        let proxied_client = tally.through_fullnode.filter(|ip| !self.is_allowlisted(ip));
+        if client.is_none()
+            && proxied_client.is_none()
+            && (tally.direct.is_some() || tally.through_fullnode.is_some())
+        {
+            return PolicyResponse {
+                decision: Some(PolicyDecision::Allowlisted),
+                ..Default::default()
+            };
+        }
+
+        let now = Instant::now();
+        let cost = self
+            .bucket_config
+            .request_cost(tally.request_kind.as_deref()) as f64;
+        let mut client_bucket = client.map(|ip| {
+            Self::bucket(
+                &mut self.clients,
+                ip,
+                self.bucket_config.client_capacity,
+                self.bucket_config.client_refill_rate,
+                now,
+            )
+        });
+        let mut proxied_client_bucket = proxied_client.map(|ip| {
+            Self::bucket(
+                &mut self.proxied_clients,
+                ip,
+                self.bucket_config.proxied_client_capacity,
+                self.bucket_config.proxied_client_refill_rate,
+                now,
+            )
+        });
+        trace!(
+            "TokenBucketPolicy handling tally -- request_kind: {:?}, cost: {:?}, client: {:?}, proxied_client: {:?}",
+            tally.request_kind,
+            cost,
+            client,
+            proxied_client,
+        );
+
+        // Requests are only charged if every bucket can pay for them, so
+        // that a blocked client does not drain the buckets it shares
+        let block_client = client.filter(|_| {
+            !client_bucket
+                .as_mut()
+                .is_some_and(|bucket| bucket.can_pay(cost, now))
+        });
+        let block_proxied_client = proxied_client.filter(|_| {
+            !proxied_client_bucket
+                .as_mut()
+                .is_some_and(|bucket| bucket.can_pay(cost, now))
+        });
+        if block_client.is_some() || block_proxied_client.is_some() {
+            return PolicyResponse {
+                block_client,
+                block_proxied_client,
+                decision: Some(if block_client.is_some() {
+                    PolicyDecision::ClientBucketExhausted
+                } else {
+                    PolicyDecision::ProxiedClientBucketExhausted
+                }),
+            };
+        }
+        if let Some(global) = self.global.as_mut() {
+            if !global.can_pay(cost, now) {
+                // Shed load from the client furthest from this node, which
+                // is the one sending the request if it is being proxied
+                return PolicyResponse {
+                    block_client: if proxied_client.is_some() {
+                        None
+                    } else {
+                        client
+                    },
+                    block_proxied_client: proxied_client,
+                    decision: Some(PolicyDecision::GlobalBucketExhausted),
+                };
+            }
+            global.tokens -= cost;
+        }
+        for bucket in client_bucket.into_iter().chain(proxied_client_bucket) {
+            bucket.tokens -= cost;
+        }
+        PolicyResponse {
+            decision: Some(PolicyDecision::Allowed),
+            ..Default::default()
         }
     }
 
@@ -458,6 +717,7 @@ impl TestNConnIPPolicy {
                 None
             },
             block_proxied_client: None,
+            decision: None,
         }
     }
 
@@ -495,12 +755,28 @@ impl TestPanicOnInvocationPolicy {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::collections::BTreeMap;
     use std::net::{IpAddr, Ipv4Addr};
     use sui_macros::sim_test;
     use sui_types::traffic_control::{
         DEFAULT_SKETCH_CAPACITY, DEFAULT_SKETCH_PROBABILITY, DEFAULT_SKETCH_TOLERANCE,
     };
 
+    fn tally(
+        direct: Option<IpAddr>,
+        through_fullnode: Option<IpAddr>,
+        request_kind: &str,
+    ) -> TrafficTally {
+        TrafficTally {
+            direct,
+            through_fullnode,
+            error_info: None,
+            spam_weight: Weight::one(),
+            request_kind: Some(request_kind.to_string()),
+            timestamp: SystemTime::now(),
+        }
+    }
+
     #[sim_test]
     async fn test_freq_threshold_policy() {
         // Create freq policy that will block on average frequency 2 requests per second
@@ -524,6 +800,7 @@ mod tests {
             through_fullnode: Some(IpAddr::V4(Ipv4Addr::new(1, 2, 3, 4))),
             error_info: None,
             spam_weight: Weight::one(),
+            request_kind: None,
             timestamp: SystemTime::now(),
         };
         let bob = TrafficTally {
@@ -531,6 +808,7 @@ mod tests {
             through_fullnode: Some(IpAddr::V4(Ipv4Addr::new(4, 3, 2, 1))),
             error_info: None,
             spam_weight: Weight::one(),
+            request_kind: None,
             timestamp: SystemTime::now(),
         };
         let charlie = TrafficTally {
@@ -538,6 +816,7 @@ mod tests {
             through_fullnode: Some(IpAddr::V4(Ipv4Addr::new(5, 6, 7, 8))),
             error_info: None,
             spam_weight: Weight::one(),
+            request_kind: None,
             timestamp: SystemTime::now(),
         };
 
@@ -655,6 +934,217 @@ mod tests {
         assert_eq!(proxied_rate, 1);
     }
 
+    #[sim_test]
+    async fn test_token_bucket_policy() {
+        // Clients may burst requests costing 10 tokens, and regain 5 tokens
+        // per second. Dry runs cost 5 tokens and other requests cost 1.
+        let mut policy = TokenBucketPolicy::new(
+            PolicyConfig::default(),
+            TokenBucketConfig {
+                client_capacity: 10,
+                client_refill_rate: 5,
+                proxied_client_capacity: 10,
+                proxied_client_refill_rate: 5,
+                request_costs: BTreeMap::from([("sui_dryRunTransactionBlock".to_string(), 5)]),
+                ..Default::default()
+            },
+        );
+        let alice = Some(IpAddr::V4(Ipv4Addr::new(8, 7, 6, 5)));
+        let bob = Some(IpAddr::V4(Ipv4Addr::new(1, 2, 3, 4)));
+        let dry_run = "sui_dryRunTransactionBlock";
+        let get_object = "sui_getObject";
+
+        // alice's burst of 2 dry runs uses up her bucket
+        for _ in 0..2 {
+            let response = policy.handle_tally(tally(alice, None, dry_run));
+            assert_eq!(response.block_client, None);
+            assert_eq!(response.decision, Some(PolicyDecision::Allowed));
+        }
+        let response = policy.handle_tally(tally(alice, None, get_object));
+        assert_eq!(response.block_client, alice);
+        assert_eq!(
+            response.decision,
+            Some(PolicyDecision::ClientBucketExhausted)
+        );
+
+        // bob may send as many cheap requests as alice's expensive ones
+        for _ in 0..10 {
+            let response = policy.handle_tally(tally(bob, None, get_object));
+            assert_eq!(response.decision, Some(PolicyDecision::Allowed));
+        }
+        let response = policy.handle_tally(tally(bob, None, get_object));
+        assert_eq!(response.block_client, bob);
+
+        // after a second, alice can pay for one more dry run, but not two
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        let response = policy.handle_tally(tally(alice, None, dry_run));
+        assert_eq!(response.decision, Some(PolicyDecision::Allowed));
+        let response = policy.handle_tally(tally(alice, None, dry_run));
+        assert_eq!(response.block_client, alice);
+
+        // a blocked request does not consume tokens
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        let response = policy.handle_tally(tally(alice, None, dry_run));
+        assert_eq!(response.decision, Some(PolicyDecision::Allowed));
+
+        // requests proxied through a fullnode are charged to both the fullnode
+        // and the proxied client, and only the exhausted one is blocked
+        let fullnode = Some(IpAddr::V4(Ipv4Addr::new(5, 6, 7, 8)));
+        let charlie = Some(IpAddr::V4(Ipv4Addr::new(4, 3, 2, 1)));
+        for _ in 0..2 {
+            let response = policy.handle_tally(tally(fullnode, charlie, dry_run));
+            assert_eq!(response.decision, Some(PolicyDecision::Allowed));
+        }
+        let response = policy.handle_tally(tally(fullnode, charlie, dry_run));
+        assert_eq!(response.block_client, fullnode);
+        assert_eq!(response.block_proxied_client, charlie);
+        assert_eq!(
+            response.decision,
+            Some(PolicyDecision::ClientBucketExhausted)
+        );
+        assert_eq!(policy.tracked_clients(), 4);
+    }
+
+    #[test]
+    #[should_panic(expected = "exceeds client bucket capacity")]
+    fn test_token_bucket_policy_rejects_cost_above_client_capacity() {
+        TokenBucketPolicy::new(
+            PolicyConfig::default(),
+            TokenBucketConfig {
+                client_capacity: 10,
+                request_costs: BTreeMap::from([("sui_dryRunTransactionBlock".to_string(), 11)]),
+                ..Default::default()
+            },
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "exceeds global bucket capacity")]
+    fn test_token_bucket_policy_rejects_cost_above_global_capacity() {
+        TokenBucketPolicy::new(
+            PolicyConfig::default(),
+            TokenBucketConfig {
+                client_capacity: 10,
+                proxied_client_capacity: 10,
+                global_capacity: Some(4),
+                global_refill_rate: 1,
+                request_costs: BTreeMap::from([("sui_dryRunTransactionBlock".to_string(), 5)]),
+                ..Default::default()
+            },
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "no refill rate")]
+    fn test_token_bucket_policy_rejects_global_bucket_without_refill() {
+        TokenBucketPolicy::new(
+            PolicyConfig::default(),
+            TokenBucketConfig {
+                global_capacity: Some(100),
+                global_refill_rate: 0,
+                ..Default::default()
+            },
+        );
+    }
+
+    #[sim_test]
+    async fn test_token_bucket_policy_global_bucket_and_allowlist() {
+        let mut policy = TokenBucketPolicy::new(
+            PolicyConfig::default(),
+            TokenBucketConfig {
+                client_capacity: 10,
+                client_refill_rate: 1,
+                proxied_client_capacity: 10,
+                proxied_client_refill_rate: 1,
+                global_capacity: Some(15),
+                global_refill_rate: 5,
+                allow_list: vec!["10.0.0.0/8".to_string(), "1.2.3.4".to_string()],
+                ..Default::default()
+            },
+        );
+        let alice = Some(IpAddr::V4(Ipv4Addr::new(8, 7, 6, 5)));
+        let bob = Some(IpAddr::V4(Ipv4Addr::new(8, 7, 6, 4)));
+        let internal = Some(IpAddr::V4(Ipv4Addr::new(10, 1, 2, 3)));
+        let partner = Some(IpAddr::V4(Ipv4Addr::new(1, 2, 3, 4)));
+
+        // allowlisted clients are never blocked and do not drain the global bucket
+        for _ in 0..100 {
+            for client in [internal, partner] {
+                let response = policy.handle_tally(tally(client, None, "sui_getObject"));
+                assert_eq!(response.block_client, None);
+                assert_eq!(response.decision, Some(PolicyDecision::Allowlisted));
+            }
+        }
+        assert_eq!(policy.global_tokens(), Some(15));
+
+        // alice and bob share the global bucket, which is exhausted before
+        // either of their own buckets
+        for _ in 0..10 {
+            let response = policy.handle_tally(tally(alice, None, "sui_getObject"));
+            assert_eq!(response.decision, Some(PolicyDecision::Allowed));
+        }
+        for _ in 0..5 {
+            let response = policy.handle_tally(tally(bob, None, "sui_getObject"));
+            assert_eq!(response.decision, Some(PolicyDecision::Allowed));
+        }
+        let response = policy.handle_tally(tally(bob, None, "sui_getObject"));
+        assert_eq!(response.block_client, bob);
+        assert_eq!(
+            response.decision,
+            Some(PolicyDecision::GlobalBucketExhausted)
+        );
+        assert_eq!(policy.global_tokens(), Some(0));
+
+        // global exhaustion blocks the proxied client of a proxied request,
+        // even if the fullnode is allowlisted
+        let response = policy.handle_tally(tally(internal, bob, "sui_getObject"));
+        assert_eq!(response.block_client, None);
+        assert_eq!(response.block_proxied_client, bob);
+        assert_eq!(
+            response.decision,
+            Some(PolicyDecision::GlobalBucketExhausted)
+        );
+
+        // the global bucket refills independently of client buckets
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        assert_eq!(policy.global_tokens(), Some(5));
+        let response = policy.handle_tally(tally(bob, None, "sui_getObject"));
+        assert_eq!(response.decision, Some(PolicyDecision::Allowed));
+    }
+
+    #[sim_test]
+    async fn test_token_bucket_policy_max_tracked_clients() {
+        let mut policy = TokenBucketPolicy::new(
+            PolicyConfig::default(),
+            TokenBucketConfig {
+                client_capacity: 1,
+                client_refill_rate: 1,
+                max_tracked_clients: 2,
+                ..Default::default()
+            },
+        );
+        let alice = Some(IpAddr::V4(Ipv4Addr::new(8, 7, 6, 5)));
+        let bob = Some(IpAddr::V4(Ipv4Addr::new(1, 2, 3, 4)));
+        let charlie = Some(IpAddr::V4(Ipv4Addr::new(4, 3, 2, 1)));
+
+        for client in [alice, bob] {
+            let response = policy.handle_tally(tally(client, None, "sui_getObject"));
+            assert_eq!(response.decision, Some(PolicyDecision::Allowed));
+        }
+        let response = policy.handle_tally(tally(alice, None, "sui_getObject"));
+        assert_eq!(response.block_client, alice);
+
+        // charlie takes the place of bob, the least recently seen client
+        let response = policy.handle_tally(tally(charlie, None, "sui_getObject"));
+        assert_eq!(response.decision, Some(PolicyDecision::Allowed));
+        assert_eq!(policy.tracked_clients(), 2);
+        let response = policy.handle_tally(tally(alice, None, "sui_getObject"));
+        assert_eq!(response.block_client, alice);
+        let response = policy.handle_tally(tally(bob, None, "sui_getObject"));
+        assert_eq!(response.decision, Some(PolicyDecision::Allowed));
+        assert_eq!(policy.tracked_clients(), 2);
+    }
+
     #[sim_test]
     async fn test_traffic_sketch_mem_estimate() {
         // Test for getting a rough estimate of memory usage for the traffic sketch
@@ -12,6 +12,7 @@ use lru::LruCache;
 use mysten_common::fatal;
 use mysten_metrics::monitored_scope;
 use parking_lot::RwLock;
+use serde::Serialize;
 use sui_types::{
     base_types::{ObjectID, SequenceNumber, TransactionDigest},
     committee::EpochId,
@@ -63,7 +64,6 @@ pub struct TransactionManager {
 #[derive(Clone, Debug)]
 pub struct PendingCertificateStats {
     // The time this certificate enters transaction manager.
-    #[allow(unused)]
     pub enqueue_time: Instant,
     // The time this certificate becomes ready for execution.
     pub ready_time: Option<Instant>,
@@ -82,6 +82,43 @@ pub struct PendingCertificate {
     pub stats: PendingCertificateStats,
 }
 
+/// A point-in-time view of the transactions held by TransactionManager, for inspecting why
+/// transactions are not being executed.
+#[derive(Clone, Debug, Serialize)]
+pub struct TransactionManagerSnapshot {
+    pub epoch: EpochId,
+    /// Total number of certificates waiting for input objects.
+    pub num_pending_certificates: usize,
+    /// Certificates waiting for input objects, longest waiting first.
+    pub pending_certificates: Vec<PendingCertificateSnapshot>,
+    /// Total number of certificates with all input objects available that have not finished
+    /// execution.
+    pub num_executing_certificates: usize,
+    /// Certificates with all input objects available that have not finished execution. These
+    /// are either queued for or being executed by the execution driver.
+    pub executing_certificates: Vec<TransactionDigest>,
+    /// Objects with transactions waiting on them, longest queue first.
+    pub object_queues: Vec<ObjectQueueSnapshot>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct PendingCertificateSnapshot {
+    pub digest: TransactionDigest,
+    /// The input objects this certificate is waiting for.
+    pub missing_input_objects: Vec<InputKey>,
+    /// Time since the certificate entered TransactionManager.
+    pub pending_ms: u64,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct ObjectQueueSnapshot {
+    pub object_id: ObjectID,
+    /// Number of transactions waiting on this object.
+    pub queue_len: usize,
+    /// Time the oldest transaction in the queue has been waiting.
+    pub oldest_txn_age_ms: Option<u64>,
+}
+
 struct CacheInner {
     versioned_cache: LruCache<ObjectID, SequenceNumber>,
     // we cache packages separately, because they are more expensive to look up in the db, so we
@@ -820,6 +857,75 @@ impl TransactionManager {
         inner.pending_certificates.len() + inner.executing_certificates.len()
     }
 
+    // Returns a snapshot of the transactions held by TransactionManager. At most `limit` entries
+    // are returned for each of pending certificates, executing certificates and object queues.
+    // Only the selected entries are copied while holding the lock, and they are sorted after it
+    // is released.
+    pub fn snapshot(&self, limit: usize) -> TransactionManagerSnapshot {
+        let reconfig_lock = self.inner.read();
+        let inner = reconfig_lock.read();
+
+        let mut oldest_pending: Vec<_> = inner
+            .pending_certificates
+            .values()
+            .map(|cert| (cert.stats.enqueue_time, cert))
+            .collect();
+        select_first(&mut oldest_pending, limit, |(enqueue_time, _)| {
+            *enqueue_time
+        });
+        let mut pending_certificates: Vec<_> = oldest_pending
+            .into_iter()
+            .map(|(enqueue_time, cert)| {
+                let snapshot = PendingCertificateSnapshot {
+                    digest: *cert.certificate.digest(),
+                    missing_input_objects: cert.waiting_input_objects.iter().copied().collect(),
+                    pending_ms: enqueue_time.elapsed().as_millis() as u64,
+                };
+                (enqueue_time, snapshot)
+            })
+            .collect();
+
+        let mut object_queues: Vec<_> = inner
+            .input_objects
+            .iter()
+            .map(|(object_id, txns)| ObjectQueueSnapshot {
+                object_id: *object_id,
+                queue_len: txns.len(),
+                oldest_txn_age_ms: txns
+                    .first()
+                    .map(|(time, _)| time.elapsed().as_millis() as u64),
+            })
+            .collect();
+        select_first(&mut object_queues, limit, |queue| Reverse(queue.queue_len));
+
+        let epoch = inner.epoch;
+        let num_pending_certificates = inner.pending_certificates.len();
+        let num_executing_certificates = inner.executing_certificates.len();
+        let executing_certificates = inner
+            .executing_certificates
+            .iter()
+            .take(limit)
+            .copied()
+            .collect();
+        drop(inner);
+        drop(reconfig_lock);
+
+        pending_certificates.sort_by_key(|(enqueue_time, _)| *enqueue_time);
+        object_queues.sort_by_key(|queue| Reverse(queue.queue_len));
+
+        TransactionManagerSnapshot {
+            epoch,
+            num_pending_certificates,
+            pending_certificates: pending_certificates
+                .into_iter()
+                .map(|(_, snapshot)| snapshot)
+                .collect(),
+            num_executing_certificates,
+            executing_certificates,
+            object_queues,
+        }
+    }
+
     // Reconfigures the TransactionManager for a new epoch. Existing transactions will be dropped
     // because they are no longer relevant and may be incorrect in the new epoch.
     pub(crate) fn reconfigure(&self, new_epoch: EpochId) {
@@ -931,6 +1037,15 @@ where
     }
 }
 
+/// Keep the `limit` items with the smallest keys, in no particular order, without sorting all of
+/// `items`.
+fn select_first<T, K: Ord>(items: &mut Vec<T>, limit: usize, key: impl FnMut(&T) -> K) {
+    if items.len() > limit {
+        items.select_nth_unstable_by_key(limit, key);
+        items.truncate(limit);
+    }
+}
+
 trait ResizableHashSet<K> {
     fn maybe_reserve_capacity(&mut self);
     fn maybe_shrink_capacity(&mut self);
@@ -1019,6 +1134,26 @@ mod test {
     use prometheus::Registry;
     use rand::{Rng, RngCore};
 
+    #[test]
+    fn test_select_first() {
+        let mut items = vec![5, 3, 9, 1, 7];
+        select_first(&mut items, 2, |item| *item);
+        items.sort();
+        assert_eq!(items, vec![1, 3]);
+
+        let mut items = vec![5, 3, 9, 1, 7];
+        select_first(&mut items, 2, |item| Reverse(*item));
+        items.sort();
+        assert_eq!(items, vec![7, 9]);
+
+        let mut items = vec![2, 1];
+        select_first(&mut items, 5, |item| *item);
+        assert_eq!(items, vec![2, 1]);
+
+        select_first(&mut items, 0, |item| *item);
+        assert!(items.is_empty());
+    }
+
     #[test]
     #[cfg_attr(msim, ignore)]
     fn test_available_objects_cache() {
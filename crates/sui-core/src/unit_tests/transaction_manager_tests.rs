@@ -176,6 +176,86 @@ async fn transaction_manager_basics() {
     transaction_manager.check_empty_for_testing();
 }
 
+#[tokio::test(flavor = "current_thread", start_paused = true)]
+async fn transaction_manager_snapshot() {
+    // Initialize an authority state.
+    let (owner, _keypair) = deterministic_random_account_key();
+    let gas_objects: Vec<Object> = (0..10)
+        .map(|_| {
+            let gas_object_id = ObjectID::random();
+            Object::with_id_owner_for_testing(gas_object_id, owner)
+        })
+        .collect();
+    let state = init_state_with_objects(gas_objects.clone()).await;
+    let (transaction_manager, mut rx_ready_certificates) = make_transaction_manager(&state);
+
+    // Enqueue a transaction waiting on a gas object that does not exist yet, and another one
+    // waiting on the same object a second later.
+    let gas_object_new =
+        Object::with_id_owner_version_for_testing(ObjectID::random(), 0.into(), owner);
+    let input_keys = get_input_keys(&[gas_object_new.clone()]);
+    let transaction_0 = make_transaction(gas_object_new.clone(), vec![]);
+    transaction_manager.enqueue(
+        vec![transaction_0.clone()],
+        &state.epoch_store_for_testing(),
+    );
+    sleep(Duration::from_secs(1)).await;
+    let transaction_1 = make_transaction(
+        gas_object_new.clone(),
+        vec![CallArg::Pure(bcs::to_bytes(&1u64).unwrap())],
+    );
+    transaction_manager.enqueue(
+        vec![transaction_1.clone()],
+        &state.epoch_store_for_testing(),
+    );
+
+    // Both transactions are pending on the gas object, longest waiting first.
+    let snapshot = transaction_manager.snapshot(10);
+    assert_eq!(snapshot.num_pending_certificates, 2);
+    assert_eq!(snapshot.num_executing_certificates, 0);
+    let pending: Vec<_> = snapshot
+        .pending_certificates
+        .iter()
+        .map(|cert| (cert.digest, cert.missing_input_objects.clone()))
+        .collect();
+    assert_eq!(
+        pending,
+        vec![
+            (*transaction_0.digest(), input_keys.clone()),
+            (*transaction_1.digest(), input_keys.clone()),
+        ]
+    );
+    assert_eq!(snapshot.pending_certificates[0].pending_ms, 1000);
+    assert_eq!(snapshot.object_queues.len(), 1);
+    assert_eq!(snapshot.object_queues[0].object_id, gas_object_new.id());
+    assert_eq!(snapshot.object_queues[0].queue_len, 2);
+    assert_eq!(snapshot.object_queues[0].oldest_txn_age_ms, Some(1000));
+
+    // The number of entries is limited, but totals are not.
+    let snapshot = transaction_manager.snapshot(1);
+    assert_eq!(snapshot.num_pending_certificates, 2);
+    assert_eq!(snapshot.pending_certificates.len(), 1);
+    assert_eq!(
+        snapshot.pending_certificates[0].digest,
+        *transaction_0.digest()
+    );
+
+    // Once the gas object is available, both transactions are executing.
+    transaction_manager.objects_available(input_keys, &state.epoch_store_for_testing());
+    rx_ready_certificates.recv().await.unwrap();
+    rx_ready_certificates.recv().await.unwrap();
+    let snapshot = transaction_manager.snapshot(10);
+    assert_eq!(snapshot.num_pending_certificates, 0);
+    assert!(snapshot.object_queues.is_empty());
+    assert_eq!(snapshot.num_executing_certificates, 2);
+    assert!(snapshot
+        .executing_certificates
+        .contains(transaction_0.digest()));
+    assert!(snapshot
+        .executing_certificates
+        .contains(transaction_1.digest()));
+}
+
 // Tests when objects become available, correct set of transactions can be sent to execute.
 // Specifically, we have following setup,
 //         shared_object     shared_object_2
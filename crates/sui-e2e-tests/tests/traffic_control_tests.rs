@@ -30,7 +30,8 @@ use sui_types::{
     quorum_driver_types::ExecuteTransactionRequestType,
     signature::GenericSignature,
     traffic_control::{
-        FreqThresholdConfig, PolicyConfig, PolicyType, RemoteFirewallConfig, Weight,
+        FreqThresholdConfig, PolicyConfig, PolicyType, RemoteFirewallConfig, TokenBucketConfig,
+        Weight,
     },
 };
 use test_cluster::{TestCluster, TestClusterBuilder};
@@ -714,6 +715,40 @@ async fn test_traffic_sketch_with_sampled_spam() {
     assert!(metrics.num_blocked > (expected_requests / 5) - 1000);
 }
 
+#[sim_test]
+async fn test_traffic_token_bucket() {
+    let bucket_config = TokenBucketConfig {
+        client_capacity: 1_000,
+        client_refill_rate: 500,
+        ..Default::default()
+    };
+    let policy = PolicyConfig {
+        connection_blocklist_ttl_sec: 1,
+        proxy_blocklist_ttl_sec: 1,
+        spam_policy_type: PolicyType::TokenBucket(bucket_config),
+        spam_sample_rate: Weight::one(),
+        dry_run: false,
+        ..Default::default()
+    };
+    let metrics = TrafficSim::run(
+        policy,
+        1,     // num_clients
+        1_000, // per_client_tps
+        Duration::from_secs(10),
+        true, // report
+    )
+    .await;
+
+    let expected_requests = 1_000 * 10;
+    assert!(metrics.num_requests > expected_requests - 200);
+    assert!(metrics.num_requests < expected_requests + 200);
+    // the client may send its initial burst of 1,000 requests and then
+    // 500 requests per second, so roughly 4,000 requests are blocked
+    assert!(metrics.num_blocked > 3_000);
+    assert!(metrics.num_blocked < 5_000);
+    assert!(metrics.abs_time_to_first_block.unwrap() < Duration::from_secs(3));
+}
+
 #[sim_test]
 async fn test_traffic_sketch_allowlist_mode() {
     let policy_config = PolicyConfig {
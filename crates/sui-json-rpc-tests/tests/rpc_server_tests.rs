@@ -28,6 +28,7 @@ use sui_types::base_types::{ObjectID, SuiAddress};
 use sui_types::coin::{TreasuryCap, COIN_MODULE_NAME};
 use sui_types::digests::ObjectDigest;
 use sui_types::gas_coin::GAS;
+use sui_types::object::{Object, Owner};
 use sui_types::quorum_driver_types::ExecuteTransactionRequestType;
 use sui_types::{parse_sui_struct_tag, SUI_FRAMEWORK_ADDRESS};
 use test_cluster::TestClusterBuilder;
@@ -138,6 +139,23 @@ async fn test_public_transfer_object() -> Result<(), anyhow::Error> {
         dryrun_response.object_changes,
         tx_response.object_changes.unwrap(),
     );
+
+    // The dry run also returns the objects written by the transaction.
+    let written: BTreeMap<ObjectID, Object> = dryrun_response
+        .raw_written_objects
+        .iter()
+        .map(|bytes| bcs::from_bytes::<Object>(bytes).map(|object| (object.id(), object)))
+        .collect::<Result<_, _>>()?;
+    let mut mutated: Vec<_> = tx_response
+        .effects
+        .unwrap()
+        .mutated()
+        .iter()
+        .map(|object| object.object_id())
+        .collect();
+    mutated.sort();
+    assert_eq!(written.keys().copied().collect::<Vec<_>>(), mutated);
+    assert_eq!(written[&obj].owner, Owner::AddressOwner(address));
     Ok(())
 }
 
@@ -1103,6 +1103,9 @@ pub struct DryRunTransactionBlockResponse {
     pub object_changes: Vec<ObjectChange>,
     pub balance_changes: Vec<BalanceChange>,
     pub input: SuiTransactionBlockData,
+    /// The BCS serialized objects that the transaction would create, mutate or unwrap.
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub raw_written_objects: Vec<Vec<u8>>,
 }
 
 #[derive(Eq, PartialEq, Clone, Debug, Default, Serialize, Deserialize, JsonSchema)]
@@ -212,9 +212,10 @@ async fn process_raw_request<L: Logger>(
         }
 
         // handle response tallying
+        let method = request.method.to_string();
         let response = process_request(request, api_version, service.call_data()).await;
         if let Some(traffic_controller) = &service.traffic_controller {
-            handle_traffic_resp(traffic_controller.clone(), client, &response);
+            handle_traffic_resp(traffic_controller.clone(), client, &response, method);
         }
 
         response
@@ -247,6 +248,7 @@ fn handle_traffic_resp(
     traffic_controller: Arc<TrafficController>,
     client: Option<IpAddr>,
     response: &MethodResponse,
+    method: String,
 ) {
     let error = response.error_code.map(ErrorCode::from);
     traffic_controller.tally(TrafficTally {
@@ -262,9 +264,11 @@ fn handle_traffic_resp(
         // such as `sui_executeTransactionBlock`, as this can enable
         // node operators who wish to rate limit their transcation
         // traffic and incentivize high volume clients to choose a
-        // suitable rpc provider (or run their own). Later we may want
-        // to provide a weight distribution based on the method being called.
+        // suitable rpc provider (or run their own). Policies that weigh
+        // requests by cost, such as the token bucket policy, charge
+        // according to the method being called.
         spam_weight: Weight::one(),
+        request_kind: Some(method),
         timestamp: SystemTime::now(),
     });
 }
@@ -293,6 +293,10 @@ impl TransactionExecutionApi {
             .state
             .dry_exec_transaction(txn_data.clone(), txn_digest)
             .await?;
+        let raw_written_objects = written_objects
+            .values()
+            .map(|(_, object, _)| bcs::to_bytes(object))
+            .collect::<Result<_, _>>()?;
         let object_cache = ObjectProviderCache::new_with_cache(self.state.clone(), written_objects);
         let balance_changes = get_balance_changes_from_effect(
             &object_cache,
@@ -317,6 +321,7 @@ impl TransactionExecutionApi {
             object_changes,
             balance_changes,
             input: resp.input,
+            raw_written_objects,
         })
     }
 }
@@ -0,0 +1,24 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! A mock external signer for tests. It serves the external signer protocol over stdin/stdout,
+//! signing with the keys of a plaintext keystore file:
+//!
+//! ```text
+//! mock-external-signer <path to sui.keystore>
+//! ```
+
+use anyhow::Context;
+use std::io::{stdin, stdout};
+use std::path::PathBuf;
+use sui_keys::external_keystore::serve_signer_requests;
+use sui_keys::keystore::FileBasedKeystore;
+
+fn main() -> Result<(), anyhow::Error> {
+    let path = std::env::args()
+        .nth(1)
+        .map(PathBuf::from)
+        .context("Usage: mock-external-signer <keystore path>")?;
+    let keystore = FileBasedKeystore::new(&path)?;
+    serve_signer_requests(&keystore, stdin().lock(), stdout().lock())
+}
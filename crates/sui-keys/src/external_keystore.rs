@@ -0,0 +1,491 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! A keystore that never holds private keys, and instead forwards every signing request to an
+//! external signer (a hardware wallet bridge, a KMS proxy, a signing daemon, ...).
+//!
+//! The signer speaks a line-delimited JSON protocol: the keystore writes one [SignerRequest]
+//! per line and reads one [SignerResponse] per line. The signer is reached either by spawning a
+//! process and talking to it over stdin/stdout, or by connecting to a Unix socket. The signer is
+//! only reached once the keystore is first used, and the same process or connection then serves
+//! every request until the keystore is dropped, so that a signer can hold a session (such as an
+//! unlocked hardware wallet) for the lifetime of a CLI invocation.
+
+use crate::keystore::{AccountKeystore, Alias};
+use crate::random_names::{random_name, random_names};
+use anyhow::{anyhow, bail, ensure, Context};
+use fastcrypto::encoding::{Base64, Encoding};
+use fastcrypto::hash::HashFunction;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use shared_crypto::intent::{Intent, IntentMessage};
+use std::collections::{BTreeMap, HashSet};
+use std::io::{BufRead, BufReader, Write};
+use std::path::PathBuf;
+use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+use std::sync::{Mutex, OnceLock};
+use sui_types::base_types::SuiAddress;
+use sui_types::crypto::{
+    DefaultHash, EncodeDecodeBase64, PublicKey, Signature, SuiKeyPair, SuiSignature,
+};
+
+/// How to reach an external signer.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ExternalSignerEndpoint {
+    /// Spawn `command` with `args`, writing requests to its stdin and reading responses from its
+    /// stdout. Its stdin is closed when the keystore is dropped.
+    Process {
+        command: String,
+        #[serde(default)]
+        args: Vec<String>,
+    },
+    /// Connect to a signer daemon listening on a Unix socket.
+    Socket { path: PathBuf },
+}
+
+/// A request sent to an external signer.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(tag = "method", content = "params", rename_all = "snake_case")]
+pub enum SignerRequest {
+    /// List the keys the signer can sign with.
+    ListKeys,
+    /// Sign `digest_base64` with the key `public_key_base64` (Base64 encoded `flag || pk`).
+    /// For intent messages, `intent_message_base64` carries the BCS bytes the digest was
+    /// computed from, so that the signer can display or apply a policy to what it signs.
+    Sign {
+        public_key_base64: String,
+        digest_base64: String,
+        intent_message_base64: Option<String>,
+    },
+}
+
+/// A response from an external signer.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum SignerResponse {
+    Keys(Vec<SignerKey>),
+    /// Base64 encoded `flag || sig || pk`.
+    Signature(String),
+    Error(String),
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SignerKey {
+    pub public_key_base64: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alias: Option<String>,
+}
+
+pub struct ExternalKeystore {
+    endpoint: ExternalSignerEndpoint,
+    /// Fetched from the signer on first use.
+    keys: OnceLock<SignerKeys>,
+    /// The open process or connection to the signer, if any.
+    session: Mutex<Option<SignerSession>>,
+}
+
+struct SignerKeys {
+    public_keys: BTreeMap<SuiAddress, PublicKey>,
+    aliases: BTreeMap<SuiAddress, Alias>,
+}
+
+enum SignerSession {
+    Process {
+        child: Child,
+        /// Taken on drop to close the signer's stdin before waiting for it to exit.
+        stdin: Option<ChildStdin>,
+        stdout: BufReader<ChildStdout>,
+    },
+    #[cfg(unix)]
+    Socket {
+        writer: std::os::unix::net::UnixStream,
+        reader: BufReader<std::os::unix::net::UnixStream>,
+    },
+}
+
+impl Serialize for ExternalKeystore {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.endpoint.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for ExternalKeystore {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Ok(ExternalKeystore::new(ExternalSignerEndpoint::deserialize(
+            deserializer,
+        )?))
+    }
+}
+
+impl AccountKeystore for ExternalKeystore {
+    fn sign_hashed(&self, address: &SuiAddress, msg: &[u8]) -> Result<Signature, signature::Error> {
+        self.request_signature(address, msg, None)
+            .map_err(|e| signature::Error::from_source(e.to_string()))
+    }
+
+    fn sign_secure<T>(
+        &self,
+        address: &SuiAddress,
+        msg: &T,
+        intent: Intent,
+    ) -> Result<Signature, signature::Error>
+    where
+        T: Serialize,
+    {
+        let intent_msg = IntentMessage::new(intent, msg);
+        let sign = || -> Result<Signature, anyhow::Error> {
+            let bytes = bcs::to_bytes(&intent_msg)?;
+            let mut hasher = DefaultHash::default();
+            hasher.update(&bytes);
+            let digest = hasher.finalize().digest;
+            let signature = self.request_signature(address, &digest, Some(&bytes))?;
+            signature
+                .verify_secure(&intent_msg, *address, signature.scheme())
+                .map_err(|e| anyhow!("External signer returned an invalid signature: {e}"))?;
+            Ok(signature)
+        };
+        sign().map_err(|e| signature::Error::from_source(e.to_string()))
+    }
+
+    fn add_key(
+        &mut self,
+        _alias: Option<String>,
+        _keypair: SuiKeyPair,
+    ) -> Result<(), anyhow::Error> {
+        bail!("Keys cannot be added to an external signer keystore, add them to the signer instead")
+    }
+
+    fn keys(&self) -> Vec<PublicKey> {
+        self.signer_keys()
+            .map(|keys| keys.public_keys.values().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    fn get_key(&self, address: &SuiAddress) -> Result<&SuiKeyPair, anyhow::Error> {
+        if self.signer_keys()?.public_keys.contains_key(address) {
+            bail!("The private key of [{address}] is held by an external signer and cannot be exported")
+        }
+        Err(anyhow!("Cannot find key for address: [{address}]"))
+    }
+
+    fn addresses_with_alias(&self) -> Vec<(&SuiAddress, &Alias)> {
+        self.signer_keys()
+            .map(|keys| keys.aliases.iter().collect())
+            .unwrap_or_default()
+    }
+
+    fn aliases(&self) -> Vec<&Alias> {
+        self.signer_keys()
+            .map(|keys| keys.aliases.values().collect())
+            .unwrap_or_default()
+    }
+
+    fn aliases_mut(&mut self) -> Vec<&mut Alias> {
+        // Fetch the keys first, so that there are aliases to update.
+        let _ = self.signer_keys();
+        self.keys
+            .get_mut()
+            .map(|keys| keys.aliases.values_mut().collect())
+            .unwrap_or_default()
+    }
+
+    fn get_alias_by_address(&self, address: &SuiAddress) -> Result<String, anyhow::Error> {
+        match self.signer_keys()?.aliases.get(address) {
+            Some(alias) => Ok(alias.alias.clone()),
+            None => bail!("Cannot find alias for address {address}"),
+        }
+    }
+
+    fn get_address_by_alias(&self, alias: String) -> Result<&SuiAddress, anyhow::Error> {
+        self.addresses_with_alias()
+            .iter()
+            .find(|x| x.1.alias == alias)
+            .ok_or_else(|| anyhow!("Cannot resolve alias {alias} to an address"))
+            .map(|x| x.0)
+    }
+
+    fn create_alias(&self, _alias: Option<String>) -> Result<String, anyhow::Error> {
+        bail!("Aliases of an external signer keystore are assigned by the signer")
+    }
+
+    /// Aliases reported by the signer can be renamed for the lifetime of the keystore, but the
+    /// new name is not persisted anywhere.
+    fn update_alias(
+        &mut self,
+        old_alias: &str,
+        new_alias: Option<&str>,
+    ) -> Result<String, anyhow::Error> {
+        self.update_alias_value(old_alias, new_alias)
+    }
+}
+
+impl ExternalKeystore {
+    /// A keystore signing with the signer at `endpoint`. The signer is not reached until the
+    /// keystore is first used.
+    pub fn new(endpoint: ExternalSignerEndpoint) -> Self {
+        Self {
+            endpoint,
+            keys: OnceLock::new(),
+            session: Mutex::new(None),
+        }
+    }
+
+    pub fn endpoint(&self) -> &ExternalSignerEndpoint {
+        &self.endpoint
+    }
+
+    /// Re-fetch the list of keys from the signer.
+    pub fn refresh_keys(&mut self) -> Result<(), anyhow::Error> {
+        self.keys = OnceLock::new();
+        self.signer_keys()?;
+        Ok(())
+    }
+
+    /// The keys of the signer, fetched on first use. A failed fetch is retried on the next use.
+    fn signer_keys(&self) -> Result<&SignerKeys, anyhow::Error> {
+        if let Some(keys) = self.keys.get() {
+            return Ok(keys);
+        }
+        let keys = self.fetch_keys()?;
+        Ok(self.keys.get_or_init(|| keys))
+    }
+
+    fn fetch_keys(&self) -> Result<SignerKeys, anyhow::Error> {
+        let SignerResponse::Keys(keys) = self.call(&SignerRequest::ListKeys)? else {
+            bail!("Unexpected response from external signer to `list_keys`");
+        };
+
+        let mut public_keys = BTreeMap::new();
+        let mut named = BTreeMap::new();
+        for key in keys {
+            let pk = PublicKey::decode_base64(&key.public_key_base64)
+                .map_err(|e| anyhow!("External signer returned an invalid public key: {e}"))?;
+            let address = SuiAddress::from(&pk);
+            if let Some(alias) = key.alias {
+                named.insert(address, alias);
+            }
+            public_keys.insert(address, pk);
+        }
+
+        let unnamed = public_keys.len() - named.len();
+        let mut names =
+            random_names(named.values().cloned().collect::<HashSet<_>>(), unnamed).into_iter();
+        let aliases = public_keys
+            .iter()
+            .map(|(address, pk)| {
+                let alias = named
+                    .remove(address)
+                    .or_else(|| names.next())
+                    .unwrap_or_else(|| random_name(&HashSet::new()));
+                (
+                    *address,
+                    Alias {
+                        alias,
+                        public_key_base64: pk.encode_base64(),
+                    },
+                )
+            })
+            .collect();
+        Ok(SignerKeys {
+            public_keys,
+            aliases,
+        })
+    }
+
+    fn request_signature(
+        &self,
+        address: &SuiAddress,
+        digest: &[u8],
+        intent_message: Option<&[u8]>,
+    ) -> Result<Signature, anyhow::Error> {
+        let pk = self
+            .signer_keys()?
+            .public_keys
+            .get(address)
+            .ok_or_else(|| anyhow!("Cannot find key for address: [{address}]"))?;
+        let request = SignerRequest::Sign {
+            public_key_base64: pk.encode_base64(),
+            digest_base64: Base64::encode(digest),
+            intent_message_base64: intent_message.map(Base64::encode),
+        };
+        let SignerResponse::Signature(signature) = self.call(&request)? else {
+            bail!("Unexpected response from external signer to `sign`");
+        };
+        let signature = Signature::decode_base64(&signature)
+            .map_err(|e| anyhow!("External signer returned an invalid signature: {e}"))?;
+        ensure!(
+            signature.public_key_bytes() == pk.as_ref(),
+            "External signer signed with a different key than requested"
+        );
+        Ok(signature)
+    }
+
+    /// Send `request` to the signer and read its response, opening the session with the signer
+    /// if it is not open yet. The session is closed if the exchange fails, and reopened by the
+    /// next request.
+    fn call(&self, request: &SignerRequest) -> Result<SignerResponse, anyhow::Error> {
+        let mut line = serde_json::to_string(request)?;
+        line.push('\n');
+
+        let mut session = self
+            .session
+            .lock()
+            .map_err(|_| anyhow!("External signer session poisoned"))?;
+        if session.is_none() {
+            *session = Some(SignerSession::open(&self.endpoint)?);
+        }
+        let response = session
+            .as_mut()
+            .expect("session was just opened")
+            .exchange(&line);
+        let response = match response {
+            Ok(response) => response,
+            Err(e) => {
+                *session = None;
+                return Err(e);
+            }
+        };
+
+        let response = response.trim_end();
+        ensure!(
+            !response.is_empty(),
+            "External signer returned an empty response"
+        );
+        match serde_json::from_str(response)
+            .with_context(|| format!("Cannot parse external signer response: {response}"))?
+        {
+            SignerResponse::Error(e) => bail!("External signer error: {e}"),
+            response => Ok(response),
+        }
+    }
+}
+
+impl SignerSession {
+    fn open(endpoint: &ExternalSignerEndpoint) -> Result<Self, anyhow::Error> {
+        match endpoint {
+            ExternalSignerEndpoint::Process { command, args } => {
+                let mut child = Command::new(command)
+                    .args(args)
+                    .stdin(Stdio::piped())
+                    .stdout(Stdio::piped())
+                    .spawn()
+                    .with_context(|| format!("Cannot start external signer `{command}`"))?;
+                let stdin = child
+                    .stdin
+                    .take()
+                    .expect("stdin of external signer is piped");
+                let stdout = child
+                    .stdout
+                    .take()
+                    .expect("stdout of external signer is piped");
+                Ok(SignerSession::Process {
+                    child,
+                    stdin: Some(stdin),
+                    stdout: BufReader::new(stdout),
+                })
+            }
+            #[cfg(unix)]
+            ExternalSignerEndpoint::Socket { path } => {
+                let writer = std::os::unix::net::UnixStream::connect(path).with_context(|| {
+                    format!("Cannot connect to external signer at {}", path.display())
+                })?;
+                let reader = BufReader::new(writer.try_clone()?);
+                Ok(SignerSession::Socket { writer, reader })
+            }
+            #[cfg(not(unix))]
+            ExternalSignerEndpoint::Socket { .. } => {
+                bail!("Unix socket external signers are not supported on this platform")
+            }
+        }
+    }
+
+    /// Write the request `line` and read the response line.
+    fn exchange(&mut self, line: &str) -> Result<String, anyhow::Error> {
+        let mut response = String::new();
+        let read = match self {
+            SignerSession::Process { stdin, stdout, .. } => {
+                let stdin = stdin.as_mut().expect("stdin is only taken on drop");
+                stdin.write_all(line.as_bytes())?;
+                stdin.flush()?;
+                stdout.read_line(&mut response)?
+            }
+            #[cfg(unix)]
+            SignerSession::Socket { writer, reader } => {
+                writer.write_all(line.as_bytes())?;
+                reader.read_line(&mut response)?
+            }
+        };
+        ensure!(read > 0, "External signer closed the session");
+        Ok(response)
+    }
+}
+
+impl Drop for SignerSession {
+    fn drop(&mut self) {
+        if let SignerSession::Process { child, stdin, .. } = self {
+            // Closing stdin signals the end of the request stream.
+            drop(stdin.take());
+            let _ = child.wait();
+        }
+    }
+}
+
+/// Serve [SignerRequest]s read line by line from `input` by signing with `keystore`, writing one
+/// [SignerResponse] line per request to `output`. This is the reference implementation of the
+/// signer side of the protocol, used by the `mock-external-signer` binary in tests.
+pub fn serve_signer_requests<K: AccountKeystore>(
+    keystore: &K,
+    input: impl BufRead,
+    mut output: impl Write,
+) -> Result<(), anyhow::Error> {
+    for line in input.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let response = match serde_json::from_str::<SignerRequest>(&line) {
+            Ok(request) => handle_signer_request(keystore, request)
+                .unwrap_or_else(|e| SignerResponse::Error(e.to_string())),
+            Err(e) => SignerResponse::Error(format!("Invalid request: {e}")),
+        };
+        writeln!(output, "{}", serde_json::to_string(&response)?)?;
+        output.flush()?;
+    }
+    Ok(())
+}
+
+fn handle_signer_request<K: AccountKeystore>(
+    keystore: &K,
+    request: SignerRequest,
+) -> Result<SignerResponse, anyhow::Error> {
+    Ok(match request {
+        SignerRequest::ListKeys => SignerResponse::Keys(
+            keystore
+                .keys()
+                .into_iter()
+                .map(|pk| SignerKey {
+                    alias: keystore.get_alias_by_address(&(&pk).into()).ok(),
+                    public_key_base64: pk.encode_base64(),
+                })
+                .collect(),
+        ),
+        SignerRequest::Sign {
+            public_key_base64,
+            digest_base64,
+            ..
+        } => {
+            let pk = PublicKey::decode_base64(&public_key_base64)
+                .map_err(|e| anyhow!("Invalid public key: {e}"))?;
+            let digest =
+                Base64::decode(&digest_base64).map_err(|e| anyhow!("Invalid digest: {e}"))?;
+            let signature = keystore.sign_hashed(&(&pk).into(), &digest)?;
+            SignerResponse::Signature(signature.encode_base64())
+        }
+    })
+}
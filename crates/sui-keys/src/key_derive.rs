@@ -168,6 +168,35 @@ pub fn validate_path(
     }
 }
 
+/// The default derivation path of the account at `index` for `key_scheme`, following the
+/// layout described in [derive_key_pair_from_path] with `index` as the last level.
+pub fn derivation_path_for_index(
+    key_scheme: &SignatureScheme,
+    index: u32,
+) -> Result<DerivationPath, SuiError> {
+    let path = match key_scheme {
+        SignatureScheme::ED25519 => format!(
+            "m/{DERVIATION_PATH_PURPOSE_ED25519}'/{DERIVATION_PATH_COIN_TYPE}'/0'/0'/{index}'"
+        ),
+        SignatureScheme::Secp256k1 => format!(
+            "m/{DERVIATION_PATH_PURPOSE_SECP256K1}'/{DERIVATION_PATH_COIN_TYPE}'/0'/0/{index}"
+        ),
+        SignatureScheme::Secp256r1 => format!(
+            "m/{DERVIATION_PATH_PURPOSE_SECP256R1}'/{DERIVATION_PATH_COIN_TYPE}'/0'/0/{index}"
+        ),
+        SignatureScheme::BLS12381
+        | SignatureScheme::MultiSig
+        | SignatureScheme::ZkLoginAuthenticator
+        | SignatureScheme::PasskeyAuthenticator => {
+            return Err(SuiError::UnsupportedFeatureError {
+                error: format!("key derivation not supported {:?}", key_scheme),
+            })
+        }
+    };
+    path.parse()
+        .map_err(|_| SuiError::SignatureKeyGenError("Cannot parse path".to_string()))
+}
+
 pub fn generate_new_key(
     key_scheme: SignatureScheme,
     derivation_path: Option<DerivationPath>,
@@ -1,12 +1,16 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::key_derive::{derive_key_pair_from_path, generate_new_key};
+use crate::external_keystore::ExternalKeystore;
+use crate::key_derive::{derivation_path_for_index, derive_key_pair_from_path, generate_new_key};
 use crate::random_names::{random_name, random_names};
+use aes_gcm::aead::{Aead, KeyInit, Payload};
+use aes_gcm::{Aes256Gcm, Nonce};
 use anyhow::{anyhow, bail, ensure, Context};
 use bip32::DerivationPath;
 use bip39::{Language, Mnemonic, Seed};
-use rand::{rngs::StdRng, SeedableRng};
+use fastcrypto::encoding::{Base64, Encoding};
+use rand::{rngs::StdRng, RngCore, SeedableRng};
 use regex::Regex;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use shared_crypto::intent::{Intent, IntentMessage};
@@ -15,19 +19,33 @@ use std::fmt::Write;
 use std::fmt::{Display, Formatter};
 use std::fs;
 use std::fs::File;
-use std::io::BufReader;
+use std::io::{BufReader, Write as _};
 use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
 use sui_types::base_types::SuiAddress;
 use sui_types::crypto::get_key_pair_from_rng;
 use sui_types::crypto::{
     enum_dispatch, EncodeDecodeBase64, PublicKey, Signature, SignatureScheme, SuiKeyPair,
 };
+use zeroize::Zeroizing;
+
+const ENCRYPTED_KEYSTORE_VERSION: u8 = 1;
+const DEFAULT_SCRYPT_LOG_N: u8 = 15;
+const DEFAULT_SCRYPT_R: u32 = 8;
+const DEFAULT_SCRYPT_P: u32 = 1;
+const SCRYPT_SALT_LENGTH: usize = 32;
+const AES_GCM_NONCE_LENGTH: usize = 12;
+const HD_SEED_AAD: &[u8] = b"hd_wallet_seed";
+const KEYSTORE_LOCKED_ERROR: &str =
+    "Keystore is locked. Unlock it with its passphrase before signing or adding keys.";
 
 #[derive(Serialize, Deserialize)]
 #[enum_dispatch(AccountKeystore)]
 pub enum Keystore {
     File(FileBasedKeystore),
     InMem(InMemKeystore),
+    Encrypted(EncryptedFileKeystore),
+    External(ExternalKeystore),
 }
 #[enum_dispatch]
 pub trait AccountKeystore: Send + Sync {
@@ -150,6 +168,66 @@ impl Display for Keystore {
                 writeln!(writer, "Keystore Type : InMem")?;
                 write!(f, "{}", writer)
             }
+            Keystore::Encrypted(file) => {
+                writeln!(writer, "Keystore Type : Encrypted File")?;
+                writeln!(writer, "Keystore Path : {:?}", file.path)?;
+                write!(
+                    writer,
+                    "Keystore State : {}",
+                    if file.is_locked() {
+                        "Locked"
+                    } else {
+                        "Unlocked"
+                    }
+                )?;
+                write!(f, "{}", writer)
+            }
+            Keystore::External(external) => {
+                writeln!(writer, "Keystore Type : External Signer")?;
+                write!(writer, "Signer : {:?}", external.endpoint())?;
+                write!(f, "{}", writer)
+            }
+        }
+    }
+}
+
+impl Keystore {
+    /// Open the keystore file at `path`, detecting whether it is a plaintext or an encrypted
+    /// keystore. Encrypted keystores are returned locked, and fail to open if they cannot be
+    /// parsed rather than being read as plaintext keystores.
+    pub fn open(path: &PathBuf) -> Result<Self, anyhow::Error> {
+        if EncryptedFileKeystore::is_encrypted_keystore(path) {
+            Ok(Keystore::Encrypted(EncryptedFileKeystore::new(path)?))
+        } else {
+            Ok(Keystore::File(FileBasedKeystore::new(path)?))
+        }
+    }
+
+    /// Returns true if this keystore needs a passphrase before it can sign.
+    pub fn is_locked(&self) -> bool {
+        matches!(self, Keystore::Encrypted(keystore) if keystore.is_locked())
+    }
+
+    /// Unlock an encrypted keystore. This is a no-op for other keystore types.
+    pub fn unlock(&mut self, passphrase: &str) -> Result<(), anyhow::Error> {
+        match self {
+            Keystore::Encrypted(keystore) => keystore.unlock(passphrase),
+            Keystore::File(_) | Keystore::InMem(_) | Keystore::External(_) => Ok(()),
+        }
+    }
+
+    /// Lock an encrypted keystore. This is a no-op for other keystore types.
+    pub fn lock(&mut self) {
+        if let Keystore::Encrypted(keystore) = self {
+            keystore.lock();
+        }
+    }
+
+    /// Set how an encrypted keystore reads its passphrase when it is locked and a private key is
+    /// first needed. This is a no-op for other keystore types.
+    pub fn set_passphrase_prompt(&mut self, prompt: PassphrasePrompt) {
+        if let Keystore::Encrypted(keystore) = self {
+            keystore.set_passphrase_prompt(prompt);
         }
     }
 }
@@ -330,67 +408,11 @@ impl FileBasedKeystore {
             BTreeMap::new()
         };
 
-        // check aliases
-        let mut aliases_path = path.clone();
-        aliases_path.set_extension("aliases");
-
-        let aliases = if aliases_path.exists() {
-            let reader = BufReader::new(File::open(&aliases_path).with_context(|| {
-                format!(
-                    "Cannot open aliases file in keystore: {}",
-                    aliases_path.display()
-                )
-            })?);
-
-            let aliases: Vec<Alias> = serde_json::from_reader(reader).with_context(|| {
-                format!(
-                    "Cannot deserialize aliases file in keystore: {}",
-                    aliases_path.display(),
-                )
-            })?;
-
-            aliases
-                .into_iter()
-                .map(|alias| {
-                    let key = PublicKey::decode_base64(&alias.public_key_base64);
-                    key.map(|k| (Into::<SuiAddress>::into(&k), alias))
-                })
-                .collect::<Result<BTreeMap<_, _>, _>>()
-                .map_err(|e| {
-                    anyhow!(
-                        "Invalid aliases file in keystore: {}. {}",
-                        aliases_path.display(),
-                        e
-                    )
-                })?
-        } else if keys.is_empty() {
-            BTreeMap::new()
-        } else {
-            let names: Vec<String> = random_names(HashSet::new(), keys.len());
-            let aliases = keys
-                .iter()
-                .zip(names)
-                .map(|((sui_address, skp), alias)| {
-                    let public_key_base64 = skp.public().encode_base64();
-                    (
-                        *sui_address,
-                        Alias {
-                            alias,
-                            public_key_base64,
-                        },
-                    )
-                })
-                .collect::<BTreeMap<_, _>>();
-            let aliases_store = serde_json::to_string_pretty(&aliases.values().collect::<Vec<_>>())
-                .with_context(|| {
-                    format!(
-                        "Cannot serialize aliases to file in keystore: {}",
-                        aliases_path.display()
-                    )
-                })?;
-            fs::write(aliases_path, aliases_store)?;
-            aliases
-        };
+        let public_keys = keys
+            .iter()
+            .map(|(address, skp)| (*address, skp.public()))
+            .collect::<BTreeMap<_, _>>();
+        let aliases = load_or_create_aliases(path, &public_keys)?;
 
         Ok(Self {
             keys,
@@ -403,20 +425,13 @@ impl FileBasedKeystore {
         self.path = Some(path.to_path_buf());
     }
 
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
     pub fn save_aliases(&self) -> Result<(), anyhow::Error> {
         if let Some(path) = &self.path {
-            let aliases_store =
-                serde_json::to_string_pretty(&self.aliases.values().collect::<Vec<_>>())
-                    .with_context(|| {
-                        format!(
-                            "Cannot serialize aliases to file in keystore: {}",
-                            path.display()
-                        )
-                    })?;
-
-            let mut aliases_path = path.clone();
-            aliases_path.set_extension("aliases");
-            fs::write(aliases_path, aliases_store)?
+            save_aliases(path, &self.aliases)?;
         }
         Ok(())
     }
@@ -434,7 +449,7 @@ impl FileBasedKeystore {
                     .collect::<Vec<_>>(),
             )
             .with_context(|| format!("Cannot serialize keystore to file: {}", path.display()))?;
-            fs::write(path, store)?;
+            write_atomically(path, store)?;
         }
         Ok(())
     }
@@ -602,7 +617,763 @@ impl InMemKeystore {
     }
 }
 
-fn validate_alias(alias: &str) -> Result<String, anyhow::Error> {
+/// Parameters of the scrypt key derivation function that turns the passphrase of an
+/// [EncryptedFileKeystore] into the key used to encrypt its entries.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct KdfParams {
+    pub log_n: u8,
+    pub r: u32,
+    pub p: u32,
+    /// Base64 encoded random salt.
+    pub salt: String,
+}
+
+impl KdfParams {
+    /// Fresh parameters with a random salt, using the recommended scrypt cost.
+    pub fn new_random() -> Self {
+        let mut salt = [0u8; SCRYPT_SALT_LENGTH];
+        rand::thread_rng().fill_bytes(&mut salt);
+        Self {
+            log_n: DEFAULT_SCRYPT_LOG_N,
+            r: DEFAULT_SCRYPT_R,
+            p: DEFAULT_SCRYPT_P,
+            salt: Base64::encode(salt),
+        }
+    }
+
+    fn derive_key(&self, passphrase: &str) -> Result<Zeroizing<[u8; 32]>, anyhow::Error> {
+        let salt = Base64::decode(&self.salt).map_err(|e| anyhow!("Invalid keystore salt: {e}"))?;
+        let params = scrypt::Params::new(self.log_n, self.r, self.p)
+            .map_err(|e| anyhow!("Invalid scrypt parameters in keystore: {e}"))?;
+        let mut key = Zeroizing::new([0u8; 32]);
+        scrypt::scrypt(passphrase.as_bytes(), &salt, &params, key.as_mut_slice())
+            .map_err(|e| anyhow!("Cannot derive keystore encryption key: {e}"))?;
+        Ok(key)
+    }
+}
+
+/// A value sealed with AES-256-GCM under the keystore encryption key.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct Sealed {
+    nonce: String,
+    ciphertext: String,
+}
+
+impl Sealed {
+    fn seal(
+        encryption_key: &[u8; 32],
+        aad: &[u8],
+        plaintext: &[u8],
+    ) -> Result<Self, anyhow::Error> {
+        let cipher = Aes256Gcm::new_from_slice(encryption_key)
+            .map_err(|_| anyhow!("Invalid keystore encryption key length"))?;
+        let mut nonce = [0u8; AES_GCM_NONCE_LENGTH];
+        rand::thread_rng().fill_bytes(&mut nonce);
+        let ciphertext = cipher
+            .encrypt(
+                Nonce::from_slice(&nonce),
+                Payload {
+                    msg: plaintext,
+                    aad,
+                },
+            )
+            .map_err(|_| anyhow!("Cannot encrypt keystore entry"))?;
+        Ok(Self {
+            nonce: Base64::encode(nonce),
+            ciphertext: Base64::encode(ciphertext),
+        })
+    }
+
+    fn open(
+        &self,
+        encryption_key: &[u8; 32],
+        aad: &[u8],
+    ) -> Result<Zeroizing<Vec<u8>>, anyhow::Error> {
+        let cipher = Aes256Gcm::new_from_slice(encryption_key)
+            .map_err(|_| anyhow!("Invalid keystore encryption key length"))?;
+        let nonce = Base64::decode(&self.nonce).map_err(|e| anyhow!("Invalid nonce: {e}"))?;
+        ensure!(nonce.len() == AES_GCM_NONCE_LENGTH, "Invalid nonce length");
+        let ciphertext =
+            Base64::decode(&self.ciphertext).map_err(|e| anyhow!("Invalid ciphertext: {e}"))?;
+        Ok(Zeroizing::new(
+            cipher
+                .decrypt(
+                    Nonce::from_slice(&nonce),
+                    Payload {
+                        msg: &ciphertext,
+                        aad,
+                    },
+                )
+                .map_err(|_| anyhow!("Incorrect passphrase or corrupted keystore entry"))?,
+        ))
+    }
+}
+
+/// A single encrypted keypair. The Base64 encoded public key is stored in the clear so that
+/// addresses can be listed while the keystore is locked, and is used as associated data so that
+/// an entry cannot be swapped for another one without detection.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct EncryptedKeyPair {
+    public_key_base64: String,
+    #[serde(flatten)]
+    sealed: Sealed,
+}
+
+impl EncryptedKeyPair {
+    fn encrypt(encryption_key: &[u8; 32], keypair: &SuiKeyPair) -> Result<Self, anyhow::Error> {
+        let public_key_base64 = keypair.public().encode_base64();
+        let plaintext = Zeroizing::new(keypair.to_bytes());
+        let sealed = Sealed::seal(encryption_key, public_key_base64.as_bytes(), &plaintext)?;
+        Ok(Self {
+            public_key_base64,
+            sealed,
+        })
+    }
+
+    fn decrypt(&self, encryption_key: &[u8; 32]) -> Result<SuiKeyPair, anyhow::Error> {
+        let plaintext = self
+            .sealed
+            .open(encryption_key, self.public_key_base64.as_bytes())?;
+        let keypair = SuiKeyPair::from_bytes(&plaintext)
+            .map_err(|e| anyhow!("Invalid decrypted keypair: {e}"))?;
+        ensure!(
+            keypair.public().encode_base64() == self.public_key_base64,
+            "Decrypted keypair does not match its public key"
+        );
+        Ok(keypair)
+    }
+}
+
+/// An account derived from the HD wallet seed of an [EncryptedFileKeystore].
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct HdAccount {
+    pub address: SuiAddress,
+    pub key_scheme: SignatureScheme,
+    pub index: u32,
+}
+
+/// The BIP-39 seed of an HD wallet, stored once and sealed like the keys, together with the
+/// accounts derived from it so far.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct HdWallet {
+    seed: Sealed,
+    accounts: Vec<HdAccount>,
+}
+
+/// On-disk format of an [EncryptedFileKeystore].
+#[derive(Serialize, Deserialize)]
+struct EncryptedKeystoreFile {
+    version: u8,
+    kdf: KdfParams,
+    keys: Vec<EncryptedKeyPair>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    hd_wallet: Option<HdWallet>,
+}
+
+/// Reads the passphrase of an [EncryptedFileKeystore] when one of its private keys is first
+/// needed.
+pub type PassphrasePrompt = Box<dyn Fn() -> Result<String, anyhow::Error> + Send + Sync>;
+
+/// The decrypted contents of an [EncryptedFileKeystore], only kept while it is unlocked.
+struct UnlockedKeys {
+    keys: BTreeMap<SuiAddress, SuiKeyPair>,
+    hd_seed: Option<Zeroizing<Vec<u8>>>,
+    encryption_key: Zeroizing<[u8; 32]>,
+}
+
+/// A keystore file where every private key is encrypted at rest with a key derived from a
+/// passphrase. The keystore is loaded locked: addresses, public keys and aliases are available,
+/// but signing and adding keys require it to be unlocked, either with
+/// [EncryptedFileKeystore::unlock] or, on first use, with its passphrase prompt.
+pub struct EncryptedFileKeystore {
+    kdf: KdfParams,
+    encrypted_keys: BTreeMap<SuiAddress, EncryptedKeyPair>,
+    public_keys: BTreeMap<SuiAddress, PublicKey>,
+    aliases: BTreeMap<SuiAddress, Alias>,
+    hd_wallet: Option<HdWallet>,
+    unlocked: OnceLock<UnlockedKeys>,
+    passphrase_prompt: Option<PassphrasePrompt>,
+    /// Held while prompting for the passphrase, so that concurrent signers only prompt once.
+    unlocking: Mutex<()>,
+    path: Option<PathBuf>,
+}
+
+impl Serialize for EncryptedFileKeystore {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(
+            self.path
+                .as_ref()
+                .unwrap_or(&PathBuf::default())
+                .to_str()
+                .unwrap_or(""),
+        )
+    }
+}
+
+impl<'de> Deserialize<'de> for EncryptedFileKeystore {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        use serde::de::Error;
+        EncryptedFileKeystore::new(&PathBuf::from(String::deserialize(deserializer)?))
+            .map_err(D::Error::custom)
+    }
+}
+
+impl AccountKeystore for EncryptedFileKeystore {
+    fn sign_hashed(&self, address: &SuiAddress, msg: &[u8]) -> Result<Signature, signature::Error> {
+        Ok(Signature::new_hashed(
+            msg,
+            self.get_key(address)
+                .map_err(|e| signature::Error::from_source(e.to_string()))?,
+        ))
+    }
+
+    fn sign_secure<T>(
+        &self,
+        address: &SuiAddress,
+        msg: &T,
+        intent: Intent,
+    ) -> Result<Signature, signature::Error>
+    where
+        T: Serialize,
+    {
+        Ok(Signature::new_secure(
+            &IntentMessage::new(intent, msg),
+            self.get_key(address)
+                .map_err(|e| signature::Error::from_source(e.to_string()))?,
+        ))
+    }
+
+    fn add_key(&mut self, alias: Option<String>, keypair: SuiKeyPair) -> Result<(), anyhow::Error> {
+        let address: SuiAddress = (&keypair.public()).into();
+        let encrypted = EncryptedKeyPair::encrypt(&self.unlocked()?.encryption_key, &keypair)?;
+        let alias = self.create_alias(alias)?;
+        self.aliases.insert(
+            address,
+            Alias {
+                alias,
+                public_key_base64: keypair.public().encode_base64(),
+            },
+        );
+        self.encrypted_keys.insert(address, encrypted);
+        self.public_keys.insert(address, keypair.public());
+        self.unlocked_mut()?.keys.insert(address, keypair);
+        self.save()?;
+        Ok(())
+    }
+
+    fn aliases(&self) -> Vec<&Alias> {
+        self.aliases.values().collect()
+    }
+
+    fn addresses_with_alias(&self) -> Vec<(&SuiAddress, &Alias)> {
+        self.aliases.iter().collect::<Vec<_>>()
+    }
+
+    fn aliases_mut(&mut self) -> Vec<&mut Alias> {
+        self.aliases.values_mut().collect()
+    }
+
+    /// Public keys are stored in the clear, so they are available even when locked.
+    fn keys(&self) -> Vec<PublicKey> {
+        self.public_keys.values().cloned().collect()
+    }
+
+    fn create_alias(&self, alias: Option<String>) -> Result<String, anyhow::Error> {
+        match alias {
+            Some(a) if self.alias_exists(&a) => {
+                bail!("Alias {a} already exists. Please choose another alias.")
+            }
+            Some(a) => validate_alias(&a),
+            None => Ok(random_name(
+                &self
+                    .alias_names()
+                    .into_iter()
+                    .map(|x| x.to_string())
+                    .collect::<HashSet<_>>(),
+            )),
+        }
+    }
+
+    fn get_address_by_alias(&self, alias: String) -> Result<&SuiAddress, anyhow::Error> {
+        self.addresses_with_alias()
+            .iter()
+            .find(|x| x.1.alias == alias)
+            .ok_or_else(|| anyhow!("Cannot resolve alias {alias} to an address"))
+            .map(|x| x.0)
+    }
+
+    fn get_alias_by_address(&self, address: &SuiAddress) -> Result<String, anyhow::Error> {
+        match self.aliases.get(address) {
+            Some(alias) => Ok(alias.alias.clone()),
+            None => bail!("Cannot find alias for address {address}"),
+        }
+    }
+
+    /// Unlocks the keystore with its passphrase prompt if it is locked.
+    fn get_key(&self, address: &SuiAddress) -> Result<&SuiKeyPair, anyhow::Error> {
+        if !self.public_keys.contains_key(address) {
+            bail!("Cannot find key for address: [{address}]");
+        }
+        match self.unlocked()?.keys.get(address) {
+            Some(key) => Ok(key),
+            None => Err(anyhow!("Cannot find key for address: [{address}]")),
+        }
+    }
+
+    fn update_alias(
+        &mut self,
+        old_alias: &str,
+        new_alias: Option<&str>,
+    ) -> Result<String, anyhow::Error> {
+        let new_alias_name = self.update_alias_value(old_alias, new_alias)?;
+        if let Some(path) = &self.path {
+            save_aliases(path, &self.aliases)?;
+        }
+        Ok(new_alias_name)
+    }
+}
+
+impl EncryptedFileKeystore {
+    /// Load the encrypted keystore at `path` in the locked state. If the file does not exist
+    /// yet, an empty keystore is returned and the passphrase given to the first
+    /// [EncryptedFileKeystore::unlock] call becomes its passphrase.
+    pub fn new(path: &PathBuf) -> Result<Self, anyhow::Error> {
+        let (kdf, encrypted_keys, hd_wallet) = if path.exists() {
+            let reader =
+                BufReader::new(File::open(path).with_context(|| {
+                    format!("Cannot open the keystore file: {}", path.display())
+                })?);
+            let file: EncryptedKeystoreFile =
+                serde_json::from_reader(reader).with_context(|| {
+                    format!(
+                        "Cannot deserialize the encrypted keystore file: {}",
+                        path.display()
+                    )
+                })?;
+            ensure!(
+                file.version == ENCRYPTED_KEYSTORE_VERSION,
+                "Unsupported encrypted keystore version {} in {}",
+                file.version,
+                path.display()
+            );
+            (file.kdf, file.keys, file.hd_wallet)
+        } else {
+            (KdfParams::new_random(), vec![], None)
+        };
+
+        let mut public_keys = BTreeMap::new();
+        let mut keys = BTreeMap::new();
+        for entry in encrypted_keys {
+            let public_key = PublicKey::decode_base64(&entry.public_key_base64)
+                .map_err(|e| anyhow!("Invalid keystore file: {}. {}", path.display(), e))?;
+            let address = SuiAddress::from(&public_key);
+            public_keys.insert(address, public_key);
+            keys.insert(address, entry);
+        }
+        let aliases = load_or_create_aliases(path, &public_keys)?;
+
+        Ok(Self {
+            kdf,
+            encrypted_keys: keys,
+            public_keys,
+            aliases,
+            hd_wallet,
+            unlocked: OnceLock::new(),
+            passphrase_prompt: None,
+            unlocking: Mutex::new(()),
+            path: Some(path.to_path_buf()),
+        })
+    }
+
+    /// Encrypt every key of a plaintext keystore with `passphrase`. The result is unlocked and
+    /// points at the same path, so saving it replaces the plaintext keystore in place.
+    pub fn from_file_keystore(
+        keystore: &FileBasedKeystore,
+        passphrase: &str,
+    ) -> Result<Self, anyhow::Error> {
+        let kdf = KdfParams::new_random();
+        let encryption_key = kdf.derive_key(passphrase)?;
+        let mut encrypted_keys = BTreeMap::new();
+        let mut public_keys = BTreeMap::new();
+        let mut keys = BTreeMap::new();
+        for (address, keypair) in &keystore.keys {
+            encrypted_keys.insert(
+                *address,
+                EncryptedKeyPair::encrypt(&encryption_key, keypair)?,
+            );
+            public_keys.insert(*address, keypair.public());
+            keys.insert(*address, keypair.copy());
+        }
+        Ok(Self {
+            kdf,
+            encrypted_keys,
+            public_keys,
+            aliases: keystore.aliases.clone(),
+            hd_wallet: None,
+            unlocked: OnceLock::from(UnlockedKeys {
+                keys,
+                hd_seed: None,
+                encryption_key,
+            }),
+            passphrase_prompt: None,
+            unlocking: Mutex::new(()),
+            path: keystore.path.clone(),
+        })
+    }
+
+    /// Returns true if the file at `path` is in the encrypted keystore format, i.e. holds a JSON
+    /// object rather than the JSON array of a plaintext keystore. The file may still fail to parse
+    /// as an encrypted keystore.
+    pub fn is_encrypted_keystore(path: &Path) -> bool {
+        fs::read_to_string(path).is_ok_and(|contents| contents.trim_start().starts_with('{'))
+    }
+
+    pub fn is_locked(&self) -> bool {
+        self.unlocked.get().is_none()
+    }
+
+    /// Set how the passphrase is read when the keystore is locked and a private key is first
+    /// needed. The keystore then stays unlocked until [EncryptedFileKeystore::lock] is called.
+    pub fn set_passphrase_prompt(&mut self, prompt: PassphrasePrompt) {
+        self.passphrase_prompt = Some(prompt);
+    }
+
+    /// Derive the encryption key from `passphrase` and decrypt all keys. Fails without changing
+    /// state if the passphrase is wrong.
+    pub fn unlock(&mut self, passphrase: &str) -> Result<(), anyhow::Error> {
+        self.unlocked = OnceLock::from(self.decrypt(passphrase)?);
+        Ok(())
+    }
+
+    /// Unlock the keystore with its passphrase prompt if it is locked.
+    pub fn ensure_unlocked(&self) -> Result<(), anyhow::Error> {
+        self.unlocked().map(|_| ())
+    }
+
+    /// Drop all decrypted keys, the HD wallet seed and the encryption key from memory.
+    pub fn lock(&mut self) {
+        self.unlocked.take();
+    }
+
+    fn decrypt(&self, passphrase: &str) -> Result<UnlockedKeys, anyhow::Error> {
+        let encryption_key = self.kdf.derive_key(passphrase)?;
+        let keys = self
+            .encrypted_keys
+            .iter()
+            .map(|(address, entry)| Ok((*address, entry.decrypt(&encryption_key)?)))
+            .collect::<Result<BTreeMap<_, _>, anyhow::Error>>()?;
+        let hd_seed = self
+            .hd_wallet
+            .as_ref()
+            .map(|hd_wallet| hd_wallet.seed.open(&encryption_key, HD_SEED_AAD))
+            .transpose()?;
+        Ok(UnlockedKeys {
+            keys,
+            hd_seed,
+            encryption_key,
+        })
+    }
+
+    /// The decrypted keys, reading the passphrase with the passphrase prompt if the keystore is
+    /// locked.
+    fn unlocked(&self) -> Result<&UnlockedKeys, anyhow::Error> {
+        if let Some(unlocked) = self.unlocked.get() {
+            return Ok(unlocked);
+        }
+        let Some(prompt) = &self.passphrase_prompt else {
+            bail!("{KEYSTORE_LOCKED_ERROR}");
+        };
+        let _unlocking = self
+            .unlocking
+            .lock()
+            .map_err(|_| anyhow!("Keystore unlocking poisoned"))?;
+        if let Some(unlocked) = self.unlocked.get() {
+            return Ok(unlocked);
+        }
+        let unlocked = self.decrypt(&prompt()?)?;
+        Ok(self.unlocked.get_or_init(|| unlocked))
+    }
+
+    fn unlocked_mut(&mut self) -> Result<&mut UnlockedKeys, anyhow::Error> {
+        self.unlocked()?;
+        Ok(self.unlocked.get_mut().expect("keystore was just unlocked"))
+    }
+
+    /// Re-encrypt every key under a new passphrase, unlocking the keystore first if needed.
+    pub fn change_passphrase(&mut self, new_passphrase: &str) -> Result<(), anyhow::Error> {
+        let unlocked = self.unlocked()?;
+        let kdf = KdfParams::new_random();
+        let encryption_key = kdf.derive_key(new_passphrase)?;
+        let encrypted_keys = unlocked
+            .keys
+            .iter()
+            .map(|(address, keypair)| {
+                Ok((
+                    *address,
+                    EncryptedKeyPair::encrypt(&encryption_key, keypair)?,
+                ))
+            })
+            .collect::<Result<BTreeMap<_, _>, anyhow::Error>>()?;
+        let hd_seed = unlocked
+            .hd_seed
+            .as_ref()
+            .map(|hd_seed| Sealed::seal(&encryption_key, HD_SEED_AAD, hd_seed))
+            .transpose()?;
+        if let (Some(hd_wallet), Some(hd_seed)) = (&mut self.hd_wallet, hd_seed) {
+            hd_wallet.seed = hd_seed;
+        }
+        self.encrypted_keys = encrypted_keys;
+        self.kdf = kdf;
+        self.unlocked_mut()?.encryption_key = encryption_key;
+        self.save()
+    }
+
+    pub fn has_hd_wallet(&self) -> bool {
+        self.hd_wallet.is_some()
+    }
+
+    /// The accounts derived from the HD wallet seed so far.
+    pub fn hd_accounts(&self) -> &[HdAccount] {
+        self.hd_wallet
+            .as_ref()
+            .map(|hd_wallet| hd_wallet.accounts.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// Store the seed of the mnemonic `phrase` as the HD wallet of this keystore. Only the seed
+    /// is kept, so the phrase itself cannot be recovered from the keystore. The keystore must not
+    /// have an HD wallet yet.
+    pub fn set_hd_wallet(&mut self, phrase: &str) -> Result<(), anyhow::Error> {
+        let encryption_key = &self.unlocked()?.encryption_key;
+        ensure!(
+            self.hd_wallet.is_none(),
+            "The keystore already has an HD wallet"
+        );
+        let mnemonic = Mnemonic::from_phrase(phrase, Language::English)
+            .map_err(|e| anyhow!("Invalid mnemonic phrase: {:?}", e))?;
+        let seed = Zeroizing::new(Seed::new(&mnemonic, "").as_bytes().to_vec());
+        self.hd_wallet = Some(HdWallet {
+            seed: Sealed::seal(encryption_key, HD_SEED_AAD, &seed)?,
+            accounts: vec![],
+        });
+        self.unlocked_mut()?.hd_seed = Some(seed);
+        self.save()
+    }
+
+    /// The lowest index that has not been derived yet for `key_scheme`.
+    pub fn next_hd_index(&self, key_scheme: SignatureScheme) -> u32 {
+        self.hd_accounts()
+            .iter()
+            .filter(|account| account.key_scheme == key_scheme)
+            .map(|account| account.index + 1)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// The address of the account at `index` for `key_scheme`, without adding it to the keystore.
+    pub fn hd_account_address(
+        &self,
+        key_scheme: SignatureScheme,
+        index: u32,
+    ) -> Result<SuiAddress, anyhow::Error> {
+        Ok(self.hd_key_pair(key_scheme, index)?.0)
+    }
+
+    /// Derive the account at `index` for `key_scheme` from the HD wallet seed and add its key to
+    /// the keystore. Deriving an account that was already derived returns its address.
+    pub fn derive_hd_account(
+        &mut self,
+        key_scheme: SignatureScheme,
+        index: u32,
+        alias: Option<String>,
+    ) -> Result<SuiAddress, anyhow::Error> {
+        let (address, keypair) = self.hd_key_pair(key_scheme, index)?;
+        if !self.public_keys.contains_key(&address) {
+            self.add_key(alias, keypair)?;
+        }
+        let account = HdAccount {
+            address,
+            key_scheme,
+            index,
+        };
+        if let Some(hd_wallet) = &mut self.hd_wallet {
+            if !hd_wallet.accounts.contains(&account) {
+                hd_wallet.accounts.push(account);
+            }
+        }
+        self.save()?;
+        Ok(address)
+    }
+
+    fn hd_key_pair(
+        &self,
+        key_scheme: SignatureScheme,
+        index: u32,
+    ) -> Result<(SuiAddress, SuiKeyPair), anyhow::Error> {
+        ensure!(
+            self.hd_wallet.is_some(),
+            "The keystore has no HD wallet, import a mnemonic with `sui keytool import --discover`"
+        );
+        let Some(hd_seed) = &self.unlocked()?.hd_seed else {
+            bail!("The HD wallet seed of the keystore was not decrypted");
+        };
+        let path = derivation_path_for_index(&key_scheme, index)
+            .map_err(|e| anyhow!("Cannot build derivation path: {e}"))?;
+        derive_key_pair_from_path(hd_seed, Some(path), &key_scheme)
+            .map_err(|e| anyhow!("error getting keypair {:?}", e))
+    }
+
+    /// Derive the account at the next unused index for `key_scheme`.
+    pub fn derive_next_hd_account(
+        &mut self,
+        key_scheme: SignatureScheme,
+        alias: Option<String>,
+    ) -> Result<(SuiAddress, u32), anyhow::Error> {
+        let index = self.next_hd_index(key_scheme);
+        let address = self.derive_hd_account(key_scheme, index, alias)?;
+        Ok((address, index))
+    }
+
+    /// Convert back into a plaintext keystore at the same path, unlocking the keystore first if
+    /// needed. Derived keys are kept, but the HD wallet seed is dropped since plaintext keystores
+    /// cannot hold it.
+    pub fn to_file_keystore(&self) -> Result<FileBasedKeystore, anyhow::Error> {
+        Ok(FileBasedKeystore {
+            keys: self
+                .unlocked()?
+                .keys
+                .iter()
+                .map(|(address, keypair)| (*address, keypair.copy()))
+                .collect(),
+            aliases: self.aliases.clone(),
+            path: self.path.clone(),
+        })
+    }
+
+    pub fn set_path(&mut self, path: &Path) {
+        self.path = Some(path.to_path_buf());
+    }
+
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    pub fn save(&self) -> Result<(), anyhow::Error> {
+        if let Some(path) = &self.path {
+            save_aliases(path, &self.aliases)?;
+            let store = serde_json::to_string_pretty(&EncryptedKeystoreFile {
+                version: ENCRYPTED_KEYSTORE_VERSION,
+                kdf: self.kdf.clone(),
+                keys: self.encrypted_keys.values().cloned().collect(),
+                hd_wallet: self.hd_wallet.clone(),
+            })
+            .with_context(|| format!("Cannot serialize keystore to file: {}", path.display()))?;
+            write_atomically(path, store)?;
+        }
+        Ok(())
+    }
+}
+
+/// Read the aliases file next to the keystore at `path`, or generate random aliases for
+/// `public_keys` and write them out if the file does not exist.
+fn load_or_create_aliases(
+    path: &Path,
+    public_keys: &BTreeMap<SuiAddress, PublicKey>,
+) -> Result<BTreeMap<SuiAddress, Alias>, anyhow::Error> {
+    let mut aliases_path = path.to_path_buf();
+    aliases_path.set_extension("aliases");
+
+    if aliases_path.exists() {
+        let reader = BufReader::new(File::open(&aliases_path).with_context(|| {
+            format!(
+                "Cannot open aliases file in keystore: {}",
+                aliases_path.display()
+            )
+        })?);
+
+        let aliases: Vec<Alias> = serde_json::from_reader(reader).with_context(|| {
+            format!(
+                "Cannot deserialize aliases file in keystore: {}",
+                aliases_path.display(),
+            )
+        })?;
+
+        aliases
+            .into_iter()
+            .map(|alias| {
+                let key = PublicKey::decode_base64(&alias.public_key_base64);
+                key.map(|k| (Into::<SuiAddress>::into(&k), alias))
+            })
+            .collect::<Result<BTreeMap<_, _>, _>>()
+            .map_err(|e| {
+                anyhow!(
+                    "Invalid aliases file in keystore: {}. {}",
+                    aliases_path.display(),
+                    e
+                )
+            })
+    } else if public_keys.is_empty() {
+        Ok(BTreeMap::new())
+    } else {
+        let names: Vec<String> = random_names(HashSet::new(), public_keys.len());
+        let aliases = public_keys
+            .iter()
+            .zip(names)
+            .map(|((sui_address, pk), alias)| {
+                let public_key_base64 = pk.encode_base64();
+                (
+                    *sui_address,
+                    Alias {
+                        alias,
+                        public_key_base64,
+                    },
+                )
+            })
+            .collect::<BTreeMap<_, _>>();
+        save_aliases(path, &aliases)?;
+        Ok(aliases)
+    }
+}
+
+/// Write `aliases` to the aliases file next to the keystore at `path`.
+fn save_aliases(path: &Path, aliases: &BTreeMap<SuiAddress, Alias>) -> Result<(), anyhow::Error> {
+    let aliases_store = serde_json::to_string_pretty(&aliases.values().collect::<Vec<_>>())
+        .with_context(|| {
+            format!(
+                "Cannot serialize aliases to file in keystore: {}",
+                path.display()
+            )
+        })?;
+
+    let mut aliases_path = path.to_path_buf();
+    aliases_path.set_extension("aliases");
+    write_atomically(&aliases_path, aliases_store)
+}
+
+/// Replace the file at `path` with `contents`, so that it is never left partially written: the
+/// contents are written to a temporary file in the same directory, which is then renamed over it.
+fn write_atomically(path: &Path, contents: String) -> Result<(), anyhow::Error> {
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    let mut file = tempfile::NamedTempFile::new_in(dir)
+        .with_context(|| format!("Cannot create a temporary file in {}", dir.display()))?;
+    file.write_all(contents.as_bytes())?;
+    file.as_file().sync_all()?;
+    file.persist(path)
+        .with_context(|| format!("Cannot write {}", path.display()))?;
+    Ok(())
+}
+
+/// Check that `alias` is a valid alias name, and return it without surrounding whitespace.
+pub fn validate_alias(alias: &str) -> Result<String, anyhow::Error> {
     let re = Regex::new(r"^[A-Za-z][A-Za-z0-9-_\.]*$")
         .map_err(|_| anyhow!("Cannot build the regex needed to validate the alias naming"))?;
     let alias = alias.trim();
@@ -3,13 +3,19 @@
 
 use std::fs;
 use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
 
 use fastcrypto::hash::HashFunction;
 use fastcrypto::traits::EncodeDecodeBase64;
 use sui_keys::key_derive::generate_new_key;
 use tempfile::TempDir;
 
-use sui_keys::keystore::{AccountKeystore, FileBasedKeystore, InMemKeystore, Keystore};
+use shared_crypto::intent::Intent;
+use sui_keys::external_keystore::{ExternalKeystore, ExternalSignerEndpoint};
+use sui_keys::keystore::{
+    AccountKeystore, EncryptedFileKeystore, FileBasedKeystore, InMemKeystore, Keystore,
+};
 use sui_types::crypto::{DefaultHash, SignatureScheme, SuiSignatureInner};
 use sui_types::{
     base_types::{SuiAddress, SUI_ADDRESS_LENGTH},
@@ -266,3 +272,270 @@ fn get_alias_by_address_test() {
     let address = generate_new_key(SignatureScheme::ED25519, None, None).unwrap();
     assert!(keystore.get_alias_by_address(&address.0).is_err())
 }
+
+#[test]
+fn encrypt_keystore_in_place_test() {
+    let temp_dir = TempDir::new().unwrap();
+    let keystore_path = temp_dir.path().join("sui.keystore");
+    let mut keystore = FileBasedKeystore::new(&keystore_path).unwrap();
+    let (address, _, _) = keystore
+        .generate_and_add_new_key(
+            SignatureScheme::ED25519,
+            Some("my_alias_test".to_string()),
+            None,
+            None,
+        )
+        .unwrap();
+
+    let encrypted = EncryptedFileKeystore::from_file_keystore(&keystore, "passphrase").unwrap();
+    encrypted.save().unwrap();
+    assert!(EncryptedFileKeystore::is_encrypted_keystore(&keystore_path));
+    assert!(FileBasedKeystore::new(&keystore_path).is_err());
+
+    // The keystore is loaded locked, but addresses and aliases are still readable.
+    let mut keystore = Keystore::open(&keystore_path).unwrap();
+    assert!(keystore.is_locked());
+    assert_eq!(vec![address], keystore.addresses());
+    assert_eq!(
+        "my_alias_test",
+        keystore.get_alias_by_address(&address).unwrap()
+    );
+    assert!(keystore
+        .sign_secure(&address, &b"msg".to_vec(), Intent::sui_transaction())
+        .is_err());
+
+    assert!(keystore.unlock("wrong passphrase").is_err());
+    assert!(keystore.is_locked());
+    keystore.unlock("passphrase").unwrap();
+    assert!(!keystore.is_locked());
+    assert!(keystore
+        .sign_secure(&address, &b"msg".to_vec(), Intent::sui_transaction())
+        .is_ok());
+
+    keystore.lock();
+    assert!(keystore.get_key(&address).is_err());
+}
+
+#[test]
+fn encrypted_keystore_add_key_test() {
+    let temp_dir = TempDir::new().unwrap();
+    let keystore_path = temp_dir.path().join("sui.keystore");
+    let mut keystore = Keystore::from(EncryptedFileKeystore::new(&keystore_path).unwrap());
+    assert!(keystore
+        .generate_and_add_new_key(SignatureScheme::ED25519, None, None, None)
+        .is_err());
+
+    // The first unlock of a new keystore sets its passphrase.
+    keystore.unlock("passphrase").unwrap();
+    let (address, _, _) = keystore
+        .generate_and_add_new_key(SignatureScheme::Secp256k1, None, None, None)
+        .unwrap();
+
+    let mut reloaded = Keystore::open(&keystore_path).unwrap();
+    assert!(matches!(reloaded, Keystore::Encrypted(_)));
+    reloaded.unlock("passphrase").unwrap();
+    assert_eq!(
+        keystore.get_key(&address).unwrap().public(),
+        reloaded.get_key(&address).unwrap().public()
+    );
+
+    let Keystore::Encrypted(encrypted) = &reloaded else {
+        unreachable!()
+    };
+    let plaintext = encrypted.to_file_keystore().unwrap();
+    plaintext.save().unwrap();
+    let reloaded = Keystore::open(&keystore_path).unwrap();
+    assert!(matches!(reloaded, Keystore::File(_)));
+    assert!(reloaded.get_key(&address).is_ok());
+}
+
+#[test]
+fn encrypted_keystore_unlocks_on_first_use_test() {
+    let temp_dir = TempDir::new().unwrap();
+    let keystore_path = temp_dir.path().join("sui.keystore");
+    let mut keystore = EncryptedFileKeystore::new(&keystore_path).unwrap();
+    keystore.unlock("passphrase").unwrap();
+    let (address, _, _) = keystore
+        .generate_and_add_new_key(SignatureScheme::ED25519, None, None, None)
+        .unwrap();
+
+    // Only the keystore and its aliases are left in the directory after saving.
+    let mut files: Vec<_> = fs::read_dir(temp_dir.path())
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name())
+        .collect();
+    files.sort();
+    assert_eq!(files, vec!["sui.aliases", "sui.keystore"]);
+
+    let mut keystore = Keystore::open(&keystore_path).unwrap();
+    let prompts = Arc::new(AtomicUsize::new(0));
+    let counter = prompts.clone();
+    keystore.set_passphrase_prompt(Box::new(move || {
+        counter.fetch_add(1, Ordering::SeqCst);
+        Ok("passphrase".to_string())
+    }));
+
+    // Reading public data does not prompt.
+    assert_eq!(vec![address], keystore.addresses());
+    assert!(keystore.is_locked());
+    assert_eq!(prompts.load(Ordering::SeqCst), 0);
+
+    // The first signature prompts, and the keystore then stays unlocked.
+    for _ in 0..2 {
+        keystore
+            .sign_secure(&address, &b"msg".to_vec(), Intent::sui_transaction())
+            .unwrap();
+    }
+    assert!(!keystore.is_locked());
+    assert_eq!(prompts.load(Ordering::SeqCst), 1);
+
+    // Locking again prompts on the next use, and a wrong passphrase leaves it locked.
+    keystore.lock();
+    keystore.set_passphrase_prompt(Box::new(|| Ok("wrong passphrase".to_string())));
+    assert!(keystore.get_key(&address).is_err());
+    assert!(keystore.is_locked());
+}
+
+#[test]
+fn corrupted_encrypted_keystore_test() {
+    let temp_dir = TempDir::new().unwrap();
+    let keystore_path = temp_dir.path().join("sui.keystore");
+    let mut keystore = EncryptedFileKeystore::new(&keystore_path).unwrap();
+    keystore.unlock("passphrase").unwrap();
+    keystore
+        .generate_and_add_new_key(SignatureScheme::ED25519, None, None, None)
+        .unwrap();
+
+    // A truncated encrypted keystore is not read as a plaintext keystore.
+    let contents = fs::read_to_string(&keystore_path).unwrap();
+    fs::write(&keystore_path, &contents[..contents.len() / 2]).unwrap();
+    let err = Keystore::open(&keystore_path).err().unwrap();
+    assert!(
+        err.to_string()
+            .contains("Cannot deserialize the encrypted keystore file"),
+        "{err}"
+    );
+
+    // Neither is one with an unexpected structure.
+    fs::write(&keystore_path, r#"{"version": 1}"#).unwrap();
+    assert!(Keystore::open(&keystore_path).is_err());
+}
+
+#[test]
+fn external_signer_keystore_test() {
+    let temp_dir = TempDir::new().unwrap();
+    let keystore_path = temp_dir.path().join("signer.keystore");
+    let mut signer_keystore = FileBasedKeystore::new(&keystore_path).unwrap();
+    let (address, _, _) = signer_keystore
+        .generate_and_add_new_key(
+            SignatureScheme::ED25519,
+            Some("signer_key".to_string()),
+            None,
+            None,
+        )
+        .unwrap();
+
+    let mut keystore = Keystore::from(ExternalKeystore::new(ExternalSignerEndpoint::Process {
+        command: env!("CARGO_BIN_EXE_mock-external-signer").to_string(),
+        args: vec![keystore_path.to_string_lossy().to_string()],
+    }));
+    assert_eq!(vec![address], keystore.addresses());
+    assert_eq!(
+        "signer_key",
+        keystore.get_alias_by_address(&address).unwrap()
+    );
+
+    // Signatures from the external signer match the ones made with the key directly.
+    let msg = b"msg".to_vec();
+    let external = keystore
+        .sign_secure(&address, &msg, Intent::sui_transaction())
+        .unwrap();
+    let local = signer_keystore
+        .sign_secure(&address, &msg, Intent::sui_transaction())
+        .unwrap();
+    assert_eq!(external, local);
+
+    // The private key never leaves the signer.
+    assert!(keystore.get_key(&address).is_err());
+    assert!(keystore
+        .generate_and_add_new_key(SignatureScheme::ED25519, None, None, None)
+        .is_err());
+
+    let unknown = SuiAddress::random_for_testing_only();
+    assert!(keystore
+        .sign_secure(&unknown, &msg, Intent::sui_transaction())
+        .is_err());
+
+    // The signer is only reached on first use.
+    let missing = ExternalKeystore::new(ExternalSignerEndpoint::Process {
+        command: temp_dir
+            .path()
+            .join("missing-signer")
+            .to_string_lossy()
+            .to_string(),
+        args: vec![],
+    });
+    assert!(missing.get_alias_by_address(&address).is_err());
+    assert!(missing.addresses().is_empty());
+}
+
+#[test]
+fn hd_wallet_derivation_test() {
+    let phrase = "result crisp session latin must fruit genuine question prevent start coconut brave speak student dismiss";
+    let temp_dir = TempDir::new().unwrap();
+    let keystore_path = temp_dir.path().join("sui.keystore");
+    let mut keystore = EncryptedFileKeystore::new(&keystore_path).unwrap();
+    keystore.unlock("passphrase").unwrap();
+    keystore.set_hd_wallet(phrase).unwrap();
+    assert!(keystore.set_hd_wallet(phrase).is_err());
+
+    // Account 0 is the key that a plain mnemonic import derives with the default path.
+    let mut plain = InMemKeystore::new_insecure_for_tests(0);
+    for scheme in [
+        SignatureScheme::ED25519,
+        SignatureScheme::Secp256k1,
+        SignatureScheme::Secp256r1,
+    ] {
+        let expected = plain
+            .import_from_mnemonic(phrase, scheme, None, None)
+            .unwrap();
+        let (address, index) = keystore.derive_next_hd_account(scheme, None).unwrap();
+        assert_eq!((expected, 0), (address, index));
+    }
+
+    let (second, index) = keystore
+        .derive_next_hd_account(SignatureScheme::ED25519, None)
+        .unwrap();
+    assert_eq!(1, index);
+    let expected = plain
+        .import_from_mnemonic(
+            phrase,
+            SignatureScheme::ED25519,
+            Some("m/44'/784'/0'/0'/1'".parse().unwrap()),
+            None,
+        )
+        .unwrap();
+    assert_eq!(expected, second);
+
+    // Deriving an existing index is idempotent.
+    assert_eq!(
+        second,
+        keystore
+            .derive_hd_account(SignatureScheme::ED25519, 1, None)
+            .unwrap()
+    );
+    assert_eq!(4, keystore.hd_accounts().len());
+    assert_eq!(4, keystore.addresses().len());
+
+    // The seed and the derived accounts survive a reload, and need the passphrase.
+    let mut reloaded = EncryptedFileKeystore::new(&keystore_path).unwrap();
+    assert_eq!(2, reloaded.next_hd_index(SignatureScheme::ED25519));
+    assert!(reloaded
+        .derive_next_hd_account(SignatureScheme::ED25519, None)
+        .is_err());
+    reloaded.unlock("passphrase").unwrap();
+    let (_, index) = reloaded
+        .derive_next_hd_account(SignatureScheme::ED25519, None)
+        .unwrap();
+    assert_eq!(2, index);
+}
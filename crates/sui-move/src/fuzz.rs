@@ -0,0 +1,783 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::manage_package::resolve_lock_file_path;
+use anyhow::bail;
+use clap::Parser;
+use move_binary_format::{
+    file_format::{AbilitySet, SignatureToken, Visibility},
+    CompiledModule,
+};
+use move_cli::base;
+use move_core_types::{
+    account_address::AccountAddress,
+    identifier::Identifier,
+    language_storage::{StructTag, TypeTag},
+    u256::U256,
+};
+use move_package::BuildConfig as MoveBuildConfig;
+use proptest::test_runner::{Config, RngAlgorithm, TestCaseError, TestError, TestRng, TestRunner};
+use rand::{rngs::StdRng, SeedableRng};
+use simulacrum::{InMemoryStore, Simulacrum};
+use std::{collections::BTreeSet, num::NonZeroUsize, path::Path, time::Duration};
+use sui_move_build::{check_invalid_dependencies, BuildConfig, CompiledPackage};
+use sui_swarm_config::{network_config::NetworkConfig, network_config_builder::ConfigBuilder};
+use sui_types::{
+    base_types::{ObjectID, SuiAddress, TxContext, TxContextKind},
+    effects::{TransactionEffects, TransactionEffectsAPI},
+    error::ExecutionError,
+    is_system_package,
+    object::{Object, Owner},
+    programmable_transaction_builder::ProgrammableTransactionBuilder,
+    transaction::{Argument, ObjectArg, Transaction, TransactionData},
+    SUI_CLOCK_OBJECT_ID,
+};
+use transaction_fuzzer::programmable_transaction_gen::{
+    gen_move_call_sequence, MoveCallSketch, MAX_MOVE_CALL_ARGS,
+};
+
+/// Functions whose names start with this prefix are invariants, checked after each transaction
+const INVARIANT_PREFIX: &str = "invariant_";
+
+/// The gas budget of each transaction executed while fuzzing
+const GAS_BUDGET: u64 = 5_000_000_000;
+
+type Chain = Simulacrum<StdRng, InMemoryStore>;
+
+#[derive(Parser)]
+#[group(id = "sui-move-fuzz")]
+pub struct Fuzz {
+    /// Number of random sequences of transactions to run
+    #[clap(name = "runs", long = "runs", default_value = "256")]
+    pub runs: u32,
+    /// Maximum number of transactions in each sequence
+    #[clap(
+        name = "max-transactions",
+        long = "max-transactions",
+        default_value = "16"
+    )]
+    pub max_transactions: usize,
+    /// Seed used to generate the sequences, to reproduce a previous run
+    #[clap(name = "seed", long = "seed")]
+    pub seed: Option<u64>,
+    /// Only call functions whose `<module>::<function>` name contains this string
+    #[clap(name = "filter", short = 'f', long = "filter")]
+    pub filter: Option<String>,
+}
+
+/// The outcome of fuzzing a package
+pub struct FuzzReport {
+    /// The seed the sequences were generated from
+    pub seed: u64,
+    /// The first invariant violation found, if any
+    pub violation: Option<Violation>,
+}
+
+/// A sequence of transactions after which an invariant does not hold
+pub struct Violation {
+    /// A description of each transaction of the shrunk sequence
+    pub trace: Vec<String>,
+    /// The invariant that does not hold, and why
+    pub message: String,
+}
+
+impl Fuzz {
+    pub fn execute(self, path: Option<&Path>, build_config: MoveBuildConfig) -> anyhow::Result<()> {
+        let runs = self.runs;
+        let report = self.run(path, build_config)?;
+        let Some(violation) = report.violation else {
+            println!("No invariant violations found in {runs} sequences");
+            return Ok(());
+        };
+        println!("Found a failing sequence of transactions, shrunk to:");
+        for (i, step) in violation.trace.iter().enumerate() {
+            println!("  {}. {step}", i + 1);
+        }
+        println!("{}", violation.message);
+        bail!(
+            "Invariant violation found. Re-run with `--seed {}` to reproduce it",
+            report.seed
+        )
+    }
+
+    /// Fuzzes the package at `path`, and shrinks the first failing sequence that is found
+    pub fn run(
+        self,
+        path: Option<&Path>,
+        build_config: MoveBuildConfig,
+    ) -> anyhow::Result<FuzzReport> {
+        let rerooted_path = base::reroot_path(path)?;
+        let build_config = resolve_lock_file_path(build_config, Some(&rerooted_path))?;
+        let pkg = BuildConfig {
+            config: build_config,
+            run_bytecode_verifier: true,
+            print_diags_to_stderr: true,
+            chain_id: None,
+        }
+        .build(&rerooted_path)?;
+        check_invalid_dependencies(&pkg.dependency_ids.invalid)?;
+        let dep_ids = pkg.get_dependency_storage_package_ids();
+        if let Some(id) = dep_ids.iter().find(|id| !is_system_package(**id)) {
+            bail!(
+                "Fuzzing packages with published dependencies other than the Sui framework is not \
+                supported, but the package depends on {id}"
+            );
+        }
+
+        let seed = self.seed.unwrap_or_else(rand::random);
+        let mut rng = StdRng::seed_from_u64(seed);
+        let network_config = ConfigBuilder::new_with_temp_dir()
+            .rng(&mut rng)
+            .with_chain_start_timestamp_ms(1)
+            .deterministic_committee_size(NonZeroUsize::new(1).unwrap())
+            .build();
+        let deployment = Deployment {
+            network_config,
+            seed,
+            modules: pkg.get_package_bytes(/* with_unpublished_deps */ true),
+            dep_ids,
+        };
+        // the package is published at the same address in every sequence
+        let (_, package, _) = deployment.setup()?;
+        let (functions, invariants) = discover(&pkg, package, self.filter.as_deref());
+        if functions.is_empty() {
+            bail!(
+                "No functions to fuzz: no public or entry function can be called with generated \
+                arguments"
+            );
+        }
+        if invariants.is_empty() {
+            bail!(
+                "No invariants to check. Declare invariants as public functions whose names start \
+                with `{INVARIANT_PREFIX}`, taking an immutable reference to an object and aborting \
+                if the invariant does not hold"
+            );
+        }
+        println!(
+            "Fuzzing {} functions against {} invariants with {} sequences of up to {} transactions \
+            (seed: {seed})",
+            functions.len(),
+            invariants.len(),
+            self.runs,
+            self.max_transactions,
+        );
+
+        let fuzzer = Fuzzer {
+            deployment,
+            package,
+            functions,
+            invariants,
+        };
+        let mut seed_bytes = [0u8; 32];
+        seed_bytes[..8].copy_from_slice(&seed.to_le_bytes());
+        let mut runner = TestRunner::new_with_rng(
+            Config {
+                cases: self.runs,
+                failure_persistence: None,
+                ..Config::default()
+            },
+            TestRng::from_seed(RngAlgorithm::ChaCha, &seed_bytes),
+        );
+        let result = runner.run(
+            &gen_move_call_sequence(self.max_transactions),
+            |calls| match fuzzer.run_sequence(&calls, None) {
+                Ok(None) => Ok(()),
+                Ok(Some(violation)) => Err(TestCaseError::fail(violation)),
+                Err(e) => Err(TestCaseError::fail(format!("Internal error: {e}"))),
+            },
+        );
+        let violation = match result {
+            Ok(()) => None,
+            Err(TestError::Fail(reason, calls)) => {
+                // replay the shrunk sequence to describe its transactions
+                let mut trace = vec![];
+                let message = fuzzer
+                    .run_sequence(&calls, Some(&mut trace))?
+                    .unwrap_or_else(|| reason.to_string());
+                Some(Violation { trace, message })
+            }
+            Err(TestError::Abort(reason)) => bail!("Fuzzing aborted: {reason}"),
+        };
+        Ok(FuzzReport { seed, violation })
+    }
+}
+
+/// The primitive types of the arguments that are generated for calls
+#[derive(Clone, Copy)]
+enum PureType {
+    Bool,
+    U8,
+    U16,
+    U32,
+    U64,
+    U128,
+    U256,
+    Address,
+}
+
+enum Parameter {
+    Pure(PureType),
+    Object { type_: StructTag, mutable: bool },
+}
+
+/// A public or entry function of the package that can be called with generated arguments
+struct FuzzFunction {
+    module: Identifier,
+    name: Identifier,
+    /// The parameters of the function, except for the `TxContext`
+    parameters: Vec<Parameter>,
+    /// The positions of the returned objects, which are transferred to the sender
+    returned_objects: Vec<u16>,
+}
+
+/// A function checking an invariant of the objects of a type, by aborting if it does not hold
+struct Invariant {
+    module: Identifier,
+    name: Identifier,
+    type_: StructTag,
+}
+
+/// The package to fuzz and the genesis of the chains it is published on
+struct Deployment {
+    network_config: NetworkConfig,
+    seed: u64,
+    modules: Vec<Vec<u8>>,
+    dep_ids: Vec<ObjectID>,
+}
+
+struct Fuzzer {
+    deployment: Deployment,
+    package: ObjectID,
+    functions: Vec<FuzzFunction>,
+    invariants: Vec<Invariant>,
+}
+
+impl Deployment {
+    /// Creates a new chain from the genesis and publishes the package on it. Returns the chain,
+    /// the ID of the package and the IDs of the objects that can be passed to functions: the
+    /// clock, the gas coins of the accounts and the objects created when publishing the package.
+    fn setup(&self) -> anyhow::Result<(Chain, ObjectID, BTreeSet<ObjectID>)> {
+        let mut chain = Simulacrum::new_with_network_config_store(
+            &self.network_config,
+            StdRng::seed_from_u64(self.seed),
+            InMemoryStore::new(&self.network_config.genesis),
+        );
+        let accounts = accounts(&chain);
+        let mut objects = BTreeSet::from([SUI_CLOCK_OBJECT_ID]);
+        for account in &accounts {
+            objects.extend(chain.store().owned_objects(*account).map(|o| o.id()));
+        }
+
+        let mut builder = ProgrammableTransactionBuilder::new();
+        builder.publish_immutable(self.modules.clone(), self.dep_ids.clone());
+        let (effects, error) = execute(&mut chain, accounts[0], builder, &[])?;
+        if let Some(error) = error {
+            bail!("Failed to publish the package: {}", error.kind());
+        }
+        let mut package = None;
+        for ((id, _, _), _) in effects.created() {
+            match chain.store().get_object(&id) {
+                Some(object) if object.is_package() => package = Some(id),
+                _ => {
+                    objects.insert(id);
+                }
+            }
+        }
+        let Some(package) = package else {
+            bail!("Failed to publish the package: no package was created");
+        };
+        Ok((chain, package, objects))
+    }
+}
+
+impl Fuzzer {
+    /// Runs the sequence of calls on a new chain, checking the invariants after each transaction.
+    /// Returns a description of the first invariant violation, if any, and describes each
+    /// transaction in `trace` if provided.
+    fn run_sequence(
+        &self,
+        calls: &[MoveCallSketch],
+        mut trace: Option<&mut Vec<String>>,
+    ) -> anyhow::Result<Option<String>> {
+        let (mut chain, _, mut objects) = self.deployment.setup()?;
+        let accounts = accounts(&chain);
+        if let Some(violation) = self.check_invariants(&mut chain, &mut objects, &accounts)? {
+            return Ok(Some(violation));
+        }
+        for call in calls {
+            if call.advance_clock_ms > 0 {
+                chain.advance_clock(Duration::from_millis(call.advance_clock_ms));
+            }
+            let function = &self.functions[call.function.index(self.functions.len())];
+            let sender = accounts[call.sender.index(accounts.len())];
+            // skip calls for which there are no objects to pass
+            let Some((builder, inputs, description)) =
+                self.build_call(&chain, &objects, &accounts, function, sender, call)?
+            else {
+                continue;
+            };
+            let outcome = match execute(&mut chain, sender, builder, &inputs) {
+                Ok((effects, error)) => {
+                    track(&mut objects, &effects);
+                    match error {
+                        None => "success".to_string(),
+                        Some(error) => format!("failure: {}", error.kind()),
+                    }
+                }
+                Err(e) => format!("rejected: {e}"),
+            };
+            if let Some(trace) = trace.as_deref_mut() {
+                trace.push(format!("{description} -> {outcome}"));
+            }
+            if let Some(violation) = self.check_invariants(&mut chain, &mut objects, &accounts)? {
+                return Ok(Some(violation));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Builds the transaction for the call, picking its arguments among the objects available to
+    /// the sender. Returns the transaction, the IDs of its object inputs and a description of the
+    /// call, or `None` if there is no object to pass to one of the parameters.
+    fn build_call(
+        &self,
+        chain: &Chain,
+        objects: &BTreeSet<ObjectID>,
+        accounts: &[SuiAddress],
+        function: &FuzzFunction,
+        sender: SuiAddress,
+        call: &MoveCallSketch,
+    ) -> anyhow::Result<Option<(ProgrammableTransactionBuilder, Vec<ObjectID>, String)>> {
+        let mut builder = ProgrammableTransactionBuilder::new();
+        let mut arguments = vec![];
+        let mut inputs = vec![];
+        let mut descriptions = vec![];
+        for (parameter, sketch) in function.parameters.iter().zip(&call.args) {
+            match parameter {
+                Parameter::Pure(ty) => {
+                    let (bytes, description) = pure_value(*ty, sketch.value, accounts);
+                    arguments.push(builder.pure_bytes(bytes, false));
+                    descriptions.push(description);
+                }
+                Parameter::Object { type_, mutable } => {
+                    let candidates = objects
+                        .iter()
+                        .filter_map(|id| chain.store().get_object(id))
+                        .filter(|o| {
+                            o.struct_tag().as_ref() == Some(type_)
+                                && !inputs.contains(&o.id())
+                                && match o.owner {
+                                    Owner::AddressOwner(owner) => owner == sender,
+                                    Owner::Shared { .. } => true,
+                                    Owner::Immutable => !mutable,
+                                    Owner::ObjectOwner(_) => false,
+                                }
+                        })
+                        .collect::<Vec<_>>();
+                    if candidates.is_empty() {
+                        return Ok(None);
+                    }
+                    let object = &candidates[sketch.object.index(candidates.len())];
+                    arguments.push(builder.obj(object_arg(object, *mutable))?);
+                    inputs.push(object.id());
+                    descriptions.push(object.id().to_string());
+                }
+            }
+        }
+        let result = builder.programmable_move_call(
+            self.package,
+            function.module.clone(),
+            function.name.clone(),
+            vec![],
+            arguments,
+        );
+        if let (Argument::Result(cmd), false) = (result, function.returned_objects.is_empty()) {
+            let returned = function
+                .returned_objects
+                .iter()
+                .map(|i| Argument::NestedResult(cmd, *i))
+                .collect();
+            builder.transfer_args(sender, returned);
+        }
+        let description = format!(
+            "{sender} calls {}::{}({})",
+            function.module,
+            function.name,
+            descriptions.join(", "),
+        );
+        Ok(Some((builder, inputs, description)))
+    }
+
+    /// Calls each invariant on every object of its type. Returns a description of the first
+    /// invariant that fails, if any.
+    fn check_invariants(
+        &self,
+        chain: &mut Chain,
+        objects: &mut BTreeSet<ObjectID>,
+        accounts: &[SuiAddress],
+    ) -> anyhow::Result<Option<String>> {
+        for invariant in &self.invariants {
+            let targets = objects
+                .iter()
+                .filter_map(|id| chain.store().get_object(id))
+                .filter(|o| o.struct_tag().as_ref() == Some(&invariant.type_))
+                .collect::<Vec<_>>();
+            for object in targets {
+                let sender = match object.owner {
+                    Owner::AddressOwner(owner) => owner,
+                    Owner::Shared { .. } | Owner::Immutable => accounts[0],
+                    Owner::ObjectOwner(_) => continue,
+                };
+                let mut builder = ProgrammableTransactionBuilder::new();
+                let argument = builder.obj(object_arg(&object, /* mutable */ false))?;
+                builder.programmable_move_call(
+                    self.package,
+                    invariant.module.clone(),
+                    invariant.name.clone(),
+                    vec![],
+                    vec![argument],
+                );
+                let Ok((effects, error)) = execute(chain, sender, builder, &[object.id()]) else {
+                    continue;
+                };
+                track(objects, &effects);
+                if let Some(error) = error {
+                    return Ok(Some(format!(
+                        "Invariant {}::{} does not hold for object {}: {}",
+                        invariant.module,
+                        invariant.name,
+                        object.id(),
+                        error.kind(),
+                    )));
+                }
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// Finds the functions of the package that can be called with generated arguments, and its
+/// invariants. Functions that cannot be fuzzed are reported and skipped.
+fn discover(
+    pkg: &CompiledPackage,
+    package: ObjectID,
+    filter: Option<&str>,
+) -> (Vec<FuzzFunction>, Vec<Invariant>) {
+    let mut functions = vec![];
+    let mut invariants = vec![];
+    for module in pkg.get_modules() {
+        let module_name = module.self_id().name().to_owned();
+        for def in module.function_defs() {
+            let handle = module.function_handle_at(def.function);
+            let name = module.identifier_at(handle.name).to_owned();
+            let qualified_name = format!("{module_name}::{name}");
+            let is_invariant = name.as_str().starts_with(INVARIANT_PREFIX);
+            let callable = def.visibility == Visibility::Public || (def.is_entry && !is_invariant);
+            if !callable || (!is_invariant && filter.is_some_and(|f| !qualified_name.contains(f))) {
+                continue;
+            }
+            if !handle.type_parameters.is_empty() {
+                eprintln!("Skipping {qualified_name}: generic functions are not supported");
+                continue;
+            }
+            let parameters = module
+                .signature_at(handle.parameters)
+                .0
+                .iter()
+                .filter(|t| TxContext::kind(module, t) == TxContextKind::None)
+                .map(|t| parameter(module, package, t))
+                .collect::<Option<Vec<_>>>();
+
+            if is_invariant {
+                match parameters.as_deref() {
+                    Some(
+                        [Parameter::Object {
+                            type_,
+                            mutable: false,
+                        }],
+                    ) => invariants.push(Invariant {
+                        module: module_name.clone(),
+                        name,
+                        type_: type_.clone(),
+                    }),
+                    _ => eprintln!(
+                        "Skipping invariant {qualified_name}: invariants must take a single \
+                        immutable reference to an object"
+                    ),
+                }
+                continue;
+            }
+            let Some(parameters) = parameters else {
+                eprintln!(
+                    "Skipping {qualified_name}: only primitive values, addresses and objects can \
+                    be generated as arguments"
+                );
+                continue;
+            };
+            if parameters.len() > MAX_MOVE_CALL_ARGS {
+                eprintln!(
+                    "Skipping {qualified_name}: functions with more than {MAX_MOVE_CALL_ARGS} \
+                    parameters are not supported"
+                );
+                continue;
+            }
+            let returned_objects = module
+                .signature_at(handle.return_)
+                .0
+                .iter()
+                .enumerate()
+                .filter(|(_, t)| {
+                    datatype_abilities(module, t).is_some_and(|a| a.has_key() && a.has_store())
+                })
+                .map(|(i, _)| i as u16)
+                .collect();
+            functions.push(FuzzFunction {
+                module: module_name.clone(),
+                name,
+                parameters,
+                returned_objects,
+            });
+        }
+    }
+    (functions, invariants)
+}
+
+/// Returns how arguments are generated for the parameter, or `None` if they cannot be
+fn parameter(
+    module: &CompiledModule,
+    package: ObjectID,
+    token: &SignatureToken,
+) -> Option<Parameter> {
+    use SignatureToken as S;
+    let (inner, mutable) = match token {
+        S::Reference(inner) => (&**inner, false),
+        S::MutableReference(inner) => (&**inner, true),
+        _ => {
+            let ty = match token {
+                S::Bool => PureType::Bool,
+                S::U8 => PureType::U8,
+                S::U16 => PureType::U16,
+                S::U32 => PureType::U32,
+                S::U64 => PureType::U64,
+                S::U128 => PureType::U128,
+                S::U256 => PureType::U256,
+                S::Address => PureType::Address,
+                _ => return object_parameter(module, package, token, /* mutable */ true),
+            };
+            return Some(Parameter::Pure(ty));
+        }
+    };
+    object_parameter(module, package, inner, mutable)
+}
+
+fn object_parameter(
+    module: &CompiledModule,
+    package: ObjectID,
+    token: &SignatureToken,
+    mutable: bool,
+) -> Option<Parameter> {
+    if !datatype_abilities(module, token)?.has_key() {
+        return None;
+    }
+    let TypeTag::Struct(type_) = type_tag(module, package, token)? else {
+        return None;
+    };
+    Some(Parameter::Object {
+        type_: *type_,
+        mutable,
+    })
+}
+
+fn datatype_abilities(module: &CompiledModule, token: &SignatureToken) -> Option<AbilitySet> {
+    let idx = match token {
+        SignatureToken::Datatype(idx) => *idx,
+        SignatureToken::DatatypeInstantiation(inst) => inst.0,
+        _ => return None,
+    };
+    Some(module.datatype_handle_at(idx).abilities)
+}
+
+/// Converts the signature token to a type, where the types of the package being fuzzed are at the
+/// address it is published at
+fn type_tag(module: &CompiledModule, package: ObjectID, token: &SignatureToken) -> Option<TypeTag> {
+    use SignatureToken as S;
+    Some(match token {
+        S::Bool => TypeTag::Bool,
+        S::U8 => TypeTag::U8,
+        S::U16 => TypeTag::U16,
+        S::U32 => TypeTag::U32,
+        S::U64 => TypeTag::U64,
+        S::U128 => TypeTag::U128,
+        S::U256 => TypeTag::U256,
+        S::Address => TypeTag::Address,
+        S::Signer => TypeTag::Signer,
+        S::Vector(t) => TypeTag::Vector(Box::new(type_tag(module, package, t)?)),
+        S::Datatype(_) | S::DatatypeInstantiation(_) => {
+            let (idx, type_args) = match token {
+                S::Datatype(idx) => (*idx, &[][..]),
+                S::DatatypeInstantiation(inst) => (inst.0, &inst.1[..]),
+                _ => unreachable!(),
+            };
+            let handle = module.datatype_handle_at(idx);
+            let module_handle = module.module_handle_at(handle.module);
+            let address = *module.address_identifier_at(module_handle.address);
+            TypeTag::Struct(Box::new(StructTag {
+                address: if address == AccountAddress::ZERO {
+                    package.into()
+                } else {
+                    address
+                },
+                module: module.identifier_at(module_handle.name).to_owned(),
+                name: module.identifier_at(handle.name).to_owned(),
+                type_params: type_args
+                    .iter()
+                    .map(|t| type_tag(module, package, t))
+                    .collect::<Option<_>>()?,
+            }))
+        }
+        S::Reference(_) | S::MutableReference(_) | S::TypeParameter(_) => return None,
+    })
+}
+
+/// Returns the BCS bytes and a description of the value of the given type generated from `value`.
+/// Addresses are picked among the accounts of the chain.
+fn pure_value(ty: PureType, value: u128, accounts: &[SuiAddress]) -> (Vec<u8>, String) {
+    match ty {
+        PureType::Bool => {
+            let v = value & 1 == 1;
+            (vec![v as u8], v.to_string())
+        }
+        PureType::U8 => (vec![value as u8], (value as u8).to_string()),
+        PureType::U16 => {
+            let v = value as u16;
+            (v.to_le_bytes().to_vec(), v.to_string())
+        }
+        PureType::U32 => {
+            let v = value as u32;
+            (v.to_le_bytes().to_vec(), v.to_string())
+        }
+        PureType::U64 => {
+            let v = value as u64;
+            (v.to_le_bytes().to_vec(), v.to_string())
+        }
+        PureType::U128 => (value.to_le_bytes().to_vec(), value.to_string()),
+        PureType::U256 => {
+            let v = U256::from(value);
+            (v.to_le_bytes().to_vec(), v.to_string())
+        }
+        PureType::Address => {
+            let v = accounts[(value % accounts.len() as u128) as usize];
+            (v.to_vec(), v.to_string())
+        }
+    }
+}
+
+fn object_arg(object: &Object, mutable: bool) -> ObjectArg {
+    match object.owner {
+        Owner::Shared {
+            initial_shared_version,
+        } => ObjectArg::SharedObject {
+            id: object.id(),
+            initial_shared_version,
+            mutable,
+        },
+        _ => ObjectArg::ImmOrOwnedObject(object.compute_object_reference()),
+    }
+}
+
+fn accounts(chain: &Chain) -> Vec<SuiAddress> {
+    chain.keystore().accounts().map(|(a, _)| *a).collect()
+}
+
+/// Tracks the objects created or unwrapped by the transaction, so they can be passed to functions
+fn track(objects: &mut BTreeSet<ObjectID>, effects: &TransactionEffects) {
+    objects.extend(
+        effects
+            .created()
+            .into_iter()
+            .chain(effects.unwrapped())
+            .map(|((id, _, _), _)| id),
+    );
+}
+
+/// Signs and executes the transaction as `sender`, paying for gas with one of the sender's coins
+/// that is not among the `inputs` of the transaction
+fn execute(
+    chain: &mut Chain,
+    sender: SuiAddress,
+    builder: ProgrammableTransactionBuilder,
+    inputs: &[ObjectID],
+) -> anyhow::Result<(TransactionEffects, Option<ExecutionError>)> {
+    let Some(gas) = chain
+        .store()
+        .owned_objects(sender)
+        .find(|o| o.is_gas_coin() && !inputs.contains(&o.id()))
+    else {
+        bail!("{sender} has no gas coin left to pay for the transaction");
+    };
+    let data = TransactionData::new_programmable(
+        sender,
+        vec![gas.compute_object_reference()],
+        builder.finish(),
+        GAS_BUDGET,
+        chain.reference_gas_price(),
+    );
+    let Some((_, key)) = chain.keystore().accounts().find(|(a, _)| **a == sender) else {
+        bail!("No key for account {sender}");
+    };
+    let transaction = Transaction::from_data_and_signer(data, vec![key]);
+    chain.execute_transaction(transaction)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn fuzz(filter: Option<&str>) -> FuzzReport {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/data/fuzz");
+        let out = tempfile::tempdir().unwrap();
+        let build_config = MoveBuildConfig {
+            install_dir: Some(out.path().to_path_buf()),
+            lock_file: Some(out.path().join("Move.lock")),
+            ..Default::default()
+        };
+        Fuzz {
+            runs: 64,
+            max_transactions: 8,
+            seed: Some(0),
+            filter: filter.map(str::to_string),
+        }
+        .run(Some(&path), build_config)
+        .unwrap()
+    }
+
+    #[test]
+    fn test_fuzz_finds_and_shrinks_violation() {
+        let violation = fuzz(None).violation.expect("the invariant can be broken");
+        assert!(
+            violation
+                .message
+                .contains("counters::invariant_below_limit"),
+            "{}",
+            violation.message
+        );
+        // setting a value above the limit breaks the invariant on its own
+        assert_eq!(violation.trace.len(), 1, "{:?}", violation.trace);
+        assert!(
+            violation.trace[0].contains("calls counters::set("),
+            "{}",
+            violation.trace[0]
+        );
+    }
+
+    #[test]
+    fn test_fuzz_holding_invariant() {
+        let report = fuzz(Some("counters::set_capped"));
+        assert!(
+            report.violation.is_none(),
+            "{}",
+            report.violation.unwrap().message
+        );
+    }
+}
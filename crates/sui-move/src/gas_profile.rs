@@ -0,0 +1,20 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use clap::Parser;
+use move_cli::base::gas_profile;
+use move_package::BuildConfig;
+use std::path::Path;
+
+#[derive(Parser)]
+#[group(id = "sui-move-gas-profile")]
+pub struct GasProfile {
+    #[clap(flatten)]
+    pub gas_profile: gas_profile::GasProfile,
+}
+
+impl GasProfile {
+    pub fn execute(self, path: Option<&Path>, build_config: BuildConfig) -> anyhow::Result<()> {
+        self.gas_profile.execute(path, build_config)
+    }
+}
@@ -14,11 +14,17 @@ pub mod build;
 pub mod coverage;
 #[cfg(feature = "disassemble")]
 pub mod disassemble;
+#[cfg(feature = "fuzz")]
+pub mod fuzz;
+pub mod gas_profile;
 pub mod manage_package;
 pub mod migrate;
+#[cfg(feature = "unit_test")]
+pub mod mutate;
 pub mod new;
 #[cfg(feature = "unit_test")]
 pub mod unit_test;
+pub mod unused;
 
 #[derive(Parser)]
 pub enum Command {
@@ -28,11 +34,17 @@ pub enum Command {
     Coverage(coverage::Coverage),
     #[cfg(feature = "disassemble")]
     Disassemble(disassemble::Disassemble),
+    #[cfg(feature = "fuzz")]
+    Fuzz(fuzz::Fuzz),
+    GasProfile(gas_profile::GasProfile),
     ManagePackage(manage_package::ManagePackage),
     Migrate(migrate::Migrate),
+    #[cfg(feature = "unit_test")]
+    Mutate(mutate::Mutate),
     New(new::New),
     #[cfg(feature = "unit_test")]
     Test(unit_test::Test),
+    Unused(unused::Unused),
 }
 #[derive(Parser)]
 pub struct Calib {
@@ -57,8 +69,13 @@ pub fn execute_move_command(
         Command::Coverage(c) => c.execute(package_path, build_config),
         #[cfg(feature = "disassemble")]
         Command::Disassemble(c) => c.execute(package_path, build_config),
+        #[cfg(feature = "fuzz")]
+        Command::Fuzz(c) => c.execute(package_path, build_config),
+        Command::GasProfile(c) => c.execute(package_path, build_config),
         Command::ManagePackage(c) => c.execute(package_path, build_config),
         Command::Migrate(c) => c.execute(package_path, build_config),
+        #[cfg(feature = "unit_test")]
+        Command::Mutate(c) => c.execute(package_path, build_config),
         Command::New(c) => c.execute(package_path),
 
         #[cfg(feature = "unit_test")]
@@ -72,5 +89,6 @@ pub fn execute_move_command(
 
             Ok(())
         }
+        Command::Unused(c) => c.execute(package_path, build_config),
     }
 }
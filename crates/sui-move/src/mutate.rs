@@ -0,0 +1,38 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::unit_test::{MAX_UNIT_TEST_INSTRUCTIONS, SET_EXTENSION_HOOK};
+use clap::Parser;
+use move_cli::base::mutate;
+use move_package::BuildConfig;
+use once_cell::sync::Lazy;
+use std::path::Path;
+use sui_protocol_config::ProtocolConfig;
+use sui_types::gas_model::tables::initial_cost_schedule_for_unit_tests;
+
+#[derive(Parser)]
+#[group(id = "sui-move-mutate")]
+pub struct Mutate {
+    #[clap(flatten)]
+    pub mutate: mutate::Mutate,
+}
+
+impl Mutate {
+    pub fn execute(mut self, path: Option<&Path>, build_config: BuildConfig) -> anyhow::Result<()> {
+        // bind the extension hook if it has not yet been done
+        Lazy::force(&SET_EXTENSION_HOOK);
+        // tests of mutants are bounded like those run by `sui move test`
+        self.mutate
+            .gas_limit
+            .get_or_insert(MAX_UNIT_TEST_INSTRUCTIONS);
+        self.mutate.execute(
+            path,
+            build_config,
+            sui_move_natives::all_natives(
+                /* silent */ true,
+                &ProtocolConfig::get_for_max_version_UNSAFE(),
+            ),
+            Some(initial_cost_schedule_for_unit_tests()),
+        )
+    }
+}
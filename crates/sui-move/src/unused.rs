@@ -0,0 +1,25 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use clap::Parser;
+use move_cli::base::unused;
+use move_package::BuildConfig as MoveBuildConfig;
+use std::path::Path;
+
+/// Module initializers are called by the runtime when a package is published.
+const INIT_FN_NAME: &str = "init";
+
+#[derive(Parser)]
+#[group(id = "sui-move-unused")]
+pub struct Unused {
+    #[clap(flatten)]
+    pub unused: unused::Unused,
+}
+
+impl Unused {
+    pub fn execute(self, path: Option<&Path>, config: MoveBuildConfig) -> anyhow::Result<()> {
+        let mut unused = self.unused;
+        unused.extra_roots.push(INIT_FN_NAME.to_string());
+        unused.execute(path, config)
+    }
+}
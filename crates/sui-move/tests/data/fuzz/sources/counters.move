@@ -0,0 +1,40 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+module fuzz::counters;
+
+const EInvariantViolated: u64 = 0;
+const LIMIT: u64 = 1000;
+
+/// Its invariant is broken by setting a value of `LIMIT` or more
+public struct Counter has key {
+    id: UID,
+    value: u64,
+}
+
+/// Its invariant always holds, as its value is capped
+public struct CappedCounter has key {
+    id: UID,
+    value: u64,
+}
+
+fun init(ctx: &mut TxContext) {
+    transfer::share_object(Counter { id: object::new(ctx), value: 0 });
+    transfer::share_object(CappedCounter { id: object::new(ctx), value: 0 });
+}
+
+public fun set(counter: &mut Counter, value: u64) {
+    counter.value = value;
+}
+
+public fun set_capped(counter: &mut CappedCounter, value: u64) {
+    counter.value = if (value < LIMIT) value else LIMIT - 1;
+}
+
+public fun invariant_below_limit(counter: &Counter) {
+    assert!(counter.value < LIMIT, EInvariantViolated);
+}
+
+public fun invariant_capped_below_limit(counter: &CappedCounter) {
+    assert!(counter.value < LIMIT, EInvariantViolated);
+}
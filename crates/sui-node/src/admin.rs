@@ -10,12 +10,13 @@ use axum::{
 };
 use base64::Engine;
 use humantime::parse_duration;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use std::{
     net::{IpAddr, Ipv4Addr, SocketAddr},
     str::FromStr,
 };
+use sui_core::overload_monitor::AuthorityOverloadSnapshot;
 use sui_types::{
     base_types::AuthorityName,
     crypto::{RandomnessPartialSignature, RandomnessRound, RandomnessSignature},
@@ -68,6 +69,20 @@ use tracing::info;
 // Inject a full signature from another node, bypassing validity checks.
 //
 //  $ curl 'http://127.0.0.1:1337/randomness-inject-full-sig?round=123&sigs=base64encodedsig'
+//
+// View the certificates pending in the transaction manager with their missing input objects, the
+// certificates being executed, and the transaction queue of each object, as JSON. At most `limit`
+// (default 100) entries of each are returned.
+//
+//  $ curl 'http://127.0.0.1:1337/transaction-manager?limit=20'
+//
+// View the certificates the execution driver is currently executing, as JSON.
+//
+//  $ curl 'http://127.0.0.1:1337/execution-driver'
+//
+// View the load shedding state of the overload monitor and the signals it is based on, as JSON.
+//
+//  $ curl 'http://127.0.0.1:1337/overload'
 
 const LOGGING_ROUTE: &str = "/logging";
 const TRACING_ROUTE: &str = "/enable-tracing";
@@ -80,6 +95,11 @@ const NODE_CONFIG: &str = "/node-config";
 const RANDOMNESS_PARTIAL_SIGS_ROUTE: &str = "/randomness-partial-sigs";
 const RANDOMNESS_INJECT_PARTIAL_SIGS_ROUTE: &str = "/randomness-inject-partial-sigs";
 const RANDOMNESS_INJECT_FULL_SIG_ROUTE: &str = "/randomness-inject-full-sig";
+const TRANSACTION_MANAGER_ROUTE: &str = "/transaction-manager";
+const EXECUTION_DRIVER_ROUTE: &str = "/execution-driver";
+const OVERLOAD_ROUTE: &str = "/overload";
+
+const DEFAULT_TRANSACTION_MANAGER_LIMIT: usize = 100;
 
 struct AppState {
     node: Arc<SuiNode>,
@@ -119,6 +139,9 @@ pub async fn run_admin_server(node: Arc<SuiNode>, port: u16, tracing_handle: Tra
             RANDOMNESS_INJECT_FULL_SIG_ROUTE,
             post(randomness_inject_full_sig),
         )
+        .route(TRANSACTION_MANAGER_ROUTE, get(transaction_manager))
+        .route(EXECUTION_DRIVER_ROUTE, get(execution_driver))
+        .route(OVERLOAD_ROUTE, get(overload))
         .with_state(Arc::new(app_state));
 
     let socket_address = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), port);
@@ -443,3 +466,39 @@ async fn randomness_inject_full_sig(
         Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
     }
 }
+
+fn json_response(value: &impl Serialize) -> (StatusCode, String) {
+    match serde_json::to_string_pretty(value) {
+        Ok(json) => (StatusCode::OK, format!("{json}\n")),
+        Err(err) => (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()),
+    }
+}
+
+#[derive(Deserialize)]
+struct Limit {
+    limit: Option<usize>,
+}
+
+async fn transaction_manager(
+    State(state): State<Arc<AppState>>,
+    limit: Query<Limit>,
+) -> (StatusCode, String) {
+    let Query(Limit { limit }) = limit;
+
+    let snapshot = state
+        .node
+        .state()
+        .transaction_manager()
+        .snapshot(limit.unwrap_or(DEFAULT_TRANSACTION_MANAGER_LIMIT));
+    json_response(&snapshot)
+}
+
+async fn execution_driver(State(state): State<Arc<AppState>>) -> (StatusCode, String) {
+    let inflight = state.node.state().execution_driver_inflight.snapshot();
+    json_response(&inflight)
+}
+
+async fn overload(State(state): State<Arc<AppState>>) -> (StatusCode, String) {
+    let snapshot = AuthorityOverloadSnapshot::new(&state.node.state());
+    json_response(&snapshot)
+}
@@ -26,6 +26,7 @@ use std::sync::{Arc, Weak};
 use std::time::Duration;
 use sui_core::authority::authority_store_tables::AuthorityPerpetualTablesOptions;
 use sui_core::authority::epoch_start_configuration::EpochFlag;
+use sui_core::authority::retention_filter::RetentionFilter;
 use sui_core::authority::RandomnessRoundReceiver;
 use sui_core::authority::CHAIN_IDENTIFIER;
 use sui_core::consensus_adapter::SubmitToConsensus;
@@ -1008,6 +1009,14 @@ impl SuiNode {
             // can be uploaded as state snapshots.
             (None, false) => Ok((db_checkpoint_config, None)),
             (_, _) => {
+                // Keep the history of the objects retained by a selective fullnode in its db
+                // checkpoints. Validators ignore the selective retention config.
+                let selective_retention_filter = match &config.selective_retention_config {
+                    Some(retention_config) if config.consensus_config().is_none() => {
+                        Some(Arc::new(RetentionFilter::new(retention_config)))
+                    }
+                    _ => None,
+                };
                 let handler = DBCheckpointHandler::new(
                     &db_checkpoint_config.checkpoint_path.clone().unwrap(),
                     db_checkpoint_config.object_store_config.as_ref(),
@@ -1019,6 +1028,8 @@ impl SuiNode {
                     config.authority_store_pruning_config.clone(),
                     prometheus_registry,
                     state_snapshot_enabled,
+                    db_checkpoint_config.incremental_upload.unwrap_or(false),
+                    selective_retention_filter,
                 )?;
                 Ok((
                     db_checkpoint_config,
@@ -7,8 +7,9 @@ use anyhow::{anyhow, Context, Result};
 use async_trait::async_trait;
 use bytes::Bytes;
 use object_store::path::Path;
+use object_store::Error;
 use std::fs::File;
-use std::io::Read;
+use std::io::{ErrorKind, Read};
 use std::path::PathBuf;
 use std::{fmt, fs};
 
@@ -38,8 +39,14 @@ impl ObjectStoreGetExt for LocalStorage {
     async fn get_bytes(&self, location: &Path) -> Result<Bytes> {
         let path_to_filesystem = path_to_filesystem(self.root.clone(), location)?;
         let handle = tokio::task::spawn_blocking(move || {
-            let mut f = File::open(path_to_filesystem)
-                .map_err(|e| anyhow!("Failed to open file with error: {}", e.to_string()))?;
+            let mut f = File::open(&path_to_filesystem).map_err(|e| match e.kind() {
+                ErrorKind::NotFound => Error::NotFound {
+                    path: path_to_filesystem.display().to_string(),
+                    source: Box::new(e),
+                }
+                .into(),
+                _ => anyhow!("Failed to open file with error: {}", e.to_string()),
+            })?;
             let mut buf = vec![];
             f.read_to_end(&mut buf)
                 .context(anyhow!("Failed to read file"))?;
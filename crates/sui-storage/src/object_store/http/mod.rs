@@ -19,7 +19,7 @@ use futures::{StreamExt, TryStreamExt};
 use object_store::path::Path;
 use object_store::{Error, GetResult, GetResultPayload, ObjectMeta};
 use reqwest::header::{HeaderMap, CONTENT_LENGTH, ETAG, LAST_MODIFIED};
-use reqwest::{Client, Method};
+use reqwest::{Client, Method, StatusCode};
 
 // http://docs.aws.amazon.com/general/latest/gr/sigv4-create-canonical-request.html
 //
@@ -78,6 +78,13 @@ async fn get(
 ) -> Result<GetResult> {
     let request = client.request(Method::GET, url);
     let response = request.send().await.context("failed to get")?;
+    if response.status() == StatusCode::NOT_FOUND {
+        return Err(Error::NotFound {
+            path: location.to_string(),
+            source: format!("{url} returned {}", response.status()).into(),
+        }
+        .into());
+    }
     let meta = header_meta(location, response.headers()).context("Failed to get header")?;
     let stream = response
         .bytes_stream()
@@ -1,7 +1,7 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use anyhow::{anyhow, Result};
+use anyhow::{anyhow, Context, Result};
 use async_trait::async_trait;
 use bytes::Bytes;
 use futures::stream::BoxStream;
@@ -37,7 +37,7 @@ impl ObjectStoreGetExt for Arc<DynObjectStore> {
     async fn get_bytes(&self, src: &Path) -> Result<Bytes> {
         self.get(src)
             .await
-            .map_err(|e| anyhow!("Failed to get file {} with error: {:?}", src, e))?
+            .with_context(|| format!("Failed to get file {}", src))?
             .bytes()
             .await
             .map_err(|e| {
@@ -1,12 +1,14 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
+use crate::compute_sha3_checksum_for_bytes;
 use crate::object_store::{
     ObjectStoreDeleteExt, ObjectStoreGetExt, ObjectStoreListExt, ObjectStorePutExt,
 };
 use anyhow::{anyhow, Context, Result};
 use backoff::future::retry;
 use bytes::Bytes;
+use fastcrypto::encoding::{Encoding, Hex};
 use futures::StreamExt;
 use futures::TryStreamExt;
 use indicatif::ProgressBar;
@@ -14,7 +16,7 @@ use itertools::Itertools;
 use object_store::path::Path;
 use object_store::{DynObjectStore, Error, ObjectStore};
 use serde::{Deserialize, Serialize};
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashSet};
 use std::num::NonZeroUsize;
 use std::ops::Range;
 use std::path::PathBuf;
@@ -25,6 +27,9 @@ use tracing::{error, warn};
 use url::Url;
 
 pub const MANIFEST_FILENAME: &str = "MANIFEST";
+pub const DB_CHECKPOINT_MANIFEST_FILENAME: &str = "DB_CHECKPOINT_MANIFEST";
+/// Directory of the remote store holding the SST files of incremental db checkpoints
+pub const SST_FILES_DIR: &str = "sst";
 
 #[derive(Serialize, Deserialize)]
 
@@ -409,10 +414,179 @@ pub async fn write_snapshot_manifest<S: ObjectStoreListExt + ObjectStorePutExt>(
     Ok(())
 }
 
+/// Describes the files that make up an incremental db checkpoint. SST files are immutable once
+/// written, so they are stored once in the remote store under `SST_FILES_DIR`, named by their
+/// checksum, and shared by all the db checkpoints that contain them. Other files are stored in
+/// the epoch directory of the db checkpoint.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DBCheckpointManifest {
+    pub files: Vec<DBCheckpointFile>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DBCheckpointFile {
+    /// Path of the file, relative to the db checkpoint directory
+    pub path: String,
+    /// Path of the file in the remote store
+    pub location: String,
+    pub size: u64,
+    /// Hex encoded sha3-256 checksum of the file
+    pub checksum: String,
+}
+
+/// Number of files and bytes of a db checkpoint that were uploaded, and that were already present
+/// in the remote store
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct DBCheckpointUploadStats {
+    pub uploaded_files: u64,
+    pub uploaded_bytes: u64,
+    pub reused_files: u64,
+    pub reused_bytes: u64,
+}
+
+impl DBCheckpointManifest {
+    pub fn filter_by_prefix(&self, prefix: &str) -> Vec<DBCheckpointFile> {
+        self.files
+            .iter()
+            .filter(|file| file.path.starts_with(prefix))
+            .cloned()
+            .collect()
+    }
+}
+
+/// Returns the manifest of the db checkpoint in `dir`, or None if it is not an incremental db
+/// checkpoint
+pub async fn read_db_checkpoint_manifest<S: ObjectStoreGetExt>(
+    store: &S,
+    dir: &Path,
+) -> Result<Option<DBCheckpointManifest>> {
+    let bytes = match store
+        .get_bytes(&dir.child(DB_CHECKPOINT_MANIFEST_FILENAME))
+        .await
+    {
+        Ok(bytes) => bytes,
+        Err(err) if matches!(err.downcast_ref::<Error>(), Some(Error::NotFound { .. })) => {
+            return Ok(None)
+        }
+        Err(err) => return Err(err),
+    };
+    let manifest = serde_json::from_slice(&bytes)
+        .with_context(|| format!("Failed to parse {dir}/{DB_CHECKPOINT_MANIFEST_FILENAME}"))?;
+    Ok(Some(manifest))
+}
+
+/// Uploads the db checkpoint in `dir` to `dest_store` as an incremental db checkpoint, only
+/// uploading the SST files that are not already in the remote store, and writes its manifest.
+pub async fn upload_db_checkpoint_incrementally<
+    S: ObjectStoreGetExt + ObjectStoreListExt,
+    D: ObjectStoreListExt + ObjectStorePutExt,
+>(
+    dir: &Path,
+    src_store: &S,
+    dest_store: &D,
+    concurrency: NonZeroUsize,
+) -> Result<(DBCheckpointManifest, DBCheckpointUploadStats)> {
+    let mut existing_sst_files = HashSet::new();
+    let mut paths = dest_store
+        .list_objects(Some(&Path::from(SST_FILES_DIR)))
+        .await;
+    while let Some(res) = paths.next().await {
+        existing_sst_files.insert(res?.location);
+    }
+    let mut input_paths = vec![];
+    let mut paths = src_store.list_objects(Some(dir)).await;
+    while let Some(res) = paths.next().await {
+        let object_metadata = res?;
+        if object_metadata.size > 0 {
+            input_paths.push(object_metadata.location);
+        }
+    }
+    let dir_prefix = format!("{dir}/");
+    let existing_sst_files = &existing_sst_files;
+    let dir_prefix = &dir_prefix;
+    let results: Vec<(DBCheckpointFile, bool)> = futures::stream::iter(input_paths)
+        .map(|path| async move {
+            let bytes = get(src_store, &path).await?;
+            let checksum = Hex::encode(compute_sha3_checksum_for_bytes(bytes.clone())?);
+            let relative_path = path
+                .as_ref()
+                .strip_prefix(dir_prefix.as_str())
+                .ok_or_else(|| anyhow!("{path} is not in the db checkpoint directory {dir}"))?
+                .to_string();
+            let location = if relative_path.ends_with(".sst") {
+                Path::from(format!("{SST_FILES_DIR}/{checksum}.sst"))
+            } else {
+                path.clone()
+            };
+            let reused = existing_sst_files.contains(&location);
+            if !reused {
+                put(dest_store, &location, bytes.clone()).await?;
+            }
+            let file = DBCheckpointFile {
+                path: relative_path,
+                location: location.to_string(),
+                size: bytes.len() as u64,
+                checksum,
+            };
+            Ok::<_, anyhow::Error>((file, reused))
+        })
+        .boxed()
+        .buffer_unordered(concurrency.get())
+        .try_collect()
+        .await?;
+
+    let mut stats = DBCheckpointUploadStats::default();
+    let mut files = vec![];
+    for (file, reused) in results {
+        if reused {
+            stats.reused_files += 1;
+            stats.reused_bytes += file.size;
+        } else {
+            stats.uploaded_files += 1;
+            stats.uploaded_bytes += file.size;
+        }
+        files.push(file);
+    }
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+    let manifest = DBCheckpointManifest { files };
+    put(
+        dest_store,
+        &dir.child(DB_CHECKPOINT_MANIFEST_FILENAME),
+        Bytes::from(serde_json::to_vec(&manifest)?),
+    )
+    .await?;
+    Ok((manifest, stats))
+}
+
+/// Downloads a file of an incremental db checkpoint to `dest_dir` and verifies its checksum
+pub async fn download_db_checkpoint_file<S: ObjectStoreGetExt, D: ObjectStorePutExt>(
+    file: &DBCheckpointFile,
+    dest_dir: &Path,
+    src_store: &S,
+    dest_store: &D,
+) -> Result<Path> {
+    let bytes = get(src_store, &Path::from(file.location.as_str())).await?;
+    let checksum = Hex::encode(compute_sha3_checksum_for_bytes(bytes.clone())?);
+    if checksum != file.checksum {
+        return Err(anyhow!(
+            "Checksum mismatch for {} downloaded from {}: expected {}, got {checksum}",
+            file.path,
+            file.location,
+            file.checksum
+        ));
+    }
+    let dest = Path::from(format!("{dest_dir}/{}", file.path));
+    put(dest_store, &dest, bytes).await?;
+    Ok(dest)
+}
+
 #[cfg(test)]
 mod tests {
+    use crate::object_store::http::HttpDownloaderBuilder;
     use crate::object_store::util::{
-        copy_recursively, delete_recursively, write_snapshot_manifest, MANIFEST_FILENAME,
+        copy_recursively, delete_recursively, download_db_checkpoint_file,
+        read_db_checkpoint_manifest, upload_db_checkpoint_incrementally, write_snapshot_manifest,
+        DBCheckpointUploadStats, DB_CHECKPOINT_MANIFEST_FILENAME, MANIFEST_FILENAME,
     };
     use object_store::path::Path;
     use std::fs;
@@ -546,4 +720,136 @@ mod tests {
             .exists());
         Ok(())
     }
+
+    #[tokio::test]
+    pub async fn test_incremental_db_checkpoint_upload() -> anyhow::Result<()> {
+        let input = TempDir::new()?;
+        let input_path = input.path();
+        for epoch in 0..2 {
+            let perpetual = input_path.join(format!("epoch_{epoch}/store/perpetual"));
+            fs::create_dir_all(&perpetual)?;
+            fs::write(perpetual.join("000001.sst"), b"shared sst file")?;
+            fs::write(perpetual.join("OPTIONS"), format!("options {epoch}"))?;
+        }
+        fs::write(
+            input_path.join("epoch_1/store/perpetual/000002.sst"),
+            b"new sst file",
+        )?;
+
+        let output = TempDir::new()?;
+        let output_path = output.path();
+        let input_store = ObjectStoreConfig {
+            object_store: Some(ObjectStoreType::File),
+            directory: Some(input_path.to_path_buf()),
+            ..Default::default()
+        }
+        .make()?;
+        let output_store = ObjectStoreConfig {
+            object_store: Some(ObjectStoreType::File),
+            directory: Some(output_path.to_path_buf()),
+            ..Default::default()
+        }
+        .make()?;
+
+        let (_, stats) = upload_db_checkpoint_incrementally(
+            &Path::from("epoch_0"),
+            &input_store,
+            &output_store,
+            NonZeroUsize::new(1).unwrap(),
+        )
+        .await?;
+        assert_eq!(stats.uploaded_files, 2);
+        assert_eq!(stats.reused_files, 0);
+
+        let (manifest, stats) = upload_db_checkpoint_incrementally(
+            &Path::from("epoch_1"),
+            &input_store,
+            &output_store,
+            NonZeroUsize::new(1).unwrap(),
+        )
+        .await?;
+        // the SST file shared with epoch 0 is not uploaded again
+        assert_eq!(
+            stats,
+            DBCheckpointUploadStats {
+                uploaded_files: 2,
+                uploaded_bytes: ("new sst file".len() + "options 1".len()) as u64,
+                reused_files: 1,
+                reused_bytes: "shared sst file".len() as u64,
+            }
+        );
+        assert_eq!(fs::read_dir(output_path.join("sst"))?.count(), 2);
+        assert!(output_path.join("epoch_1/store/perpetual/OPTIONS").exists());
+        assert!(!output_path
+            .join("epoch_1/store/perpetual/000001.sst")
+            .exists());
+        assert!(output_path
+            .join("epoch_1")
+            .join(DB_CHECKPOINT_MANIFEST_FILENAME)
+            .exists());
+        assert_eq!(
+            read_db_checkpoint_manifest(&output_store, &Path::from("epoch_1")).await?,
+            Some(manifest.clone())
+        );
+        assert_eq!(
+            read_db_checkpoint_manifest(&output_store, &Path::from("epoch_2")).await?,
+            None
+        );
+        // a missing manifest is told apart from a failure to read it
+        let http_store = ObjectStoreConfig {
+            object_store: Some(ObjectStoreType::File),
+            directory: Some(output_path.to_path_buf()),
+            ..Default::default()
+        }
+        .make_http()?;
+        assert_eq!(
+            read_db_checkpoint_manifest(&http_store, &Path::from("epoch_2")).await?,
+            None
+        );
+        fs::create_dir_all(
+            output_path
+                .join("epoch_3")
+                .join(DB_CHECKPOINT_MANIFEST_FILENAME),
+        )?;
+        assert!(
+            read_db_checkpoint_manifest(&http_store, &Path::from("epoch_3"))
+                .await
+                .is_err()
+        );
+
+        let restored = TempDir::new()?;
+        let restored_store = ObjectStoreConfig {
+            object_store: Some(ObjectStoreType::File),
+            directory: Some(restored.path().to_path_buf()),
+            ..Default::default()
+        }
+        .make()?;
+        for file in &manifest.files {
+            download_db_checkpoint_file(
+                file,
+                &Path::from("epoch_1"),
+                &output_store,
+                &restored_store,
+            )
+            .await?;
+        }
+        for file in ["000001.sst", "000002.sst", "OPTIONS"] {
+            assert_eq!(
+                fs::read(restored.path().join("epoch_1/store/perpetual").join(file))?,
+                fs::read(input_path.join("epoch_1/store/perpetual").join(file))?
+            );
+        }
+
+        let mut corrupted = manifest.files[0].clone();
+        corrupted.checksum = "00".to_string();
+        assert!(download_db_checkpoint_file(
+            &corrupted,
+            &Path::from("epoch_1"),
+            &output_store,
+            &restored_store,
+        )
+        .await
+        .is_err());
+        Ok(())
+    }
 }
@@ -13,7 +13,7 @@ use sui_config::node::{
     default_enable_index_processing, default_end_of_epoch_broadcast_channel_capacity,
     AuthorityKeyPairWithPath, AuthorityOverloadConfig, AuthorityStorePruningConfig,
     CheckpointExecutorConfig, DBCheckpointConfig, ExecutionCacheConfig, ExpensiveSafetyCheckConfig,
-    Genesis, KeyPairWithPath, StateArchiveConfig, StateSnapshotConfig,
+    Genesis, KeyPairWithPath, ServerType, StateArchiveConfig, StateSnapshotConfig,
     DEFAULT_GRPC_CONCURRENCY_LIMIT,
 };
 use sui_config::node::{default_zklogin_oauth_providers, RunWithRange};
@@ -247,6 +247,7 @@ impl ValidatorConfigBuilder {
             enable_validator_tx_finalizer: true,
             verifier_signing_config: VerifierSigningConfig::default(),
             enable_db_write_stall: None,
+            selective_retention_config: None,
         }
     }
 
@@ -283,6 +284,7 @@ pub struct FullnodeConfigBuilder {
     fw_config: Option<RemoteFirewallConfig>,
     data_ingestion_dir: Option<PathBuf>,
     disable_pruning: bool,
+    jsonrpc_server_type: Option<ServerType>,
 }
 
 impl FullnodeConfigBuilder {
@@ -395,6 +397,11 @@ impl FullnodeConfigBuilder {
         self
     }
 
+    pub fn with_jsonrpc_server_type(mut self, server_type: Option<ServerType>) -> Self {
+        self.jsonrpc_server_type = server_type;
+        self
+    }
+
     pub fn with_data_ingestion_dir(mut self, path: Option<PathBuf>) -> Self {
         self.data_ingestion_dir = path;
         self
@@ -538,7 +545,7 @@ impl FullnodeConfigBuilder {
             zklogin_oauth_providers: default_zklogin_oauth_providers(),
             authority_overload_config: Default::default(),
             run_with_range: self.run_with_range,
-            jsonrpc_server_type: None,
+            jsonrpc_server_type: self.jsonrpc_server_type,
             policy_config: self.policy_config,
             firewall_config: self.fw_config,
             execution_cache: ExecutionCacheConfig::default(),
@@ -548,6 +555,7 @@ impl FullnodeConfigBuilder {
             enable_validator_tx_finalizer: false,
             verifier_signing_config: VerifierSigningConfig::default(),
             enable_db_write_stall: None,
+            selective_retention_config: None,
         }
     }
 }
@@ -229,6 +229,7 @@ pub async fn prune_objects(db_path: PathBuf) -> anyhow::Result<()> {
         metrics,
         usize::MAX,
         EPOCH_DURATION_MS_FOR_TESTING,
+        None,
     )
     .await?;
     Ok(())
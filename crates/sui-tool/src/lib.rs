@@ -6,6 +6,7 @@ use anyhow::Result;
 use fastcrypto::traits::ToFromBytes;
 use futures::future::join_all;
 use futures::future::AbortHandle;
+use futures::future::BoxFuture;
 use itertools::Itertools;
 use std::collections::BTreeMap;
 use std::fmt::Write;
@@ -56,7 +57,10 @@ use sui_core::epoch::committee_store::CommitteeStore;
 use sui_core::storage::RocksDbStore;
 use sui_snapshot::reader::StateSnapshotReaderV1;
 use sui_snapshot::setup_db_state;
-use sui_storage::object_store::util::{copy_file, exists, get_path};
+use sui_storage::object_store::util::{
+    copy_file, download_db_checkpoint_file, exists, get_path, read_db_checkpoint_manifest,
+    DBCheckpointManifest, DB_CHECKPOINT_MANIFEST_FILENAME,
+};
 use sui_storage::object_store::ObjectStoreGetExt;
 use sui_storage::verify_checkpoint_range;
 use sui_types::messages_checkpoint::{CheckpointCommitment, ECMHLiveObjectSetDigest};
@@ -572,7 +576,44 @@ pub async fn restore_from_db_checkpoint(
     config: &NodeConfig,
     db_checkpoint_path: &Path,
 ) -> Result<(), anyhow::Error> {
-    copy_dir_all(db_checkpoint_path, config.db_path(), vec![])?;
+    let manifest_path = db_checkpoint_path.join(DB_CHECKPOINT_MANIFEST_FILENAME);
+    if manifest_path.exists() {
+        let manifest: DBCheckpointManifest = serde_json::from_slice(&fs::read(&manifest_path)?)
+            .map_err(|err| anyhow!("Error parsing {}: {}", manifest_path.display(), err))?;
+        verify_db_checkpoint_files(db_checkpoint_path, &manifest)?;
+    }
+    copy_dir_all(db_checkpoint_path, config.db_path(), vec![manifest_path])?;
+    Ok(())
+}
+
+/// Checks that the files of an incremental db checkpoint downloaded with `download-db-snapshot`
+/// are all present, except for indexes which may have been skipped, and have the expected size.
+/// Their checksums are verified when they are downloaded.
+fn verify_db_checkpoint_files(
+    db_checkpoint_path: &Path,
+    manifest: &DBCheckpointManifest,
+) -> Result<(), anyhow::Error> {
+    for file in &manifest.files {
+        let local_path = db_checkpoint_path.join(&file.path);
+        if !local_path.exists() {
+            if file.path.starts_with("indexes") {
+                continue;
+            }
+            return Err(anyhow!(
+                "File {} of the db checkpoint is missing",
+                local_path.display()
+            ));
+        }
+        let size = fs::metadata(&local_path)?.len();
+        if size != file.size {
+            return Err(anyhow!(
+                "File {} of the db checkpoint has size {}, expected {}",
+                local_path.display(),
+                size,
+                file.size
+            ));
+        }
+    }
     Ok(())
 }
 
@@ -1031,33 +1072,63 @@ pub async fn download_db_snapshot(
 
     let epoch_path = format!("epoch_{}", epoch);
     let epoch_dir = get_path(&epoch_path);
-
-    let manifest_file = epoch_dir.child(MANIFEST_FILENAME);
-    let epoch_manifest_contents =
-        String::from_utf8(remote_store.get_bytes(&manifest_file).await?.to_vec())
-            .map_err(|err| anyhow!("Error parsing {}/MANIFEST from bytes: {}", epoch_path, err))?;
-
-    let epoch_manifest =
-        PerEpochManifest::deserialize_from_newline_delimited(&epoch_manifest_contents);
-
-    let mut files: Vec<String> = vec![];
-    files.extend(epoch_manifest.filter_by_prefix("store/perpetual").lines);
-    files.extend(epoch_manifest.filter_by_prefix("epochs").lines);
-    files.extend(epoch_manifest.filter_by_prefix("checkpoints").lines);
-    if !skip_indexes {
-        files.extend(epoch_manifest.filter_by_prefix("indexes").lines)
-    }
     let local_store = ObjectStoreConfig {
         object_store: Some(ObjectStoreType::File),
         directory: Some(path.to_path_buf()),
         ..Default::default()
     }
     .make()?;
+
+    let prefixes = ["store/perpetual", "epochs", "checkpoints", "indexes"];
+    let prefixes = if skip_indexes {
+        &prefixes[..3]
+    } else {
+        &prefixes[..]
+    };
+    let mut downloads: Vec<BoxFuture<'static, Result<::object_store::path::Path>>> = vec![];
+    // Incremental db checkpoints store their SST files outside of the epoch directory, and are
+    // described by their own manifest
+    if let Some(manifest) = read_db_checkpoint_manifest(&remote_store, &epoch_dir).await? {
+        for prefix in prefixes {
+            for file in manifest.filter_by_prefix(prefix) {
+                let local_store = local_store.clone();
+                let remote_store = remote_store.clone();
+                let epoch_dir = epoch_dir.clone();
+                downloads.push(Box::pin(async move {
+                    download_db_checkpoint_file(&file, &epoch_dir, &remote_store, &local_store)
+                        .await
+                }));
+            }
+        }
+        // keep the manifest so that `restore-db` can check the downloaded files
+        let manifest_file = epoch_dir.child(DB_CHECKPOINT_MANIFEST_FILENAME);
+        copy_file(&manifest_file, &manifest_file, &remote_store, &local_store).await?;
+    } else {
+        let manifest_file = epoch_dir.child(MANIFEST_FILENAME);
+        let epoch_manifest_contents = String::from_utf8(
+            remote_store.get_bytes(&manifest_file).await?.to_vec(),
+        )
+        .map_err(|err| anyhow!("Error parsing {}/MANIFEST from bytes: {}", epoch_path, err))?;
+
+        let epoch_manifest =
+            PerEpochManifest::deserialize_from_newline_delimited(&epoch_manifest_contents);
+        for prefix in prefixes {
+            for file in epoch_manifest.filter_by_prefix(prefix).lines {
+                let local_store = local_store.clone();
+                let remote_store = remote_store.clone();
+                downloads.push(Box::pin(async move {
+                    let file_path = get_path(format!("epoch_{}/{}", epoch, file).as_str());
+                    copy_file(&file_path, &file_path, &remote_store, &local_store).await?;
+                    Ok(file_path)
+                }));
+            }
+        }
+    }
     let m = MultiProgress::new();
     let path = path.to_path_buf();
     let snapshot_handle = tokio::spawn(async move {
         let progress_bar = m.add(
-            ProgressBar::new(files.len() as u64).with_style(
+            ProgressBar::new(downloads.len() as u64).with_style(
                 ProgressStyle::with_template(
                     "[{elapsed_precise}] {wide_bar} {pos} out of {len} files done ({msg})",
                 )
@@ -1066,16 +1137,12 @@ pub async fn download_db_snapshot(
         );
         let cloned_progress_bar = progress_bar.clone();
         let file_counter = Arc::new(AtomicUsize::new(0));
-        futures::stream::iter(files.iter())
-            .map(|file| {
-                let local_store = local_store.clone();
-                let remote_store = remote_store.clone();
+        futures::stream::iter(downloads)
+            .map(|download| {
                 let counter_cloned = file_counter.clone();
                 async move {
                     counter_cloned.fetch_add(1, Ordering::Relaxed);
-                    let file_path = get_path(format!("epoch_{}/{}", epoch, file).as_str());
-                    copy_file(&file_path, &file_path, &remote_store, &local_store).await?;
-                    Ok::<::object_store::path::Path, anyhow::Error>(file_path.clone())
+                    download.await
                 }
             })
             .boxed()
@@ -3,6 +3,7 @@
 
 use serde::{Deserialize, Serialize};
 use serde_with::serde_as;
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 
 // These values set to loosely attempt to limit
@@ -202,6 +203,91 @@ fn default_sketch_tolerance() -> f64 {
     DEFAULT_SKETCH_TOLERANCE
 }
 
+#[serde_as]
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct TokenBucketConfig {
+    /// Maximum number of tokens in each client's bucket, i.e. the total cost
+    /// of requests a client may send in a burst
+    #[serde(default = "default_client_bucket_capacity")]
+    pub client_capacity: u64,
+    /// Number of tokens added to each client's bucket per second, i.e. the
+    /// sustained cost of requests per second a client may send
+    #[serde(default = "default_client_refill_rate")]
+    pub client_refill_rate: u64,
+    #[serde(default = "default_client_bucket_capacity")]
+    pub proxied_client_capacity: u64,
+    #[serde(default = "default_client_refill_rate")]
+    pub proxied_client_refill_rate: u64,
+    /// Capacity of a bucket shared by all clients, bounding the total cost of
+    /// requests served by the node. Once it is exhausted, clients are blocked
+    /// as they send requests until it refills. If unset, there is no global bucket.
+    #[serde(default)]
+    pub global_capacity: Option<u64>,
+    /// Number of tokens added to the global bucket per second. Must be
+    /// greater than 0 if `global_capacity` is set.
+    #[serde(default)]
+    pub global_refill_rate: u64,
+    /// Cost of requests whose kind is not listed in `request_costs`
+    #[serde(default = "default_request_cost")]
+    pub default_cost: u64,
+    /// Cost of each kind of request, keyed by JSON-RPC method name (e.g.
+    /// `sui_dryRunTransactionBlock`) or validator endpoint (e.g. `transaction`)
+    #[serde(default)]
+    pub request_costs: BTreeMap<String, u64>,
+    /// List of String which should all parse to an IP address or a CIDR block
+    /// (e.g. `10.0.0.0/8`). Requests from these clients do not consume tokens
+    /// and are never blocked by this policy.
+    #[serde(default)]
+    pub allow_list: Vec<String>,
+    /// Maximum number of client buckets kept in memory. When exceeded, the
+    /// bucket of the least recently seen client is dropped.
+    #[serde(default = "default_max_tracked_clients")]
+    pub max_tracked_clients: usize,
+}
+
+impl Default for TokenBucketConfig {
+    fn default() -> Self {
+        Self {
+            client_capacity: default_client_bucket_capacity(),
+            client_refill_rate: default_client_refill_rate(),
+            proxied_client_capacity: default_client_bucket_capacity(),
+            proxied_client_refill_rate: default_client_refill_rate(),
+            global_capacity: None,
+            global_refill_rate: 0,
+            default_cost: default_request_cost(),
+            request_costs: BTreeMap::new(),
+            allow_list: vec![],
+            max_tracked_clients: default_max_tracked_clients(),
+        }
+    }
+}
+
+impl TokenBucketConfig {
+    pub fn request_cost(&self, request_kind: Option<&str>) -> u64 {
+        request_kind
+            .and_then(|kind| self.request_costs.get(kind))
+            .copied()
+            .unwrap_or(self.default_cost)
+    }
+}
+
+fn default_client_bucket_capacity() -> u64 {
+    1_000
+}
+
+fn default_client_refill_rate() -> u64 {
+    100
+}
+
+fn default_request_cost() -> u64 {
+    1
+}
+
+fn default_max_tracked_clients() -> usize {
+    100_000
+}
+
 // Serializable representation of policy types, used in config
 // in order to easily change in tests or to killswitch
 #[derive(Clone, Serialize, Deserialize, Debug, Default)]
@@ -215,6 +301,11 @@ pub enum PolicyType {
     /// with granularity of `update_interval_secs`
     FreqThreshold(FreqThresholdConfig),
 
+    /// Charges each request a configurable cost, depending on its kind, against
+    /// token buckets for its client, its proxied client and optionally all clients,
+    /// and blocks a client when a bucket cannot pay for its request
+    TokenBucket(TokenBucketConfig),
+
     /* Below this point are test policies, and thus should not be used in production */
     ///
     /// Simple policy that adds connection_ip to blocklist when the same connection_ip
@@ -247,7 +338,9 @@ pub struct PolicyConfig {
     /// endpoint-specific sample policy (not configurable) which
     /// weighs endpoints by the relative effort required to serve
     /// them. Therefore a sample rate of N will yield an actual
-    /// sample rate <= N.
+    /// sample rate <= N. Tallies that are not sampled are not
+    /// charged by a token bucket policy, so its costs and rates
+    /// should account for the sample rate.
     pub spam_sample_rate: Weight,
     #[serde(default = "default_dry_run")]
     pub dry_run: bool,
@@ -0,0 +1,138 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! A persistent address book for addresses that are not in the keystore, such as treasury,
+//! vendor or multisig addresses. It is stored beside `client.yaml`, and its names resolve
+//! wherever an address alias is accepted, including `@name` in `sui client ptb`.
+
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{anyhow, ensure};
+use clap::*;
+use serde::{Deserialize, Serialize};
+use sui_config::{Config, PersistedConfig, SUI_ADDRESS_BOOK_FILENAME};
+use sui_keys::keystore::{validate_alias, AccountKeystore};
+use sui_sdk::{sui_client_config::SuiClientConfig, wallet_context::WalletContext};
+use sui_types::base_types::SuiAddress;
+
+use crate::client_commands::SuiClientCommandResult;
+
+#[derive(Parser)]
+#[clap(rename_all = "kebab-case")]
+pub enum AddressBookCommand {
+    /// Add a named address to the address book. The name must not already be used by the
+    /// address book or by a key alias in the keystore.
+    Add {
+        /// The name must start with a letter and can contain only letters, digits, dots, hyphens
+        /// (-), or underscores (_).
+        name: String,
+        address: SuiAddress,
+    },
+    /// Remove a named address from the address book.
+    Remove { name: String },
+    /// List the named addresses in the address book.
+    List,
+}
+
+#[derive(Serialize, Deserialize, Default, Debug)]
+pub struct AddressBook {
+    #[serde(default)]
+    contacts: BTreeMap<String, SuiAddress>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct AddressBookEntry {
+    pub name: String,
+    pub address: SuiAddress,
+}
+
+impl Config for AddressBook {}
+
+impl AddressBook {
+    /// Path of the address book that belongs to the client config at `config`.
+    pub fn path(config: &PersistedConfig<SuiClientConfig>) -> PathBuf {
+        config
+            .path()
+            .parent()
+            .unwrap_or_else(|| Path::new(""))
+            .join(SUI_ADDRESS_BOOK_FILENAME)
+    }
+
+    /// Load the address book at `path`, which is empty if the file does not exist yet.
+    pub fn load_or_default(path: &Path) -> Result<Self, anyhow::Error> {
+        if path.exists() {
+            Self::load(path)
+        } else {
+            Ok(Self::default())
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&SuiAddress> {
+        self.contacts.get(name)
+    }
+
+    pub fn entries(&self) -> Vec<AddressBookEntry> {
+        self.contacts
+            .iter()
+            .map(|(name, address)| AddressBookEntry {
+                name: name.clone(),
+                address: *address,
+            })
+            .collect()
+    }
+
+    pub fn add(
+        &mut self,
+        name: &str,
+        address: SuiAddress,
+        keystore: &impl AccountKeystore,
+    ) -> Result<AddressBookEntry, anyhow::Error> {
+        let name = validate_alias(name)?;
+        ensure!(
+            !keystore.alias_exists(&name),
+            "{name} is already the alias of a key in the keystore"
+        );
+        ensure!(
+            !self.contacts.contains_key(&name),
+            "{name} is already in the address book, remove it first to change its address"
+        );
+        self.contacts.insert(name.clone(), address);
+        Ok(AddressBookEntry { name, address })
+    }
+
+    pub fn remove(&mut self, name: &str) -> Result<AddressBookEntry, anyhow::Error> {
+        let address = self
+            .contacts
+            .remove(name)
+            .ok_or_else(|| anyhow!("{name} is not in the address book"))?;
+        Ok(AddressBookEntry {
+            name: name.to_string(),
+            address,
+        })
+    }
+}
+
+impl AddressBookCommand {
+    pub fn execute(self, context: &WalletContext) -> Result<SuiClientCommandResult, anyhow::Error> {
+        let path = AddressBook::path(&context.config);
+        let mut address_book = AddressBook::load_or_default(&path)?;
+        let entries = match self {
+            AddressBookCommand::Add { name, address } => {
+                let entry = address_book.add(&name, address, &context.config.keystore)?;
+                address_book.save(&path)?;
+                vec![entry]
+            }
+            AddressBookCommand::Remove { name } => {
+                let entry = address_book.remove(&name)?;
+                address_book.save(&path)?;
+                vec![entry]
+            }
+            AddressBookCommand::List => address_book.entries(),
+        };
+        Ok(SuiClientCommandResult::AddressBook(entries))
+    }
+}
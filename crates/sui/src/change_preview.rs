@@ -0,0 +1,433 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! `--preview` for transaction commands: what a transaction will change, in human terms, shown
+//! before it is signed. The transaction is dry run for its object and balance changes, and for the
+//! contents of the objects it writes, which are decoded with the package resolver and compared
+//! field by field with their contents before the transaction.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::Arc,
+};
+
+use anyhow::anyhow;
+use async_trait::async_trait;
+use fastcrypto::encoding::{Encoding, Hex};
+use move_core_types::{
+    account_address::AccountAddress,
+    annotated_value::{MoveStruct, MoveValue},
+    language_storage::{StructTag, TypeTag},
+};
+use sui_json_rpc_types::{
+    ObjectChange, SuiExecutionStatus, SuiObjectDataOptions, SuiPastObjectResponse, SuiRawData,
+    SuiTransactionBlockEffectsAPI,
+};
+use sui_package_resolver::{
+    error::Error as ResolverError, Package, PackageStore, PackageStoreWithLruCache, Resolver,
+};
+use sui_sdk::SuiClient;
+use sui_types::{
+    base_types::{ObjectID, SequenceNumber, SuiAddress},
+    object::{Object, Owner},
+    transaction::TransactionData,
+    MOVE_STDLIB_ADDRESS, SUI_FRAMEWORK_ADDRESS,
+};
+
+#[cfg(test)]
+#[path = "unit_tests/change_preview_tests.rs"]
+mod change_preview_tests;
+
+/// The changes a transaction would make if it were executed now.
+pub struct ChangePreview {
+    pub status: SuiExecutionStatus,
+    /// Objects that exist before and after the transaction, and whose contents or owner change.
+    pub mutated: Vec<ObjectDiff>,
+    pub balance_changes: Vec<BalanceDelta>,
+    pub created: Vec<(ObjectID, String)>,
+    pub deleted: Vec<(ObjectID, String)>,
+    pub wrapped: Vec<(ObjectID, String)>,
+}
+
+pub struct ObjectDiff {
+    pub object_id: ObjectID,
+    pub object_type: StructTag,
+    /// Fields whose value changes, including the object's owner.
+    pub fields: Vec<FieldDiff>,
+    /// Whether the contents after the transaction are known. They are unknown if the fullnode
+    /// does not return the objects written by a dry run.
+    pub contents_known: bool,
+}
+
+/// A change to a field, named by its path from the object, e.g. `balance.value`. The value is
+/// `None` on the side where the field does not exist, e.g. for elements added to a vector.
+#[derive(Debug, PartialEq, Eq)]
+pub struct FieldDiff {
+    pub path: String,
+    pub before: Option<String>,
+    pub after: Option<String>,
+}
+
+pub struct BalanceDelta {
+    pub owner: Owner,
+    pub coin_type: TypeTag,
+    pub amount: i128,
+}
+
+/// Fetches packages from the fullnode, for the package resolver.
+struct RpcPackageStore {
+    client: SuiClient,
+}
+
+#[async_trait]
+impl PackageStore for RpcPackageStore {
+    async fn fetch(&self, id: AccountAddress) -> sui_package_resolver::Result<Arc<Package>> {
+        let store_error = |error: String| ResolverError::Store {
+            store: "RPC",
+            error,
+        };
+        let object: Object = self
+            .client
+            .read_api()
+            .get_object_with_options(id.into(), SuiObjectDataOptions::bcs_lossless())
+            .await
+            .map_err(|e| store_error(e.to_string()))?
+            .into_object()
+            .map_err(|_| ResolverError::PackageNotFound(id))?
+            .try_into()
+            .map_err(|e: anyhow::Error| store_error(e.to_string()))?;
+        Ok(Arc::new(Package::read_from_object(&object)?))
+    }
+}
+
+impl ChangePreview {
+    pub async fn new(client: &SuiClient, tx_data: &TransactionData) -> Result<Self, anyhow::Error> {
+        let dry_run = client
+            .read_api()
+            .dry_run_transaction_block(tx_data.clone())
+            .await
+            .map_err(|e| anyhow!("Dry run failed: {e}"))?;
+        let resolver = Resolver::new(PackageStoreWithLruCache::new(RpcPackageStore {
+            client: client.clone(),
+        }));
+        let contents_after = written_contents(&dry_run.raw_written_objects)?;
+
+        let mut mutated = vec![];
+        let (mut created, mut deleted, mut wrapped) = (vec![], vec![], vec![]);
+        for change in dry_run.object_changes {
+            match change {
+                ObjectChange::Mutated {
+                    object_id,
+                    object_type,
+                    owner,
+                    previous_version,
+                    ..
+                } => {
+                    let after = match contents_after.get(&object_id) {
+                        Some(contents) => ContentsAfter::Changed(contents),
+                        None => ContentsAfter::Unknown,
+                    };
+                    mutated.push(
+                        object_diff(
+                            client,
+                            &resolver,
+                            object_id,
+                            object_type,
+                            Some(previous_version),
+                            owner,
+                            after,
+                        )
+                        .await?,
+                    )
+                }
+                ObjectChange::Transferred {
+                    object_id,
+                    object_type,
+                    recipient,
+                    ..
+                } => {
+                    let after = match contents_after.get(&object_id) {
+                        Some(contents) => ContentsAfter::Changed(contents),
+                        None => ContentsAfter::Unchanged,
+                    };
+                    mutated.push(
+                        object_diff(
+                            client,
+                            &resolver,
+                            object_id,
+                            object_type,
+                            None,
+                            recipient,
+                            after,
+                        )
+                        .await?,
+                    )
+                }
+                ObjectChange::Created {
+                    object_id,
+                    object_type,
+                    ..
+                } => created.push((object_id, object_type.to_canonical_string(true))),
+                ObjectChange::Published { package_id, .. } => {
+                    created.push((package_id, "package".to_string()))
+                }
+                ObjectChange::Deleted {
+                    object_id,
+                    object_type,
+                    ..
+                } => deleted.push((object_id, object_type.to_canonical_string(true))),
+                ObjectChange::Wrapped {
+                    object_id,
+                    object_type,
+                    ..
+                } => wrapped.push((object_id, object_type.to_canonical_string(true))),
+            }
+        }
+
+        let mut balances = BTreeMap::new();
+        for change in dry_run.balance_changes {
+            *balances
+                .entry((change.owner, change.coin_type))
+                .or_insert(0i128) += change.amount;
+        }
+        let balance_changes = balances
+            .into_iter()
+            .filter(|(_, amount)| *amount != 0)
+            .map(|((owner, coin_type), amount)| BalanceDelta {
+                owner,
+                coin_type,
+                amount,
+            })
+            .collect();
+
+        Ok(Self {
+            status: dry_run.effects.status().clone(),
+            mutated,
+            balance_changes,
+            created,
+            deleted,
+            wrapped,
+        })
+    }
+}
+
+/// Contents of the Move objects written by the transaction, from the BCS serialized objects
+/// returned by its dry run.
+fn written_contents(
+    raw_objects: &[Vec<u8>],
+) -> Result<HashMap<ObjectID, (TypeTag, Vec<u8>)>, anyhow::Error> {
+    let mut contents = HashMap::new();
+    for bytes in raw_objects {
+        let object: Object = bcs::from_bytes(bytes)
+            .map_err(|e| anyhow!("Cannot decode an object written by the dry run: {e}"))?;
+        if let Some(move_object) = object.data.try_as_move() {
+            contents.insert(
+                object.id(),
+                (
+                    move_object.type_().clone().into(),
+                    move_object.contents().to_vec(),
+                ),
+            );
+        }
+    }
+    Ok(contents)
+}
+
+/// What is known of an object's contents after the transaction.
+enum ContentsAfter<'a> {
+    Unchanged,
+    Changed(&'a (TypeTag, Vec<u8>)),
+    Unknown,
+}
+
+/// Compare an object before the transaction, at `previous_version` or its latest version, with
+/// its owner and contents after the transaction.
+async fn object_diff<S: PackageStore>(
+    client: &SuiClient,
+    resolver: &Resolver<S>,
+    object_id: ObjectID,
+    object_type: StructTag,
+    previous_version: Option<SequenceNumber>,
+    owner_after: Owner,
+    after: ContentsAfter<'_>,
+) -> Result<ObjectDiff, anyhow::Error> {
+    let options = SuiObjectDataOptions::bcs_lossless();
+    let before = match previous_version {
+        Some(version) => match client
+            .read_api()
+            .try_get_parsed_past_object(object_id, version, options)
+            .await?
+        {
+            SuiPastObjectResponse::VersionFound(data) => Some(data),
+            _ => None,
+        },
+        None => {
+            client
+                .read_api()
+                .get_object_with_options(object_id, options)
+                .await?
+                .data
+        }
+    };
+
+    let mut before_fields = vec![];
+    let mut owner_before = None;
+    if let Some(data) = before {
+        owner_before = data.owner;
+        if let Some(SuiRawData::MoveObject(object)) = data.bcs {
+            let type_ = TypeTag::Struct(Box::new(object.type_));
+            before_fields = decode_fields(resolver, type_, &object.bcs_bytes).await?;
+        }
+    }
+
+    let mut fields = vec![];
+    if owner_before != Some(owner_after) {
+        fields.push(FieldDiff {
+            path: "(owner)".to_string(),
+            before: owner_before.map(|o| o.to_string()),
+            after: Some(owner_after.to_string()),
+        });
+    }
+    let contents_known = match after {
+        ContentsAfter::Unchanged => true,
+        ContentsAfter::Changed((type_, bytes)) => {
+            let after_fields = decode_fields(resolver, type_.clone(), bytes).await?;
+            fields.extend(diff_fields(&before_fields, &after_fields));
+            true
+        }
+        ContentsAfter::Unknown => false,
+    };
+    Ok(ObjectDiff {
+        object_id,
+        object_type,
+        fields,
+        contents_known,
+    })
+}
+
+async fn decode_fields<S: PackageStore>(
+    resolver: &Resolver<S>,
+    type_: TypeTag,
+    bytes: &[u8],
+) -> Result<Vec<(String, String)>, anyhow::Error> {
+    let layout = resolver.type_layout(type_).await?;
+    let value = MoveValue::simple_deserialize(bytes, &layout)?;
+    let mut fields = vec![];
+    flatten_value(String::new(), &value, &mut fields);
+    Ok(fields)
+}
+
+/// Flatten a Move value into `(path, value)` pairs, one per primitive value. Strings, IDs and
+/// byte vectors are kept whole.
+pub(crate) fn flatten_value(path: String, value: &MoveValue, out: &mut Vec<(String, String)>) {
+    match value {
+        MoveValue::Struct(s) => {
+            if let Some(rendered) = render_special_struct(s) {
+                out.push((path, rendered));
+                return;
+            }
+            for (name, field) in &s.fields {
+                flatten_value(join(&path, name.as_str()), field, out);
+            }
+        }
+        MoveValue::Variant(v) => {
+            out.push((join(&path, "(variant)"), v.variant_name.to_string()));
+            for (name, field) in &v.fields {
+                flatten_value(join(&path, name.as_str()), field, out);
+            }
+        }
+        MoveValue::Vector(elements) => {
+            if let Some(bytes) = as_bytes(elements) {
+                out.push((path, format!("0x{}", Hex::encode(bytes))));
+                return;
+            }
+            out.push((format!("{path}.length"), elements.len().to_string()));
+            for (i, element) in elements.iter().enumerate() {
+                flatten_value(format!("{path}[{i}]"), element, out);
+            }
+        }
+        MoveValue::Address(a) | MoveValue::Signer(a) => {
+            out.push((path, SuiAddress::from(*a).to_string()))
+        }
+        MoveValue::Bool(b) => out.push((path, b.to_string())),
+        MoveValue::U8(n) => out.push((path, n.to_string())),
+        MoveValue::U16(n) => out.push((path, n.to_string())),
+        MoveValue::U32(n) => out.push((path, n.to_string())),
+        MoveValue::U64(n) => out.push((path, n.to_string())),
+        MoveValue::U128(n) => out.push((path, n.to_string())),
+        MoveValue::U256(n) => out.push((path, n.to_string())),
+    }
+}
+
+fn join(path: &str, field: &str) -> String {
+    if path.is_empty() {
+        field.to_string()
+    } else {
+        format!("{path}.{field}")
+    }
+}
+
+/// Render `std::string::String`, `std::ascii::String` and `sui::object::ID` as a single value.
+fn render_special_struct(s: &MoveStruct) -> Option<String> {
+    let tag = &s.type_;
+    let is = |address: AccountAddress, module: &str, name: &str| {
+        tag.address == address && tag.module.as_str() == module && tag.name.as_str() == name
+    };
+    match s.fields.as_slice() {
+        [(_, MoveValue::Vector(elements))]
+            if is(MOVE_STDLIB_ADDRESS, "string", "String")
+                || is(MOVE_STDLIB_ADDRESS, "ascii", "String") =>
+        {
+            let bytes = as_bytes(elements)?;
+            Some(format!("{:?}", String::from_utf8_lossy(&bytes)))
+        }
+        [(_, MoveValue::Address(a))] if is(SUI_FRAMEWORK_ADDRESS, "object", "ID") => {
+            Some(SuiAddress::from(*a).to_string())
+        }
+        _ => None,
+    }
+}
+
+/// The bytes of a non-empty `vector<u8>`.
+fn as_bytes(elements: &[MoveValue]) -> Option<Vec<u8>> {
+    if elements.is_empty() {
+        return None;
+    }
+    elements
+        .iter()
+        .map(|e| match e {
+            MoveValue::U8(b) => Some(*b),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Fields that differ between `before` and `after`, in the order they appear in `after`, followed
+/// by the fields that only exist in `before`.
+pub(crate) fn diff_fields(
+    before: &[(String, String)],
+    after: &[(String, String)],
+) -> Vec<FieldDiff> {
+    let before_map: HashMap<_, _> = before.iter().map(|(p, v)| (p, v)).collect();
+    let after_map: HashMap<_, _> = after.iter().map(|(p, v)| (p, v)).collect();
+    let mut diffs: Vec<_> = after
+        .iter()
+        .filter(|(path, value)| before_map.get(path) != Some(&value))
+        .map(|(path, value)| FieldDiff {
+            path: path.clone(),
+            before: before_map.get(path).map(|v| v.to_string()),
+            after: Some(value.clone()),
+        })
+        .collect();
+    diffs.extend(
+        before
+            .iter()
+            .filter(|(path, _)| !after_map.contains_key(path))
+            .map(|(path, value)| FieldDiff {
+                path: path.clone(),
+                before: Some(value.clone()),
+                after: None,
+            }),
+    );
+    diffs
+}
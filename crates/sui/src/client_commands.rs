@@ -2,11 +2,17 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
+    address_book::{AddressBookCommand, AddressBookEntry},
+    change_preview::ChangePreview,
     clever_error_rendering::render_clever_error_opt,
     client_ptb::ptb::PTB,
+    coin_management::{consolidate, get_gas_coin_candidates, select_gas_coins, ConsolidateOutput},
     displays::Pretty,
     key_identity::{get_identity_address, KeyIdentity},
+    multisig_proposal::MultiSigProposal,
+    offline::{OfflineCommand, OfflineTransactionBundle},
     verifier_meter::{AccumulatingMeter, Accumulator},
+    watch::{WatchTarget, Watcher},
 };
 use std::{
     collections::{btree_map::Entry, BTreeMap},
@@ -15,6 +21,7 @@ use std::{
     path::{Path, PathBuf},
     str::FromStr,
     sync::Arc,
+    time::Duration,
 };
 
 use anyhow::{anyhow, bail, ensure, Context};
@@ -25,6 +32,7 @@ use fastcrypto::{
     encoding::{Base64, Encoding},
     traits::ToFromBytes,
 };
+use inquire::Confirm;
 use reqwest::StatusCode;
 
 use move_binary_format::CompiledModule;
@@ -48,7 +56,9 @@ use sui_json_rpc_types::{
     SuiTransactionBlockEffects, SuiTransactionBlockEffectsAPI, SuiTransactionBlockResponse,
     SuiTransactionBlockResponseOptions,
 };
-use sui_keys::keystore::AccountKeystore;
+use sui_keys::external_keystore::{ExternalKeystore, ExternalSignerEndpoint};
+use sui_keys::key_derive::derivation_path_for_index;
+use sui_keys::keystore::{AccountKeystore, Keystore};
 use sui_move_build::{
     build_from_resolution_graph, check_invalid_dependencies, check_unpublished_dependencies,
     gather_published_ids, BuildConfig, CompiledPackage, PackageDependencies,
@@ -112,6 +122,13 @@ pub enum SuiClientCommands {
     /// Default environment used for commands when none specified
     #[clap(name = "active-env")]
     ActiveEnv,
+    /// Manage the address book of named addresses that are not in the keystore. Names in the
+    /// address book can be used wherever an address alias is accepted.
+    #[clap(name = "address-book")]
+    AddressBook {
+        #[clap(subcommand)]
+        cmd: AddressBookCommand,
+    },
     /// Obtain the Addresses managed by the client.
     #[clap(name = "addresses")]
     Addresses {
@@ -169,6 +186,32 @@ pub enum SuiClientCommands {
     #[clap(name = "chain-identifier")]
     ChainIdentifier,
 
+    /// Merge the coins of a type owned by an address into a few coins, using as few transactions
+    /// as possible. The other coins are merged into the largest ones, balancing their amounts.
+    #[clap(name = "consolidate")]
+    Consolidate {
+        /// Type of the coins to merge, e.g. 0x2::sui::SUI (the default).
+        #[clap(long, default_value = "0x2::sui::SUI")]
+        coin_type: String,
+        /// Number of coins to keep.
+        #[clap(long, default_value = "1")]
+        target_count: usize,
+        /// Address (or its alias) owning the coins. Defaults to the active address.
+        #[clap(long)]
+        address: Option<KeyIdentity>,
+        /// Gas budget of each transaction (in MIST). If not provided, it is estimated with a dry
+        /// run.
+        #[clap(long)]
+        gas_budget: Option<u64>,
+        /// Show how the coins would be merged, without executing any transaction.
+        #[clap(long)]
+        dry_run: bool,
+        /// Before signing each transaction, show what it will change, and only sign it if the
+        /// change is confirmed.
+        #[clap(long, conflicts_with = "dry_run")]
+        preview: bool,
+    },
+
     /// Query a dynamic field by its address.
     #[clap(name = "dynamic-field")]
     DynamicFieldQuery {
@@ -203,6 +246,24 @@ pub enum SuiClientCommands {
         signed_tx_bytes: String,
     },
 
+    /// Sign with an external signer instead of a keystore file, by pointing the keystore of the
+    /// client config at a signer process or socket speaking the external signer protocol. The
+    /// signer is started (or connected to) once per command, when it first needs to sign or
+    /// list its keys.
+    #[clap(name = "external-signer")]
+    ExternalSigner {
+        /// Command that starts the signer, which reads requests from its stdin and writes
+        /// responses to its stdout.
+        #[clap(long, conflicts_with = "socket", required_unless_present = "socket")]
+        command: Option<String>,
+        /// Arguments passed to the signer command.
+        #[clap(long = "arg", requires = "command")]
+        args: Vec<String>,
+        /// Unix socket of a running signer.
+        #[clap(long)]
+        socket: Option<PathBuf>,
+    },
+
     /// Request gas coin from faucet. By default, it will use the active address and the active network.
     #[clap[name = "faucet"]]
     Faucet {
@@ -237,10 +298,22 @@ pub enum SuiClientCommands {
         opts: OptsWithGas,
     },
 
+    /// Execute a multisig proposal created with `sui keytool multi-sig-propose`. The members'
+    /// partial signatures are combined into a MultiSig signature, which fails if their weight
+    /// is below the threshold.
+    MultiSigExecute {
+        /// Path of the proposal file.
+        proposal: PathBuf,
+    },
+
     /// Generate new address and keypair with keypair scheme flag {ed25519 | secp256k1 | secp256r1}
     /// with optional derivation path, default to m/44'/784'/0'/0'/0' for ed25519 or
     /// m/54'/784'/0'/0/0 for secp256k1 or m/74'/784'/0'/0/0 for secp256r1. Word length can be
     /// { word12 | word15 | word18 | word21 | word24} default to word12 if not specified.
+    ///
+    /// If the keystore holds an HD wallet (see `sui keytool import --discover`) and neither a word
+    /// length nor a derivation path is given, the next unused account of the HD wallet is
+    /// derived instead of generating an unrelated key.
     #[clap(name = "new-address")]
     NewAddress {
         key_scheme: SignatureScheme,
@@ -282,6 +355,13 @@ pub enum SuiClientCommands {
         #[clap(name = "owner_address")]
         address: Option<KeyIdentity>,
     },
+    /// Prepare, and later submit, transactions that are signed on another machine with
+    /// `sui keytool sign-bundle`.
+    #[clap(name = "offline")]
+    Offline {
+        #[clap(subcommand)]
+        cmd: OfflineCommand,
+    },
     /// Pay coins to recipients following specified amounts, with input coins.
     /// Length of recipients must be the same as that of amounts.
     #[clap(name = "pay")]
@@ -516,6 +596,25 @@ pub enum SuiClientCommands {
         address_override: Option<ObjectID>,
     },
 
+    /// Follow the transactions that affect an address, an object or a package, printing their
+    /// balance changes, object changes and events as they are finalized. Runs until interrupted,
+    /// or until --limit transactions have been printed.
+    #[clap(name = "watch")]
+    Watch {
+        /// Address (or its alias), object ID or package ID to watch.
+        target: KeyIdentity,
+        /// Stop after printing this many transactions.
+        #[clap(long)]
+        limit: Option<usize>,
+        /// Interval between polls (in milliseconds), when the active environment has no websocket
+        /// URL to subscribe to transactions, or for the objects created for a watched address.
+        #[clap(long, default_value = "1000")]
+        poll_interval_ms: u64,
+        /// Print each transaction as a JSON object on its own line.
+        #[clap(long)]
+        json: bool,
+    },
+
     /// Profile the gas usage of a transaction. Unless an output filepath is not specified, outputs a file `gas_profile_{tx_digest}_{unix_timestamp}.json` which can be opened in a flamegraph tool such as speedscope.
     #[clap(name = "profile-transaction")]
     ProfileTransaction {
@@ -608,13 +707,22 @@ pub struct Opts {
     /// `sui client execute-combined-signed-tx --signed-tx-bytes <SIGNED_TX_BYTES>`.
     #[arg(long, required = false)]
     pub serialize_signed_transaction: bool,
+    /// Before signing, show what the transaction will change: a before/after diff of the fields of
+    /// every mutated object, the balance changes per address and coin type, and the objects it
+    /// creates, deletes or wraps. The transaction is only signed if the change is confirmed.
+    #[arg(
+        long,
+        conflicts_with_all = ["dry_run", "dev_inspect", "serialize_unsigned_transaction"]
+    )]
+    pub preview: bool,
 }
 
 /// Global options with gas
 #[derive(Args, Debug)]
 pub struct OptsWithGas {
     /// ID of the gas object for gas payment.
-    /// If not provided, a gas object with at least gas_budget value will be selected
+    /// If not provided, the smallest SUI coin whose balance covers the gas budget is selected. If
+    /// no single coin covers it, the largest coins are merged to pay for gas, up to 256 coins.
     #[clap(long)]
     pub gas: Option<ObjectID>,
     #[clap(flatten)]
@@ -630,6 +738,7 @@ impl Opts {
             dev_inspect: false,
             serialize_unsigned_transaction: false,
             serialize_signed_transaction: false,
+            preview: false,
         }
     }
     /// Uses the passed gas_budget for the gas budget variable, sets dry run to true,
@@ -641,6 +750,7 @@ impl Opts {
             dev_inspect: false,
             serialize_unsigned_transaction: false,
             serialize_signed_transaction: false,
+            preview: false,
         }
     }
 }
@@ -757,6 +867,7 @@ impl SuiClientCommands {
                 // this will be displayed via trace info, so no output is needed here
                 SuiClientCommandResult::NoOutput
             }
+            SuiClientCommands::AddressBook { cmd } => cmd.execute(context)?,
             SuiClientCommands::Addresses { sort_by_alias } => {
                 let active_address = context.active_address()?;
                 let mut addresses: Vec<(String, SuiAddress)> = context
@@ -1372,6 +1483,8 @@ impl SuiClientCommands {
                 .await?
             }
 
+            SuiClientCommands::Offline { cmd } => cmd.execute(context).await?,
+
             SuiClientCommands::Objects { address } => {
                 let address = get_identity_address(address, context)?;
                 let client = context.get_client().await?;
@@ -1406,12 +1519,28 @@ impl SuiClientCommands {
                 derivation_path,
                 word_length,
             } => {
-                let (address, phrase, scheme) = context.config.keystore.generate_and_add_new_key(
-                    key_scheme,
-                    alias.clone(),
-                    derivation_path,
-                    word_length,
-                )?;
+                let (address, recovery_phrase, derivation_path) = match &mut context.config.keystore
+                {
+                    Keystore::Encrypted(keystore)
+                        if keystore.has_hd_wallet()
+                            && derivation_path.is_none()
+                            && word_length.is_none() =>
+                    {
+                        let (address, index) =
+                            keystore.derive_next_hd_account(key_scheme, alias.clone())?;
+                        let path = derivation_path_for_index(&key_scheme, index)?;
+                        (address, None, Some(path.to_string()))
+                    }
+                    keystore => {
+                        let (address, phrase, _) = keystore.generate_and_add_new_key(
+                            key_scheme,
+                            alias.clone(),
+                            derivation_path,
+                            word_length,
+                        )?;
+                        (address, Some(phrase), None)
+                    }
+                };
 
                 let alias = match alias {
                     Some(x) => x,
@@ -1421,8 +1550,9 @@ impl SuiClientCommands {
                 SuiClientCommandResult::NewAddress(NewAddressOutput {
                     alias,
                     address,
-                    key_scheme: scheme,
-                    recovery_phrase: phrase,
+                    key_scheme,
+                    recovery_phrase,
+                    derivation_path,
                 })
             }
             SuiClientCommands::Gas { address } => {
@@ -1467,6 +1597,27 @@ impl SuiClientCommands {
                     .await?;
                 SuiClientCommandResult::ChainIdentifier(ci)
             }
+            SuiClientCommands::Consolidate {
+                coin_type,
+                target_count,
+                address,
+                gas_budget,
+                dry_run,
+                preview,
+            } => {
+                let address = get_identity_address(address, context)?;
+                let output = consolidate(
+                    context,
+                    address,
+                    coin_type,
+                    target_count,
+                    gas_budget,
+                    dry_run,
+                    preview,
+                )
+                .await?;
+                SuiClientCommandResult::Consolidate(output)
+            }
             SuiClientCommands::SplitCoin {
                 coin_id,
                 amounts,
@@ -1507,6 +1658,43 @@ impl SuiClientCommands {
                 )
                 .await?
             }
+            SuiClientCommands::MultiSigExecute { proposal } => {
+                let transaction = MultiSigProposal::read(&proposal)?.transaction()?;
+                let response = context.execute_transaction_may_fail(transaction).await?;
+                SuiClientCommandResult::TransactionBlock(response)
+            }
+            SuiClientCommands::ExternalSigner {
+                command,
+                args,
+                socket,
+            } => {
+                let endpoint = match (command, socket) {
+                    (Some(command), _) => ExternalSignerEndpoint::Process { command, args },
+                    (None, Some(path)) => ExternalSignerEndpoint::Socket { path },
+                    (None, None) => bail!("Either a signer command or a socket is required"),
+                };
+                let keystore = Keystore::External(ExternalKeystore::new(endpoint));
+                let mut addresses: Vec<(String, SuiAddress)> = keystore
+                    .addresses_with_alias()
+                    .into_iter()
+                    .map(|(address, alias)| (alias.alias.to_string(), *address))
+                    .collect();
+                addresses.sort();
+                let active_address = match context.config.active_address {
+                    Some(address) if addresses.iter().any(|(_, a)| *a == address) => address,
+                    _ => addresses
+                        .first()
+                        .map(|(_, address)| *address)
+                        .ok_or_else(|| anyhow!("The external signer has no keys"))?,
+                };
+                context.config.keystore = keystore;
+                context.config.active_address = Some(active_address);
+                context.config.save()?;
+                SuiClientCommandResult::Addresses(AddressesOutput {
+                    active_address,
+                    addresses,
+                })
+            }
             SuiClientCommands::Switch { address, env } => {
                 let mut addr = None;
 
@@ -1605,6 +1793,37 @@ impl SuiClientCommands {
                 context.config.envs.clone(),
                 context.config.active_env.clone(),
             ),
+            SuiClientCommands::Watch {
+                target,
+                limit,
+                poll_interval_ms,
+                json,
+            } => {
+                let target = WatchTarget::resolve(target, context).await?;
+                let client = context.get_client().await?;
+                let poll_interval = Duration::from_millis(poll_interval_ms);
+                let watcher = Watcher::connect(&client, target, limit, poll_interval).await?;
+                if !json {
+                    if watcher.is_subscribed() {
+                        println!("Watching {target}");
+                    } else {
+                        println!("Watching {target} (polling every {poll_interval_ms}ms)");
+                    }
+                }
+                watcher
+                    .run(|update| {
+                        if json {
+                            let update = serde_json::to_string(&update)
+                                .context("Cannot serialize watch update")?;
+                            println!("{update}");
+                        } else {
+                            println!("{update}");
+                        }
+                        Ok(())
+                    })
+                    .await?;
+                SuiClientCommandResult::NoOutput
+            }
             SuiClientCommands::VerifySource {
                 package_path,
                 build_config,
@@ -1920,6 +2139,16 @@ impl Display for SuiClientCommandResult {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         let mut writer = String::new();
         match self {
+            SuiClientCommandResult::AddressBook(entries) => {
+                let mut builder = TableBuilder::default();
+                builder.set_header(vec!["name", "address"]);
+                for entry in entries {
+                    builder.push_record([entry.name.clone(), entry.address.to_string()]);
+                }
+                let mut table = builder.build();
+                table.with(TableStyle::rounded());
+                write!(f, "{}", table)?
+            }
             SuiClientCommandResult::Addresses(addresses) => {
                 let mut builder = TableBuilder::default();
                 builder.set_header(vec!["alias", "address", "active address"]);
@@ -2014,10 +2243,12 @@ impl Display for SuiClientCommandResult {
                     "keyScheme",
                     new_address.key_scheme.to_string().as_str(),
                 ]);
-                builder.push_record(vec![
-                    "recoveryPhrase",
-                    new_address.recovery_phrase.to_string().as_str(),
-                ]);
+                if let Some(recovery_phrase) = &new_address.recovery_phrase {
+                    builder.push_record(vec!["recoveryPhrase", recovery_phrase.as_str()]);
+                }
+                if let Some(derivation_path) = &new_address.derivation_path {
+                    builder.push_record(vec!["derivationPath", derivation_path.as_str()]);
+                }
 
                 let mut table = builder.build();
                 table.with(TableStyle::rounded());
@@ -2106,6 +2337,9 @@ impl Display for SuiClientCommandResult {
             SuiClientCommandResult::ChainIdentifier(ci) => {
                 writeln!(writer, "{}", ci)?;
             }
+            SuiClientCommandResult::Consolidate(output) => {
+                write!(writer, "{output}")?;
+            }
             SuiClientCommandResult::Switch(response) => {
                 write!(writer, "{}", response)?;
             }
@@ -2229,6 +2463,10 @@ impl Display for SuiClientCommandResult {
                 writeln!(f, "{}", table)?;
             }
             SuiClientCommandResult::NoOutput => {}
+            SuiClientCommandResult::OfflineBundle { path, bundle } => {
+                writeln!(f, "{bundle}")?;
+                writeln!(f, "Offline bundle written to {}", path.display())?;
+            }
             SuiClientCommandResult::DryRun(response) => {
                 writeln!(f, "{}", Pretty(response))?;
             }
@@ -2333,9 +2571,11 @@ impl SuiClientCommandResult {
             }) => (),
             SuiClientCommandResult::ActiveAddress(_)
             | SuiClientCommandResult::ActiveEnv(_)
+            | SuiClientCommandResult::AddressBook(_)
             | SuiClientCommandResult::Addresses(_)
             | SuiClientCommandResult::Balance(_, _)
             | SuiClientCommandResult::ChainIdentifier(_)
+            | SuiClientCommandResult::Consolidate(_)
             | SuiClientCommandResult::DynamicFieldQuery(_)
             | SuiClientCommandResult::DevInspect(_)
             | SuiClientCommandResult::Envs(_, _)
@@ -2345,6 +2585,7 @@ impl SuiClientCommandResult {
             | SuiClientCommandResult::NoOutput
             | SuiClientCommandResult::Object(_)
             | SuiClientCommandResult::Objects(_)
+            | SuiClientCommandResult::OfflineBundle { .. }
             | SuiClientCommandResult::RawObject(_)
             | SuiClientCommandResult::SerializedSignedTransaction(_)
             | SuiClientCommandResult::SerializedUnsignedTransaction(_)
@@ -2378,7 +2619,11 @@ pub struct NewAddressOutput {
     pub alias: String,
     pub address: SuiAddress,
     pub key_scheme: SignatureScheme,
-    pub recovery_phrase: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recovery_phrase: Option<String>,
+    /// Set when the address was derived from the keystore's HD wallet.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub derivation_path: Option<String>,
 }
 
 #[derive(Serialize)]
@@ -2482,9 +2727,11 @@ impl ObjectsOutput {
 pub enum SuiClientCommandResult {
     ActiveAddress(Option<SuiAddress>),
     ActiveEnv(Option<String>),
+    AddressBook(Vec<AddressBookEntry>),
     Addresses(AddressesOutput),
     Balance(Vec<(Option<SuiCoinMetadata>, Vec<Coin>)>, bool),
     ChainIdentifier(String),
+    Consolidate(ConsolidateOutput),
     DynamicFieldQuery(DynamicFieldPage),
     DryRun(DryRunTransactionBlockResponse),
     DevInspect(DevInspectResults),
@@ -2495,6 +2742,10 @@ pub enum SuiClientCommandResult {
     NoOutput,
     Object(SuiObjectResponse),
     Objects(Vec<SuiObjectResponse>),
+    OfflineBundle {
+        path: PathBuf,
+        bundle: OfflineTransactionBundle,
+    },
     RawObject(SuiObjectResponse),
     SerializedSignedTransaction(SenderSignedData),
     SerializedUnsignedTransaction(TransactionData),
@@ -2816,12 +3067,14 @@ pub(crate) async fn dry_run_or_execute_or_serialize(
         gas_budget,
         serialize_unsigned_transaction,
         serialize_signed_transaction,
+        preview,
     ) = (
         opts.dry_run,
         opts.dev_inspect,
         opts.gas_budget,
         opts.serialize_unsigned_transaction,
         opts.serialize_signed_transaction,
+        opts.preview,
     );
     ensure!(
         !serialize_unsigned_transaction || !serialize_signed_transaction,
@@ -2886,16 +3139,21 @@ pub(crate) async fn dry_run_or_execute_or_serialize(
     };
 
     debug!("Preparing transaction data");
+    let gas = match gas {
+        Some(gas) => gas,
+        None => {
+            let excluded = tx_kind
+                .input_objects()?
+                .iter()
+                .map(|input| input.object_id())
+                .collect();
+            let coins = get_gas_coin_candidates(&client, signer, gas_budget, &excluded).await?;
+            select_gas_coins(&coins, gas_budget, &excluded)?
+        }
+    };
     let tx_data = client
         .transaction_builder()
-        .tx_data(
-            signer,
-            tx_kind,
-            gas_budget,
-            gas_price,
-            gas.unwrap_or_default(),
-            None,
-        )
+        .tx_data(signer, tx_kind, gas_budget, gas_price, gas, None)
         .await?;
     debug!("Finished preparing transaction data");
 
@@ -2904,6 +3162,15 @@ pub(crate) async fn dry_run_or_execute_or_serialize(
             tx_data,
         ))
     } else {
+        if preview {
+            println!("{}", ChangePreview::new(&client, &tx_data).await?);
+            if !Confirm::new("Sign this transaction?")
+                .with_default(false)
+                .prompt()?
+            {
+                bail!("Transaction cancelled, it was not signed");
+            }
+        }
         let signature = context.config.keystore.sign_secure(
             &tx_data.sender(),
             &tx_data,
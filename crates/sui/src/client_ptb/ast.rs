@@ -29,6 +29,7 @@ pub const PUBLISH: &str = "publish";
 pub const UPGRADE: &str = "upgrade";
 pub const ASSIGN: &str = "assign";
 pub const PREVIEW: &str = "preview";
+pub const PREVIEW_CHANGES: &str = "preview-changes";
 pub const WARN_SHADOWS: &str = "warn-shadows";
 pub const GAS_BUDGET: &str = "gas-budget";
 pub const SUMMARY: &str = "summary";
@@ -39,6 +40,12 @@ pub const DEV_INSPECT: &str = "dev-inspect";
 pub const SERIALIZE_UNSIGNED: &str = "serialize-unsigned-transaction";
 pub const SERIALIZE_SIGNED: &str = "serialize-signed-transaction";
 
+// Script commands, expanded before parsing
+pub const INCLUDE: &str = "include";
+pub const CONST: &str = "const";
+pub const FOR: &str = "for";
+pub const END: &str = "end";
+
 // Types
 pub const U8: &str = "u8";
 pub const U16: &str = "u16";
@@ -69,6 +76,7 @@ pub const COMMANDS: &[&str] = &[
     UPGRADE,
     ASSIGN,
     PREVIEW,
+    PREVIEW_CHANGES,
     WARN_SHADOWS,
     GAS_BUDGET,
     SUMMARY,
@@ -78,6 +86,10 @@ pub const COMMANDS: &[&str] = &[
     DEV_INSPECT,
     SERIALIZE_UNSIGNED,
     SERIALIZE_SIGNED,
+    INCLUDE,
+    CONST,
+    FOR,
+    END,
 ];
 
 pub fn is_keyword(s: &str) -> bool {
@@ -107,6 +119,7 @@ pub struct Program {
 #[derive(Debug, Clone)]
 pub struct ProgramMetadata {
     pub preview_set: bool,
+    pub preview_changes_set: bool,
     pub summary_set: bool,
     pub serialize_unsigned_set: bool,
     pub serialize_signed_set: bool,
@@ -35,6 +35,7 @@ struct ProgramParsingState {
     parsed: Vec<Spanned<ParsedPTBCommand>>,
     errors: Vec<PTBError>,
     preview_set: bool,
+    preview_changes_set: bool,
     summary_set: bool,
     warn_shadows_set: bool,
     serialize_unsigned_set: bool,
@@ -58,6 +59,7 @@ impl<'a, I: Iterator<Item = &'a str>> ProgramParser<'a, I> {
                 parsed: Vec::new(),
                 errors: Vec::new(),
                 preview_set: false,
+                preview_changes_set: false,
                 summary_set: false,
                 warn_shadows_set: false,
                 serialize_unsigned_set: false,
@@ -114,6 +116,7 @@ impl<'a, I: Iterator<Item = &'a str>> ProgramParser<'a, I> {
                 L(T::Command, A::DRY_RUN) => flag!(dry_run_set),
                 L(T::Command, A::DEV_INSPECT) => flag!(dev_inspect_set),
                 L(T::Command, A::PREVIEW) => flag!(preview_set),
+                L(T::Command, A::PREVIEW_CHANGES) => flag!(preview_changes_set),
                 L(T::Command, A::WARN_SHADOWS) => flag!(warn_shadows_set),
                 L(T::Command, A::GAS_COIN) => {
                     let specifier = try_!(self.parse_gas_specifier());
@@ -204,6 +207,7 @@ impl<'a, I: Iterator<Item = &'a str>> ProgramParser<'a, I> {
                 },
                 A::ProgramMetadata {
                     preview_set: self.state.preview_set,
+                    preview_changes_set: self.state.preview_changes_set,
                     summary_set: self.state.summary_set,
                     serialize_unsigned_set: self.state.serialize_unsigned_set,
                     serialize_signed_set: self.state.serialize_signed_set,
@@ -2,15 +2,17 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
+    address_book::AddressBook,
     client_commands::{dry_run_or_execute_or_serialize, Opts, OptsWithGas, SuiClientCommandResult},
     client_ptb::{
         ast::{ParsedProgram, Program},
         builder::PTBBuilder,
-        error::{build_error_reports, PTBError},
+        error::{build_error_reports, PTBError, Span},
+        script::{expand_script, Expansion},
         token::{Lexeme, Token},
     },
     displays::Pretty,
-    sp,
+    err, sp,
 };
 
 use super::{ast::ProgramMetadata, lexer::Lexer, parser::ProgramParser};
@@ -18,6 +20,7 @@ use anyhow::{anyhow, ensure, Error};
 use clap::{arg, Args, ValueHint};
 use move_core_types::account_address::AccountAddress;
 use serde::Serialize;
+use std::collections::BTreeMap;
 use sui_json_rpc_types::{SuiExecutionStatus, SuiTransactionBlockEffectsAPI};
 use sui_keys::keystore::AccountKeystore;
 use sui_sdk::{wallet_context::WalletContext, SuiClient};
@@ -66,6 +69,22 @@ impl PTB {
             }
         }
 
+        // Expand script commands (--include, --const, --for) into regular commands. Errors in the
+        // expanded commands are mapped back onto the source they were expanded from.
+        let expansion = match expand_script(self.args) {
+            Err(errors) => {
+                let suffix = if errors.len() > 1 { "s" } else { "" };
+                let rendered = build_error_reports(&source_string, errors);
+                eprintln!("Encountered error{suffix} when expanding PTB script:");
+                for e in rendered.iter() {
+                    eprintln!("{:?}", e);
+                }
+                anyhow::bail!("Could not build PTB due to previous error{suffix}");
+            }
+            Ok(expansion) => expansion,
+        };
+        let tokens = expansion.args.iter().map(|s| s.as_str());
+
         // Tokenize and parse to get the program
         let (program, program_metadata) = match ProgramParser::new(tokens)
             .map_err(|e| vec![e])
@@ -73,7 +92,7 @@ impl PTB {
         {
             Err(errors) => {
                 let suffix = if errors.len() > 1 { "s" } else { "" };
-                let rendered = build_error_reports(&source_string, errors);
+                let rendered = build_error_reports(&source_string, expansion.remap_errors(errors));
                 eprintln!("Encountered error{suffix} when parsing PTB:");
                 for e in rendered.iter() {
                     eprintln!("{:?}", e);
@@ -88,6 +107,15 @@ impl PTB {
             "Cannot specify both flags: --serialize-unsigned-transaction and --serialize-signed-transaction."
         );
 
+        ensure!(
+            !program_metadata.preview_changes_set
+                || !(program_metadata.dry_run_set
+                    || program_metadata.dev_inspect_set
+                    || program_metadata.serialize_unsigned_set),
+            "Cannot specify --preview-changes with --dry-run, --dev-inspect or \
+            --serialize-unsigned-transaction, as the transaction is not signed."
+        );
+
         if program_metadata.preview_set {
             println!(
                 "{}",
@@ -107,7 +135,7 @@ impl PTB {
         if !warnings.is_empty() {
             let suffix = if warnings.len() > 1 { "s" } else { "" };
             eprintln!("Warning{suffix} produced when building PTB:");
-            let rendered = build_error_reports(&source_string, warnings);
+            let rendered = build_error_reports(&source_string, expansion.remap_errors(warnings));
             for e in rendered.iter() {
                 eprintln!("{:?}", e);
             }
@@ -116,7 +144,7 @@ impl PTB {
             Err(errors) => {
                 let suffix = if errors.len() > 1 { "s" } else { "" };
                 eprintln!("Encountered error{suffix} when building PTB:");
-                let rendered = build_error_reports(&source_string, errors);
+                let rendered = build_error_reports(&source_string, expansion.remap_errors(errors));
                 for e in rendered.iter() {
                     eprintln!("{:?}", e);
                 }
@@ -154,6 +182,7 @@ impl PTB {
                 gas_budget: program_metadata.gas_budget.map(|x| x.value),
                 serialize_unsigned_transaction: program_metadata.serialize_unsigned_set,
                 serialize_signed_transaction: program_metadata.serialize_signed_set,
+                preview: program_metadata.preview_changes_set,
             },
         };
 
@@ -224,22 +253,44 @@ impl PTB {
         Result<ProgrammableTransaction, Vec<PTBError>>,
         Vec<PTBError>,
     ) {
-        let starting_addresses = context
-            .config
-            .keystore
-            .addresses_with_alias()
+        let address_book = match AddressBook::load_or_default(&AddressBook::path(&context.config)) {
+            Ok(address_book) => address_book,
+            Err(e) => {
+                let error = err!(Span::eof_span(), "Cannot load the address book: {e}");
+                return (Err(vec![error]), vec![]);
+            }
+        };
+        let mut starting_addresses: BTreeMap<_, _> = address_book
+            .entries()
             .into_iter()
-            .map(|(sa, alias)| (alias.alias.clone(), AccountAddress::from(*sa)))
+            .map(|entry| (entry.name, AccountAddress::from(entry.address)))
             .collect();
+        // Aliases of keys in the keystore take precedence over names in the address book.
+        starting_addresses.extend(
+            context
+                .config
+                .keystore
+                .addresses_with_alias()
+                .into_iter()
+                .map(|(sa, alias)| (alias.alias.clone(), AccountAddress::from(*sa))),
+        );
         let builder = PTBBuilder::new(starting_addresses, client.read_api());
         builder.build(program).await
     }
 
-    /// Exposed for testing
-    pub fn parse_ptb_commands(args: Vec<String>) -> Result<ParsedProgram, Vec<PTBError>> {
-        ProgramParser::new(args.iter().map(|s| s.as_str()))
+    /// Exposed for testing. Errors from building the program are mapped back onto `args` with
+    /// the returned expansion.
+    pub fn parse_ptb_commands(
+        args: Vec<String>,
+    ) -> Result<(ParsedProgram, Expansion), Vec<PTBError>> {
+        let expansion = expand_script(args)?;
+        match ProgramParser::new(expansion.args.iter().map(|s| s.as_str()))
             .map_err(|e| vec![e])
             .and_then(|parser| parser.parse())
+        {
+            Ok(program) => Ok((program, expansion)),
+            Err(errors) => Err(expansion.remap_errors(errors)),
+        }
     }
 }
 
@@ -393,6 +444,11 @@ pub fn ptb_description() -> clap::Command {
             --"preview"
             "Preview the list of PTB transactions instead of executing them."
         ))
+        .arg(arg!(
+            --"preview-changes"
+            "Before signing, show the object and balance changes of the transaction, and only \
+            sign it if they are confirmed."
+        ))
         .arg(arg!(
             --"serialize-unsigned-transaction"
             "Instead of executing the transaction, serialize the bcs bytes of the unsigned \
@@ -416,4 +472,49 @@ pub fn ptb_description() -> clap::Command {
             --"json"
             "Return command outputs in json format."
         ))
+        .arg(arg!(
+            --"include" <PTB_FILE>
+            "Include the commands of a PTB file. PTB files can contain # comments, and the \
+            --const and --for script commands."
+        )
+        .long_help(
+            "Include the commands of a PTB file. PTB files can contain # comments, and the \
+            --const and --for script commands. Paths in a file are relative to that file.\
+            \n\nExamples:\
+            \n --include setup.ptb --gas-budget 5000000"
+        ).value_hint(ValueHint::FilePath))
+        .arg(arg!(
+            --"const" <CONST>
+            "Declare a named constant, which cannot be reassigned."
+        )
+        .long_help(
+            "Declare a named constant, which cannot be reassigned. It can be used like a \
+            variable, and as the list of a --for loop.\
+            \n\nExamples:\
+            \n --const AMOUNTS [1000, 2000]\
+            \n --const ADMIN @0x42"
+        )
+        .value_names(["NAME", "VALUE"]))
+        .arg(arg!(
+            --"for" <FOR>
+            "Repeat the commands up to the matching --end for each element of a list, or for each \
+            row of a CSV file."
+        )
+        .long_help(
+            "Repeat the commands up to the matching --end for each element of a list, or for each \
+            row of a CSV file, binding the loop variables with --assign. A CSV file binds one \
+            variable per column, and a first row repeating the variable names is skipped. \
+            Loops are limited to 1024 iterations.\
+            \n\nExamples:\
+            \n --for amount in [1000, 2000] --split-coins gas [amount] --end\
+            \n --for recipient amount in csv recipients.csv\
+            \n   --split-coins gas [amount] --assign coin\
+            \n   --transfer-objects [coin] recipient\
+            \n --end"
+        )
+        .value_names(["VARS", "in", "LIST"]))
+        .arg(arg!(
+            --"end"
+            "Close a --for loop."
+        ))
 }
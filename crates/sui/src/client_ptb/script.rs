@@ -0,0 +1,595 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! PTB scripts. On top of the regular PTB commands, a script can declare named constants
+//! (`--const`), repeat commands with bounded loops over a list or the rows of a CSV file
+//! (`--for ... --end`), and splice in other PTB files (`--include`). Files may contain `#`
+//! comments. Scripts are expanded into a flat sequence of regular PTB commands before parsing:
+//!
+//! ```text
+//! --const amounts [1000, 2000]
+//! --for recipient amount in csv recipients.csv
+//!   --split-coins gas [amount]
+//!   --assign coin
+//!   --transfer-objects [coin] recipient
+//! --end
+//! ```
+//!
+//! Constants and loop variables are bound with `--assign`, so they are used like any other PTB
+//! variable. Errors in the expanded commands are reported on the script they were expanded from.
+
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{err, error};
+
+use super::{
+    ast::{self as A, all_keywords, is_keyword},
+    error::{PTBError, PTBResult, Span, Spanned},
+};
+
+/// Maximum number of iterations of a single `--for` loop.
+pub const MAX_LOOP_ITERATIONS: usize = 1024;
+/// Maximum number of commands a script can expand to.
+pub const MAX_EXPANDED_COMMANDS: usize = 4096;
+/// Maximum nesting depth of `--include`d files.
+pub const MAX_INCLUDE_DEPTH: usize = 16;
+
+/// Regular PTB commands expanded from a script, along with the span of the script's source that
+/// each of their shell tokens comes from.
+pub struct Expansion {
+    pub args: Vec<String>,
+    origins: Vec<Span>,
+}
+
+impl Expansion {
+    /// Map errors in the expanded commands onto the script's source. Tokens copied from the script
+    /// keep their exact location, while tokens generated by a script command or read from an
+    /// included file point at the command that produced them.
+    pub fn remap_errors(&self, errors: Vec<PTBError>) -> Vec<PTBError> {
+        errors
+            .into_iter()
+            .map(|e| PTBError {
+                span: self.remap_span(e.span),
+                ..e
+            })
+            .collect()
+    }
+
+    fn remap_span(&self, span: Span) -> Span {
+        let start = self.remap_offset(span.start, false);
+        let end = self.remap_offset(span.end, true);
+        Span {
+            start: start.min(end),
+            end: start.max(end),
+        }
+    }
+
+    /// Map an offset into the expanded commands, joined by spaces like the spans produced by the
+    /// lexer, to an offset into the script's source.
+    fn remap_offset(&self, offset: usize, is_end: bool) -> usize {
+        let mut token_start = 0;
+        for (arg, origin) in self.args.iter().zip(&self.origins) {
+            let token_end = token_start + arg.len();
+            if offset <= token_end {
+                return if origin.end - origin.start == arg.len() {
+                    origin.start + offset.saturating_sub(token_start)
+                } else if is_end {
+                    origin.end
+                } else {
+                    origin.start
+                };
+            }
+            token_start = token_end + 1;
+        }
+        // past the expanded commands, e.g. an unexpected end of input
+        usize::MAX
+    }
+}
+
+/// Expand the PTB script commands in `args` into regular PTB commands. Relative paths are
+/// resolved against the current directory. Arguments without script commands are returned as is.
+pub fn expand_script(args: Vec<String>) -> Result<Expansion, Vec<PTBError>> {
+    let source = args.join(" ");
+    let commands = split_commands(&args, &source);
+    if !commands.iter().any(|cmd| cmd.is_script_command()) {
+        let mut offset = 0;
+        let origins = args
+            .iter()
+            .map(|arg| {
+                let span = Span {
+                    start: offset,
+                    end: offset + arg.len(),
+                };
+                offset = span.end + 1;
+                span
+            })
+            .collect();
+        return Ok(Expansion { args, origins });
+    }
+
+    let mut expander = ScriptExpander {
+        constants: BTreeMap::new(),
+        include_stack: vec![],
+        num_commands: 0,
+    };
+    let (args, origins) = expander
+        .expand(&commands, Path::new("."), false)
+        .map_err(|e| vec![e])?
+        .into_iter()
+        .unzip();
+    Ok(Expansion { args, origins })
+}
+
+/// A command, and the source text of its arguments.
+struct RawCommand<'a> {
+    /// Name of the command without the leading `--`. Empty for input before the first command.
+    name: Spanned<&'a str>,
+    /// The shell token following a command that takes a path (e.g. `--publish <PATH>`).
+    file: Option<Spanned<&'a str>>,
+    args: Spanned<&'a str>,
+}
+
+impl<'a> RawCommand<'a> {
+    fn is(&self, name: &str) -> bool {
+        self.name.value == name
+    }
+
+    fn is_script_command(&self) -> bool {
+        [A::INCLUDE, A::CONST, A::FOR, A::END]
+            .iter()
+            .any(|name| self.is(name))
+    }
+
+    fn span(&self) -> Span {
+        if !self.args.value.is_empty() {
+            return self.name.span.widen(self.args.span);
+        }
+        self.name.span.widen_opt(self.file.map(|file| file.span))
+    }
+
+    /// Shell tokens that reproduce this command, and their spans.
+    fn to_tokens(&self) -> Vec<(String, Span)> {
+        let mut tokens = vec![];
+        if !self.name.value.is_empty() {
+            tokens.push((format!("--{}", self.name.value), self.name.span));
+        }
+        tokens.extend(self.file.map(|file| (file.value.to_owned(), file.span)));
+        if !self.args.value.is_empty() {
+            tokens.push((self.args.value.to_owned(), self.args.span));
+        }
+        tokens
+    }
+}
+
+/// Split shell tokens into commands. A command starts with `--` at the beginning of a shell token
+/// or after whitespace, outside of a string. Spans index into `source`, the tokens joined by
+/// spaces, like the spans produced by the lexer.
+fn split_commands<'a>(tokens: &'a [String], source: &'a str) -> Vec<RawCommand<'a>> {
+    // Command names, the file each one takes if any, and where their arguments start.
+    let mut starts: Vec<(Spanned<&'a str>, Option<Spanned<&'a str>>, usize)> = vec![];
+    let mut offset = 0;
+    let mut tokens = tokens.iter();
+    while let Some(token) = tokens.next() {
+        let mut quote = None;
+        let mut after_whitespace = true;
+        let mut takes_file = false;
+        for (i, c) in token.char_indices() {
+            match quote {
+                Some(q) if c == q => quote = None,
+                Some(_) => (),
+                None if c == '"' || c == '\'' => quote = Some(c),
+                None if after_whitespace && is_command_start(&token[i..]) => {
+                    let len = 2 + token[i + 2..]
+                        .find(|c: char| !is_ident_continue(c))
+                        .unwrap_or(token.len() - i - 2);
+                    let start = offset + i;
+                    let name = Span {
+                        start,
+                        end: start + len,
+                    }
+                    .wrap(&source[start + 2..start + len]);
+                    // Like the lexer, `--publish <PATH>` takes the whole next shell token.
+                    takes_file = i + len == token.len()
+                        && [A::PUBLISH, A::UPGRADE, A::INCLUDE].contains(&name.value);
+                    starts.push((name, None, name.span.end));
+                }
+                None => (),
+            }
+            after_whitespace = c.is_whitespace();
+        }
+        offset += token.len() + 1;
+
+        if takes_file {
+            if let (Some(file), Some(last)) = (tokens.next(), starts.last_mut()) {
+                let span = Span {
+                    start: offset,
+                    end: offset + file.len(),
+                };
+                last.1 = Some(span.wrap(&source[span.start..span.end]));
+                last.2 = offset + file.len();
+                offset += file.len() + 1;
+            }
+        }
+    }
+
+    let mut commands = vec![];
+    // Input before the first command is kept as a nameless command.
+    let first = starts
+        .first()
+        .map_or(source.len(), |(name, _, _)| name.span.start);
+    if !source[..first].trim().is_empty() {
+        commands.push(RawCommand {
+            name: Span { start: 0, end: 0 }.wrap(""),
+            file: None,
+            args: trimmed_span(source, 0, first),
+        });
+    }
+    for (i, (name, file, args_start)) in starts.iter().enumerate() {
+        let args_end = starts
+            .get(i + 1)
+            .map_or(source.len(), |(next, _, _)| next.span.start);
+        commands.push(RawCommand {
+            name: *name,
+            file: *file,
+            args: trimmed_span(source, *args_start, args_end),
+        });
+    }
+    commands
+}
+
+/// The source text between `start` and `end`, without surrounding whitespace.
+fn trimmed_span(source: &str, start: usize, end: usize) -> Spanned<&str> {
+    let text = &source[start.min(end)..end];
+    let trimmed = text.trim_start();
+    let start = end - text.len() + (text.len() - trimmed.len());
+    let trimmed = trimmed.trim_end();
+    Span {
+        start,
+        end: start + trimmed.len(),
+    }
+    .wrap(trimmed)
+}
+
+fn is_command_start(s: &str) -> bool {
+    s.strip_prefix("--")
+        .and_then(|rest| rest.chars().next())
+        .is_some_and(|c| c.is_ascii_alphabetic())
+}
+
+fn is_ident_continue(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_' || c == '-'
+}
+
+struct ScriptExpander {
+    /// Constants declared so far, and their values.
+    constants: BTreeMap<String, String>,
+    /// Canonical paths of the files currently being included, to detect cycles.
+    include_stack: Vec<PathBuf>,
+    /// Number of commands emitted so far.
+    num_commands: usize,
+}
+
+impl ScriptExpander {
+    /// Expand `commands`, resolving relative paths against `base_dir`. Returns the expanded shell
+    /// tokens with the span of the command they come from.
+    fn expand(
+        &mut self,
+        commands: &[RawCommand],
+        base_dir: &Path,
+        in_loop: bool,
+    ) -> PTBResult<Vec<(String, Span)>> {
+        let mut expanded = vec![];
+        let mut i = 0;
+        while i < commands.len() {
+            let cmd = &commands[i];
+            i += 1;
+
+            if cmd.is(A::INCLUDE) {
+                expanded.extend(self.include(cmd, base_dir, in_loop)?);
+            } else if cmd.is(A::CONST) {
+                if in_loop {
+                    error!(cmd.span(), "Constants cannot be declared inside a loop");
+                }
+                let (name, value) = self.binding(cmd, "--const <NAME> <VALUE>")?;
+                self.constants.insert(name.clone(), value.clone());
+                expanded.extend(self.assign(&name, &value, cmd.span())?);
+            } else if cmd.is(A::FOR) {
+                let body_end = find_loop_end(commands, i).ok_or_else(|| {
+                    err!(cmd.span() => help: { "Close the loop with --end" }, "Unterminated loop")
+                })?;
+                let body = &commands[i..body_end];
+                i = body_end + 1;
+
+                let (vars, rows) = self.loop_header(cmd, base_dir)?;
+                for row in rows {
+                    for (var, value) in vars.iter().zip(row) {
+                        expanded.extend(self.assign(var, &value, cmd.span())?);
+                    }
+                    expanded.extend(self.expand(body, base_dir, true)?);
+                }
+            } else if cmd.is(A::END) {
+                error!(cmd.span(), "Found --end without a matching --for");
+            } else {
+                if cmd.is(A::ASSIGN) {
+                    let name = cmd.args.value.split_whitespace().next().unwrap_or_default();
+                    if self.constants.contains_key(name) {
+                        error!(cmd.span(), "Cannot assign to constant '{name}'");
+                    }
+                }
+                self.count_command(cmd.span())?;
+                expanded.extend(cmd.to_tokens());
+            }
+        }
+        Ok(expanded)
+    }
+
+    /// Expand `--include <FILE>`.
+    fn include(
+        &mut self,
+        cmd: &RawCommand,
+        base_dir: &Path,
+        in_loop: bool,
+    ) -> PTBResult<Vec<(String, Span)>> {
+        let sp = cmd.span();
+        let path = resolve_path(cmd, base_dir, "--include <FILE>")?;
+        let canonical = path
+            .canonicalize()
+            .map_err(|e| err!(sp, "Cannot read PTB file '{}': {e}", path.display()))?;
+        if self.include_stack.contains(&canonical) {
+            error!(sp, "PTB file '{}' includes itself", path.display());
+        }
+        if self.include_stack.len() >= MAX_INCLUDE_DEPTH {
+            error!(
+                sp,
+                "PTB files are included more than {MAX_INCLUDE_DEPTH} levels deep"
+            );
+        }
+
+        let contents = fs::read_to_string(&canonical)
+            .map_err(|e| err!(sp, "Cannot read PTB file '{}': {e}", path.display()))?;
+        let tokens = shlex::split(&contents).ok_or_else(|| {
+            err!(
+                sp,
+                "PTB file '{}' contains an unterminated quote",
+                path.display()
+            )
+        })?;
+        let source = tokens.join(" ");
+        let commands = split_commands(&tokens, &source);
+
+        self.include_stack.push(canonical.clone());
+        // Paths are resolved against the include path rather than the canonical one, so that they
+        // are reported the way the user wrote them.
+        let dir = path.parent().unwrap_or(Path::new("."));
+        let result = self.expand(&commands, dir, in_loop);
+        self.include_stack.pop();
+
+        // Errors and tokens point into the included file, which is not part of the source shown
+        // to the user, so they are reported on the --include command instead.
+        let tokens = result.map_err(|e| {
+            let context = &source[e.span.start.min(source.len())..e.span.end.min(source.len())];
+            PTBError {
+                message: format!("In included file '{}': {}", path.display(), e.message),
+                span: sp,
+                help: e.help.or_else(|| Some(format!("Found in: {context}"))),
+                severity: e.severity,
+            }
+        })?;
+        Ok(tokens.into_iter().map(|(token, _)| (token, sp)).collect())
+    }
+
+    /// Parse `<NAME> <VALUE>`, checking that the name can be bound.
+    fn binding(&self, cmd: &RawCommand, usage: &str) -> PTBResult<(String, String)> {
+        let sp = cmd.span();
+        let Some((name, value)) = cmd.args.value.split_once(char::is_whitespace) else {
+            error!(sp => help: { "Expected: {usage}" }, "Missing value");
+        };
+        self.check_name(sp, name)?;
+        Ok((name.to_owned(), value.trim().to_owned()))
+    }
+
+    fn check_name(&self, sp: Span, name: &str) -> PTBResult<()> {
+        if is_keyword(name) {
+            error!(sp => help: { "Names cannot be {}.", all_keywords() },
+                "Expected a name but found reserved word '{name}'.");
+        }
+        if !is_identifier(name) {
+            error!(sp, "Invalid name '{name}'");
+        }
+        if self.constants.contains_key(name) {
+            error!(sp, "Constant '{name}' is already declared");
+        }
+        Ok(())
+    }
+
+    /// Parse `--for <VAR>... in (<LIST> | <CONSTANT> | csv <FILE>)`, returning the loop variables
+    /// and the values they take in each iteration.
+    fn loop_header(
+        &self,
+        cmd: &RawCommand,
+        base_dir: &Path,
+    ) -> PTBResult<(Vec<String>, Vec<Vec<String>>)> {
+        let sp = cmd.span();
+        let usage = "--for <VAR> in [<VALUE>, ...] | --for <VAR> in <CONSTANT> | \
+                     --for <VAR>... in csv <FILE>";
+        let args = cmd.args.value;
+        let Some((vars, source)) = args
+            .split_once(" in ")
+            .or_else(|| args.strip_suffix(" in").map(|vars| (vars, "")))
+        else {
+            error!(sp => help: { "Expected: {usage}" }, "Malformed loop");
+        };
+
+        let vars: Vec<String> = vars.split_whitespace().map(str::to_owned).collect();
+        if vars.is_empty() {
+            error!(sp => help: { "Expected: {usage}" }, "Missing loop variable");
+        }
+        for var in &vars {
+            self.check_name(sp, var)?;
+        }
+
+        let source = source.trim();
+        let rows = if let Some(file) = source.strip_prefix("csv ") {
+            let path = base_dir.join(unquote(file.trim()));
+            read_csv(sp, &path, &vars)?
+        } else {
+            let list = self.constants.get(source).map_or(source, String::as_str);
+            if vars.len() != 1 {
+                error!(sp => help: { "Use a CSV file to bind several variables per iteration" },
+                    "Looping over a list binds exactly one variable");
+            }
+            split_list(sp, list)?
+                .into_iter()
+                .map(|value| vec![value])
+                .collect()
+        };
+
+        if rows.len() > MAX_LOOP_ITERATIONS {
+            error!(
+                sp,
+                "Loop has {} iterations, the maximum is {MAX_LOOP_ITERATIONS}",
+                rows.len()
+            );
+        }
+        Ok((vars, rows))
+    }
+
+    /// Bind `name` to `value`, on behalf of the script command at `sp`.
+    fn assign(&mut self, name: &str, value: &str, sp: Span) -> PTBResult<Vec<(String, Span)>> {
+        self.count_command(sp)?;
+        Ok(vec![
+            (format!("--{}", A::ASSIGN), sp),
+            (name.to_owned(), sp),
+            (value.to_owned(), sp),
+        ])
+    }
+
+    fn count_command(&mut self, sp: Span) -> PTBResult<()> {
+        self.num_commands += 1;
+        if self.num_commands > MAX_EXPANDED_COMMANDS {
+            error!(
+                sp,
+                "PTB script expands to more than {MAX_EXPANDED_COMMANDS} commands"
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Index of the `--end` closing the loop whose body starts at `start`.
+fn find_loop_end(commands: &[RawCommand], start: usize) -> Option<usize> {
+    let mut depth = 0;
+    for (i, cmd) in commands.iter().enumerate().skip(start) {
+        if cmd.is(A::FOR) {
+            depth += 1;
+        } else if cmd.is(A::END) {
+            if depth == 0 {
+                return Some(i);
+            }
+            depth -= 1;
+        }
+    }
+    None
+}
+
+fn resolve_path(cmd: &RawCommand, base_dir: &Path, usage: &str) -> PTBResult<PathBuf> {
+    let file = unquote(cmd.file.map_or(cmd.args.value, |file| file.value));
+    if file.is_empty() {
+        error!(cmd.span() => help: { "Expected: {usage}" }, "Missing file path");
+    }
+    if cmd.file.is_some() && !cmd.args.value.is_empty() {
+        error!(cmd.args.span => help: { "Expected: {usage}" }, "Unexpected arguments");
+    }
+    Ok(base_dir.join(file))
+}
+
+fn unquote(s: &str) -> &str {
+    ["\"", "'"]
+        .iter()
+        .find_map(|q| s.strip_prefix(q).and_then(|s| s.strip_suffix(q)))
+        .unwrap_or(s)
+}
+
+fn is_identifier(s: &str) -> bool {
+    let mut chars = s.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+/// Split a list literal `[a, b, ...]` into the source text of its elements.
+fn split_list(sp: Span, list: &str) -> PTBResult<Vec<String>> {
+    let Some(inner) = list
+        .strip_prefix('[')
+        .and_then(|list| list.strip_suffix(']'))
+    else {
+        error!(sp => help: { "Loop over a list literal such as [1, 2, 3], a constant holding one, \
+            or csv <FILE>" }, "Cannot loop over '{list}'");
+    };
+
+    let mut elements = vec![];
+    let mut depth = 0i32;
+    let mut quote = None;
+    let mut start = 0;
+    for (i, c) in inner.char_indices() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => (),
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '[' | '(' | '<') => depth += 1,
+            (None, ']' | ')' | '>') => depth -= 1,
+            (None, ',') if depth == 0 => {
+                elements.push(inner[start..i].trim().to_owned());
+                start = i + 1;
+            }
+            _ => (),
+        }
+    }
+    let last = inner[start..].trim();
+    if !last.is_empty() {
+        elements.push(last.to_owned());
+    }
+    if elements.iter().any(String::is_empty) {
+        error!(sp, "Empty element in list '{list}'");
+    }
+    Ok(elements)
+}
+
+/// Read the rows of a CSV file, one value per variable in each row. A first row that repeats the
+/// variable names is treated as a header and skipped.
+fn read_csv(sp: Span, path: &Path, vars: &[String]) -> PTBResult<Vec<Vec<String>>> {
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .comment(Some(b'#'))
+        .trim(csv::Trim::All)
+        .flexible(true)
+        .from_path(path)
+        .map_err(|e| err!(sp, "Cannot read CSV file '{}': {e}", path.display()))?;
+
+    let mut rows = vec![];
+    for (line, record) in reader.records().enumerate() {
+        let record =
+            record.map_err(|e| err!(sp, "Cannot read CSV file '{}': {e}", path.display()))?;
+        let row: Vec<String> = record.iter().map(str::to_owned).collect();
+        if line == 0 && row == vars {
+            continue;
+        }
+        if row.len() != vars.len() {
+            error!(
+                sp,
+                "Row {} of '{}' has {} values, expected one for each of {}",
+                line + 1,
+                path.display(),
+                row.len(),
+                vars.join(", ")
+            );
+        }
+        rows.push(row);
+    }
+    Ok(rows)
+}
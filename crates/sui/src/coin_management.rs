@@ -0,0 +1,288 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Coin management for the client: the automatic gas coin selection policy, and consolidation of
+//! many small coins into a few.
+
+use std::{
+    collections::BTreeSet,
+    fmt::{Display, Formatter},
+};
+
+use anyhow::{anyhow, bail, ensure};
+use serde::Serialize;
+use sui_json_rpc_types::{Coin, SuiObjectDataOptions};
+use sui_sdk::{wallet_context::WalletContext, SuiClient};
+use sui_types::{
+    base_types::{ObjectID, SuiAddress},
+    digests::TransactionDigest,
+    gas_coin::GAS,
+    parse_sui_type_tag,
+    programmable_transaction_builder::ProgrammableTransactionBuilder,
+    transaction::{Argument, Command, ObjectArg, TransactionKind},
+};
+
+use crate::client_commands::{dry_run_or_execute_or_serialize, Opts, SuiClientCommandResult};
+
+#[cfg(test)]
+#[path = "unit_tests/coin_management_tests.rs"]
+mod coin_management_tests;
+
+/// Maximum number of coins in the gas payment of a transaction, the protocol's
+/// `max_gas_payment_objects`.
+pub const MAX_GAS_PAYMENT_OBJECTS: usize = 256;
+
+/// Maximum number of coins merged by one consolidation transaction, which keeps it well within
+/// the protocol's limits on input objects and command arguments.
+pub const MAX_COINS_MERGED_PER_TX: usize = 500;
+
+/// Fetch every coin of `coin_type` owned by `owner`.
+pub async fn get_all_coins(
+    client: &SuiClient,
+    owner: SuiAddress,
+    coin_type: &str,
+) -> Result<Vec<Coin>, anyhow::Error> {
+    let mut coins = vec![];
+    let mut cursor = None;
+    loop {
+        let page = client
+            .coin_read_api()
+            .get_coins(owner, Some(coin_type.to_string()), cursor, None)
+            .await?;
+        coins.extend(page.data);
+        if !page.has_next_page {
+            return Ok(coins);
+        }
+        cursor = page.next_cursor;
+    }
+}
+
+/// Fetch the SUI coins of `owner` that `select_gas_coins` chooses the gas payment from, ignoring
+/// the coins in `excluded`. Pages of coins are fetched until they cover `gas_budget` or until
+/// `MAX_GAS_PAYMENT_OBJECTS` coins were found, so that addresses with many coins do not page
+/// through all of them.
+pub async fn get_gas_coin_candidates(
+    client: &SuiClient,
+    owner: SuiAddress,
+    gas_budget: u64,
+    excluded: &BTreeSet<ObjectID>,
+) -> Result<Vec<Coin>, anyhow::Error> {
+    let mut coins = vec![];
+    let mut total = 0u128;
+    let mut cursor = None;
+    loop {
+        let page = client
+            .coin_read_api()
+            .get_coins(owner, Some(GAS::type_().to_string()), cursor, None)
+            .await?;
+        for coin in page.data {
+            if excluded.contains(&coin.coin_object_id) {
+                continue;
+            }
+            total += coin.balance as u128;
+            coins.push(coin);
+        }
+        if !page.has_next_page
+            || total >= gas_budget as u128
+            || coins.len() >= MAX_GAS_PAYMENT_OBJECTS
+        {
+            return Ok(coins);
+        }
+        cursor = page.next_cursor;
+    }
+}
+
+/// Select the gas payment for a transaction with `gas_budget`, ignoring the coins in `excluded`
+/// (e.g. coins that the transaction uses as inputs). The smallest coin that covers the budget on
+/// its own is preferred. Otherwise the largest coins are used, up to
+/// `MAX_GAS_PAYMENT_OBJECTS`, until their total covers the budget; they are merged into the
+/// first one when the transaction executes.
+pub fn select_gas_coins(
+    coins: &[Coin],
+    gas_budget: u64,
+    excluded: &BTreeSet<ObjectID>,
+) -> Result<Vec<ObjectID>, anyhow::Error> {
+    let mut candidates: Vec<_> = coins
+        .iter()
+        .filter(|c| !excluded.contains(&c.coin_object_id))
+        .collect();
+    candidates.sort_by_key(|c| (c.balance, c.coin_object_id));
+
+    if let Some(coin) = candidates.iter().find(|c| c.balance >= gas_budget) {
+        return Ok(vec![coin.coin_object_id]);
+    }
+
+    let mut selected = vec![];
+    let mut total = 0u128;
+    for coin in candidates.iter().rev().take(MAX_GAS_PAYMENT_OBJECTS) {
+        selected.push(coin.coin_object_id);
+        total += coin.balance as u128;
+        if total >= gas_budget as u128 {
+            return Ok(selected);
+        }
+    }
+    bail!(
+        "Cannot find gas coins with a total balance sufficient for the gas budget {gas_budget} \
+         among {} coins (at most {MAX_GAS_PAYMENT_OBJECTS} coins can pay for gas). Consolidate \
+         coins with `sui client consolidate` or lower the gas budget.",
+        candidates.len()
+    )
+}
+
+/// How coins are merged into target coins, in batches of at most `MAX_COINS_MERGED_PER_TX` coins.
+#[derive(Debug)]
+pub struct ConsolidationPlan {
+    /// The largest coins, which the others are merged into.
+    pub targets: Vec<Coin>,
+    /// Each batch is one transaction, merging coins into the target at the given index.
+    pub batches: Vec<Vec<(usize, Coin)>>,
+}
+
+impl ConsolidationPlan {
+    /// Plan merging `coins` into `target_count` coins. Each coin is merged into the target with
+    /// the smallest balance so far, so that the remaining coins have similar balances.
+    pub fn new(mut coins: Vec<Coin>, target_count: usize) -> Result<Self, anyhow::Error> {
+        ensure!(target_count > 0, "The target coin count must be at least 1");
+        coins.sort_by(|a, b| (b.balance, a.coin_object_id).cmp(&(a.balance, b.coin_object_id)));
+        let sources = coins.split_off(target_count.min(coins.len()));
+        let targets = coins;
+
+        let mut balances: Vec<u128> = targets.iter().map(|c| c.balance as u128).collect();
+        let mut merges = vec![];
+        for coin in sources {
+            let (target, _) = balances
+                .iter()
+                .enumerate()
+                .min_by_key(|(i, balance)| (**balance, *i))
+                .expect("there is at least one target coin");
+            balances[target] += coin.balance as u128;
+            merges.push((target, coin));
+        }
+
+        let mut batches = vec![];
+        let mut merges = merges.into_iter().peekable();
+        while merges.peek().is_some() {
+            batches.push(merges.by_ref().take(MAX_COINS_MERGED_PER_TX).collect());
+        }
+        Ok(Self { targets, batches })
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConsolidateOutput {
+    pub coin_type: String,
+    pub coins_before: usize,
+    pub target_coins: Vec<ObjectID>,
+    /// Number of transactions needed to merge the coins.
+    pub batches: usize,
+    /// Digests of the executed transactions, empty for a dry run.
+    pub transactions: Vec<TransactionDigest>,
+    pub dry_run: bool,
+}
+
+/// Merge `owner`'s coins of `coin_type` into `target_count` coins. SUI coins are merged into the
+/// largest coin as the gas coin, other coins pay for gas with the automatic selection policy.
+/// With `preview`, the changes of each transaction are shown and confirmed before it is signed.
+pub async fn consolidate(
+    context: &mut WalletContext,
+    owner: SuiAddress,
+    coin_type: String,
+    target_count: usize,
+    gas_budget: Option<u64>,
+    dry_run: bool,
+    preview: bool,
+) -> Result<ConsolidateOutput, anyhow::Error> {
+    let is_sui = parse_sui_type_tag(&coin_type)? == GAS::type_tag();
+    let client = context.get_client().await?;
+    let coins = get_all_coins(&client, owner, &coin_type).await?;
+    let coins_before = coins.len();
+    let plan = ConsolidationPlan::new(coins, target_count)?;
+
+    let mut output = ConsolidateOutput {
+        coin_type,
+        coins_before,
+        target_coins: plan.targets.iter().map(|c| c.coin_object_id).collect(),
+        batches: plan.batches.len(),
+        transactions: vec![],
+        dry_run,
+    };
+    if dry_run {
+        return Ok(output);
+    }
+
+    for batch in plan.batches {
+        let mut sources = vec![vec![]; plan.targets.len()];
+        for (target, coin) in batch {
+            sources[target].push(coin);
+        }
+        let mut builder = ProgrammableTransactionBuilder::new();
+        for (i, (target, sources)) in plan.targets.iter().zip(sources).enumerate() {
+            if sources.is_empty() {
+                continue;
+            }
+            let target = if is_sui && i == 0 {
+                Argument::GasCoin
+            } else {
+                // Targets change version with every batch, so fetch their latest reference.
+                let object_ref = client
+                    .read_api()
+                    .get_object_with_options(target.coin_object_id, SuiObjectDataOptions::new())
+                    .await?
+                    .object_ref_if_exists()
+                    .ok_or_else(|| anyhow!("Coin {} no longer exists", target.coin_object_id))?;
+                builder.obj(ObjectArg::ImmOrOwnedObject(object_ref))?
+            };
+            let sources = sources
+                .iter()
+                .map(|coin| builder.obj(ObjectArg::ImmOrOwnedObject(coin.object_ref())))
+                .collect::<Result<Vec<_>, _>>()?;
+            builder.command(Command::MergeCoins(target, sources));
+        }
+
+        let gas = is_sui.then(|| plan.targets[0].coin_object_id);
+        let opts = Opts {
+            gas_budget,
+            dry_run: false,
+            dev_inspect: false,
+            serialize_unsigned_transaction: false,
+            serialize_signed_transaction: false,
+            preview,
+        };
+        let tx_kind = TransactionKind::programmable(builder.finish());
+        match dry_run_or_execute_or_serialize(owner, tx_kind, context, None, None, gas, opts)
+            .await?
+        {
+            SuiClientCommandResult::TransactionBlock(response) => {
+                output.transactions.push(response.digest)
+            }
+            _ => bail!("Expected a transaction block response"),
+        }
+    }
+    Ok(output)
+}
+
+impl Display for ConsolidateOutput {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let verb = if self.dry_run {
+            "Would merge"
+        } else {
+            "Merged"
+        };
+        writeln!(
+            f,
+            "{verb} {} coins of type {} into {} coins in {} transactions",
+            self.coins_before,
+            self.coin_type,
+            self.target_coins.len(),
+            self.batches
+        )?;
+        for coin in &self.target_coins {
+            writeln!(f, "  Target coin: {coin}")?;
+        }
+        for digest in &self.transactions {
+            writeln!(f, "  Transaction: {digest}")?;
+        }
+        Ok(())
+    }
+}
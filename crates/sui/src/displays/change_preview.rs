@@ -0,0 +1,94 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::change_preview::ChangePreview;
+use std::fmt::{Display, Formatter};
+use sui_json_rpc_types::SuiExecutionStatus;
+use tabled::{
+    builder::Builder as TableBuilder,
+    settings::{style::HorizontalLine, Panel as TablePanel, Style as TableStyle},
+};
+
+impl Display for ChangePreview {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if let SuiExecutionStatus::Failure { error } = &self.status {
+            writeln!(f, "The transaction is expected to fail: {error}")?;
+        }
+
+        if self.mutated.is_empty() {
+            writeln!(f, "╭─────────────────────────────╮")?;
+            writeln!(f, "│ No mutated objects          │")?;
+            writeln!(f, "╰─────────────────────────────╯")?;
+        } else {
+            let mut builder = TableBuilder::default();
+            builder.set_header(["field", "before", "after"]);
+            for object in &self.mutated {
+                builder.push_record([
+                    format!("{} ({})", object.object_id, object.object_type),
+                    String::new(),
+                    String::new(),
+                ]);
+                for field in &object.fields {
+                    builder.push_record([
+                        format!("  {}", field.path),
+                        field.before.clone().unwrap_or_else(|| "-".to_string()),
+                        field.after.clone().unwrap_or_else(|| "-".to_string()),
+                    ]);
+                }
+                if !object.contents_known {
+                    builder.push_record([
+                        "  (contents)".to_string(),
+                        String::new(),
+                        "not known before execution".to_string(),
+                    ]);
+                }
+            }
+            write_table(f, builder, "Mutated Objects")?;
+        }
+
+        if self.balance_changes.is_empty() {
+            writeln!(f, "╭─────────────────────────────╮")?;
+            writeln!(f, "│ No balance changes          │")?;
+            writeln!(f, "╰─────────────────────────────╯")?;
+        } else {
+            let mut builder = TableBuilder::default();
+            builder.set_header(["owner", "coin type", "amount"]);
+            for change in &self.balance_changes {
+                builder.push_record([
+                    change.owner.to_string(),
+                    change.coin_type.to_canonical_string(true),
+                    format!("{:+}", change.amount),
+                ]);
+            }
+            write_table(f, builder, "Balance Changes")?;
+        }
+
+        let lifecycle = [
+            ("Created", &self.created),
+            ("Deleted", &self.deleted),
+            ("Wrapped", &self.wrapped),
+        ];
+        if lifecycle.iter().any(|(_, objects)| !objects.is_empty()) {
+            let mut builder = TableBuilder::default();
+            builder.set_header(["change", "object", "type"]);
+            for (change, objects) in lifecycle {
+                for (id, type_) in objects {
+                    builder.push_record([change.to_string(), id.to_string(), type_.clone()]);
+                }
+            }
+            write_table(f, builder, "Created, Deleted and Wrapped Objects")?;
+        }
+        Ok(())
+    }
+}
+
+fn write_table(f: &mut Formatter<'_>, builder: TableBuilder, title: &str) -> std::fmt::Result {
+    let mut table = builder.build();
+    table.with(TablePanel::header(title));
+    table.with(TableStyle::rounded().horizontals([
+        HorizontalLine::new(1, TableStyle::modern().get_horizontal()),
+        HorizontalLine::new(2, TableStyle::modern().get_horizontal()),
+    ]));
+    table.with(tabled::settings::style::BorderSpanCorrection);
+    writeln!(f, "{table}")
+}
@@ -1,6 +1,7 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
+mod change_preview;
 mod dev_inspect;
 mod dry_run_tx_block;
 mod gas_cost_summary;
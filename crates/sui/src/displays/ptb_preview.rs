@@ -3,15 +3,24 @@
 
 use crate::{
     client_ptb::{
-        ast::{GAS_BUDGET, GAS_COIN, JSON, SUMMARY, WARN_SHADOWS},
+        ast::{
+            GAS_BUDGET, GAS_COIN, JSON, MAKE_MOVE_VEC, MERGE_COINS, MOVE_CALL, PUBLISH,
+            SPLIT_COINS, SUMMARY, TRANSFER_OBJECTS, UPGRADE, WARN_SHADOWS,
+        },
         ptb::PTBPreview,
     },
+    offline::TransactionPreview,
     sp,
 };
+use fastcrypto::encoding::{Encoding, Hex};
 use std::fmt::{Display, Formatter};
+use sui_types::transaction::{
+    CallArg, Command, ObjectArg, TransactionDataAPI, TransactionExpiration, TransactionKind,
+};
 use tabled::{
     builder::Builder as TableBuilder,
     settings::{style::HorizontalLine, Panel as TablePanel, Style as TableStyle},
+    Table,
 };
 
 impl<'a> Display for PTBPreview<'a> {
@@ -45,14 +54,89 @@ impl<'a> Display for PTBPreview<'a> {
         if builder.count_rows() < 1 {
             return write!(f, "PTB is empty.");
         }
-        let mut table = builder.build();
-        table.with(TablePanel::header("PTB Preview"));
-        table.with(TableStyle::rounded().horizontals([
-            HorizontalLine::new(1, TableStyle::modern().get_horizontal()),
-            HorizontalLine::new(2, TableStyle::modern().get_horizontal()),
-        ]));
-        table.with(tabled::settings::style::BorderSpanCorrection);
+        write!(f, "{}", preview_table(builder, "PTB Preview"))
+    }
+}
 
-        write!(f, "{}", table)
+impl<'a> Display for TransactionPreview<'a> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mut builder = TableBuilder::default();
+        builder.set_header(["command", "values"]);
+        match self.tx_data.kind() {
+            TransactionKind::ProgrammableTransaction(pt) => {
+                for (i, input) in pt.inputs.iter().enumerate() {
+                    builder.push_record([format!("input {i}"), call_arg_to_string(input)]);
+                }
+                for cmd in &pt.commands {
+                    let name = match cmd {
+                        Command::MoveCall(_) => MOVE_CALL,
+                        Command::TransferObjects(_, _) => TRANSFER_OBJECTS,
+                        Command::SplitCoins(_, _) => SPLIT_COINS,
+                        Command::MergeCoins(_, _) => MERGE_COINS,
+                        Command::Publish(_, _) => PUBLISH,
+                        Command::MakeMoveVec(_, _) => MAKE_MOVE_VEC,
+                        Command::Upgrade(_, _, _, _) => UPGRADE,
+                    };
+                    // Commands render as `Name(values)`, keep only the values.
+                    let rendered = cmd.to_string();
+                    let values = rendered
+                        .split_once('(')
+                        .and_then(|(_, rest)| rest.strip_suffix(')'))
+                        .unwrap_or_default();
+                    builder.push_record([name, values]);
+                }
+            }
+            kind => builder.push_record(["transaction", kind.name()]),
+        }
+        builder.push_record(["sender".to_string(), self.tx_data.sender().to_string()]);
+        if self.tx_data.gas_owner() != self.tx_data.sender() {
+            builder.push_record([
+                "gas-owner".to_string(),
+                self.tx_data.gas_owner().to_string(),
+            ]);
+        }
+        builder.push_record([
+            GAS_BUDGET.to_string(),
+            self.tx_data.gas_budget().to_string(),
+        ]);
+        builder.push_record([
+            "gas-price".to_string(),
+            self.tx_data.gas_price().to_string(),
+        ]);
+        for (id, version, _) in self.tx_data.gas() {
+            builder.push_record([GAS_COIN.to_string(), format!("{id} @ version {version}")]);
+        }
+        if let TransactionExpiration::Epoch(epoch) = self.tx_data.expiration() {
+            builder.push_record(["expiration-epoch".to_string(), epoch.to_string()]);
+        }
+
+        write!(f, "{}", preview_table(builder, "Transaction Preview"))
     }
 }
+
+fn call_arg_to_string(arg: &CallArg) -> String {
+    match arg {
+        CallArg::Pure(bytes) => format!("pure 0x{}", Hex::encode(bytes)),
+        CallArg::Object(ObjectArg::ImmOrOwnedObject((id, version, _))) => {
+            format!("object {id} @ version {version}")
+        }
+        CallArg::Object(ObjectArg::SharedObject { id, mutable, .. }) => {
+            let access = if *mutable { "mutable" } else { "immutable" };
+            format!("shared object {id} ({access})")
+        }
+        CallArg::Object(ObjectArg::Receiving((id, version, _))) => {
+            format!("receiving object {id} @ version {version}")
+        }
+    }
+}
+
+fn preview_table(builder: TableBuilder, title: &str) -> Table {
+    let mut table = builder.build();
+    table.with(TablePanel::header(title));
+    table.with(TableStyle::rounded().horizontals([
+        HorizontalLine::new(1, TableStyle::modern().get_horizontal()),
+        HorizontalLine::new(2, TableStyle::modern().get_horizontal()),
+    ]));
+    table.with(tabled::settings::style::BorderSpanCorrection);
+    table
+}
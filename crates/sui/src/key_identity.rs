@@ -3,14 +3,16 @@
 
 use std::{fmt::Display, str::FromStr};
 
-use anyhow::Error;
+use anyhow::{anyhow, Error};
 use serde::Serialize;
 use sui_keys::keystore::{AccountKeystore, Keystore};
 use sui_sdk::wallet_context::WalletContext;
 use sui_types::base_types::SuiAddress;
 
-/// An address or an alias associated with a key in the wallet
-/// This is used to distinguish between an address or an alias,
+use crate::address_book::AddressBook;
+
+/// An address or an alias associated with a key in the wallet, or with an address in the address
+/// book. This is used to distinguish between an address or an alias,
 /// enabling a user to use an alias for any command that requires an address.
 #[derive(Serialize, Clone)]
 pub enum KeyIdentity {
@@ -41,14 +43,21 @@ impl Display for KeyIdentity {
 
 /// Get the SuiAddress corresponding to this key identity.
 /// If no string is provided, then the current active address is returned.
+/// Aliases of keys in the keystore take precedence over names in the address book.
 pub fn get_identity_address(
     input: Option<KeyIdentity>,
     ctx: &mut WalletContext,
 ) -> Result<SuiAddress, Error> {
-    if let Some(addr) = input {
-        get_identity_address_from_keystore(addr, &ctx.config.keystore)
-    } else {
-        Ok(ctx.active_address()?)
+    match input {
+        Some(KeyIdentity::Alias(alias)) if !ctx.config.keystore.alias_exists(&alias) => {
+            let address_book = AddressBook::load_or_default(&AddressBook::path(&ctx.config))?;
+            address_book
+                .get(&alias)
+                .copied()
+                .ok_or_else(|| anyhow!("Cannot resolve alias {alias} to an address"))
+        }
+        Some(addr) => get_identity_address_from_keystore(addr, &ctx.config.keystore),
+        None => Ok(ctx.active_address()?),
     }
 }
 
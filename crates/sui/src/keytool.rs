@@ -1,8 +1,10 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 use crate::key_identity::{get_identity_address_from_keystore, KeyIdentity};
+use crate::multisig_proposal::{MultiSigProposal, MultiSigProposalStatus};
+use crate::offline::{OfflineTransactionBundle, TransactionPreview};
 use crate::zklogin_commands_util::{perform_zk_login_test_tx, read_cli_line};
-use anyhow::anyhow;
+use anyhow::{anyhow, bail, ensure};
 use bip32::DerivationPath;
 use clap::*;
 use fastcrypto::ed25519::Ed25519KeyPair;
@@ -19,6 +21,7 @@ use fastcrypto_zkp::bn254::zk_login::{fetch_jwks, OIDCProvider, ZkLoginInputs};
 use fastcrypto_zkp::bn254::zk_login::{JwkId, JWK};
 use fastcrypto_zkp::bn254::zk_login_api::ZkLoginEnv;
 use im::hashmap::HashMap as ImHashMap;
+use inquire::{Confirm, Password};
 use json_to_table::{json_to_table, Orientation};
 use num_bigint::BigUint;
 use rand::rngs::StdRng;
@@ -33,12 +36,15 @@ use std::fmt::{Debug, Display, Formatter};
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use sui_keys::key_derive::generate_new_key;
+use sui_config::{sui_config_dir, PersistedConfig, SUI_CLIENT_CONFIG};
+use sui_json_rpc_types::{SuiTransactionBlockResponseQuery, TransactionFilter};
+use sui_keys::key_derive::{derivation_path_for_index, generate_new_key};
 use sui_keys::keypair_file::{
     read_authority_keypair_from_file, read_keypair_from_file, write_authority_keypair_to_file,
     write_keypair_to_file,
 };
-use sui_keys::keystore::{AccountKeystore, Keystore};
+use sui_keys::keystore::{AccountKeystore, EncryptedFileKeystore, Keystore};
+use sui_sdk::{sui_client_config::SuiClientConfig, SuiClient};
 use sui_types::base_types::SuiAddress;
 use sui_types::committee::EpochId;
 use sui_types::crypto::{
@@ -61,6 +67,9 @@ use tracing::info;
 #[path = "unit_tests/keytool_tests.rs"]
 mod keytool_tests;
 
+/// Environment variable holding the passphrase of an encrypted keystore, for non-interactive use.
+pub const SUI_KEYSTORE_PASSPHRASE_ENV: &str = "SUI_KEYSTORE_PASSPHRASE";
+
 #[allow(clippy::large_enum_variant)]
 #[derive(Subcommand)]
 #[clap(rename_all = "kebab-case")]
@@ -99,6 +108,16 @@ pub enum KeyToolCommand {
         #[clap(long, default_value = "0")]
         cur_epoch: u64,
     },
+    /// Convert an encrypted keystore back into a plaintext keystore in place. Every key is
+    /// written to disk unencrypted, so only do this on a trusted machine.
+    DecryptKeystore,
+    /// Encrypt every key of a plaintext keystore in place with a passphrase. The passphrase is
+    /// read from the SUI_KEYSTORE_PASSPHRASE environment variable if set, otherwise it is
+    /// prompted for. Afterwards, `sui client` and `sui keytool` ask for the passphrase before
+    /// they can sign.
+    EncryptKeystore,
+    /// Re-encrypt an encrypted keystore under a new passphrase.
+    ChangeKeystorePassphrase,
     /// Generate a new keypair with key scheme flag {ed25519 | secp256k1 | secp256r1}
     /// with optional derivation path, default to m/44'/784'/0'/0'/0' for ed25519 or
     /// m/54'/784'/0'/0/0 for secp256k1 or m/74'/784'/0'/0/0 for secp256r1. Word
@@ -121,14 +140,35 @@ pub enum KeyToolCommand {
     /// for secp256k1 or m/74'/784'/0'/0/0 for secp256r1. Supports mnemonic phrase of word length 12, 15,
     /// 18, 21, 24. Set an alias for the key with the --alias flag. If no alias is provided, the tool will
     /// automatically generate one.
+    ///
+    /// With --discover N, the mnemonic is imported as an HD wallet instead: its seed is stored
+    /// once in the encrypted keystore, and the accounts of the key scheme are scanned by index on
+    /// the active environment of the client config until N consecutive accounts own no objects
+    /// and sent or received no transactions. The accounts up to the last one in use (or the first
+    /// account, if none is) are derived. Further accounts are derived on demand by
+    /// `sui keytool derive-hd-account` and `sui client new-address`.
     Import {
         /// Sets an alias for this address. The alias must start with a letter and can contain only letters, digits, hyphens (-), or underscores (_).
         #[clap(long)]
         alias: Option<String>,
+        /// Import the mnemonic as an HD wallet and derive its accounts in use, stopping the scan
+        /// after this many consecutive unused accounts.
+        #[clap(long, value_name = "GAP_LIMIT", conflicts_with_all = ["alias", "derivation_path"])]
+        discover: Option<u32>,
         input_string: String,
         key_scheme: SignatureScheme,
         derivation_path: Option<DerivationPath>,
     },
+    /// Derive an account from the HD wallet of an encrypted keystore and add it to the
+    /// keystore. Without --index, the next unused index of the key scheme is used.
+    DeriveHdAccount {
+        key_scheme: SignatureScheme,
+        #[clap(long)]
+        index: Option<u32>,
+        /// Sets an alias for this address. The alias must start with a letter and can contain only letters, digits, hyphens (-), or underscores (_).
+        #[clap(long)]
+        alias: Option<String>,
+    },
     /// Output the private key of the given key identity in Sui CLI Keystore as Bech32
     /// encoded string starting with `suiprivkey`.
     Export {
@@ -186,6 +226,49 @@ pub enum KeyToolCommand {
         #[clap(long)]
         threshold: ThresholdUnit,
     },
+    /// Create a multisig proposal file from unsigned transaction bytes sent from a MultiSig
+    /// address and its public keys, weights and threshold. Members add their partial signatures
+    /// with `multi-sig-sign`, and the transaction is executed with
+    /// `sui client multi-sig-execute` once the signatures reach the threshold.
+    MultiSigPropose {
+        /// BCS serialized transaction data bytes without its type tag, as base64 encoded string.
+        /// This is the output of sui client command using --serialize-unsigned-transaction.
+        #[clap(long)]
+        tx_bytes: String,
+        #[clap(long, num_args(1..))]
+        pks: Vec<PublicKey>,
+        #[clap(long, num_args(1..))]
+        weights: Vec<WeightUnit>,
+        #[clap(long)]
+        threshold: ThresholdUnit,
+        /// Path of the proposal file to write.
+        #[clap(long)]
+        output: PathBuf,
+    },
+    /// Add a partial signature to a multisig proposal, either by signing with a member key in
+    /// sui keystore or by providing a signature created elsewhere (e.g. with a hardware wallet).
+    /// Reports the accumulated weight against the threshold.
+    MultiSigSign {
+        /// Path of the proposal file.
+        proposal: PathBuf,
+        /// Address (or its alias) to sign with. Defaults to the first member key in the keystore
+        /// that has not signed yet.
+        #[clap(long, conflicts_with = "signature")]
+        address: Option<KeyIdentity>,
+        /// A member's signature `flag || signature || pubkey` over the proposed transaction, as
+        /// base64 encoded string.
+        #[clap(long)]
+        signature: Option<GenericSignature>,
+        /// Sign without asking for confirmation.
+        #[clap(long)]
+        yes: bool,
+    },
+    /// Show which members have signed a multisig proposal, and the accumulated weight against
+    /// the threshold.
+    MultiSigStatus {
+        /// Path of the proposal file.
+        proposal: PathBuf,
+    },
 
     /// Read the content at the provided file path. The accepted format can be
     /// [enum SuiKeyPair] (Base64 encoded of 33-byte `flag || privkey`) or `type AuthorityKeyPair`
@@ -203,6 +286,19 @@ pub enum KeyToolCommand {
         #[clap(long)]
         intent: Option<Intent>,
     },
+    /// Sign an offline transaction bundle created with `sui client offline export`. A summary of
+    /// the transaction is shown before signing, and the signature is added to the bundle file.
+    /// Does not need network access.
+    SignBundle {
+        /// Path of the bundle file.
+        bundle: PathBuf,
+        /// Address (or its alias) to sign with. Defaults to the transaction sender.
+        #[clap(long)]
+        address: Option<KeyIdentity>,
+        /// Sign without asking for confirmation.
+        #[clap(long)]
+        yes: bool,
+    },
     /// Creates a signature by leveraging AWS KMS. Pass in a key-id to leverage Amazon
     /// KMS to sign a message and the base64 pubkey.
     /// Generate PubKey from pem using MystenLabs/base64pemkey
@@ -339,6 +435,14 @@ pub struct Key {
     peer_id: Option<String>,
 }
 
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HdAccountKey {
+    index: u32,
+    derivation_path: String,
+    key: Key,
+}
+
 #[derive(Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ExportedKey {
@@ -346,6 +450,14 @@ pub struct ExportedKey {
     key: Key,
 }
 
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KeystoreConversion {
+    keystore_path: Option<PathBuf>,
+    encrypted: bool,
+    num_keys: usize,
+}
+
 #[derive(Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct KeypairData {
@@ -378,6 +490,14 @@ pub struct MultiSigCombinePartialSigLegacyOutput {
     multisig_legacy_serialized: String,
 }
 
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MultiSigProposalOutput {
+    proposal_path: PathBuf,
+    #[serde(flatten)]
+    status: MultiSigProposalStatus,
+}
+
 #[derive(Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct MultiSigOutput {
@@ -407,6 +527,17 @@ pub struct SerializedSig {
     serialized_sig_base64: String,
 }
 
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SignedBundle {
+    bundle_path: PathBuf,
+    signer: SuiAddress,
+    // Base64 encoded `flag || signature || pubkey` added to the bundle.
+    sui_signature: String,
+    // Whether the bundle now has all the signatures needed to submit it.
+    fully_signed: bool,
+}
+
 #[derive(Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SignData {
@@ -456,7 +587,9 @@ pub enum CommandOutput {
     DecodeMultiSig(DecodedMultiSigOutput),
     DecodeOrVerifyTx(DecodeOrVerifyTxOutput),
     Error(String),
+    KeystoreConversion(KeystoreConversion),
     Generate(Key),
+    HdAccounts(Vec<HdAccountKey>),
     Import(Key),
     Export(ExportedKey),
     List(Vec<Key>),
@@ -464,9 +597,11 @@ pub enum CommandOutput {
     MultiSigAddress(MultiSigAddress),
     MultiSigCombinePartialSig(MultiSigCombinePartialSig),
     MultiSigCombinePartialSigLegacy(MultiSigCombinePartialSigLegacyOutput),
+    MultiSigProposal(MultiSigProposalOutput),
     PrivateKeyBase64(PrivateKeyBase64),
     Show(Key),
     Sign(SignData),
+    SignBundle(SignedBundle),
     SignKMS(SerializedSig),
     ZkLoginSignAndExecuteTx(ZkLoginSignAndExecuteTx),
     ZkLoginInsecureSignPersonalMessage(ZkLoginInsecureSignPersonalMessage),
@@ -553,6 +688,53 @@ impl KeyToolCommand {
                 CommandOutput::DecodeMultiSig(output)
             }
 
+            KeyToolCommand::ChangeKeystorePassphrase => {
+                let Keystore::Encrypted(encrypted) = keystore else {
+                    bail!("The keystore is not encrypted, use `sui keytool encrypt-keystore`");
+                };
+                // Read the current passphrase before the new one.
+                encrypted.ensure_unlocked()?;
+                println!("Enter the new keystore passphrase.");
+                let passphrase = read_keystore_passphrase(true)?;
+                encrypted.change_passphrase(&passphrase)?;
+                CommandOutput::KeystoreConversion(KeystoreConversion {
+                    keystore_path: encrypted.path().map(Path::to_path_buf),
+                    encrypted: true,
+                    num_keys: encrypted.keys().len(),
+                })
+            }
+
+            KeyToolCommand::DecryptKeystore => {
+                let Keystore::Encrypted(encrypted) = keystore else {
+                    bail!("The keystore is not encrypted");
+                };
+                let plaintext = encrypted.to_file_keystore()?;
+                plaintext.save()?;
+                let output = KeystoreConversion {
+                    keystore_path: plaintext.path().map(Path::to_path_buf),
+                    encrypted: false,
+                    num_keys: plaintext.keys().len(),
+                };
+                *keystore = Keystore::File(plaintext);
+                CommandOutput::KeystoreConversion(output)
+            }
+
+            KeyToolCommand::EncryptKeystore => {
+                let Keystore::File(plaintext) = keystore else {
+                    bail!("Only a plaintext file keystore can be encrypted");
+                };
+                let passphrase = read_keystore_passphrase(true)?;
+                let encrypted = EncryptedFileKeystore::from_file_keystore(plaintext, &passphrase)?;
+                encrypted.save()?;
+                let output = KeystoreConversion {
+                    keystore_path: encrypted.path().map(Path::to_path_buf),
+                    encrypted: true,
+                    num_keys: encrypted.keys().len(),
+                };
+                *keystore = Keystore::Encrypted(encrypted);
+                CommandOutput::KeystoreConversion(output)
+            }
+
             KeyToolCommand::DecodeOrVerifyTx {
                 tx_bytes,
                 sig,
@@ -611,8 +793,37 @@ impl KeyToolCommand {
                 }
             },
 
+            KeyToolCommand::Import {
+                alias: _,
+                discover: Some(gap_limit),
+                input_string,
+                key_scheme,
+                derivation_path: _,
+            } => {
+                let Keystore::Encrypted(encrypted) = keystore else {
+                    bail!("HD wallets can only be stored in an encrypted keystore, run `sui keytool encrypt-keystore` first");
+                };
+                let client_config = sui_config_dir()?.join(SUI_CLIENT_CONFIG);
+                let config: SuiClientConfig =
+                    PersistedConfig::read(&client_config).map_err(|err| {
+                        anyhow!(
+                            "Cannot open the client config {} to discover accounts: {err}",
+                            client_config.display()
+                        )
+                    })?;
+                let client = config
+                    .get_active_env()?
+                    .create_rpc_client(None, None)
+                    .await?;
+                encrypted.set_hd_wallet(&input_string)?;
+                CommandOutput::HdAccounts(
+                    discover_hd_accounts(encrypted, key_scheme, gap_limit, &client).await?,
+                )
+            }
+
             KeyToolCommand::Import {
                 alias,
+                discover: None,
                 input_string,
                 key_scheme,
                 derivation_path,
@@ -661,6 +872,19 @@ impl KeyToolCommand {
                     }
                 }
             }
+            KeyToolCommand::DeriveHdAccount {
+                key_scheme,
+                index,
+                alias,
+            } => {
+                let Keystore::Encrypted(encrypted) = keystore else {
+                    bail!("HD wallets can only be stored in an encrypted keystore");
+                };
+                let index = index.unwrap_or_else(|| encrypted.next_hd_index(key_scheme));
+                encrypted.derive_hd_account(key_scheme, index, alias)?;
+                CommandOutput::HdAccounts(vec![hd_account_key(encrypted, key_scheme, index)?])
+            }
+
             KeyToolCommand::Export { key_identity } => {
                 let address = get_identity_address_from_keystore(key_identity, keystore)?;
                 let skp = keystore.get_key(&address)?;
@@ -789,6 +1013,91 @@ impl KeyToolCommand {
                 )
             }
 
+            KeyToolCommand::MultiSigPropose {
+                tx_bytes,
+                pks,
+                weights,
+                threshold,
+                output,
+            } => {
+                let tx_data: TransactionData = bcs::from_bytes(
+                    &Base64::decode(&tx_bytes).map_err(|_| anyhow!("Invalid Base64 encoding"))?,
+                )
+                .map_err(|_| anyhow!("Failed to parse tx bytes, check if it matches the output of sui client commands with --serialize-unsigned-transaction"))?;
+                let multisig_pk = MultiSigPublicKey::new(pks, weights, threshold)?;
+                let proposal = MultiSigProposal::new(&tx_data, &multisig_pk)?;
+                proposal.write(&output)?;
+                CommandOutput::MultiSigProposal(MultiSigProposalOutput {
+                    proposal_path: output,
+                    status: proposal.status()?,
+                })
+            }
+
+            KeyToolCommand::MultiSigSign {
+                proposal: proposal_path,
+                address,
+                signature,
+                yes,
+            } => {
+                let mut proposal = MultiSigProposal::read(&proposal_path)?;
+                let signature = match signature {
+                    Some(signature) => signature,
+                    None => {
+                        let tx_data = proposal.transaction_data()?;
+                        let signer = match address {
+                            Some(address) => get_identity_address_from_keystore(address, keystore)?,
+                            None => {
+                                let multisig_pk = proposal.multisig_pk()?;
+                                let signed_keys = proposal.signed_keys()?;
+                                keystore
+                                    .keys()
+                                    .iter()
+                                    .find(|pk| {
+                                        multisig_pk.get_index(pk).is_some()
+                                            && !signed_keys.contains(pk)
+                                    })
+                                    .map(SuiAddress::from)
+                                    .ok_or_else(|| {
+                                        anyhow!(
+                                            "No key in the keystore can add a signature to this \
+                                             proposal"
+                                        )
+                                    })?
+                            }
+                        };
+
+                        println!("{}", TransactionPreview { tx_data: &tx_data });
+                        if !yes
+                            && !Confirm::new(&format!(
+                                "Sign this transaction for {} as {signer}?",
+                                proposal.multisig_address
+                            ))
+                            .with_default(false)
+                            .prompt()?
+                        {
+                            bail!("Signing cancelled");
+                        }
+                        keystore
+                            .sign_secure(&signer, &tx_data, Intent::sui_transaction())?
+                            .into()
+                    }
+                };
+                proposal.add_signature(signature)?;
+                proposal.write(&proposal_path)?;
+                CommandOutput::MultiSigProposal(MultiSigProposalOutput {
+                    proposal_path,
+                    status: proposal.status()?,
+                })
+            }
+
+            KeyToolCommand::MultiSigStatus { proposal } => {
+                let status = MultiSigProposal::read(&proposal)?.status()?;
+                CommandOutput::MultiSigProposal(MultiSigProposalOutput {
+                    proposal_path: proposal,
+                    status,
+                })
+            }
+
             KeyToolCommand::Show { file } => {
                 let res = read_keypair_from_file(&file);
                 match res {
@@ -846,6 +1155,39 @@ impl KeyToolCommand {
                 })
             }
 
+            KeyToolCommand::SignBundle {
+                bundle: bundle_path,
+                address,
+                yes,
+            } => {
+                let mut bundle = OfflineTransactionBundle::read(&bundle_path)?;
+                let tx_data = bundle.transaction_data()?;
+                let signer = match address {
+                    Some(address) => get_identity_address_from_keystore(address, keystore)?,
+                    None => tx_data.sender(),
+                };
+
+                println!("{}", TransactionPreview { tx_data: &tx_data });
+                if !yes
+                    && !Confirm::new(&format!("Sign this transaction as {signer}?"))
+                        .with_default(false)
+                        .prompt()?
+                {
+                    bail!("Signing cancelled");
+                }
+
+                let signature =
+                    keystore.sign_secure(&signer, &tx_data, Intent::sui_transaction())?;
+                bundle.add_signature(signer, signature.clone().into())?;
+                bundle.write(&bundle_path)?;
+                CommandOutput::SignBundle(SignedBundle {
+                    bundle_path,
+                    signer,
+                    sui_signature: signature.encode_base64(),
+                    fully_signed: bundle.is_fully_signed()?,
+                })
+            }
+
             KeyToolCommand::SignKMS {
                 data,
                 keyid,
@@ -1391,6 +1733,105 @@ impl Debug for CommandOutput {
     }
 }
 
+/// Describe the HD wallet account at `index` for `key_scheme`, which must have been derived.
+/// Derive the accounts of the HD wallet of `keystore` for `key_scheme` up to the last one in use,
+/// scanning by index until `gap_limit` consecutive accounts are unused. The first account is
+/// derived even if no account is in use.
+pub async fn discover_hd_accounts(
+    keystore: &mut EncryptedFileKeystore,
+    key_scheme: SignatureScheme,
+    gap_limit: u32,
+    client: &SuiClient,
+) -> Result<Vec<HdAccountKey>, anyhow::Error> {
+    ensure!(gap_limit > 0, "The gap limit must be at least 1");
+    let mut last_used = None;
+    let mut index = 0;
+    while index - last_used.map_or(0, |last| last + 1) < gap_limit {
+        let address = keystore.hd_account_address(key_scheme, index)?;
+        if is_address_used(client, address).await? {
+            last_used = Some(index);
+        }
+        index += 1;
+    }
+    (0..=last_used.unwrap_or(0))
+        .map(|index| {
+            keystore.derive_hd_account(key_scheme, index, None)?;
+            hd_account_key(keystore, key_scheme, index)
+        })
+        .collect()
+}
+
+/// Returns true if `address` owns an object, or sent or received a transaction.
+async fn is_address_used(client: &SuiClient, address: SuiAddress) -> Result<bool, anyhow::Error> {
+    let objects = client
+        .read_api()
+        .get_owned_objects(address, None, None, Some(1))
+        .await?;
+    if !objects.data.is_empty() {
+        return Ok(true);
+    }
+    for filter in [
+        TransactionFilter::FromAddress(address),
+        TransactionFilter::ToAddress(address),
+    ] {
+        let transactions = client
+            .read_api()
+            .query_transaction_blocks(
+                SuiTransactionBlockResponseQuery::new_with_filter(filter),
+                None,
+                Some(1),
+                false,
+            )
+            .await?;
+        if !transactions.data.is_empty() {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+fn hd_account_key(
+    keystore: &EncryptedFileKeystore,
+    key_scheme: SignatureScheme,
+    index: u32,
+) -> Result<HdAccountKey, anyhow::Error> {
+    let account = keystore
+        .hd_accounts()
+        .iter()
+        .find(|account| account.key_scheme == key_scheme && account.index == index)
+        .ok_or_else(|| anyhow!("HD wallet account {index} for {key_scheme} was not derived"))?;
+    let mut key = Key::from(keystore.get_key(&account.address)?);
+    key.alias = keystore.get_alias_by_address(&account.address).ok();
+    Ok(HdAccountKey {
+        index,
+        derivation_path: derivation_path_for_index(&key_scheme, index)?.to_string(),
+        key,
+    })
+}
+
+/// Read the passphrase of an encrypted keystore from the SUI_KEYSTORE_PASSPHRASE environment
+/// variable, or prompt for it. When `confirm` is set, a prompted passphrase must be typed twice.
+pub fn read_keystore_passphrase(confirm: bool) -> Result<String, anyhow::Error> {
+    if let Ok(passphrase) = std::env::var(SUI_KEYSTORE_PASSPHRASE_ENV) {
+        return Ok(passphrase);
+    }
+    let prompt = Password::new("Keystore passphrase:");
+    let prompt = if confirm {
+        prompt
+    } else {
+        prompt.without_confirmation()
+    };
+    prompt
+        .prompt()
+        .map_err(|e| anyhow!("Cannot read the keystore passphrase: {e}"))
+}
+
+/// Read the passphrase of `keystore`, if it is an encrypted keystore, the first time one of its
+/// private keys is needed.
+pub fn prompt_for_keystore_passphrase(keystore: &mut Keystore) {
+    keystore.set_passphrase_prompt(Box::new(|| read_keystore_passphrase(false)));
+}
+
 /// Converts legacy formatted private key to 33 bytes bech32 encoded private key or vice versa.
 /// It can handle:
 /// 1) Hex encoded 32 byte private key (assumes scheme is Ed25519), this is the legacy wallet format
@@ -2,10 +2,13 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
+pub mod address_book;
+pub mod change_preview;
 pub mod client_commands;
 #[macro_use]
 pub mod client_ptb;
 mod clever_error_rendering;
+pub mod coin_management;
 pub mod console;
 pub mod displays;
 pub mod fire_drill;
@@ -13,8 +16,11 @@ pub mod genesis_ceremony;
 pub mod genesis_inspector;
 pub mod key_identity;
 pub mod keytool;
+pub mod multisig_proposal;
+pub mod offline;
 pub mod shell;
 pub mod sui_commands;
 pub mod validator_commands;
 mod verifier_meter;
+pub mod watch;
 pub mod zklogin_commands_util;
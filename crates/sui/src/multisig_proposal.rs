@@ -0,0 +1,254 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! File-based coordination of multisig transactions. A proposal holds an unsigned transaction
+//! sent from a multisig address, together with the multisig public key. Each signer adds a
+//! partial signature to the file, and once the signatures reach the threshold they are combined
+//! into a `MultiSig` and the transaction can be executed.
+
+use std::{fs, path::Path};
+
+use anyhow::{anyhow, ensure, Context};
+use fastcrypto::{
+    encoding::{Base64, Encoding},
+    traits::ToFromBytes,
+};
+use serde::{Deserialize, Serialize};
+use shared_crypto::intent::{Intent, IntentMessage};
+use sui_types::{
+    base_types::SuiAddress,
+    crypto::{EncodeDecodeBase64, PublicKey, SuiSignature},
+    multisig::{MultiSig, MultiSigPublicKey, ThresholdUnit, WeightUnit},
+    signature::GenericSignature,
+    transaction::{Transaction, TransactionData, TransactionDataAPI},
+};
+
+/// Version of the multisig proposal file format.
+pub const MULTISIG_PROPOSAL_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ProposalMember {
+    /// Base64 encoded `flag || pubkey`.
+    pub public_key: String,
+    pub weight: WeightUnit,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct MultiSigProposal {
+    pub version: u32,
+    pub multisig_address: SuiAddress,
+    pub members: Vec<ProposalMember>,
+    pub threshold: ThresholdUnit,
+    /// Base64 encoded BCS serialized `TransactionData`.
+    pub tx_bytes: String,
+    /// Base64 encoded partial signatures `flag || signature || pubkey` of members.
+    #[serde(default)]
+    pub signatures: Vec<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProposalMemberStatus {
+    pub address: SuiAddress,
+    pub public_base64_key: String,
+    pub weight: WeightUnit,
+    pub signed: bool,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MultiSigProposalStatus {
+    pub multisig_address: SuiAddress,
+    pub threshold: ThresholdUnit,
+    /// Sum of the weights of the members that have signed.
+    pub weight: ThresholdUnit,
+    /// Whether enough members have signed to execute the transaction.
+    pub ready: bool,
+    pub members: Vec<ProposalMemberStatus>,
+}
+
+impl MultiSigProposal {
+    /// Propose `tx_data`, which must be sent from and paid for by the multisig address.
+    pub fn new(
+        tx_data: &TransactionData,
+        multisig_pk: &MultiSigPublicKey,
+    ) -> Result<Self, anyhow::Error> {
+        let multisig_address = SuiAddress::from(multisig_pk);
+        ensure!(
+            tx_data.sender() == multisig_address && tx_data.gas_owner() == multisig_address,
+            "The transaction must be sent from and paid for by the multisig address \
+             {multisig_address}, but its sender is {} and its gas owner is {}",
+            tx_data.sender(),
+            tx_data.gas_owner()
+        );
+        Ok(Self {
+            version: MULTISIG_PROPOSAL_VERSION,
+            multisig_address,
+            members: multisig_pk
+                .pubkeys()
+                .iter()
+                .map(|(pk, weight)| ProposalMember {
+                    public_key: pk.encode_base64(),
+                    weight: *weight,
+                })
+                .collect(),
+            threshold: *multisig_pk.threshold(),
+            tx_bytes: Base64::encode(bcs::to_bytes(tx_data)?),
+            signatures: vec![],
+        })
+    }
+
+    pub fn read(path: &Path) -> Result<Self, anyhow::Error> {
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("Cannot read multisig proposal {}", path.display()))?;
+        let proposal: Self = serde_json::from_str(&contents)
+            .with_context(|| format!("Cannot parse multisig proposal {}", path.display()))?;
+        ensure!(
+            proposal.version == MULTISIG_PROPOSAL_VERSION,
+            "Unsupported multisig proposal version {}, expected {MULTISIG_PROPOSAL_VERSION}",
+            proposal.version
+        );
+        Ok(proposal)
+    }
+
+    pub fn write(&self, path: &Path) -> Result<(), anyhow::Error> {
+        fs::write(path, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("Cannot write multisig proposal {}", path.display()))
+    }
+
+    /// The multisig public key, checked against the proposal's address.
+    pub fn multisig_pk(&self) -> Result<MultiSigPublicKey, anyhow::Error> {
+        let pks = self
+            .members
+            .iter()
+            .map(|m| PublicKey::decode_base64(&m.public_key).map_err(|e| anyhow!(e)))
+            .collect::<Result<Vec<_>, _>>()?;
+        let weights = self.members.iter().map(|m| m.weight).collect();
+        let multisig_pk = MultiSigPublicKey::new(pks, weights, self.threshold)?;
+        ensure!(
+            SuiAddress::from(&multisig_pk) == self.multisig_address,
+            "The proposal's public keys do not match its multisig address"
+        );
+        Ok(multisig_pk)
+    }
+
+    pub fn transaction_data(&self) -> Result<TransactionData, anyhow::Error> {
+        let tx_data: TransactionData = bcs::from_bytes(
+            &Base64::decode(&self.tx_bytes)
+                .map_err(|_| anyhow!("Invalid Base64 encoding of the proposal's tx bytes"))?,
+        )
+        .map_err(|_| anyhow!("Failed to parse the proposal's tx bytes as TransactionData"))?;
+        ensure!(
+            tx_data.sender() == self.multisig_address,
+            "The proposal's transaction is not sent from its multisig address"
+        );
+        Ok(tx_data)
+    }
+
+    /// Public keys of the members that have signed.
+    pub fn signed_keys(&self) -> Result<Vec<PublicKey>, anyhow::Error> {
+        self.partial_signatures()?
+            .iter()
+            .map(|sig| sig.to_public_key().map_err(|e| anyhow!(e)))
+            .collect()
+    }
+
+    /// Add a member's partial signature. Single key signatures are verified against the
+    /// transaction, other kinds of signatures are verified when the transaction is executed.
+    pub fn add_signature(&mut self, signature: GenericSignature) -> Result<(), anyhow::Error> {
+        let multisig_pk = self.multisig_pk()?;
+        let pk = signature.to_public_key()?;
+        let signer = SuiAddress::from(&pk);
+        ensure!(
+            multisig_pk.get_index(&pk).is_some(),
+            "{signer} is not a member of multisig address {}",
+            self.multisig_address
+        );
+        ensure!(
+            !self.signed_keys()?.contains(&pk),
+            "The proposal is already signed by {signer}"
+        );
+        if let GenericSignature::Signature(sig) = &signature {
+            let intent_msg =
+                IntentMessage::new(Intent::sui_transaction(), self.transaction_data()?);
+            sig.verify_secure(&intent_msg, signer, sig.scheme())
+                .map_err(|e| anyhow!("Invalid signature from {signer}: {e}"))?;
+        }
+        self.signatures.push(Base64::encode(signature.as_ref()));
+        Ok(())
+    }
+
+    /// Report which members have signed, and the accumulated weight against the threshold.
+    pub fn status(&self) -> Result<MultiSigProposalStatus, anyhow::Error> {
+        let multisig_pk = self.multisig_pk()?;
+        let signed_keys = self.signed_keys()?;
+        let members: Vec<_> = multisig_pk
+            .pubkeys()
+            .iter()
+            .map(|(pk, weight)| ProposalMemberStatus {
+                address: pk.into(),
+                public_base64_key: pk.encode_base64(),
+                weight: *weight,
+                signed: signed_keys.contains(pk),
+            })
+            .collect();
+        let weight = members
+            .iter()
+            .filter(|m| m.signed)
+            .map(|m| m.weight as ThresholdUnit)
+            .sum();
+        Ok(MultiSigProposalStatus {
+            multisig_address: self.multisig_address,
+            threshold: self.threshold,
+            weight,
+            ready: weight >= self.threshold,
+            members,
+        })
+    }
+
+    /// Combine the partial signatures into a multisig, once they reach the threshold.
+    pub fn combine(&self) -> Result<GenericSignature, anyhow::Error> {
+        let status = self.status()?;
+        ensure!(
+            status.ready,
+            "The proposal has signatures with weight {}, but needs {} to execute",
+            status.weight,
+            status.threshold
+        );
+        let multisig_pk = self.multisig_pk()?;
+        // MultiSig::combine expects signatures in the order of the public keys.
+        let mut sigs = self
+            .partial_signatures()?
+            .into_iter()
+            .map(|sig| {
+                let index = multisig_pk.get_index(&sig.to_public_key()?);
+                Ok((index, sig))
+            })
+            .collect::<Result<Vec<_>, anyhow::Error>>()?;
+        sigs.sort_by_key(|(index, _)| *index);
+        let multisig =
+            MultiSig::combine(sigs.into_iter().map(|(_, sig)| sig).collect(), multisig_pk)?;
+        Ok(multisig.into())
+    }
+
+    pub fn transaction(&self) -> Result<Transaction, anyhow::Error> {
+        Ok(Transaction::from_generic_sig_data(
+            self.transaction_data()?,
+            vec![self.combine()?],
+        ))
+    }
+
+    fn partial_signatures(&self) -> Result<Vec<GenericSignature>, anyhow::Error> {
+        self.signatures
+            .iter()
+            .map(|sig| {
+                GenericSignature::from_bytes(
+                    &Base64::decode(sig).map_err(|_| anyhow!("Invalid Base64 encoding"))?,
+                )
+                .map_err(|_| anyhow!("Invalid generic signature"))
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,325 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Offline transaction signing. A transaction is prepared on a machine with network access and
+//! exported as a bundle, signed with `sui keytool sign-bundle` on a machine that only holds the
+//! keystore, and submitted later with `sui client offline submit`.
+
+use std::{
+    fmt::{Display, Formatter},
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{anyhow, bail, ensure, Context};
+use clap::*;
+use fastcrypto::{
+    encoding::{Base64, Encoding},
+    traits::ToFromBytes,
+};
+use serde::{Deserialize, Serialize};
+use sui_json_rpc_types::SuiObjectDataOptions;
+use sui_sdk::wallet_context::WalletContext;
+use sui_types::{
+    base_types::{ObjectDigest, ObjectID, ObjectRef, SequenceNumber, SuiAddress},
+    digests::TransactionDigest,
+    signature::GenericSignature,
+    transaction::{InputObjectKind, Transaction, TransactionData, TransactionDataAPI},
+};
+
+use crate::client_commands::SuiClientCommandResult;
+
+/// Version of the offline bundle file format.
+pub const OFFLINE_BUNDLE_VERSION: u32 = 1;
+
+#[derive(Parser)]
+#[clap(rename_all = "kebab-case")]
+pub enum OfflineCommand {
+    /// Export an unsigned transaction as an offline bundle, recording the object versions and gas
+    /// price it was built against. The bundle can be signed with `sui keytool sign-bundle` on a
+    /// machine without network access.
+    Export {
+        /// BCS serialized transaction data bytes without its type tag, as base64 encoded string.
+        /// This is the output of sui client command using --serialize-unsigned-transaction.
+        #[clap(long)]
+        tx_bytes: String,
+        /// Path of the bundle file to write.
+        #[clap(long)]
+        output: PathBuf,
+    },
+    /// Submit a signed offline bundle. Fails without submitting if any object the transaction
+    /// uses has been modified since the bundle was exported.
+    Submit {
+        /// Path of the signed bundle file.
+        bundle: PathBuf,
+    },
+}
+
+/// A reference to an owned or immutable object, pinned at the version the transaction was built
+/// against.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleObjectRef {
+    pub object_id: ObjectID,
+    pub version: SequenceNumber,
+    pub digest: ObjectDigest,
+}
+
+impl From<ObjectRef> for BundleObjectRef {
+    fn from((object_id, version, digest): ObjectRef) -> Self {
+        Self {
+            object_id,
+            version,
+            digest,
+        }
+    }
+}
+
+/// An unsigned (or partially signed) transaction together with everything needed to review and
+/// sign it offline. The object references and gas fields are derived from `tx_bytes` and are
+/// checked against them whenever the bundle is used.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct OfflineTransactionBundle {
+    pub version: u32,
+    pub chain_identifier: String,
+    pub sender: SuiAddress,
+    pub gas_owner: SuiAddress,
+    pub gas_price: u64,
+    pub gas_budget: u64,
+    /// Owned and immutable objects used by the transaction, including gas and receiving objects.
+    pub owned_objects: Vec<BundleObjectRef>,
+    /// Shared objects used by the transaction. Their versions are assigned at execution time.
+    pub shared_objects: Vec<ObjectID>,
+    /// Digest of the transaction when it was exported, which signatures are checked against.
+    pub digest: TransactionDigest,
+    pub tx_bytes: String,
+    /// Base64 encoded signatures `flag || signature || pubkey`, collected by `sign-bundle`.
+    #[serde(default)]
+    pub signatures: Vec<String>,
+}
+
+/// Human-readable summary of a transaction, shown before it is signed.
+pub struct TransactionPreview<'a> {
+    pub tx_data: &'a TransactionData,
+}
+
+impl OfflineTransactionBundle {
+    pub fn new(tx_data: &TransactionData, chain_identifier: String) -> Result<Self, anyhow::Error> {
+        let mut owned_objects = vec![];
+        let mut shared_objects = vec![];
+        for input in tx_data.input_objects()? {
+            match input {
+                InputObjectKind::ImmOrOwnedMoveObject(obj_ref) => {
+                    owned_objects.push(obj_ref.into())
+                }
+                InputObjectKind::SharedMoveObject { id, .. } => shared_objects.push(id),
+                InputObjectKind::MovePackage(_) => (),
+            }
+        }
+        owned_objects.extend(tx_data.receiving_objects().into_iter().map(Into::into));
+
+        Ok(Self {
+            version: OFFLINE_BUNDLE_VERSION,
+            chain_identifier,
+            sender: tx_data.sender(),
+            gas_owner: tx_data.gas_owner(),
+            gas_price: tx_data.gas_price(),
+            gas_budget: tx_data.gas_budget(),
+            owned_objects,
+            shared_objects,
+            digest: tx_data.digest(),
+            tx_bytes: Base64::encode(bcs::to_bytes(tx_data)?),
+            signatures: vec![],
+        })
+    }
+
+    pub fn read(path: &Path) -> Result<Self, anyhow::Error> {
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("Cannot read offline bundle {}", path.display()))?;
+        let bundle: Self = serde_json::from_str(&contents)
+            .with_context(|| format!("Cannot parse offline bundle {}", path.display()))?;
+        ensure!(
+            bundle.version == OFFLINE_BUNDLE_VERSION,
+            "Unsupported offline bundle version {}, expected {OFFLINE_BUNDLE_VERSION}",
+            bundle.version
+        );
+        Ok(bundle)
+    }
+
+    pub fn write(&self, path: &Path) -> Result<(), anyhow::Error> {
+        fs::write(path, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("Cannot write offline bundle {}", path.display()))
+    }
+
+    /// Decode the transaction, and check that it is the transaction the bundle was exported for
+    /// and that the bundle's metadata describes it faithfully.
+    pub fn transaction_data(&self) -> Result<TransactionData, anyhow::Error> {
+        let tx_data: TransactionData = bcs::from_bytes(
+            &Base64::decode(&self.tx_bytes)
+                .map_err(|_| anyhow!("Invalid Base64 encoding of the bundle's tx bytes"))?,
+        )
+        .map_err(|_| anyhow!("Failed to parse the bundle's tx bytes as TransactionData"))?;
+        ensure!(
+            tx_data.digest() == self.digest,
+            "The bundle's tx bytes are for transaction {}, but the bundle was exported for \
+             transaction {}",
+            tx_data.digest(),
+            self.digest
+        );
+
+        let expected = Self::new(&tx_data, self.chain_identifier.clone())?;
+        ensure!(
+            expected.sender == self.sender
+                && expected.gas_owner == self.gas_owner
+                && expected.gas_price == self.gas_price
+                && expected.gas_budget == self.gas_budget
+                && expected.owned_objects == self.owned_objects
+                && expected.shared_objects == self.shared_objects,
+            "Offline bundle metadata does not match its transaction bytes"
+        );
+        Ok(tx_data)
+    }
+
+    /// Addresses that have already signed the bundle.
+    pub fn signers(&self) -> Result<Vec<SuiAddress>, anyhow::Error> {
+        self.generic_signatures()?
+            .iter()
+            .map(|sig| SuiAddress::try_from(sig).map_err(|e| anyhow!(e)))
+            .collect()
+    }
+
+    /// Add `signer`'s signature, which must be the transaction sender or the gas owner.
+    pub fn add_signature(
+        &mut self,
+        signer: SuiAddress,
+        signature: GenericSignature,
+    ) -> Result<(), anyhow::Error> {
+        ensure!(
+            signer == self.sender || signer == self.gas_owner,
+            "Address {signer} is neither the sender nor the gas owner of this transaction"
+        );
+        ensure!(
+            !self.signers()?.contains(&signer),
+            "The bundle is already signed by {signer}"
+        );
+        self.signatures.push(Base64::encode(signature.as_ref()));
+        Ok(())
+    }
+
+    /// True if the bundle carries every signature required to execute the transaction.
+    pub fn is_fully_signed(&self) -> Result<bool, anyhow::Error> {
+        let signers = self.signers()?;
+        Ok(signers.contains(&self.sender) && signers.contains(&self.gas_owner))
+    }
+
+    pub fn transaction(&self) -> Result<Transaction, anyhow::Error> {
+        let tx_data = self.transaction_data()?;
+        ensure!(
+            self.is_fully_signed()?,
+            "The bundle must be signed by the sender {} and the gas owner {} before it can be \
+             submitted",
+            self.sender,
+            self.gas_owner
+        );
+        Ok(Transaction::from_generic_sig_data(
+            tx_data,
+            self.generic_signatures()?,
+        ))
+    }
+
+    fn generic_signatures(&self) -> Result<Vec<GenericSignature>, anyhow::Error> {
+        self.signatures
+            .iter()
+            .map(|sig| {
+                GenericSignature::from_bytes(
+                    &Base64::decode(sig).map_err(|_| anyhow!("Invalid Base64 encoding"))?,
+                )
+                .map_err(|_| anyhow!("Invalid generic signature"))
+            })
+            .collect()
+    }
+}
+
+impl OfflineCommand {
+    pub async fn execute(
+        self,
+        context: &mut WalletContext,
+    ) -> Result<SuiClientCommandResult, anyhow::Error> {
+        let client = context.get_client().await?;
+        Ok(match self {
+            OfflineCommand::Export { tx_bytes, output } => {
+                let tx_data: TransactionData = bcs::from_bytes(
+                    &Base64::decode(&tx_bytes).map_err(|_| anyhow!("Invalid Base64 encoding"))?,
+                )
+                .map_err(|_| anyhow!("Failed to parse tx bytes, check if it matches the output of sui client commands with --serialize-unsigned-transaction"))?;
+                let chain_identifier = client.read_api().get_chain_identifier().await?;
+                let bundle = OfflineTransactionBundle::new(&tx_data, chain_identifier)?;
+                bundle.write(&output)?;
+                SuiClientCommandResult::OfflineBundle {
+                    path: output,
+                    bundle,
+                }
+            }
+            OfflineCommand::Submit { bundle } => {
+                let bundle = OfflineTransactionBundle::read(&bundle)?;
+                let transaction = bundle.transaction()?;
+
+                let chain_identifier = client.read_api().get_chain_identifier().await?;
+                ensure!(
+                    chain_identifier == bundle.chain_identifier,
+                    "The bundle was exported for chain {}, but the active environment is chain \
+                     {chain_identifier}",
+                    bundle.chain_identifier
+                );
+
+                let reference_gas_price = context.get_reference_gas_price().await?;
+                ensure!(
+                    bundle.gas_price >= reference_gas_price,
+                    "The bundle's gas price {} is below the current reference gas price \
+                     {reference_gas_price}. Export and sign the transaction again.",
+                    bundle.gas_price
+                );
+
+                let ids = bundle.owned_objects.iter().map(|o| o.object_id).collect();
+                let current = client
+                    .read_api()
+                    .multi_get_object_with_options(ids, SuiObjectDataOptions::new())
+                    .await?;
+                let mut stale = vec![];
+                for (expected, response) in bundle.owned_objects.iter().zip(current) {
+                    match response.object() {
+                        Ok(object) if object.version == expected.version => (),
+                        Ok(object) => stale.push(format!(
+                            "{} is at version {}, the bundle uses version {}",
+                            expected.object_id, object.version, expected.version
+                        )),
+                        Err(_) => stale.push(format!(
+                            "{} no longer exists, the bundle uses version {}",
+                            expected.object_id, expected.version
+                        )),
+                    }
+                }
+                if !stale.is_empty() {
+                    bail!(
+                        "The bundle is stale, objects were modified since it was exported:\n  {}\n\
+                         Export and sign the transaction again.",
+                        stale.join("\n  ")
+                    );
+                }
+
+                let response = context.execute_transaction_may_fail(transaction).await?;
+                SuiClientCommandResult::TransactionBlock(response)
+            }
+        })
+    }
+}
+
+impl Display for OfflineTransactionBundle {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.transaction_data() {
+            Ok(tx_data) => write!(f, "{}", TransactionPreview { tx_data: &tx_data }),
+            Err(e) => write!(f, "{e}"),
+        }
+    }
+}
@@ -5,7 +5,7 @@ use crate::client_commands::SuiClientCommands;
 use crate::console::start_console;
 use crate::fire_drill::{run_fire_drill, FireDrill};
 use crate::genesis_ceremony::{run, Ceremony};
-use crate::keytool::KeyToolCommand;
+use crate::keytool::{prompt_for_keystore_passphrase, KeyToolCommand};
 use crate::validator_commands::SuiValidatorCommand;
 use anyhow::{anyhow, bail, ensure, Context};
 use clap::*;
@@ -13,6 +13,7 @@ use fastcrypto::traits::KeyPair;
 use move_analyzer::analyzer;
 use move_package::BuildConfig;
 use rand::rngs::OsRng;
+use std::collections::BTreeMap;
 use std::io::{stderr, stdout, Write};
 use std::net::{AddrParseError, IpAddr, Ipv4Addr, SocketAddr};
 use std::num::NonZeroUsize;
@@ -411,14 +412,26 @@ impl SuiCommand {
             } => {
                 let keystore_path =
                     keystore_path.unwrap_or(sui_config_dir()?.join(SUI_KEYSTORE_FILENAME));
-                let mut keystore = Keystore::from(FileBasedKeystore::new(&keystore_path)?);
-                cmd.execute(&mut keystore).await?.print(!json);
+                let mut keystore = Keystore::open(&keystore_path)?;
+                prompt_for_keystore_passphrase(&mut keystore);
+                let converts_keystore = matches!(
+                    cmd,
+                    KeyToolCommand::EncryptKeystore | KeyToolCommand::DecryptKeystore
+                );
+                let output = cmd.execute(&mut keystore).await?;
+                if converts_keystore {
+                    update_client_config_keystore(&keystore_path, &keystore)?;
+                }
+                keystore.lock();
+                output.print(!json);
                 Ok(())
             }
             SuiCommand::Console { config } => {
                 let config = config.unwrap_or(sui_config_dir()?.join(SUI_CLIENT_CONFIG));
                 prompt_if_no_config(&config, false).await?;
-                let context = WalletContext::new(&config, None, None)?;
+                let mut context = WalletContext::new(&config, None, None)?;
+                // The keystore stays unlocked for the rest of the session once a command signs.
+                prompt_for_keystore_passphrase(&mut context.config.keystore);
                 start_console(context, &mut stdout(), &mut stderr()).await
             }
             SuiCommand::Client {
@@ -431,7 +444,9 @@ impl SuiCommand {
                 prompt_if_no_config(&config_path, accept_defaults).await?;
                 if let Some(cmd) = cmd {
                     let mut context = WalletContext::new(&config_path, None, None)?;
+                    prompt_for_keystore_passphrase(&mut context.config.keystore);
                     cmd.execute(&mut context).await?.print(!json);
+                    context.config.keystore.lock();
                 } else {
                     // Print help
                     let mut app: Command = SuiCommand::command();
@@ -450,7 +465,9 @@ impl SuiCommand {
                 prompt_if_no_config(&config_path, accept_defaults).await?;
                 let mut context = WalletContext::new(&config_path, None, None)?;
                 if let Some(cmd) = cmd {
+                    prompt_for_keystore_passphrase(&mut context.config.keystore);
                     cmd.execute(&mut context).await?.print(!json);
+                    context.config.keystore.lock();
                 } else {
                     // Print help
                     let mut app: Command = SuiCommand::command();
@@ -1194,6 +1211,34 @@ async fn prompt_if_no_config(
     Ok(())
 }
 
+/// Point the client config at `keystore` if it refers to the keystore file at `keystore_path`.
+/// Converting a keystore between the plaintext and encrypted formats changes how the client
+/// config has to load it, and the old entry would no longer deserialize, so the entry is
+/// rewritten without loading the keystore it names.
+fn update_client_config_keystore(
+    keystore_path: &Path,
+    keystore: &Keystore,
+) -> Result<(), anyhow::Error> {
+    let config_path = sui_config_dir()?.join(SUI_CLIENT_CONFIG);
+    if !config_path.exists() {
+        return Ok(());
+    }
+    let mut config: serde_yaml::Value = serde_yaml::from_str(&fs::read_to_string(&config_path)?)?;
+    let Some(entry) = config.get_mut("keystore") else {
+        return Ok(());
+    };
+    let refers_to_keystore = serde_yaml::from_value::<BTreeMap<String, PathBuf>>(entry.clone())
+        .ok()
+        .and_then(|entry| entry.into_values().next())
+        .is_some_and(|path| path == keystore_path);
+    if refers_to_keystore {
+        *entry = serde_yaml::to_value(keystore)?;
+        fs::write(&config_path, serde_yaml::to_string(&config)?)?;
+        info!("Updated the keystore entry of {:?}", config_path);
+    }
+    Ok(())
+}
+
 fn read_line() -> Result<String, anyhow::Error> {
     let mut s = String::new();
     let _ = stdout().flush();
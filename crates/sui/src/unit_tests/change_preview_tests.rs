@@ -0,0 +1,136 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use move_core_types::{
+    account_address::AccountAddress,
+    annotated_value::{MoveStruct, MoveValue},
+    identifier::Identifier,
+    language_storage::StructTag,
+};
+use sui_types::{base_types::ObjectID, MOVE_STDLIB_ADDRESS, SUI_FRAMEWORK_ADDRESS};
+
+use super::{diff_fields, flatten_value, FieldDiff};
+
+fn move_struct(
+    address: AccountAddress,
+    module: &str,
+    name: &str,
+    fields: Vec<(&str, MoveValue)>,
+) -> MoveValue {
+    MoveValue::Struct(MoveStruct {
+        type_: StructTag {
+            address,
+            module: Identifier::new(module).unwrap(),
+            name: Identifier::new(name).unwrap(),
+            type_params: vec![],
+        },
+        fields: fields
+            .into_iter()
+            .map(|(name, value)| (Identifier::new(name).unwrap(), value))
+            .collect(),
+    })
+}
+
+fn flatten(value: &MoveValue) -> Vec<(String, String)> {
+    let mut fields = vec![];
+    flatten_value(String::new(), value, &mut fields);
+    fields
+}
+
+fn bytes(bytes: &[u8]) -> MoveValue {
+    MoveValue::Vector(bytes.iter().copied().map(MoveValue::U8).collect())
+}
+
+fn vault(name: &str, balance: u64, holders: Vec<u64>) -> MoveValue {
+    let id = ObjectID::from_single_byte(7);
+    move_struct(
+        SUI_FRAMEWORK_ADDRESS,
+        "vault",
+        "Vault",
+        vec![
+            (
+                "id",
+                move_struct(
+                    SUI_FRAMEWORK_ADDRESS,
+                    "object",
+                    "UID",
+                    vec![(
+                        "id",
+                        move_struct(
+                            SUI_FRAMEWORK_ADDRESS,
+                            "object",
+                            "ID",
+                            vec![("bytes", MoveValue::Address(id.into()))],
+                        ),
+                    )],
+                ),
+            ),
+            (
+                "name",
+                move_struct(
+                    MOVE_STDLIB_ADDRESS,
+                    "string",
+                    "String",
+                    vec![("bytes", bytes(name.as_bytes()))],
+                ),
+            ),
+            (
+                "balance",
+                move_struct(
+                    SUI_FRAMEWORK_ADDRESS,
+                    "balance",
+                    "Balance",
+                    vec![("value", MoveValue::U64(balance))],
+                ),
+            ),
+            ("key", bytes(&[0xab, 0xcd])),
+            (
+                "holders",
+                MoveValue::Vector(holders.into_iter().map(MoveValue::U64).collect()),
+            ),
+        ],
+    )
+}
+
+#[test]
+fn test_flatten_value() {
+    let fields = flatten(&vault("main", 10, vec![1, 2]));
+    let paths: Vec<_> = fields.iter().map(|(path, _)| path.as_str()).collect();
+    assert_eq!(
+        paths,
+        vec![
+            "id.id",
+            "name",
+            "balance.value",
+            "key",
+            "holders.length",
+            "holders[0]",
+            "holders[1]"
+        ]
+    );
+    assert_eq!(fields[0].1, ObjectID::from_single_byte(7).to_string());
+    assert_eq!(fields[1].1, "\"main\"");
+    assert_eq!(fields[2].1, "10");
+    assert_eq!(fields[3].1, "0xabcd");
+}
+
+#[test]
+fn test_diff_fields() {
+    let before = flatten(&vault("main", 10, vec![1, 2]));
+    let after = flatten(&vault("main", 25, vec![2]));
+    let diff = |path: &str, before: Option<&str>, after: Option<&str>| FieldDiff {
+        path: path.to_string(),
+        before: before.map(str::to_string),
+        after: after.map(str::to_string),
+    };
+    assert_eq!(
+        diff_fields(&before, &after),
+        vec![
+            diff("balance.value", Some("10"), Some("25")),
+            diff("holders.length", Some("2"), Some("1")),
+            diff("holders[0]", Some("1"), Some("2")),
+            diff("holders[1]", Some("2"), None),
+        ]
+    );
+    assert!(diff_fields(&before, &before).is_empty());
+}
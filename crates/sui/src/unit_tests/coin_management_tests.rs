@@ -0,0 +1,85 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::BTreeSet;
+
+use sui_json_rpc_types::Coin;
+use sui_types::base_types::{ObjectDigest, ObjectID, SequenceNumber};
+use sui_types::digests::TransactionDigest;
+
+use super::{
+    select_gas_coins, ConsolidationPlan, MAX_COINS_MERGED_PER_TX, MAX_GAS_PAYMENT_OBJECTS,
+};
+
+fn coin(balance: u64) -> Coin {
+    Coin {
+        coin_type: "0x2::sui::SUI".to_string(),
+        coin_object_id: ObjectID::random(),
+        version: SequenceNumber::new(),
+        digest: ObjectDigest::random(),
+        balance,
+        previous_transaction: TransactionDigest::random(),
+    }
+}
+
+#[test]
+fn test_select_smallest_sufficient_gas_coin() {
+    let coins: Vec<_> = [5, 1000, 50, 100].into_iter().map(coin).collect();
+    let selected = select_gas_coins(&coins, 40, &BTreeSet::new()).unwrap();
+    assert_eq!(selected, vec![coins[2].coin_object_id]);
+
+    // Coins used by the transaction are not used for gas.
+    let excluded = BTreeSet::from([coins[2].coin_object_id]);
+    let selected = select_gas_coins(&coins, 40, &excluded).unwrap();
+    assert_eq!(selected, vec![coins[3].coin_object_id]);
+}
+
+#[test]
+fn test_select_merged_gas_coins() {
+    let coins: Vec<_> = [10, 3, 10, 10, 1].into_iter().map(coin).collect();
+    let selected = select_gas_coins(&coins, 25, &BTreeSet::new()).unwrap();
+    assert_eq!(selected.len(), 3);
+    assert!(!selected.contains(&coins[1].coin_object_id));
+    assert!(!selected.contains(&coins[4].coin_object_id));
+
+    assert!(select_gas_coins(&coins, 35, &BTreeSet::new()).is_err());
+}
+
+#[test]
+fn test_select_gas_coins_payment_limit() {
+    let coins: Vec<_> = (0..300).map(|_| coin(1)).collect();
+    let budget = MAX_GAS_PAYMENT_OBJECTS as u64;
+    let selected = select_gas_coins(&coins, budget, &BTreeSet::new()).unwrap();
+    assert_eq!(selected.len(), MAX_GAS_PAYMENT_OBJECTS);
+    assert!(select_gas_coins(&coins, budget + 1, &BTreeSet::new()).is_err());
+}
+
+#[test]
+fn test_consolidation_plan() {
+    let mut coins: Vec<_> = (0..1200).map(|_| coin(1)).collect();
+    coins.push(coin(700));
+    coins.push(coin(1000));
+    let plan = ConsolidationPlan::new(coins.clone(), 2).unwrap();
+
+    // The largest coins are the targets.
+    let targets: Vec<_> = plan.targets.iter().map(|c| c.balance).collect();
+    assert_eq!(targets, vec![1000, 700]);
+
+    // 1200 coins are merged in batches, and the targets end up with similar balances.
+    let batch_sizes: Vec<_> = plan.batches.iter().map(|b| b.len()).collect();
+    assert_eq!(
+        batch_sizes,
+        vec![MAX_COINS_MERGED_PER_TX, MAX_COINS_MERGED_PER_TX, 200]
+    );
+    let mut balances: Vec<_> = plan.targets.iter().map(|c| c.balance).collect();
+    for (target, coin) in plan.batches.iter().flatten() {
+        balances[*target] += coin.balance;
+    }
+    assert_eq!(balances, vec![1450, 1450]);
+
+    // Nothing to merge when there are no more coins than targets.
+    let plan = ConsolidationPlan::new(coins[..2].to_vec(), 3).unwrap();
+    assert_eq!(plan.targets.len(), 2);
+    assert!(plan.batches.is_empty());
+    assert!(ConsolidationPlan::new(coins, 0).is_err());
+}
@@ -7,6 +7,8 @@ use crate::key_identity::KeyIdentity;
 use crate::keytool::read_authority_keypair_from_file;
 use crate::keytool::read_keypair_from_file;
 use crate::keytool::CommandOutput;
+use crate::multisig_proposal::MultiSigProposal;
+use crate::offline::OfflineTransactionBundle;
 
 use super::write_keypair_to_file;
 use super::KeyToolCommand;
@@ -36,6 +38,8 @@ use sui_types::crypto::Signature;
 use sui_types::crypto::SignatureScheme;
 use sui_types::crypto::SuiKeyPair;
 use sui_types::crypto::SuiSignatureInner;
+use sui_types::multisig::MultiSigPublicKey;
+use sui_types::signature::GenericSignature;
 use sui_types::transaction::TransactionData;
 use sui_types::transaction::TEST_ONLY_GAS_UNIT_FOR_TRANSFER;
 use tempfile::TempDir;
@@ -234,6 +238,7 @@ async fn test_private_keys_import_export() -> Result<(), anyhow::Error> {
         let mut keystore = Keystore::from(InMemKeystore::new_insecure_for_tests(0));
         KeyToolCommand::Import {
             alias: None,
+            discover: None,
             input_string: private_key.to_string(),
             key_scheme: SignatureScheme::ED25519,
             derivation_path: None,
@@ -272,6 +277,7 @@ async fn test_private_keys_import_export() -> Result<(), anyhow::Error> {
         // assert failure when private key is malformed
         let output = KeyToolCommand::Import {
             alias: None,
+            discover: None,
             input_string: private_key[1..].to_string(),
             key_scheme: SignatureScheme::ED25519,
             derivation_path: None,
@@ -283,6 +289,7 @@ async fn test_private_keys_import_export() -> Result<(), anyhow::Error> {
         // importing an hex encoded string should fail
         let output = KeyToolCommand::Import {
             alias: None,
+            discover: None,
             input_string: addr.to_string(),
             key_scheme: SignatureScheme::ED25519,
             derivation_path: None,
@@ -306,6 +313,7 @@ async fn test_mnemonics_ed25519() -> Result<(), anyhow::Error> {
         let mut keystore = Keystore::from(InMemKeystore::new_insecure_for_tests(0));
         KeyToolCommand::Import {
             alias: None,
+            discover: None,
             input_string: t[0].to_string(),
             key_scheme: SignatureScheme::ED25519,
             derivation_path: None,
@@ -331,6 +339,7 @@ async fn test_mnemonics_secp256k1() -> Result<(), anyhow::Error> {
         let mut keystore = Keystore::from(InMemKeystore::new_insecure_for_tests(0));
         KeyToolCommand::Import {
             alias: None,
+            discover: None,
             input_string: t[0].to_string(),
             key_scheme: SignatureScheme::Secp256k1,
             derivation_path: None,
@@ -370,6 +379,7 @@ async fn test_mnemonics_secp256r1() -> Result<(), anyhow::Error> {
         let mut keystore = Keystore::from(InMemKeystore::new_insecure_for_tests(0));
         KeyToolCommand::Import {
             alias: None,
+            discover: None,
             input_string: mnemonics.to_string(),
             key_scheme: SignatureScheme::Secp256r1,
             derivation_path: None,
@@ -391,6 +401,7 @@ async fn test_invalid_derivation_path() -> Result<(), anyhow::Error> {
     let mut keystore = Keystore::from(InMemKeystore::new_insecure_for_tests(0));
     assert!(KeyToolCommand::Import {
         alias: None,
+        discover: None,
         input_string: TEST_MNEMONIC.to_string(),
         key_scheme: SignatureScheme::ED25519,
         derivation_path: Some("m/44'/1'/0'/0/0".parse().unwrap()),
@@ -401,6 +412,7 @@ async fn test_invalid_derivation_path() -> Result<(), anyhow::Error> {
 
     assert!(KeyToolCommand::Import {
         alias: None,
+        discover: None,
         input_string: TEST_MNEMONIC.to_string(),
         key_scheme: SignatureScheme::ED25519,
         derivation_path: Some("m/0'/784'/0'/0/0".parse().unwrap()),
@@ -411,6 +423,7 @@ async fn test_invalid_derivation_path() -> Result<(), anyhow::Error> {
 
     assert!(KeyToolCommand::Import {
         alias: None,
+        discover: None,
         input_string: TEST_MNEMONIC.to_string(),
         key_scheme: SignatureScheme::ED25519,
         derivation_path: Some("m/54'/784'/0'/0/0".parse().unwrap()),
@@ -421,6 +434,7 @@ async fn test_invalid_derivation_path() -> Result<(), anyhow::Error> {
 
     assert!(KeyToolCommand::Import {
         alias: None,
+        discover: None,
         input_string: TEST_MNEMONIC.to_string(),
         key_scheme: SignatureScheme::Secp256k1,
         derivation_path: Some("m/54'/784'/0'/0'/0'".parse().unwrap()),
@@ -431,6 +445,7 @@ async fn test_invalid_derivation_path() -> Result<(), anyhow::Error> {
 
     assert!(KeyToolCommand::Import {
         alias: None,
+        discover: None,
         input_string: TEST_MNEMONIC.to_string(),
         key_scheme: SignatureScheme::Secp256k1,
         derivation_path: Some("m/44'/784'/0'/0/0".parse().unwrap()),
@@ -447,6 +462,7 @@ async fn test_valid_derivation_path() -> Result<(), anyhow::Error> {
     let mut keystore = Keystore::from(InMemKeystore::new_insecure_for_tests(0));
     assert!(KeyToolCommand::Import {
         alias: None,
+        discover: None,
         input_string: TEST_MNEMONIC.to_string(),
         key_scheme: SignatureScheme::ED25519,
         derivation_path: Some("m/44'/784'/0'/0'/0'".parse().unwrap()),
@@ -457,6 +473,7 @@ async fn test_valid_derivation_path() -> Result<(), anyhow::Error> {
 
     assert!(KeyToolCommand::Import {
         alias: None,
+        discover: None,
         input_string: TEST_MNEMONIC.to_string(),
         key_scheme: SignatureScheme::ED25519,
         derivation_path: Some("m/44'/784'/0'/0'/1'".parse().unwrap()),
@@ -467,6 +484,7 @@ async fn test_valid_derivation_path() -> Result<(), anyhow::Error> {
 
     assert!(KeyToolCommand::Import {
         alias: None,
+        discover: None,
         input_string: TEST_MNEMONIC.to_string(),
         key_scheme: SignatureScheme::ED25519,
         derivation_path: Some("m/44'/784'/1'/0'/1'".parse().unwrap()),
@@ -477,6 +495,7 @@ async fn test_valid_derivation_path() -> Result<(), anyhow::Error> {
 
     assert!(KeyToolCommand::Import {
         alias: None,
+        discover: None,
         input_string: TEST_MNEMONIC.to_string(),
         key_scheme: SignatureScheme::Secp256k1,
         derivation_path: Some("m/54'/784'/0'/0/1".parse().unwrap()),
@@ -487,6 +506,7 @@ async fn test_valid_derivation_path() -> Result<(), anyhow::Error> {
 
     assert!(KeyToolCommand::Import {
         alias: None,
+        discover: None,
         input_string: TEST_MNEMONIC.to_string(),
         key_scheme: SignatureScheme::Secp256k1,
         derivation_path: Some("m/54'/784'/1'/0/1".parse().unwrap()),
@@ -565,3 +585,200 @@ async fn test_sign_command() -> Result<(), anyhow::Error> {
     .await?;
     Ok(())
 }
+
+#[test]
+async fn test_sign_bundle_command() -> Result<(), anyhow::Error> {
+    let temp_dir = TempDir::new().unwrap();
+    let bundle_path = temp_dir.path().join("tx.bundle.json");
+    let mut keystore = Keystore::from(InMemKeystore::new_insecure_for_tests(2));
+    let binding = keystore.addresses();
+    let sender = binding[0];
+    let other = binding[1];
+
+    let gas = (
+        ObjectID::random(),
+        SequenceNumber::new(),
+        ObjectDigest::random(),
+    );
+    let gas_price = 1;
+    let tx_data = TransactionData::new_pay_sui(
+        sender,
+        vec![gas],
+        vec![SuiAddress::random_for_testing_only()],
+        vec![10000],
+        gas,
+        gas_price * TEST_ONLY_GAS_UNIT_FOR_TRANSFER,
+        gas_price,
+    )
+    .unwrap();
+    let bundle = OfflineTransactionBundle::new(&tx_data, "4c78adac".to_string())?;
+    assert_eq!(bundle.owned_objects.len(), 1);
+    assert_eq!(bundle.owned_objects[0].object_id, gas.0);
+    bundle.write(&bundle_path)?;
+
+    // Only the sender or the gas owner can sign.
+    assert!(KeyToolCommand::SignBundle {
+        bundle: bundle_path.clone(),
+        address: Some(KeyIdentity::Address(other)),
+        yes: true,
+    }
+    .execute(&mut keystore)
+    .await
+    .is_err());
+
+    let output = KeyToolCommand::SignBundle {
+        bundle: bundle_path.clone(),
+        address: None,
+        yes: true,
+    }
+    .execute(&mut keystore)
+    .await?;
+    assert!(matches!(output, CommandOutput::SignBundle(_)));
+
+    let signed = OfflineTransactionBundle::read(&bundle_path)?;
+    assert_eq!(signed.signers()?, vec![sender]);
+    assert!(signed.is_fully_signed()?);
+    let transaction = signed.transaction()?;
+    assert_eq!(transaction.data().transaction_data(), &tx_data);
+
+    // Signing twice is rejected.
+    assert!(KeyToolCommand::SignBundle {
+        bundle: bundle_path.clone(),
+        address: None,
+        yes: true,
+    }
+    .execute(&mut keystore)
+    .await
+    .is_err());
+
+    // Transaction bytes swapped for another transaction with the same metadata are rejected.
+    let other_tx_data = TransactionData::new_pay_sui(
+        sender,
+        vec![gas],
+        vec![SuiAddress::random_for_testing_only()],
+        vec![10000],
+        gas,
+        gas_price * TEST_ONLY_GAS_UNIT_FOR_TRANSFER,
+        gas_price,
+    )
+    .unwrap();
+    let mut swapped = signed.clone();
+    swapped.tx_bytes = Base64::encode(bcs::to_bytes(&other_tx_data)?);
+    assert!(swapped.transaction_data().is_err());
+    assert!(swapped.transaction().is_err());
+    let mut swapped = bundle.clone();
+    swapped.tx_bytes = Base64::encode(bcs::to_bytes(&other_tx_data)?);
+    let swapped_path = temp_dir.path().join("swapped.json");
+    swapped.write(&swapped_path)?;
+    assert!(KeyToolCommand::SignBundle {
+        bundle: swapped_path,
+        address: None,
+        yes: true,
+    }
+    .execute(&mut keystore)
+    .await
+    .is_err());
+
+    // Metadata that does not match the transaction bytes is rejected.
+    let mut tampered = signed;
+    tampered.gas_price += 1;
+    assert!(tampered.transaction_data().is_err());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_multisig_proposal_commands() -> Result<(), anyhow::Error> {
+    let temp_dir = TempDir::new().unwrap();
+    let proposal_path = temp_dir.path().join("tx.proposal.json");
+    let mut keystore = Keystore::from(InMemKeystore::new_insecure_for_tests(4));
+    let keys = keystore.keys();
+    let outsider = SuiAddress::from(&keys[3]);
+    let pks = keys[..3].to_vec();
+    let multisig_pk = MultiSigPublicKey::new(pks.clone(), vec![1, 1, 1], 2)?;
+    let multisig_address = SuiAddress::from(&multisig_pk);
+
+    let gas = (
+        ObjectID::random(),
+        SequenceNumber::new(),
+        ObjectDigest::random(),
+    );
+    let gas_price = 1;
+    let tx_data = TransactionData::new_pay_sui(
+        multisig_address,
+        vec![gas],
+        vec![SuiAddress::random_for_testing_only()],
+        vec![10000],
+        gas,
+        gas_price * TEST_ONLY_GAS_UNIT_FOR_TRANSFER,
+        gas_price,
+    )
+    .unwrap();
+
+    KeyToolCommand::MultiSigPropose {
+        tx_bytes: Base64::encode(bcs::to_bytes(&tx_data)?),
+        pks,
+        weights: vec![1, 1, 1],
+        threshold: 2,
+        output: proposal_path.clone(),
+    }
+    .execute(&mut keystore)
+    .await?;
+
+    // The first member key in the keystore signs by default.
+    KeyToolCommand::MultiSigSign {
+        proposal: proposal_path.clone(),
+        address: None,
+        signature: None,
+        yes: true,
+    }
+    .execute(&mut keystore)
+    .await?;
+    let proposal = MultiSigProposal::read(&proposal_path)?;
+    let status = proposal.status()?;
+    assert_eq!(status.weight, 1);
+    assert!(!status.ready);
+    assert!(proposal.transaction().is_err());
+
+    let sign_with = |address: SuiAddress| -> Result<GenericSignature, anyhow::Error> {
+        Ok(keystore
+            .sign_secure(&address, &tx_data, Intent::sui_transaction())?
+            .into())
+    };
+    let first_signature = sign_with(status.members[0].address)?;
+    let outsider_signature = sign_with(outsider)?;
+    let second_signature = sign_with(status.members[2].address)?;
+
+    // Signatures from members that already signed, or from non-members, are rejected.
+    for signature in [first_signature, outsider_signature] {
+        assert!(KeyToolCommand::MultiSigSign {
+            proposal: proposal_path.clone(),
+            address: None,
+            signature: Some(signature),
+            yes: true,
+        }
+        .execute(&mut keystore)
+        .await
+        .is_err());
+    }
+
+    KeyToolCommand::MultiSigSign {
+        proposal: proposal_path.clone(),
+        address: None,
+        signature: Some(second_signature),
+        yes: true,
+    }
+    .execute(&mut keystore)
+    .await?;
+    let proposal = MultiSigProposal::read(&proposal_path)?;
+    let status = proposal.status()?;
+    assert_eq!(status.weight, 2);
+    assert!(status.ready);
+
+    let transaction = proposal.transaction()?;
+    assert_eq!(transaction.data().transaction_data(), &tx_data);
+    assert!(matches!(
+        transaction.data().tx_signatures(),
+        [GenericSignature::MultiSig(_)]
+    ));
+    Ok(())
+}
@@ -0,0 +1,456 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! `sui client watch`: follow the transactions that affect an address, an object or a package.
+//! Transactions are received through JSON-RPC subscriptions when the active environment has a
+//! websocket URL, and otherwise by polling for newly checkpointed transactions. Subscriptions do
+//! not match transactions that only create objects for an address, so these are still polled for
+//! when watching an address.
+
+use std::{
+    collections::{HashSet, VecDeque},
+    fmt::{Display, Formatter},
+    time::Duration,
+};
+
+use futures::{
+    stream::{select_all, BoxStream, SelectAll},
+    StreamExt,
+};
+use serde::Serialize;
+use sui_json_rpc_types::{
+    BalanceChange, ObjectChange, SuiEvent, SuiExecutionStatus, SuiObjectDataOptions,
+    SuiTransactionBlockEffects, SuiTransactionBlockEffectsAPI, SuiTransactionBlockResponse,
+    SuiTransactionBlockResponseOptions, SuiTransactionBlockResponseQuery, TransactionFilter,
+};
+use sui_sdk::{error::SuiRpcResult, wallet_context::WalletContext, SuiClient};
+use sui_types::{
+    base_types::{ObjectID, ObjectType, SuiAddress},
+    digests::TransactionDigest,
+    messages_checkpoint::CheckpointSequenceNumber,
+    object::Owner,
+};
+use tracing::info;
+
+use crate::key_identity::{get_identity_address, KeyIdentity};
+
+/// Number of recently seen transactions remembered, to avoid reporting a transaction twice when
+/// it matches several filters.
+const RECENT_DIGESTS: usize = 1024;
+
+/// What `sui client watch` follows.
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum WatchTarget {
+    Address(SuiAddress),
+    Object(ObjectID),
+    Package(ObjectID),
+}
+
+/// The changes a finalized transaction made to the watched target.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WatchUpdate {
+    pub digest: TransactionDigest,
+    pub checkpoint: Option<CheckpointSequenceNumber>,
+    pub timestamp_ms: Option<u64>,
+    pub status: Option<SuiExecutionStatus>,
+    pub balance_changes: Vec<BalanceChange>,
+    pub object_changes: Vec<ObjectChange>,
+    pub events: Vec<SuiEvent>,
+}
+
+impl WatchTarget {
+    /// Resolve an alias to an address, and an ID to an object or a package if such an object
+    /// exists, or to an address otherwise.
+    pub async fn resolve(
+        target: KeyIdentity,
+        context: &mut WalletContext,
+    ) -> Result<Self, anyhow::Error> {
+        let address = match target {
+            KeyIdentity::Address(address) => address,
+            alias => return Ok(Self::Address(get_identity_address(Some(alias), context)?)),
+        };
+        let response = context
+            .get_client()
+            .await?
+            .read_api()
+            .get_object_with_options(address.into(), SuiObjectDataOptions::new().with_type())
+            .await?;
+        Ok(match response.data {
+            Some(data) if data.type_ == Some(ObjectType::Package) => Self::Package(data.object_id),
+            Some(data) => Self::Object(data.object_id),
+            None => Self::Address(address),
+        })
+    }
+
+    /// Transaction filters for subscriptions that together match every transaction affecting
+    /// the target.
+    fn subscription_filters(&self) -> Vec<TransactionFilter> {
+        match *self {
+            // `FromOrToAddress` is not supported by subscriptions.
+            WatchTarget::Address(address) => vec![
+                TransactionFilter::FromAddress(address),
+                TransactionFilter::ToAddress(address),
+            ],
+            WatchTarget::Object(id) => vec![TransactionFilter::AffectedObject(id)],
+            WatchTarget::Package(package) => vec![TransactionFilter::MoveFunction {
+                package,
+                module: None,
+                function: None,
+            }],
+        }
+    }
+
+    /// Transaction filters for queries that together match every transaction affecting the
+    /// target. Fullnodes do not index `AffectedObject`, but an object that is deleted or wrapped
+    /// is an input of the transaction doing so.
+    fn query_filters(&self) -> Vec<TransactionFilter> {
+        match *self {
+            WatchTarget::Object(id) => vec![
+                TransactionFilter::ChangedObject(id),
+                TransactionFilter::InputObject(id),
+            ],
+            _ => self.subscription_filters(),
+        }
+    }
+
+    /// Keep the parts of `response` that concern the target.
+    fn update(&self, response: SuiTransactionBlockResponse) -> WatchUpdate {
+        let mut balance_changes = response.balance_changes.unwrap_or_default();
+        let mut object_changes = response.object_changes.unwrap_or_default();
+        let mut events = response.events.map(|e| e.data).unwrap_or_default();
+        match *self {
+            WatchTarget::Address(address) => {
+                balance_changes.retain(|c| c.owner == Owner::AddressOwner(address))
+            }
+            WatchTarget::Object(id) => object_changes.retain(|c| c.object_id() == id),
+            WatchTarget::Package(package) => events.retain(|e| e.package_id == package),
+        }
+        WatchUpdate {
+            digest: response.digest,
+            checkpoint: response.checkpoint,
+            timestamp_ms: response.timestamp_ms,
+            status: response.effects.map(|e| e.status().clone()),
+            balance_changes,
+            object_changes,
+            events,
+        }
+    }
+}
+
+/// Where a `Watcher` receives transactions from.
+enum WatchSource<'a> {
+    Subscription {
+        transactions: SelectAll<BoxStream<'a, SuiRpcResult<SuiTransactionBlockEffects>>>,
+        /// Polls for the transactions that create objects owned by the target, if it is an
+        /// address.
+        created: Option<Polling>,
+    },
+    Polling(Polling),
+}
+
+/// Polls for transactions matching each filter after the last one seen, every `interval`.
+struct Polling {
+    filters: Vec<TransactionFilter>,
+    cursors: Vec<Option<TransactionDigest>>,
+    interval: Duration,
+}
+
+/// Follows a target, reporting an update for each transaction that affects it, until `limit`
+/// transactions have been reported (if given).
+pub struct Watcher<'a> {
+    client: &'a SuiClient,
+    target: WatchTarget,
+    limit: Option<usize>,
+    /// Taken by `run`.
+    source: Option<WatchSource<'a>>,
+    reported: usize,
+    recent: VecDeque<TransactionDigest>,
+    recent_set: HashSet<TransactionDigest>,
+}
+
+impl<'a> Watcher<'a> {
+    /// Subscribe to the transactions affecting the target, falling back to polling every
+    /// `poll_interval` if the client cannot subscribe. Only transactions finalized after this
+    /// returns are reported.
+    pub async fn connect(
+        client: &'a SuiClient,
+        target: WatchTarget,
+        limit: Option<usize>,
+        poll_interval: Duration,
+    ) -> Result<Self, anyhow::Error> {
+        let source = match Self::subscribe(client, &target).await {
+            Ok(streams) => WatchSource::Subscription {
+                transactions: select_all(streams),
+                created: match target {
+                    WatchTarget::Address(address) => Some(
+                        Self::start_polling(
+                            client,
+                            vec![TransactionFilter::ToAddress(address)],
+                            poll_interval,
+                        )
+                        .await?,
+                    ),
+                    _ => None,
+                },
+            },
+            Err(e) => {
+                info!("Cannot subscribe to transactions ({e}), polling instead");
+                WatchSource::Polling(
+                    Self::start_polling(client, target.query_filters(), poll_interval).await?,
+                )
+            }
+        };
+        Ok(Self {
+            client,
+            target,
+            limit,
+            source: Some(source),
+            reported: 0,
+            recent: VecDeque::new(),
+            recent_set: HashSet::new(),
+        })
+    }
+
+    /// Returns true if transactions are received through subscriptions rather than polling.
+    pub fn is_subscribed(&self) -> bool {
+        matches!(self.source, Some(WatchSource::Subscription { .. }))
+    }
+
+    /// Pass an update to `output` for each transaction affecting the target, until the limit is
+    /// reached.
+    pub async fn run(
+        mut self,
+        mut output: impl FnMut(WatchUpdate) -> Result<(), anyhow::Error>,
+    ) -> Result<(), anyhow::Error> {
+        match self.source.take().expect("a watcher only runs once") {
+            WatchSource::Subscription {
+                transactions,
+                created,
+            } => self.follow(transactions, created, &mut output).await,
+            WatchSource::Polling(polling) => self.poll(polling, &mut output).await,
+        }
+    }
+
+    async fn subscribe(
+        client: &'a SuiClient,
+        target: &WatchTarget,
+    ) -> Result<Vec<BoxStream<'a, SuiRpcResult<SuiTransactionBlockEffects>>>, anyhow::Error> {
+        let mut streams = vec![];
+        for filter in target.subscription_filters() {
+            let stream = client.read_api().subscribe_transaction(filter).await?;
+            streams.push(stream.boxed());
+        }
+        Ok(streams)
+    }
+
+    /// Start polling after the latest transaction matching each filter.
+    async fn start_polling(
+        client: &SuiClient,
+        filters: Vec<TransactionFilter>,
+        interval: Duration,
+    ) -> Result<Polling, anyhow::Error> {
+        let mut cursors = vec![];
+        for filter in &filters {
+            let page = client
+                .read_api()
+                .query_transaction_blocks(
+                    SuiTransactionBlockResponseQuery::new_with_filter(filter.clone()),
+                    None,
+                    Some(1),
+                    true,
+                )
+                .await?;
+            cursors.push(page.data.first().map(|response| response.digest));
+        }
+        Ok(Polling {
+            filters,
+            cursors,
+            interval,
+        })
+    }
+
+    /// Report the transactions received through `transactions`, and those that create objects
+    /// for the target address found by polling with `created`, which subscriptions miss.
+    async fn follow(
+        &mut self,
+        mut transactions: SelectAll<BoxStream<'a, SuiRpcResult<SuiTransactionBlockEffects>>>,
+        mut created: Option<Polling>,
+        output: &mut impl FnMut(WatchUpdate) -> Result<(), anyhow::Error>,
+    ) -> Result<(), anyhow::Error> {
+        let interval = created.as_ref().map_or(Duration::from_secs(1), |p| p.interval);
+        let mut ticks = tokio::time::interval(interval);
+        ticks.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            tokio::select! {
+                effects = transactions.next() => {
+                    let Some(effects) = effects else {
+                        return Ok(());
+                    };
+                    let digest = *effects?.transaction_digest();
+                    if !self.is_new(digest) {
+                        continue;
+                    }
+                    let response = self
+                        .client
+                        .read_api()
+                        .get_transaction_with_options(digest, Self::response_options())
+                        .await?;
+                    if self.report(response, output)? {
+                        return Ok(());
+                    }
+                }
+                _ = ticks.tick(), if created.is_some() => {
+                    let Some(polling) = created.as_mut() else {
+                        continue;
+                    };
+                    let WatchTarget::Address(address) = self.target else {
+                        continue;
+                    };
+                    for response in self.poll_once(polling).await? {
+                        let creates_owned = response.effects.as_ref().is_some_and(|effects| {
+                            effects.created().iter().any(|oref| {
+                                matches!(oref.owner, Owner::AddressOwner(owner) if owner == address)
+                            })
+                        });
+                        if creates_owned
+                            && self.is_new(response.digest)
+                            && self.report(response, output)?
+                        {
+                            return Ok(());
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Poll for transactions that were checkpointed since the last poll.
+    async fn poll(
+        &mut self,
+        mut polling: Polling,
+        output: &mut impl FnMut(WatchUpdate) -> Result<(), anyhow::Error>,
+    ) -> Result<(), anyhow::Error> {
+        loop {
+            for response in self.poll_once(&mut polling).await? {
+                if self.is_new(response.digest) && self.report(response, output)? {
+                    return Ok(());
+                }
+            }
+            tokio::time::sleep(polling.interval).await;
+        }
+    }
+
+    /// The transactions matching each filter after its cursor, in checkpoint order, advancing the
+    /// cursors past them.
+    async fn poll_once(
+        &self,
+        polling: &mut Polling,
+    ) -> Result<Vec<SuiTransactionBlockResponse>, anyhow::Error> {
+        let mut responses = vec![];
+        for (filter, cursor) in polling.filters.iter().zip(polling.cursors.iter_mut()) {
+            loop {
+                let page = self
+                    .client
+                    .read_api()
+                    .query_transaction_blocks(
+                        SuiTransactionBlockResponseQuery::new(
+                            Some(filter.clone()),
+                            Some(Self::response_options()),
+                        ),
+                        *cursor,
+                        None,
+                        false,
+                    )
+                    .await?;
+                if let Some(last) = page.data.last() {
+                    *cursor = Some(last.digest);
+                }
+                responses.extend(page.data);
+                if !page.has_next_page {
+                    break;
+                }
+            }
+        }
+        responses.sort_by_key(|response| (response.checkpoint, response.timestamp_ms));
+        Ok(responses)
+    }
+
+    fn response_options() -> SuiTransactionBlockResponseOptions {
+        SuiTransactionBlockResponseOptions::new()
+            .with_effects()
+            .with_events()
+            .with_balance_changes()
+            .with_object_changes()
+    }
+
+    fn is_new(&mut self, digest: TransactionDigest) -> bool {
+        if !self.recent_set.insert(digest) {
+            return false;
+        }
+        self.recent.push_back(digest);
+        if self.recent.len() > RECENT_DIGESTS {
+            if let Some(oldest) = self.recent.pop_front() {
+                self.recent_set.remove(&oldest);
+            }
+        }
+        true
+    }
+
+    /// Pass the update for `response` to `output`, and return true once the limit is reached.
+    fn report(
+        &mut self,
+        response: SuiTransactionBlockResponse,
+        output: &mut impl FnMut(WatchUpdate) -> Result<(), anyhow::Error>,
+    ) -> Result<bool, anyhow::Error> {
+        output(self.target.update(response))?;
+        self.reported += 1;
+        Ok(self.limit.is_some_and(|limit| self.reported >= limit))
+    }
+}
+
+impl Display for WatchTarget {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WatchTarget::Address(address) => write!(f, "address {address}"),
+            WatchTarget::Object(id) => write!(f, "object {id}"),
+            WatchTarget::Package(id) => write!(f, "package {id}"),
+        }
+    }
+}
+
+impl Display for WatchUpdate {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Transaction {}", self.digest)?;
+        if let Some(checkpoint) = self.checkpoint {
+            write!(f, " (checkpoint {checkpoint})")?;
+        }
+        writeln!(f)?;
+        if let Some(SuiExecutionStatus::Failure { error }) = &self.status {
+            writeln!(f, "  Failed: {error}")?;
+        }
+        for change in &self.balance_changes {
+            writeln!(
+                f,
+                "  {}",
+                change.to_string().trim_end().replace('\n', "\n  ")
+            )?;
+        }
+        for change in &self.object_changes {
+            writeln!(
+                f,
+                "  {}",
+                change.to_string().trim_end().replace('\n', "\n  ")
+            )?;
+        }
+        for event in &self.events {
+            writeln!(
+                f,
+                "  {}",
+                event.to_string().trim_end().replace('\n', "\n  ")
+            )?;
+        }
+        Ok(())
+    }
+}
@@ -2,9 +2,12 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use std::collections::BTreeSet;
-use std::io::Read;
+use std::io::{BufReader, Read};
 use std::net::SocketAddr;
+use std::os::unix::net::UnixListener;
 use std::os::unix::prelude::FileExt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
 use std::{fmt::Write, fs::read_dir, path::PathBuf, str, thread, time::Duration};
 
 use std::env;
@@ -13,11 +16,16 @@ use std::str::FromStr;
 
 use expect_test::expect;
 use move_package::{lock_file::schema::ManagedPackage, BuildConfig as MoveBuildConfig};
+use rand::rngs::OsRng;
 use serde_json::json;
+use sui::change_preview::{ChangePreview, FieldDiff};
 use sui::client_ptb::ptb::PTB;
 use sui::key_identity::{get_identity_address, KeyIdentity};
+use sui::keytool::discover_hd_accounts;
 use sui::sui_commands::IndexerArgs;
-use sui_sdk::SuiClient;
+use sui::watch::{WatchTarget, Watcher};
+use sui_config::node::ServerType;
+use sui_sdk::{SuiClient, SuiClientBuilder};
 use sui_test_transaction_builder::batch_make_transfer_transactions;
 use sui_types::object::Owner;
 use sui_types::transaction::{
@@ -28,6 +36,7 @@ use sui_types::transaction::{
 use tokio::time::sleep;
 
 use sui::{
+    address_book::AddressBookCommand,
     client_commands::{
         estimate_gas_budget, Opts, OptsWithGas, SuiClientCommandResult, SuiClientCommands,
         SwitchResponse,
@@ -40,11 +49,13 @@ use sui_config::{
 };
 use sui_json::SuiJsonValue;
 use sui_json_rpc_types::{
-    get_new_package_obj_from_response, OwnedObjectRef, SuiExecutionStatus, SuiObjectData,
-    SuiObjectDataFilter, SuiObjectDataOptions, SuiObjectResponse, SuiObjectResponseQuery,
-    SuiTransactionBlockDataAPI, SuiTransactionBlockEffects, SuiTransactionBlockEffectsAPI,
+    get_new_package_obj_from_response, ObjectChange, OwnedObjectRef, SuiExecutionStatus,
+    SuiObjectData, SuiObjectDataFilter, SuiObjectDataOptions, SuiObjectResponse,
+    SuiObjectResponseQuery, SuiTransactionBlockDataAPI, SuiTransactionBlockEffects,
+    SuiTransactionBlockEffectsAPI,
 };
-use sui_keys::keystore::AccountKeystore;
+use sui_keys::external_keystore::serve_signer_requests;
+use sui_keys::keystore::{AccountKeystore, EncryptedFileKeystore, FileBasedKeystore, Keystore};
 use sui_macros::sim_test;
 use sui_move_build::{BuildConfig, SuiPackageHooks};
 use sui_sdk::sui_client_config::SuiClientConfig;
@@ -162,6 +173,67 @@ async fn test_addresses_command() -> Result<(), anyhow::Error> {
     Ok(())
 }
 
+#[sim_test]
+async fn test_address_book_command() -> Result<(), anyhow::Error> {
+    let mut test_cluster = TestClusterBuilder::new().build().await;
+    let address = test_cluster.get_address_0();
+    let context = &mut test_cluster.wallet;
+    let treasury = SuiAddress::random_for_testing_only();
+
+    let SuiClientCommandResult::AddressBook(entries) = SuiClientCommands::AddressBook {
+        cmd: AddressBookCommand::Add {
+            name: "treasury".to_string(),
+            address: treasury,
+        },
+    }
+    .execute(context)
+    .await?
+    else {
+        panic!("Expected address book entries");
+    };
+    assert_eq!(entries.len(), 1);
+
+    // Names in the address book resolve like key aliases.
+    let resolved = get_identity_address(Some(KeyIdentity::from_str("treasury")?), context)?;
+    assert_eq!(resolved, treasury);
+
+    // Names are unique, and cannot shadow key aliases.
+    let key_alias = context
+        .config
+        .keystore
+        .get_alias_by_address(&address)
+        .unwrap();
+    for name in ["treasury".to_string(), key_alias] {
+        assert!(SuiClientCommands::AddressBook {
+            cmd: AddressBookCommand::Add {
+                name,
+                address: treasury,
+            },
+        }
+        .execute(context)
+        .await
+        .is_err());
+    }
+
+    SuiClientCommands::AddressBook {
+        cmd: AddressBookCommand::List,
+    }
+    .execute(context)
+    .await?
+    .print(true);
+
+    SuiClientCommands::AddressBook {
+        cmd: AddressBookCommand::Remove {
+            name: "treasury".to_string(),
+        },
+    }
+    .execute(context)
+    .await?;
+    assert!(get_identity_address(Some(KeyIdentity::from_str("treasury")?), context).is_err());
+
+    Ok(())
+}
+
 #[sim_test]
 async fn test_objects_command() -> Result<(), anyhow::Error> {
     let mut test_cluster = TestClusterBuilder::new().build().await;
@@ -2824,6 +2896,67 @@ async fn test_split_coin() -> Result<(), anyhow::Error> {
     Ok(())
 }
 
+#[sim_test]
+async fn test_preview_split_coin() -> Result<(), anyhow::Error> {
+    let mut test_cluster = TestClusterBuilder::new().build().await;
+    let rgp = test_cluster.get_reference_gas_price().await;
+    let address = test_cluster.get_address_0();
+    let context = &mut test_cluster.wallet;
+    let client = context.get_client().await?;
+    let coins = client
+        .coin_read_api()
+        .get_coins(address, None, None, None)
+        .await?
+        .data;
+    let gas = coins[0].coin_object_id;
+    let coin = &coins[1];
+
+    let mut opts = OptsWithGas::for_testing(Some(gas), rgp * TEST_ONLY_GAS_UNIT_FOR_SPLIT_COIN);
+    opts.rest.serialize_unsigned_transaction = true;
+    let resp = SuiClientCommands::SplitCoin {
+        opts,
+        coin_id: coin.coin_object_id,
+        amounts: Some(vec![1000, 10]),
+        count: None,
+    }
+    .execute(context)
+    .await?;
+    let SuiClientCommandResult::SerializedUnsignedTransaction(tx_data) = resp else {
+        panic!("Expected an unsigned transaction, got {resp}");
+    };
+
+    let preview = ChangePreview::new(&client, &tx_data).await?;
+    assert_eq!(preview.status, SuiExecutionStatus::Success);
+    assert_eq!(preview.created.len(), 2);
+
+    // The split coin's balance decreases by the split amounts.
+    let split = preview
+        .mutated
+        .iter()
+        .find(|diff| diff.object_id == coin.coin_object_id)
+        .expect("The split coin is mutated");
+    assert!(split.contents_known);
+    assert_eq!(
+        split.fields,
+        vec![FieldDiff {
+            path: "balance.value".to_string(),
+            before: Some(coin.balance.to_string()),
+            after: Some((coin.balance - 1010).to_string()),
+        }]
+    );
+
+    // The gas coin's contents are known too, it pays for gas.
+    let gas_diff = preview
+        .mutated
+        .iter()
+        .find(|diff| diff.object_id == gas)
+        .expect("The gas coin is mutated");
+    assert!(gas_diff.contents_known);
+    assert_eq!(gas_diff.fields.len(), 1);
+    assert_eq!(gas_diff.fields[0].path, "balance.value");
+    Ok(())
+}
+
 #[sim_test]
 async fn test_signature_flag() -> Result<(), anyhow::Error> {
     let res = SignatureScheme::from_flag("0");
@@ -2900,6 +3033,7 @@ async fn test_serialize_tx() -> Result<(), anyhow::Error> {
             dev_inspect: false,
             serialize_unsigned_transaction: true,
             serialize_signed_transaction: false,
+            preview: false,
         },
     }
     .execute(context)
@@ -2915,6 +3049,7 @@ async fn test_serialize_tx() -> Result<(), anyhow::Error> {
             dev_inspect: false,
             serialize_unsigned_transaction: false,
             serialize_signed_transaction: true,
+            preview: false,
         },
     }
     .execute(context)
@@ -2931,6 +3066,7 @@ async fn test_serialize_tx() -> Result<(), anyhow::Error> {
             dev_inspect: false,
             serialize_unsigned_transaction: false,
             serialize_signed_transaction: true,
+            preview: false,
         },
     }
     .execute(context)
@@ -3778,6 +3914,7 @@ async fn test_gas_estimation() -> Result<(), anyhow::Error> {
             dev_inspect: false,
             serialize_unsigned_transaction: false,
             serialize_signed_transaction: false,
+            preview: false,
         },
     }
     .execute(context)
@@ -4022,3 +4159,257 @@ async fn test_parse_host_port() {
     let input = "127.9.0.1:asb";
     assert!(parse_host_port(input.to_string(), 9123).is_err());
 }
+
+/// Watch a coin until it is merged into another coin, which deletes it, and an address until it
+/// is sent a new coin, checking that `client` reaches the watchers through subscriptions or
+/// through polling as expected.
+async fn check_watch(
+    test_cluster: &mut TestCluster,
+    client: &SuiClient,
+    subscribed: bool,
+) -> Result<(), anyhow::Error> {
+    let rgp = test_cluster.get_reference_gas_price().await;
+    let address = test_cluster.get_address_0();
+    let recipient = test_cluster.get_address_1();
+    let context = &mut test_cluster.wallet;
+    let coins = client
+        .coin_read_api()
+        .get_coins(address, None, None, None)
+        .await?
+        .data;
+    let primary_coin = coins[0].coin_object_id;
+    let coin_to_merge = coins[1].coin_object_id;
+    let poll_interval = Duration::from_millis(100);
+
+    let watcher = Watcher::connect(
+        client,
+        WatchTarget::Object(coin_to_merge),
+        Some(1),
+        poll_interval,
+    )
+    .await?;
+    assert_eq!(watcher.is_subscribed(), subscribed);
+    let mut updates = vec![];
+    let (watched, merged) = tokio::time::timeout(Duration::from_secs(60), async {
+        tokio::join!(
+            watcher.run(|update| {
+                updates.push(update);
+                Ok(())
+            }),
+            SuiClientCommands::MergeCoin {
+                primary_coin,
+                coin_to_merge,
+                opts: OptsWithGas::for_testing(None, rgp * TEST_ONLY_GAS_UNIT_FOR_GENERIC),
+            }
+            .execute(context),
+        )
+    })
+    .await?;
+    watched?;
+    let SuiClientCommandResult::TransactionBlock(merged) = merged? else {
+        panic!("MergeCoin should return a transaction block");
+    };
+    assert_eq!(updates.len(), 1);
+    assert_eq!(updates[0].digest, merged.digest);
+    assert!(updates[0].object_changes.iter().any(|change| matches!(
+        change,
+        ObjectChange::Deleted { object_id, .. } if *object_id == coin_to_merge
+    )));
+
+    let watcher = Watcher::connect(
+        client,
+        WatchTarget::Address(recipient),
+        Some(1),
+        poll_interval,
+    )
+    .await?;
+    let mut updates = vec![];
+    let (watched, paid) = tokio::time::timeout(Duration::from_secs(60), async {
+        tokio::join!(
+            watcher.run(|update| {
+                updates.push(update);
+                Ok(())
+            }),
+            SuiClientCommands::PaySui {
+                input_coins: vec![primary_coin],
+                recipients: vec![KeyIdentity::Address(recipient)],
+                amounts: vec![1000],
+                opts: Opts::for_testing(rgp * TEST_ONLY_GAS_UNIT_FOR_TRANSFER),
+            }
+            .execute(context),
+        )
+    })
+    .await?;
+    watched?;
+    let SuiClientCommandResult::TransactionBlock(paid) = paid? else {
+        panic!("PaySui should return a transaction block");
+    };
+    // The recipient only receives a newly created coin.
+    assert_eq!(updates.len(), 1);
+    assert_eq!(updates[0].digest, paid.digest);
+    assert_eq!(updates[0].balance_changes.len(), 1);
+    assert_eq!(
+        updates[0].balance_changes[0].owner,
+        Owner::AddressOwner(recipient)
+    );
+    assert_eq!(updates[0].balance_changes[0].amount, 1000);
+    Ok(())
+}
+
+#[sim_test]
+async fn test_watch_polling() -> Result<(), anyhow::Error> {
+    let mut test_cluster = TestClusterBuilder::new().build().await;
+    // The fullnode of the test cluster does not serve websockets, so the watcher polls.
+    let client = test_cluster.wallet.get_client().await?;
+    check_watch(&mut test_cluster, &client, false).await
+}
+
+#[sim_test]
+async fn test_watch_subscription() -> Result<(), anyhow::Error> {
+    let mut test_cluster = TestClusterBuilder::new().build().await;
+    let config = test_cluster
+        .fullnode_config_builder()
+        .with_jsonrpc_server_type(Some(ServerType::Both))
+        .build(&mut OsRng, test_cluster.swarm.config());
+    let json_rpc_address = config.json_rpc_address;
+    test_cluster.start_fullnode_from_config(config).await;
+    let client = SuiClientBuilder::default()
+        .ws_url(format!("ws://{json_rpc_address}"))
+        .build(format!("http://{json_rpc_address}"))
+        .await?;
+    check_watch(&mut test_cluster, &client, true).await
+}
+
+#[sim_test]
+async fn test_discover_hd_accounts() -> Result<(), anyhow::Error> {
+    let phrase = "result crisp session latin must fruit genuine question prevent start coconut brave speak student dismiss";
+    let mut test_cluster = TestClusterBuilder::new().build().await;
+    let temp_dir = tempfile::tempdir()?;
+    let new_keystore = |name: &str| -> Result<EncryptedFileKeystore, anyhow::Error> {
+        let mut keystore = EncryptedFileKeystore::new(&temp_dir.path().join(name))?;
+        keystore.unlock("passphrase")?;
+        keystore.set_hd_wallet(phrase)?;
+        Ok(keystore)
+    };
+
+    // Only account 2 is in use.
+    let mut keystore = new_keystore("gap3.keystore")?;
+    let used = keystore.hd_account_address(SignatureScheme::ED25519, 2)?;
+    let rgp = test_cluster.get_reference_gas_price().await;
+    let address = test_cluster.get_address_0();
+    let context = test_cluster.wallet_mut();
+    let coin = context
+        .get_one_gas_object_owned_by_address(address)
+        .await?
+        .unwrap()
+        .0;
+    SuiClientCommands::TransferSui {
+        to: KeyIdentity::Address(used),
+        sui_coin_object_id: coin,
+        amount: Some(1000),
+        opts: Opts::for_testing(rgp * TEST_ONLY_GAS_UNIT_FOR_TRANSFER),
+    }
+    .execute(context)
+    .await?;
+
+    // A gap limit of 3 reaches account 2, and derives the unused accounts before it.
+    let client = test_cluster.sui_client();
+    let accounts = discover_hd_accounts(&mut keystore, SignatureScheme::ED25519, 3, client).await?;
+    assert_eq!(accounts.len(), 3);
+    let indices: Vec<_> = keystore.hd_accounts().iter().map(|a| a.index).collect();
+    assert_eq!(indices, vec![0, 1, 2]);
+    assert!(keystore.addresses().contains(&used));
+
+    // A gap limit of 2 stops before account 2, and only derives the first account.
+    let mut keystore = new_keystore("gap2.keystore")?;
+    let accounts = discover_hd_accounts(&mut keystore, SignatureScheme::ED25519, 2, client).await?;
+    assert_eq!(accounts.len(), 1);
+    let indices: Vec<_> = keystore.hd_accounts().iter().map(|a| a.index).collect();
+    assert_eq!(indices, vec![0]);
+
+    let mut keystore = new_keystore("gap0.keystore")?;
+    assert!(
+        discover_hd_accounts(&mut keystore, SignatureScheme::ED25519, 0, client)
+            .await
+            .is_err()
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_external_signer() -> Result<(), anyhow::Error> {
+    let mut test_cluster = TestClusterBuilder::new().build().await;
+    let rgp = test_cluster.get_reference_gas_price().await;
+    let address = test_cluster.get_address_0();
+    let recipient = SuiAddress::random_for_testing_only();
+
+    // A stub signer that signs with the keys of the cluster's keystore file over a Unix socket,
+    // counting the sessions opened with it.
+    let signer_keystore =
+        FileBasedKeystore::new(&test_cluster.swarm.dir().join(SUI_KEYSTORE_FILENAME))?;
+    let mut signer_addresses = signer_keystore.addresses();
+    signer_addresses.sort();
+    let socket_dir = tempfile::tempdir()?;
+    let socket = socket_dir.path().join("signer.sock");
+    let listener = UnixListener::bind(&socket)?;
+    let sessions = Arc::new(AtomicUsize::new(0));
+    let signer_sessions = sessions.clone();
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let Ok(stream) = stream else {
+                break;
+            };
+            signer_sessions.fetch_add(1, Ordering::SeqCst);
+            let Ok(reader) = stream.try_clone() else {
+                continue;
+            };
+            let _ = serve_signer_requests(&signer_keystore, BufReader::new(reader), stream);
+        }
+    });
+
+    let context = test_cluster.wallet_mut();
+    let SuiClientCommandResult::Addresses(addresses) = SuiClientCommands::ExternalSigner {
+        command: None,
+        args: vec![],
+        socket: Some(socket.clone()),
+    }
+    .execute(context)
+    .await?
+    else {
+        panic!("Expected the addresses of the external signer");
+    };
+    assert_eq!(addresses.active_address, address);
+    let mut external_addresses: Vec<_> = addresses.addresses.iter().map(|(_, a)| *a).collect();
+    external_addresses.sort();
+    assert_eq!(external_addresses, signer_addresses);
+
+    // The client config now refers to the signer.
+    let config: SuiClientConfig =
+        PersistedConfig::read(&test_cluster.swarm.dir().join(SUI_CLIENT_CONFIG))?;
+    assert!(matches!(config.keystore, Keystore::External(_)));
+
+    // Transactions are signed through the signer, over the session opened to list its keys.
+    let context = test_cluster.wallet_mut();
+    let coin = context
+        .get_one_gas_object_owned_by_address(address)
+        .await?
+        .unwrap()
+        .0;
+    let SuiClientCommandResult::TransactionBlock(response) = SuiClientCommands::TransferSui {
+        to: KeyIdentity::Address(recipient),
+        sui_coin_object_id: coin,
+        amount: Some(1000),
+        opts: Opts::for_testing(rgp * TEST_ONLY_GAS_UNIT_FOR_TRANSFER),
+    }
+    .execute(context)
+    .await?
+    else {
+        panic!("Expected the executed transaction");
+    };
+    assert_eq!(
+        response.effects.unwrap().status(),
+        &SuiExecutionStatus::Success
+    );
+    assert_eq!(sessions.load(Ordering::SeqCst), 1);
+    Ok(())
+}
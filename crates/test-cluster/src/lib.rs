@@ -938,6 +938,7 @@ impl TestClusterBuilder {
             object_store_config: None,
             perform_index_db_checkpoints_at_epoch_end: None,
             prune_and_compact_before_upload: None,
+            incremental_upload: None,
         };
         self
     }
@@ -949,6 +950,7 @@ impl TestClusterBuilder {
             object_store_config: None,
             perform_index_db_checkpoints_at_epoch_end: None,
             prune_and_compact_before_upload: Some(true),
+            incremental_upload: None,
         };
         self
     }
@@ -7,6 +7,7 @@ use move_core_types::identifier::Identifier;
 use once_cell::sync::Lazy;
 use proptest::collection::vec;
 use proptest::prelude::*;
+use proptest::sample::Index;
 use sui_protocol_config::ProtocolConfig;
 use sui_types::base_types::{ObjectID, ObjectRef, SuiAddress};
 use sui_types::programmable_transaction_builder::ProgrammableTransactionBuilder;
@@ -545,3 +546,77 @@ fn create_unpack_call(
         vec![Argument::Result(prev_cmd_num as u16)],
     );
 }
+
+/// The maximum number of arguments generated for each Move call in a sequence of calls. Functions
+/// taking more parameters cannot be called in generated sequences.
+pub const MAX_MOVE_CALL_ARGS: usize = 16;
+
+/// Stand-in for a call to a Move function in a sequence of transactions, used to randomly generate
+/// the values that pick the function, the sender and the arguments of the actual call once the
+/// functions and objects that are available are known
+#[derive(Debug, Clone)]
+pub struct MoveCallSketch {
+    /// picks the function to call
+    pub function: Index,
+    /// picks the sender of the transaction
+    pub sender: Index,
+    /// one sketch per parameter of the function, in order (extra sketches are ignored)
+    pub args: Vec<ArgumentSketch>,
+    /// milliseconds the clock is advanced by before the call
+    pub advance_clock_ms: u64,
+}
+
+/// Stand-in for an argument of a Move call
+#[derive(Debug, Clone)]
+pub struct ArgumentSketch {
+    /// picks an object among the ones of the parameter's type, for object parameters
+    pub object: Index,
+    /// the value of the argument, for primitive parameters, truncated to the parameter's type
+    pub value: u128,
+}
+
+prop_compose! {
+    pub fn gen_argument_sketch()
+        (object in any::<Index>(), value in u128_with_boundaries_strategy()) -> ArgumentSketch {
+            ArgumentSketch { object, value }
+    }
+}
+
+prop_compose! {
+    pub fn gen_move_call_sketch()
+        (
+            function in any::<Index>(),
+            sender in any::<Index>(),
+            args in vec(gen_argument_sketch(), MAX_MOVE_CALL_ARGS..=MAX_MOVE_CALL_ARGS),
+            advance_clock_ms in advance_clock_strategy(),
+        ) -> MoveCallSketch {
+            MoveCallSketch { function, sender, args, advance_clock_ms }
+    }
+}
+
+/// Generates sequences of at most `max_len` Move calls, which shrink by removing calls and by
+/// simplifying the choices made in each call
+pub fn gen_move_call_sequence(max_len: usize) -> impl Strategy<Value = Vec<MoveCallSketch>> {
+    vec(gen_move_call_sketch(), 1..=max_len.max(1))
+}
+
+/// Favors values at the boundaries of the integer types, where arithmetic errors occur
+pub fn u128_with_boundaries_strategy() -> impl Strategy<Value = u128> {
+    prop_oneof![
+        4 => 0u128..100,
+        4 => any::<u128>(),
+        1 => Just(u8::MAX as u128),
+        1 => Just(u16::MAX as u128),
+        1 => Just(u32::MAX as u128),
+        1 => Just(u64::MAX as u128),
+        1 => Just(u128::MAX),
+    ]
+}
+
+/// Favors calls without advancing the clock, but advances it by up to a day every once in a while
+pub fn advance_clock_strategy() -> impl Strategy<Value = u64> {
+    prop_oneof![
+        5 => Just(0u64),
+        1 => 1u64..=86_400_000,
+    ]
+}
@@ -6,10 +6,13 @@ use anyhow::Result;
 use crossbeam::channel::{bounded, select};
 use lsp_server::{Connection, Message, Notification, Request, Response};
 use lsp_types::{
-    notification::Notification as _, request::Request as _, CompletionOptions, Diagnostic,
-    HoverProviderCapability, InlayHintOptions, InlayHintServerCapabilities, OneOf, SaveOptions,
-    TextDocumentSyncCapability, TextDocumentSyncKind, TextDocumentSyncOptions,
-    TypeDefinitionProviderCapability, WorkDoneProgressOptions,
+    notification::Notification as _, request::Request as _, CallHierarchyServerCapability,
+    CodeActionKind, CodeActionOptions, CodeActionProviderCapability, CompletionOptions, Diagnostic,
+    HoverProviderCapability, InlayHintOptions, InlayHintServerCapabilities, OneOf, RenameOptions,
+    SaveOptions, SemanticTokensFullOptions, SemanticTokensOptions,
+    SemanticTokensServerCapabilities, SignatureHelpOptions, TextDocumentSyncCapability,
+    TextDocumentSyncKind, TextDocumentSyncOptions, TypeDefinitionProviderCapability,
+    WorkDoneProgressOptions,
 };
 use move_compiler::linters::LintLevel;
 use std::{
@@ -19,8 +22,9 @@ use std::{
 };
 
 use crate::{
-    completions::on_completion_request, context::Context, inlay_hints, symbols,
-    vfs::on_text_document_sync_notification,
+    call_hierarchy, code_actions, completions::on_completion_request, context::Context,
+    inlay_hints, rename, semantic_tokens, signature_help, symbols,
+    vfs::on_text_document_sync_notification, workspace_symbols,
 };
 use url::Url;
 use vfs::{impls::memory::MemoryFS, VfsPath};
@@ -108,6 +112,42 @@ pub fn run() {
                 resolve_provider: None,
             },
         ))),
+        // The server can check whether a symbol can be renamed before renaming it.
+        rename_provider: Some(OneOf::Right(RenameOptions {
+            prepare_provider: Some(true),
+            work_done_progress_options: WorkDoneProgressOptions {
+                work_done_progress: None,
+            },
+        })),
+        // The server provides quick fixes for some of the diagnostics it reports.
+        code_action_provider: Some(CodeActionProviderCapability::Options(CodeActionOptions {
+            code_action_kinds: Some(vec![CodeActionKind::QUICKFIX]),
+            work_done_progress_options: WorkDoneProgressOptions {
+                work_done_progress: None,
+            },
+            resolve_provider: None,
+        })),
+        // Signature help pops up when the argument list of a call is opened and is updated
+        // when moving to the next argument.
+        signature_help_provider: Some(SignatureHelpOptions {
+            trigger_characters: Some(vec!["(".to_string(), ",".to_string()]),
+            retrigger_characters: None,
+            work_done_progress_options: WorkDoneProgressOptions {
+                work_done_progress: None,
+            },
+        }),
+        semantic_tokens_provider: Some(SemanticTokensServerCapabilities::SemanticTokensOptions(
+            SemanticTokensOptions {
+                work_done_progress_options: WorkDoneProgressOptions {
+                    work_done_progress: None,
+                },
+                legend: semantic_tokens::semantic_tokens_legend(),
+                range: None,
+                full: Some(SemanticTokensFullOptions::Bool(true)),
+            },
+        )),
+        workspace_symbol_provider: Some(OneOf::Left(true)),
+        call_hierarchy_provider: Some(CallHierarchyServerCapability::Simple(true)),
         ..Default::default()
     })
     .expect("could not serialize server capabilities");
@@ -319,6 +359,33 @@ fn on_request(
         lsp_types::request::InlayHintRequest::METHOD => {
             inlay_hints::on_inlay_hint_request(context, request);
         }
+        lsp_types::request::PrepareRenameRequest::METHOD => {
+            rename::on_prepare_rename_request(context, request);
+        }
+        lsp_types::request::Rename::METHOD => {
+            rename::on_rename_request(context, request);
+        }
+        lsp_types::request::CodeActionRequest::METHOD => {
+            code_actions::on_code_action_request(context, request, &ide_files_root);
+        }
+        lsp_types::request::SignatureHelpRequest::METHOD => {
+            signature_help::on_signature_help_request(context, request);
+        }
+        lsp_types::request::SemanticTokensFullRequest::METHOD => {
+            semantic_tokens::on_semantic_tokens_request(context, request);
+        }
+        lsp_types::request::WorkspaceSymbolRequest::METHOD => {
+            workspace_symbols::on_workspace_symbol_request(context, request);
+        }
+        lsp_types::request::CallHierarchyPrepare::METHOD => {
+            call_hierarchy::on_prepare_call_hierarchy_request(context, request);
+        }
+        lsp_types::request::CallHierarchyIncomingCalls::METHOD => {
+            call_hierarchy::on_incoming_calls_request(context, request);
+        }
+        lsp_types::request::CallHierarchyOutgoingCalls::METHOD => {
+            call_hierarchy::on_outgoing_calls_request(context, request);
+        }
         lsp_types::request::Shutdown::METHOD => {
             eprintln!("Shutdown request received");
             let response =
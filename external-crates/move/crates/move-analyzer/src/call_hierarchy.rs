@@ -0,0 +1,232 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Call hierarchy for Move functions, computed from the call information collected during
+//! symbolication. Both regular and method-style calls are included, as well as macro calls
+//! (which are represented in the hierarchy even though they are expanded at compile time).
+
+use crate::{
+    context::Context,
+    symbols::{DefInfo, ModuleDefs, SymbolicatorRunner, Symbols},
+};
+use lsp_server::Request;
+use lsp_types::{
+    CallHierarchyIncomingCall, CallHierarchyIncomingCallsParams, CallHierarchyItem,
+    CallHierarchyOutgoingCall, CallHierarchyOutgoingCallsParams, CallHierarchyPrepareParams,
+    Position, Range, SymbolKind, Url,
+};
+use move_ir_types::location::Loc;
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+/// Handles prepare call hierarchy request of the language server
+pub fn on_prepare_call_hierarchy_request(context: &Context, request: &Request) {
+    let parameters = serde_json::from_value::<CallHierarchyPrepareParams>(request.params.clone())
+        .expect("could not deserialize prepare call hierarchy request");
+
+    let fpath = parameters
+        .text_document_position_params
+        .text_document
+        .uri
+        .to_file_path()
+        .unwrap();
+    let position = parameters.text_document_position_params.position;
+
+    let symbols_map = &context.symbols.lock().unwrap();
+    let items = SymbolicatorRunner::root_dir(&fpath).and_then(|pkg_path| {
+        let symbols = symbols_map.get(&pkg_path)?;
+        let mut item = prepare_call_hierarchy(symbols, &fpath, position)?;
+        // remember which package's symbols the item was computed from as the item itself
+        // may be located in a dependency
+        item.data = Some(serde_json::Value::String(
+            pkg_path.to_string_lossy().to_string(),
+        ));
+        Some(vec![item])
+    });
+    send_response(context, request, items);
+}
+
+/// Handles incoming calls request of the language server
+pub fn on_incoming_calls_request(context: &Context, request: &Request) {
+    let parameters =
+        serde_json::from_value::<CallHierarchyIncomingCallsParams>(request.params.clone())
+            .expect("could not deserialize incoming calls request");
+
+    let symbols_map = &context.symbols.lock().unwrap();
+    let calls = item_pkg_path(&parameters.item)
+        .and_then(|pkg_path| symbols_map.get(&pkg_path))
+        .map(|symbols| incoming_calls(symbols, &parameters.item));
+    send_response(context, request, calls);
+}
+
+/// Handles outgoing calls request of the language server
+pub fn on_outgoing_calls_request(context: &Context, request: &Request) {
+    let parameters =
+        serde_json::from_value::<CallHierarchyOutgoingCallsParams>(request.params.clone())
+            .expect("could not deserialize outgoing calls request");
+
+    let symbols_map = &context.symbols.lock().unwrap();
+    let calls = item_pkg_path(&parameters.item)
+        .and_then(|pkg_path| symbols_map.get(&pkg_path))
+        .map(|symbols| outgoing_calls(symbols, &parameters.item));
+    send_response(context, request, calls);
+}
+
+fn send_response<T: serde::Serialize>(context: &Context, request: &Request, result: T) {
+    let response = lsp_server::Response::new_ok(request.id.clone(), result);
+    if let Err(err) = context
+        .connection
+        .sender
+        .send(lsp_server::Message::Response(response))
+    {
+        eprintln!("could not send call hierarchy response: {:?}", err);
+    }
+}
+
+fn item_pkg_path(item: &CallHierarchyItem) -> Option<PathBuf> {
+    match &item.data {
+        Some(serde_json::Value::String(pkg_path)) => Some(PathBuf::from(pkg_path)),
+        _ => SymbolicatorRunner::root_dir(&item.uri.to_file_path().ok()?),
+    }
+}
+
+/// Returns the call hierarchy item for a function used or defined at a given position.
+pub fn prepare_call_hierarchy(
+    symbols: &Symbols,
+    fpath: &Path,
+    position: Position,
+) -> Option<CallHierarchyItem> {
+    let use_def = symbols
+        .line_uses(fpath, position.line)
+        .into_iter()
+        .find(|u| position.character >= u.col_start() && position.character <= u.col_end())?;
+    call_hierarchy_item(symbols, &use_def.def_loc())
+}
+
+/// Returns functions calling the function represented by the item, along with the locations
+/// of the calls.
+pub fn incoming_calls(
+    symbols: &Symbols,
+    item: &CallHierarchyItem,
+) -> Vec<CallHierarchyIncomingCall> {
+    let Some(def_loc) = item_def_loc(symbols, item) else {
+        return vec![];
+    };
+    let mut callers: BTreeMap<Loc, Vec<Range>> = BTreeMap::new();
+    for mod_defs in symbols.file_mods.values().flatten() {
+        for (call_loc, call_info) in &mod_defs.call_infos {
+            if call_info.def_loc != Some(def_loc) {
+                continue;
+            }
+            let (Some(caller_loc), Some(range)) = (
+                enclosing_function(mod_defs, call_loc),
+                symbols.files.lsp_range_opt(call_loc),
+            ) else {
+                continue;
+            };
+            callers.entry(caller_loc).or_default().push(range);
+        }
+    }
+    callers
+        .into_iter()
+        .filter_map(|(caller_loc, from_ranges)| {
+            Some(CallHierarchyIncomingCall {
+                from: call_hierarchy_item(symbols, &caller_loc)?,
+                from_ranges,
+            })
+        })
+        .collect()
+}
+
+/// Returns functions called by the function represented by the item, along with the locations
+/// of the calls.
+pub fn outgoing_calls(
+    symbols: &Symbols,
+    item: &CallHierarchyItem,
+) -> Vec<CallHierarchyOutgoingCall> {
+    let Some(def_loc) = item_def_loc(symbols, item) else {
+        return vec![];
+    };
+    let fpath = symbols.files.file_path(&def_loc.file_hash());
+    let Some(mod_defs) = symbols.file_mods.get(fpath).and_then(|mods| {
+        mods.iter().find(|mod_defs| {
+            mod_defs
+                .functions
+                .values()
+                .any(|fdef| fdef.name_loc == def_loc)
+        })
+    }) else {
+        return vec![];
+    };
+    let mut callees: BTreeMap<Loc, Vec<Range>> = BTreeMap::new();
+    for (call_loc, call_info) in &mod_defs.call_infos {
+        let Some(callee_loc) = call_info.def_loc else {
+            continue;
+        };
+        if enclosing_function(mod_defs, call_loc) != Some(def_loc) {
+            continue;
+        }
+        if let Some(range) = symbols.files.lsp_range_opt(call_loc) {
+            callees.entry(callee_loc).or_default().push(range);
+        }
+    }
+    callees
+        .into_iter()
+        .filter_map(|(callee_loc, from_ranges)| {
+            Some(CallHierarchyOutgoingCall {
+                to: call_hierarchy_item(symbols, &callee_loc)?,
+                from_ranges,
+            })
+        })
+        .collect()
+}
+
+fn call_hierarchy_item(symbols: &Symbols, def_loc: &Loc) -> Option<CallHierarchyItem> {
+    let DefInfo::Function(mod_ident, _, _, name, ..) = symbols.def_info(def_loc)? else {
+        return None;
+    };
+    let range = symbols.files.lsp_range_opt(def_loc)?;
+    let path = symbols.files.file_path(&def_loc.file_hash());
+    Some(CallHierarchyItem {
+        name: name.to_string(),
+        kind: SymbolKind::FUNCTION,
+        tags: None,
+        detail: Some(mod_ident.to_string()),
+        uri: Url::from_file_path(path).ok()?,
+        range,
+        selection_range: range,
+        data: None,
+    })
+}
+
+/// Finds location of the name of the function represented by the item.
+fn item_def_loc(symbols: &Symbols, item: &CallHierarchyItem) -> Option<Loc> {
+    let fpath = item.uri.to_file_path().ok()?;
+    symbols
+        .file_mods
+        .get(&fpath)?
+        .iter()
+        .flat_map(|mod_defs| mod_defs.functions.values())
+        .map(|fdef| fdef.name_loc)
+        .find(|name_loc| {
+            symbols
+                .files
+                .lsp_range_opt(name_loc)
+                .is_some_and(|range| range.start == item.selection_range.start)
+        })
+}
+
+/// Returns location of the name of the function containing a call, that is the last function
+/// in the module starting before the call.
+fn enclosing_function(mod_defs: &ModuleDefs, call_loc: &Loc) -> Option<Loc> {
+    mod_defs
+        .functions
+        .values()
+        .map(|fdef| fdef.name_loc)
+        .filter(|name_loc| {
+            name_loc.file_hash() == call_loc.file_hash() && name_loc.start() <= call_loc.start()
+        })
+        .max_by_key(|name_loc| name_loc.start())
+}
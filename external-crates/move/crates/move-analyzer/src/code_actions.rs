@@ -0,0 +1,421 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Quick fixes for compiler diagnostics. Diagnostics are recognized by the compiler code attached
+//! to them (see `diagnostics::lsp_diagnostics`), and the fixes are computed from the text of the
+//! file being edited, using symbols (if available) to find definitions of unbound names.
+
+use crate::{
+    context::Context,
+    symbols::{DefInfo, SymbolicatorRunner, Symbols},
+};
+use lsp_server::Request;
+use lsp_types::{
+    CodeAction, CodeActionKind, CodeActionOrCommand, CodeActionParams, Diagnostic, NumberOrString,
+    Position, Range, TextEdit, Url, WorkspaceEdit,
+};
+use move_compiler::{
+    diagnostics::codes::{Category, Declarations, NameResolution, UnusedItem},
+    expansion::ast::{Address, ModuleIdent_, Visibility},
+    linters::{LinterDiagnosticCategory, StyleCodes, LINT_WARNING_PREFIX},
+    shared::Identifier,
+};
+use move_symbol_pool::Symbol;
+use std::{collections::BTreeSet, io::Read, path::Path};
+use vfs::VfsPath;
+
+/// Handles code action request of the language server
+pub fn on_code_action_request(context: &Context, request: &Request, ide_files_root: &VfsPath) {
+    let parameters = serde_json::from_value::<CodeActionParams>(request.params.clone())
+        .expect("could not deserialize code action request");
+
+    let fpath = parameters.text_document.uri.to_file_path().unwrap();
+    // prefer the content of the file open in the editor over the content on disk
+    let mut content = String::new();
+    let in_vfs = ide_files_root
+        .join(fpath.to_string_lossy())
+        .and_then(|p| p.open_file())
+        .map(|mut f| f.read_to_string(&mut content).is_ok())
+        .unwrap_or_default();
+    if !in_vfs {
+        content = std::fs::read_to_string(&fpath).unwrap_or_default();
+    }
+
+    let symbols_map = &context.symbols.lock().unwrap();
+    let symbols =
+        SymbolicatorRunner::root_dir(&fpath).and_then(|pkg_path| symbols_map.get(&pkg_path));
+    let actions = code_actions(symbols, &fpath, &content, &parameters.context.diagnostics)
+        .into_iter()
+        .map(CodeActionOrCommand::CodeAction)
+        .collect::<Vec<_>>();
+
+    let response = lsp_server::Response::new_ok(request.id.clone(), actions);
+    if let Err(err) = context
+        .connection
+        .sender
+        .send(lsp_server::Message::Response(response))
+    {
+        eprintln!("could not send code action response: {:?}", err);
+    }
+}
+
+/// Computes quick fixes for the given diagnostics reported for a file with a given content.
+pub fn code_actions(
+    symbols: Option<&Symbols>,
+    fpath: &Path,
+    content: &str,
+    diagnostics: &[Diagnostic],
+) -> Vec<CodeAction> {
+    let Ok(uri) = Url::from_file_path(fpath) else {
+        return vec![];
+    };
+    let mut actions = vec![];
+    for diag in diagnostics {
+        let Some(NumberOrString::String(code)) = &diag.code else {
+            continue;
+        };
+        let Some(start) = offset(content, diag.range.start) else {
+            continue;
+        };
+        let code = code.as_str();
+        let mut quick_fix = |title: String, edit: TextEdit| {
+            actions.push(CodeAction {
+                title,
+                kind: Some(CodeActionKind::QUICKFIX),
+                diagnostics: Some(vec![diag.clone()]),
+                edit: Some(WorkspaceEdit::new(
+                    [(uri.clone(), vec![edit])].into_iter().collect(),
+                )),
+                ..Default::default()
+            });
+        };
+
+        let unused_alias = compiler_code('W', Category::UnusedItem, UnusedItem::Alias as u8);
+        let duplicate_alias = compiler_code(
+            'W',
+            Category::Declarations,
+            Declarations::DuplicateAlias as u8,
+        );
+        if code == unused_alias || code == duplicate_alias {
+            if let Some(edit) = remove_alias_edit(content, start) {
+                let title = if code == unused_alias {
+                    "Remove unused alias"
+                } else {
+                    "Remove unnecessary alias"
+                };
+                quick_fix(title.to_string(), edit);
+            }
+        } else if let Some(unbound) = Unbound::from_code(code, content, start) {
+            let Some(symbols) = symbols else {
+                continue;
+            };
+            let name = ident_at(content, start);
+            for path in use_candidates(symbols, unbound, name) {
+                if let Some(edit) = add_use_edit(content, diag.range.start.line, &path) {
+                    quick_fix(format!("Add 'use {path}'"), edit);
+                }
+            }
+        } else if code == lint_code(StyleCodes::WhileTrueToLoop) {
+            if let Some(edit) = while_true_edit(content, start) {
+                quick_fix("Replace 'while (true)' with 'loop'".to_string(), edit);
+            }
+        } else if code == lint_code(StyleCodes::UnneededReturn) {
+            let rest = &content[start..];
+            if let Some(expr) = rest.strip_prefix("return") {
+                let len = rest.len() - expr.trim_start().len();
+                quick_fix(
+                    "Remove unnecessary 'return'".to_string(),
+                    text_edit(content, start, start + len, ""),
+                );
+            }
+        } else if code == lint_code(StyleCodes::UnnecessaryUnit) {
+            let rest = &content[start..];
+            // only units in a sequence can be removed, other cases need restructuring
+            if let Some(after_unit) = rest.strip_prefix("()") {
+                if let Some(after_semi) = after_unit.trim_start().strip_prefix(';') {
+                    let len = rest.len() - after_semi.len();
+                    quick_fix(
+                        "Remove unnecessary unit".to_string(),
+                        remove_edit(content, start, start + len),
+                    );
+                }
+            }
+        }
+    }
+    actions
+}
+
+/// Code of a diagnostic reported by the compiler.
+fn compiler_code(severity: char, category: Category, code: u8) -> String {
+    format!("{severity}{:02}{code:03}", category as u8)
+}
+
+/// Code of a diagnostic reported by a style linter.
+fn lint_code(code: StyleCodes) -> String {
+    format!(
+        "{LINT_WARNING_PREFIX}W{:02}{:03}",
+        LinterDiagnosticCategory::Style as u8,
+        code as u8
+    )
+}
+
+/// Kind of a name that could not be resolved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Unbound {
+    Module,
+    Type,
+    Function,
+}
+
+impl Unbound {
+    fn from_code(code: &str, content: &str, start: usize) -> Option<Self> {
+        let resolution_code =
+            |c: NameResolution| compiler_code('E', Category::NameResolution, c as u8);
+        if code == resolution_code(NameResolution::UnboundModule) {
+            Some(Unbound::Module)
+        } else if code == resolution_code(NameResolution::UnboundType) {
+            Some(Unbound::Type)
+        } else if code == resolution_code(NameResolution::UnboundUnscopedName) {
+            Some(Unbound::Function)
+        } else if code == resolution_code(NameResolution::NamePositionMismatch) {
+            // an unresolved leading name in an access chain (e.g., `m` in `m::f`) is a module
+            let name = ident_at(content, start);
+            content[start + name.len()..]
+                .starts_with("::")
+                .then_some(Unbound::Module)
+        } else {
+            None
+        }
+    }
+}
+
+/// Paths that could be imported to bind `name`.
+fn use_candidates(symbols: &Symbols, unbound: Unbound, name: &str) -> BTreeSet<String> {
+    let name = Symbol::from(name);
+    let mut candidates = BTreeSet::new();
+    if name.is_empty() {
+        return candidates;
+    }
+    for mod_defs in symbols.file_mods.values().flatten() {
+        let mod_path = module_path(&mod_defs.ident);
+        let found = match unbound {
+            Unbound::Module => {
+                if mod_defs.ident.module.value() == name {
+                    candidates.insert(mod_path);
+                }
+                continue;
+            }
+            Unbound::Type => {
+                mod_defs.structs.contains_key(&name) || mod_defs.enums.contains_key(&name)
+            }
+            Unbound::Function => mod_defs.functions.get(&name).is_some_and(|def| {
+                matches!(
+                    symbols.def_info(&def.name_loc),
+                    Some(DefInfo::Function(_, vis, ..)) if *vis != Visibility::Internal
+                )
+            }),
+        };
+        if found {
+            candidates.insert(format!("{mod_path}::{name}"));
+        }
+    }
+    candidates
+}
+
+fn module_path(ident: &ModuleIdent_) -> String {
+    let address = match &ident.address {
+        Address::Numerical {
+            name: Some(name), ..
+        }
+        | Address::NamedUnassigned(name) => name.to_string(),
+        Address::Numerical {
+            name: None, value, ..
+        } => value.to_string(),
+    };
+    format!("{address}::{}", ident.module)
+}
+
+/// Inserts `use <path>;` after the last `use` declaration of the module containing a given line
+/// (or at the beginning of the module if it has no `use` declarations).
+fn add_use_edit(content: &str, line: u32, path: &str) -> Option<TextEdit> {
+    let lines = content.lines().take(line as usize).collect::<Vec<_>>();
+    let header = lines
+        .iter()
+        .rposition(|l| l.trim_start().starts_with("module "))?;
+    let indent_of = |l: &str| l[..l.len() - l.trim_start().len()].to_string();
+    let (insert_line, indent) = match lines[header + 1..]
+        .iter()
+        .rposition(|l| l.trim_start().starts_with("use "))
+    {
+        Some(idx) => {
+            let use_line = header + 1 + idx;
+            // the declaration may span multiple lines
+            let end = (use_line..lines.len()).find(|i| lines[*i].contains(';'))?;
+            (end + 1, indent_of(lines[use_line]))
+        }
+        None => {
+            let header_line = lines[header];
+            let indent = if header_line.trim_end().ends_with(';') {
+                indent_of(header_line)
+            } else {
+                format!("{}    ", indent_of(header_line))
+            };
+            (header + 1, indent)
+        }
+    };
+    let position = Position::new(insert_line as u32, 0);
+    Some(TextEdit::new(
+        Range::new(position, position),
+        format!("{indent}use {path};\n"),
+    ))
+}
+
+/// Removes the alias starting at a given offset from its `use` declaration, or the whole
+/// declaration if the alias is the only one introduced by it.
+fn remove_alias_edit(content: &str, alias_start: usize) -> Option<TextEdit> {
+    let use_start = content[..alias_start]
+        .rmatch_indices("use")
+        .map(|(i, _)| i)
+        .find(|i| {
+            !content[..*i].ends_with(is_ident_char)
+                && content[*i + 3..].starts_with(char::is_whitespace)
+        })?;
+    let use_end = alias_start + content[alias_start..].find(';')? + 1;
+
+    // find the item containing the alias within (possibly nested) braces, moving to the
+    // enclosing item for as long as the item is the only one within its braces
+    let mut at = alias_start;
+    loop {
+        let mut depth = 0;
+        let prev = content[use_start..at].char_indices().rev().find(|(_, c)| {
+            match c {
+                '}' => depth += 1,
+                '{' if depth > 0 => depth -= 1,
+                '{' | ',' => return true,
+                _ => (),
+            }
+            false
+        });
+        let Some((prev_idx, prev_char)) = prev.map(|(i, c)| (use_start + i, c)) else {
+            break;
+        };
+        let mut depth = 0;
+        let (next_idx, next_char) = content[at..use_end]
+            .char_indices()
+            .find(|(_, c)| {
+                match c {
+                    '{' => depth += 1,
+                    '}' if depth > 0 => depth -= 1,
+                    '}' | ',' => return true,
+                    _ => (),
+                }
+                false
+            })
+            .map(|(i, c)| (at + i, c))?;
+        match (prev_char, next_char) {
+            ('{', '}') => {
+                at = prev_idx;
+                continue;
+            }
+            (',', _) => {
+                let item_end = content[..next_idx].trim_end().len();
+                return Some(remove_edit(content, prev_idx, item_end));
+            }
+            _ => {
+                let after = &content[next_idx + 1..];
+                let len = after.len() - after.trim_start().len();
+                return Some(remove_edit(content, prev_idx + 1, next_idx + 1 + len));
+            }
+        }
+    }
+
+    // remove the whole declaration
+    let mut start = use_start;
+    if let Some(vis) = content[..start].trim_end().strip_suffix("public") {
+        start = vis.len();
+    }
+    Some(remove_edit(content, start, use_end))
+}
+
+/// Replaces `while (true)` starting at a given offset with `loop`.
+fn while_true_edit(content: &str, start: usize) -> Option<TextEdit> {
+    let cond = content[start..].strip_prefix("while")?.trim_start();
+    let cond_start = content.len() - cond.len();
+    if !cond.starts_with('(') {
+        return None;
+    }
+    let mut depth = 0;
+    let cond_end = cond.char_indices().find_map(|(i, c)| {
+        match c {
+            '(' => depth += 1,
+            ')' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(cond_start + i + 1);
+                }
+            }
+            _ => (),
+        }
+        None
+    })?;
+    Some(text_edit(content, start, cond_end, "loop"))
+}
+
+/// Removes the text between two offsets, along with the whole line if nothing else remains on it.
+fn remove_edit(content: &str, start: usize, end: usize) -> TextEdit {
+    let line_start = content[..start].rfind('\n').map_or(0, |i| i + 1);
+    let line_end = content[end..]
+        .find('\n')
+        .map_or(content.len(), |i| end + i + 1);
+    if content[line_start..start].trim().is_empty() && content[end..line_end].trim().is_empty() {
+        text_edit(content, line_start, line_end, "")
+    } else {
+        text_edit(content, start, end, "")
+    }
+}
+
+fn text_edit(content: &str, start: usize, end: usize, new_text: &str) -> TextEdit {
+    TextEdit::new(
+        Range::new(position(content, start), position(content, end)),
+        new_text.to_string(),
+    )
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+/// Identifier starting at a given offset.
+fn ident_at(content: &str, start: usize) -> &str {
+    let rest = &content[start..];
+    let len = rest.find(|c| !is_ident_char(c)).unwrap_or(rest.len());
+    &rest[..len]
+}
+
+/// Converts a position (with the character counted in chars) into a byte offset.
+fn offset(content: &str, position: Position) -> Option<usize> {
+    let mut line_start = 0;
+    for (idx, line) in content.split_inclusive('\n').enumerate() {
+        if idx == position.line as usize {
+            let line = line.trim_end_matches(['\n', '\r']);
+            let column = line
+                .char_indices()
+                .map(|(i, _)| i)
+                .chain(std::iter::once(line.len()))
+                .nth(position.character as usize)?;
+            return Some(line_start + column);
+        }
+        line_start += line.len();
+    }
+    None
+}
+
+/// Converts a byte offset into a position (with the character counted in chars).
+fn position(content: &str, offset: usize) -> Position {
+    let before = &content[..offset];
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    Position::new(
+        before.matches('\n').count() as u32,
+        before[line_start..].chars().count() as u32,
+    )
+}
@@ -3,29 +3,30 @@
 
 use crate::utils::{loc_end_to_lsp_position_opt, loc_start_to_lsp_position_opt};
 use codespan_reporting::diagnostic::Severity;
-use lsp_types::{Diagnostic, DiagnosticRelatedInformation, DiagnosticSeverity, Location, Range};
+use lsp_types::{
+    Diagnostic, DiagnosticRelatedInformation, DiagnosticSeverity, Location, NumberOrString, Range,
+};
 use move_command_line_common::files::FileHash;
-use move_compiler::shared::files::MappedFiles;
-use move_ir_types::location::Loc;
+use move_compiler::{diagnostics::Diagnostics, shared::files::MappedFiles};
 use std::{collections::BTreeMap, path::PathBuf};
 use url::Url;
 
-/// Converts diagnostics from the codespan format to the format understood by the language server.
+/// Converts compiler diagnostics to the format understood by the language server. Each diagnostic
+/// carries its compiler code (e.g. `W09001`) so that code actions can recognize it.
 pub fn lsp_diagnostics(
-    diagnostics: &Vec<(
-        codespan_reporting::diagnostic::Severity,
-        &'static str,
-        (Loc, String),
-        Vec<(Loc, String)>,
-        Vec<String>,
-    )>,
+    diagnostics: Diagnostics,
     files: &MappedFiles,
 ) -> BTreeMap<PathBuf, Vec<Diagnostic>> {
+    let codes = diagnostics
+        .clone()
+        .into_vec()
+        .into_iter()
+        .map(|diag| diag.info().clone().render().0);
     let mut lsp_diagnostics = BTreeMap::new();
-    for (s, _, (loc, msg), labels, notes) in diagnostics {
+    for (code, (s, _, (loc, msg), labels, notes)) in codes.zip(diagnostics.into_codespan_format()) {
         let fpath = files.file_path(&loc.file_hash());
-        if let Some(start) = loc_start_to_lsp_position_opt(files, loc) {
-            if let Some(end) = loc_end_to_lsp_position_opt(files, loc) {
+        if let Some(start) = loc_start_to_lsp_position_opt(files, &loc) {
+            if let Some(end) = loc_end_to_lsp_position_opt(files, &loc) {
                 let range = Range::new(start, end);
                 let related_info_opt = if labels.is_empty() && notes.is_empty() {
                     None
@@ -64,8 +65,8 @@ pub fn lsp_diagnostics(
                     .or_insert_with(Vec::new)
                     .push(Diagnostic::new(
                         range,
-                        Some(severity(*s)),
-                        None,
+                        Some(severity(s)),
+                        Some(NumberOrString::String(code)),
                         None,
                         msg.to_string(),
                         related_info_opt,
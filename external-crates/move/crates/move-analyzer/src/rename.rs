@@ -0,0 +1,404 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Renaming of module members (functions, structs, enums, variants and constants), fields and
+//! locals. A rename is only performed if the renamed definition belongs to the package being
+//! edited and if the new name is a valid identifier that does not clash with an existing
+//! definition it could be confused with.
+
+use crate::{
+    context::Context,
+    symbols::{DefInfo, MemberDefInfo, SymbolicatorRunner, Symbols, UseDef, UseLoc},
+};
+use lsp_server::{ErrorCode, Request, RequestId};
+use lsp_types::{
+    Position, PrepareRenameResponse, Range, RenameParams, TextDocumentPositionParams, TextEdit,
+    Url, WorkspaceEdit,
+};
+use move_compiler::{
+    expansion::{ast::ModuleIdent_, name_validation::is_valid_datatype_or_constant_name},
+    parser::{
+        ast::Var,
+        keywords::{BUILTINS, KEYWORDS, PRIMITIVE_TYPES},
+    },
+};
+use move_ir_types::location::Loc;
+use move_symbol_pool::Symbol;
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    path::Path,
+};
+
+/// Names that are not keywords but still cannot be used as identifiers.
+const RESERVED_NAMES: &[&str] = &["enum", "match", "mut", "Self"];
+
+/// Kind of a definition that can be renamed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RenameKind {
+    Function,
+    Datatype,
+    Variant,
+    Field,
+    Const,
+    Local,
+}
+
+/// Definition being renamed.
+struct RenameTarget {
+    def_loc: Loc,
+    kind: RenameKind,
+    /// Module defining the member (absent for locals)
+    mod_ident: Option<ModuleIdent_>,
+    name: Symbol,
+}
+
+/// Handles prepare-rename request of the language server
+pub fn on_prepare_rename_request(context: &Context, request: &Request) {
+    let parameters = serde_json::from_value::<TextDocumentPositionParams>(request.params.clone())
+        .expect("could not deserialize prepare rename request");
+    let fpath = parameters.text_document.uri.to_file_path().unwrap();
+
+    let symbols_map = &context.symbols.lock().unwrap();
+    let result = SymbolicatorRunner::root_dir(&fpath)
+        .and_then(|pkg_path| symbols_map.get(&pkg_path))
+        .ok_or_else(|| "No symbols available for this file".to_string())
+        .and_then(|symbols| prepare_rename(symbols, &fpath, parameters.position));
+    send_response(context, request.id.clone(), result);
+}
+
+/// Handles rename request of the language server
+pub fn on_rename_request(context: &Context, request: &Request) {
+    let parameters = serde_json::from_value::<RenameParams>(request.params.clone())
+        .expect("could not deserialize rename request");
+    let fpath = parameters
+        .text_document_position
+        .text_document
+        .uri
+        .to_file_path()
+        .unwrap();
+
+    let symbols_map = &context.symbols.lock().unwrap();
+    let result = SymbolicatorRunner::root_dir(&fpath)
+        .and_then(|pkg_path| symbols_map.get(&pkg_path))
+        .ok_or_else(|| "No symbols available for this file".to_string())
+        .and_then(|symbols| {
+            rename_edits(
+                symbols,
+                &fpath,
+                parameters.text_document_position.position,
+                &parameters.new_name,
+            )
+        });
+    send_response(context, request.id.clone(), result);
+}
+
+fn send_response<T: serde::Serialize>(context: &Context, id: RequestId, result: Result<T, String>) {
+    let response = match result {
+        Ok(value) => lsp_server::Response::new_ok(id, value),
+        Err(msg) => lsp_server::Response::new_err(id, ErrorCode::RequestFailed as i32, msg),
+    };
+    if let Err(err) = context
+        .connection
+        .sender
+        .send(lsp_server::Message::Response(response))
+    {
+        eprintln!("could not send rename response: {:?}", err);
+    }
+}
+
+/// Checks that the identifier at a given position can be renamed and returns its range.
+pub fn prepare_rename(
+    symbols: &Symbols,
+    use_fpath: &Path,
+    position: Position,
+) -> Result<PrepareRenameResponse, String> {
+    let use_def = use_at_position(symbols, use_fpath, position)?;
+    let target = rename_target(symbols, use_fpath, &use_def)?;
+    Ok(PrepareRenameResponse::RangeWithPlaceholder {
+        range: Range::new(
+            Position::new(position.line, use_def.col_start()),
+            Position::new(position.line, use_def.col_end()),
+        ),
+        placeholder: target.name.to_string(),
+    })
+}
+
+/// Computes edits renaming the definition of the identifier at a given position, along with all
+/// its uses, to `new_name`.
+pub fn rename_edits(
+    symbols: &Symbols,
+    use_fpath: &Path,
+    position: Position,
+    new_name: &str,
+) -> Result<WorkspaceEdit, String> {
+    let use_def = use_at_position(symbols, use_fpath, position)?;
+    let target = rename_target(symbols, use_fpath, &use_def)?;
+    if new_name == target.name.as_str() {
+        return Ok(WorkspaceEdit::default());
+    }
+    check_new_name(&target, new_name)?;
+    check_conflicts(symbols, &target, new_name)?;
+
+    let Some(refs) = symbols.references.get(&target.def_loc) else {
+        return Err(format!("No uses of '{}' found", target.name));
+    };
+    // uses of the renamed definition that are also uses of another definition are punned
+    // fields (e.g., `S { f }` in a pack or unpack) and need to be expanded
+    let punned = punned_uses(symbols, &target, refs);
+
+    let mut changes: HashMap<Url, Vec<TextEdit>> = HashMap::new();
+    for use_loc in refs {
+        let Some(use_text) = use_loc_text(symbols, use_loc) else {
+            continue;
+        };
+        // uses through an alias (e.g., `use a::m::foo as bar`) keep the alias
+        if use_text != target.name.as_str() {
+            continue;
+        }
+        let new_text = if punned.contains(use_loc) {
+            if target.kind == RenameKind::Field {
+                format!("{new_name}: {}", target.name)
+            } else {
+                format!("{}: {new_name}", target.name)
+            }
+        } else {
+            new_name.to_string()
+        };
+        let path = symbols.files.file_path(&use_loc.fhash());
+        changes
+            .entry(Url::from_file_path(path).unwrap())
+            .or_default()
+            .push(TextEdit::new(
+                Range::new(
+                    use_loc.start(),
+                    Position::new(use_loc.start().line, use_loc.col_end()),
+                ),
+                new_text,
+            ));
+    }
+    for edits in changes.values_mut() {
+        edits.sort_by_key(|e| (e.range.start.line, e.range.start.character));
+    }
+    Ok(WorkspaceEdit::new(changes))
+}
+
+fn use_at_position(
+    symbols: &Symbols,
+    use_fpath: &Path,
+    position: Position,
+) -> Result<UseDef, String> {
+    symbols
+        .line_uses(use_fpath, position.line)
+        .into_iter()
+        .find(|u| position.character >= u.col_start() && position.character <= u.col_end())
+        .ok_or_else(|| "No symbol to rename at this position".to_string())
+}
+
+fn rename_target(
+    symbols: &Symbols,
+    use_fpath: &Path,
+    use_def: &UseDef,
+) -> Result<RenameTarget, String> {
+    let def_loc = use_def.def_loc();
+    let Some(def_info) = symbols.def_info(&def_loc) else {
+        return Err("No definition found for this symbol".to_string());
+    };
+    let (kind, mod_ident, name) = match def_info {
+        DefInfo::Function(mod_ident, _, _, name, ..) => {
+            (RenameKind::Function, Some(*mod_ident), *name)
+        }
+        DefInfo::Struct(mod_ident, name, ..) | DefInfo::Enum(mod_ident, name, ..) => {
+            (RenameKind::Datatype, Some(*mod_ident), *name)
+        }
+        DefInfo::Variant(mod_ident, _, name, ..) => (RenameKind::Variant, Some(*mod_ident), *name),
+        DefInfo::Field(mod_ident, _, name, ..) => (RenameKind::Field, Some(*mod_ident), *name),
+        DefInfo::Const(mod_ident, name, ..) => (RenameKind::Const, Some(*mod_ident), *name),
+        DefInfo::Local(name, ..) => (RenameKind::Local, None, *name),
+        DefInfo::Module(..) => return Err("Renaming modules is not supported".to_string()),
+        DefInfo::Type(_) => return Err("Built-in types cannot be renamed".to_string()),
+    };
+    if kind == RenameKind::Field && name.parse::<u64>().is_ok() {
+        return Err("Positional fields cannot be renamed".to_string());
+    }
+    let def_path = symbols.files.file_path(&def_loc.file_hash());
+    if SymbolicatorRunner::root_dir(def_path) != SymbolicatorRunner::root_dir(use_fpath) {
+        return Err(format!(
+            "'{name}' is defined outside of this package and cannot be renamed"
+        ));
+    }
+    Ok(RenameTarget {
+        def_loc,
+        kind,
+        mod_ident,
+        name,
+    })
+}
+
+/// Checks that the new name is a valid identifier for the kind of definition being renamed.
+fn check_new_name(target: &RenameTarget, new_name: &str) -> Result<(), String> {
+    let ident = new_name.strip_prefix('$').unwrap_or(new_name);
+    if ident.is_empty()
+        || ident.starts_with(|c: char| c.is_ascii_digit())
+        || !ident.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+    {
+        return Err(format!("'{new_name}' is not a valid identifier"));
+    }
+    if KEYWORDS
+        .iter()
+        .chain(PRIMITIVE_TYPES)
+        .chain(RESERVED_NAMES)
+        .any(|k| *k == new_name)
+    {
+        return Err(format!("'{new_name}' is a reserved name"));
+    }
+
+    let is_syntax_ident = Var::is_syntax_identifier_name(target.name);
+    if is_syntax_ident != Var::is_syntax_identifier_name(Symbol::from(new_name)) {
+        return Err(if is_syntax_ident {
+            format!("Macro parameter names must start with '$' but '{new_name}' does not")
+        } else {
+            "Only macro parameter names can start with '$'".to_string()
+        });
+    }
+    match target.kind {
+        RenameKind::Datatype | RenameKind::Variant | RenameKind::Const => {
+            if !is_valid_datatype_or_constant_name(new_name) {
+                return Err(format!("'{new_name}' must start with 'A'..'Z'"));
+            }
+        }
+        RenameKind::Function => {
+            if new_name.starts_with('_') {
+                return Err(format!("Function name '{new_name}' cannot start with '_'"));
+            }
+            if BUILTINS.contains(&new_name) {
+                return Err(format!("'{new_name}' is the name of a built-in function"));
+            }
+        }
+        RenameKind::Local => {
+            if !Var::is_valid_name(Symbol::from(new_name)) {
+                return Err(format!("'{new_name}' must start with 'a'..'z' or '_'"));
+            }
+        }
+        RenameKind::Field => (),
+    }
+    Ok(())
+}
+
+/// Checks that the new name does not clash with a definition in the same scope.
+fn check_conflicts(symbols: &Symbols, target: &RenameTarget, new_name: &str) -> Result<(), String> {
+    let new_name = Symbol::from(new_name);
+    let conflict = || Err(format!("'{new_name}' is already defined in this scope"));
+    if target.kind == RenameKind::Local {
+        let Some((start, end)) = enclosing_function(symbols, &target.def_loc) else {
+            return Ok(());
+        };
+        let clash = symbols.def_info.iter().any(|(loc, info)| {
+            matches!(info, DefInfo::Local(name, ..) if *name == new_name)
+                && loc.file_hash() == target.def_loc.file_hash()
+                && loc.start() >= start
+                && loc.start() < end
+        });
+        return if clash { conflict() } else { Ok(()) };
+    }
+
+    let Some(mod_defs) = target
+        .mod_ident
+        .and_then(|ident| symbols.mod_defs(&target.def_loc.file_hash(), ident))
+    else {
+        return Ok(());
+    };
+    let clash = match target.kind {
+        // all module members share a namespace
+        RenameKind::Function | RenameKind::Datatype | RenameKind::Const => {
+            mod_defs.functions.contains_key(&new_name)
+                || mod_defs.structs.contains_key(&new_name)
+                || mod_defs.enums.contains_key(&new_name)
+                || mod_defs.constants.contains_key(&new_name)
+        }
+        RenameKind::Variant => mod_defs.enums.values().any(|def| match &def.info {
+            MemberDefInfo::Enum { variants_info } => {
+                variants_info
+                    .values()
+                    .any(|(loc, _, _)| *loc == target.def_loc)
+                    && variants_info.contains_key(&new_name)
+            }
+            _ => false,
+        }),
+        RenameKind::Field => mod_defs
+            .structs
+            .values()
+            .chain(mod_defs.enums.values())
+            .flat_map(|def| match &def.info {
+                MemberDefInfo::Struct { field_defs, .. } => vec![field_defs],
+                MemberDefInfo::Enum { variants_info } => variants_info
+                    .values()
+                    .map(|(_, field_defs, _)| field_defs)
+                    .collect(),
+                _ => vec![],
+            })
+            .any(|field_defs| {
+                field_defs.iter().any(|f| f.loc == target.def_loc)
+                    && field_defs.iter().any(|f| f.name == new_name)
+            }),
+        RenameKind::Local => false,
+    };
+    if clash {
+        conflict()
+    } else {
+        Ok(())
+    }
+}
+
+/// Returns the (approximate) byte range of the function containing a given location, spanning
+/// from the function's name to the next function's name in the same file.
+fn enclosing_function(symbols: &Symbols, loc: &Loc) -> Option<(u32, u32)> {
+    let fpath = symbols.files.file_path(&loc.file_hash());
+    let mut starts: Vec<u32> = symbols
+        .file_mods
+        .get(fpath)?
+        .iter()
+        .flat_map(|mod_defs| mod_defs.functions.values())
+        .map(|def| def.name_loc.start())
+        .collect();
+    starts.sort();
+    let idx = starts.iter().rposition(|start| *start <= loc.start())?;
+    Some((
+        starts[idx],
+        starts.get(idx + 1).copied().unwrap_or(u32::MAX),
+    ))
+}
+
+/// Finds uses of a field or local that also refer to a local or field, respectively, which is
+/// the case for a field name punned with a local of the same name.
+fn punned_uses(
+    symbols: &Symbols,
+    target: &RenameTarget,
+    refs: &BTreeSet<UseLoc>,
+) -> BTreeSet<UseLoc> {
+    let complementary = |info: &DefInfo| match target.kind {
+        RenameKind::Field => matches!(info, DefInfo::Local(..)),
+        RenameKind::Local => matches!(info, DefInfo::Field(..)),
+        _ => false,
+    };
+    if !matches!(target.kind, RenameKind::Field | RenameKind::Local) {
+        return BTreeSet::new();
+    }
+    let ref_positions: BTreeMap<_, _> = refs.iter().map(|u| ((u.fhash(), u.start()), *u)).collect();
+    symbols
+        .references
+        .iter()
+        .filter(|(def_loc, _)| {
+            **def_loc != target.def_loc && symbols.def_info(def_loc).is_some_and(complementary)
+        })
+        .flat_map(|(_, uses)| uses.iter())
+        .filter_map(|u| ref_positions.get(&(u.fhash(), u.start())).copied())
+        .collect()
+}
+
+fn use_loc_text(symbols: &Symbols, use_loc: &UseLoc) -> Option<String> {
+    let (_, content) = symbols.files.get(&use_loc.fhash())?;
+    let line = content.lines().nth(use_loc.start().line as usize)?;
+    let start = use_loc.start().character as usize;
+    let len = (use_loc.col_end() as usize).checked_sub(start)?;
+    Some(line.chars().skip(start).take(len).collect())
+}
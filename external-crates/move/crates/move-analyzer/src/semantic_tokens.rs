@@ -0,0 +1,155 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    context::Context,
+    symbols::{DefInfo, SymbolicatorRunner, Symbols},
+};
+use lsp_server::Request;
+use lsp_types::{
+    SemanticToken, SemanticTokenModifier, SemanticTokenType, SemanticTokens, SemanticTokensLegend,
+    SemanticTokensParams, SemanticTokensResult,
+};
+use move_compiler::naming::ast::Type_;
+use std::path::Path;
+
+/// Token types reported by the server (a token's type is an index into this array).
+const TOKEN_TYPES: &[SemanticTokenType] = &[
+    SemanticTokenType::NAMESPACE,
+    SemanticTokenType::STRUCT,
+    SemanticTokenType::ENUM,
+    SemanticTokenType::ENUM_MEMBER,
+    SemanticTokenType::FUNCTION,
+    SemanticTokenType::MACRO,
+    SemanticTokenType::VARIABLE,
+    SemanticTokenType::PROPERTY,
+    SemanticTokenType::TYPE_PARAMETER,
+    SemanticTokenType::TYPE,
+];
+
+/// Token modifiers reported by the server (a token's modifiers are a bit set of indexes into
+/// this array).
+const TOKEN_MODIFIERS: &[SemanticTokenModifier] = &[
+    SemanticTokenModifier::DECLARATION,
+    SemanticTokenModifier::READONLY,
+];
+
+const DECLARATION: u32 = 1 << 0;
+const READONLY: u32 = 1 << 1;
+
+pub fn semantic_tokens_legend() -> SemanticTokensLegend {
+    SemanticTokensLegend {
+        token_types: TOKEN_TYPES.to_vec(),
+        token_modifiers: TOKEN_MODIFIERS.to_vec(),
+    }
+}
+
+/// Handles semantic tokens request of the language server
+pub fn on_semantic_tokens_request(context: &Context, request: &Request) {
+    let parameters = serde_json::from_value::<SemanticTokensParams>(request.params.clone())
+        .expect("could not deserialize semantic tokens request");
+
+    let fpath = parameters.text_document.uri.to_file_path().unwrap();
+    let symbols_map = &context.symbols.lock().unwrap();
+    let data = SymbolicatorRunner::root_dir(&fpath)
+        .and_then(|pkg_path| symbols_map.get(&pkg_path))
+        .map(|symbols| semantic_tokens(symbols, &fpath))
+        .unwrap_or_default();
+
+    let result = SemanticTokensResult::Tokens(SemanticTokens {
+        result_id: None,
+        data,
+    });
+    let response = lsp_server::Response::new_ok(request.id.clone(), result);
+    if let Err(err) = context
+        .connection
+        .sender
+        .send(lsp_server::Message::Response(response))
+    {
+        eprintln!("could not send semantic tokens response: {:?}", err);
+    }
+}
+
+/// Computes semantic tokens for all identifiers in a file whose definitions were resolved by
+/// the typing analysis, encoded relative to one another as required by the protocol.
+pub fn semantic_tokens(symbols: &Symbols, fpath: &Path) -> Vec<SemanticToken> {
+    let Some(use_defs) = symbols.file_use_defs.get(fpath) else {
+        return vec![];
+    };
+    let fhash = symbols.file_hash(fpath);
+
+    let mut tokens = vec![];
+    let (mut prev_line, mut prev_start, mut prev_end) = (0, 0, 0);
+    for (line, uses) in use_defs.clone().elements() {
+        for use_def in uses {
+            let Some((token_type, mut modifiers)) = symbols
+                .def_info(&use_def.def_loc())
+                .and_then(token_type_and_modifiers)
+            else {
+                continue;
+            };
+            let (start, end) = (use_def.col_start(), use_def.col_end());
+            // tokens cannot overlap
+            if line == prev_line && start < prev_end && !tokens.is_empty() {
+                continue;
+            }
+            let is_def = symbols
+                .files
+                .file_start_position_opt(&use_def.def_loc())
+                .is_some_and(|def_pos| {
+                    Some(def_pos.file_hash()) == fhash
+                        && def_pos.position().line_offset() as u32 == line
+                        && def_pos.position().column_offset() as u32 == start
+                });
+            if is_def {
+                modifiers |= DECLARATION;
+            }
+            let delta_line = line - prev_line;
+            tokens.push(SemanticToken {
+                delta_line,
+                delta_start: if delta_line == 0 {
+                    start - prev_start
+                } else {
+                    start
+                },
+                length: end - start,
+                token_type,
+                token_modifiers_bitset: modifiers,
+            });
+            (prev_line, prev_start, prev_end) = (line, start, end);
+        }
+    }
+    tokens
+}
+
+fn token_type_and_modifiers(def_info: &DefInfo) -> Option<(u32, u32)> {
+    let token_type = match def_info {
+        DefInfo::Module(..) => SemanticTokenType::NAMESPACE,
+        DefInfo::Struct(..) => SemanticTokenType::STRUCT,
+        DefInfo::Enum(..) => SemanticTokenType::ENUM,
+        DefInfo::Variant(..) => SemanticTokenType::ENUM_MEMBER,
+        DefInfo::Function(_, _, fun_type, ..) => {
+            if *fun_type == crate::symbols::FunType::Macro {
+                SemanticTokenType::MACRO
+            } else {
+                SemanticTokenType::FUNCTION
+            }
+        }
+        DefInfo::Local(..) | DefInfo::Const(..) => SemanticTokenType::VARIABLE,
+        DefInfo::Field(..) => SemanticTokenType::PROPERTY,
+        DefInfo::Type(t) => {
+            if matches!(t.value, Type_::Param(_)) {
+                SemanticTokenType::TYPE_PARAMETER
+            } else {
+                SemanticTokenType::TYPE
+            }
+        }
+    };
+    let modifiers = if matches!(def_info, DefInfo::Const(..)) {
+        READONLY
+    } else {
+        0
+    };
+    let idx = TOKEN_TYPES.iter().position(|t| *t == token_type)?;
+    Some((idx as u32, modifiers))
+}
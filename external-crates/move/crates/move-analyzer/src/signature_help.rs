@@ -0,0 +1,139 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    context::Context,
+    symbols::{
+        ret_type_to_ide_str, type_args_to_ide_string, type_to_ide_string, DefInfo, FunType,
+        SymbolicatorRunner, Symbols,
+    },
+    utils::lsp_position_to_loc,
+};
+use lsp_server::Request;
+use lsp_types::{
+    Documentation, ParameterInformation, ParameterLabel, Position, SignatureHelp,
+    SignatureHelpParams, SignatureInformation,
+};
+use move_ir_types::location::Loc;
+use std::path::Path;
+
+/// Handles signature help request of the language server
+pub fn on_signature_help_request(context: &Context, request: &Request) {
+    let parameters = serde_json::from_value::<SignatureHelpParams>(request.params.clone())
+        .expect("could not deserialize signature help request");
+
+    let fpath = parameters
+        .text_document_position_params
+        .text_document
+        .uri
+        .to_file_path()
+        .unwrap();
+    let position = parameters.text_document_position_params.position;
+
+    let symbols_map = &context.symbols.lock().unwrap();
+    let help = SymbolicatorRunner::root_dir(&fpath)
+        .and_then(|pkg_path| symbols_map.get(&pkg_path))
+        .and_then(|symbols| signature_help(symbols, &fpath, position));
+
+    let response = lsp_server::Response::new_ok(request.id.clone(), help);
+    if let Err(err) = context
+        .connection
+        .sender
+        .send(lsp_server::Message::Response(response))
+    {
+        eprintln!("could not send signature help response: {:?}", err);
+    }
+}
+
+/// Computes signature of the function whose argument list (innermost, in case of nested calls)
+/// contains a given position, along with the parameter corresponding to this position. For
+/// method calls (using `.` syntax) the receiver is the first parameter and the first argument
+/// in the parentheses is the second one.
+pub fn signature_help(
+    symbols: &Symbols,
+    fpath: &Path,
+    position: Position,
+) -> Option<SignatureHelp> {
+    let fhash = symbols.file_hash(fpath)?;
+    let cursor = lsp_position_to_loc(&symbols.files, fhash, &position)?.start();
+    let call_info = symbols
+        .file_mods
+        .get(fpath)?
+        .iter()
+        .flat_map(|mod_defs| mod_defs.call_infos.values())
+        .filter(|info| info.args_loc.start() < cursor && cursor < info.args_loc.end())
+        .max_by_key(|info| info.args_loc.start())?;
+    let DefInfo::Function(
+        mod_ident,
+        _,
+        fun_type,
+        name,
+        type_args,
+        arg_names,
+        arg_types,
+        ret_type,
+        doc,
+    ) = symbols.def_info(&call_info.def_loc?)?
+    else {
+        return None;
+    };
+
+    // an argument is before the cursor if it is followed by a comma before the cursor
+    let args_before = call_info
+        .arg_locs
+        .iter()
+        .filter(|arg_loc| {
+            arg_loc.end() < cursor
+                && symbols
+                    .files
+                    .source_of_loc_opt(&Loc::new(fhash, arg_loc.end(), cursor))
+                    .is_some_and(|src| src.contains(','))
+        })
+        .count();
+    let active_parameter = (args_before + usize::from(call_info.dot_call)) as u32;
+
+    let mut label = format!(
+        "{}fun {}::{}{}(",
+        if *fun_type == FunType::Macro {
+            "macro "
+        } else {
+            ""
+        },
+        mod_ident.module,
+        name,
+        type_args_to_ide_string(type_args, /* separate_lines */ false, /* verbose */ false)
+    );
+    let mut parameters = vec![];
+    for (idx, (arg_name, arg_type)) in arg_names.iter().zip(arg_types).enumerate() {
+        if idx > 0 {
+            label.push_str(", ");
+        }
+        let start = label.chars().count() as u32;
+        label.push_str(&format!(
+            "{}: {}",
+            arg_name.value,
+            type_to_ide_string(arg_type, /* verbose */ false)
+        ));
+        let end = label.chars().count() as u32;
+        parameters.push(ParameterInformation {
+            label: ParameterLabel::LabelOffsets([start, end]),
+            documentation: None,
+        });
+    }
+    label.push(')');
+    label.push_str(&ret_type_to_ide_str(ret_type, /* verbose */ false));
+
+    let signature = SignatureInformation {
+        label,
+        documentation: doc
+            .as_ref()
+            .map(|d| Documentation::String(d.trim().to_string())),
+        parameters: Some(parameters),
+        active_parameter: Some(active_parameter),
+    };
+    Some(SignatureHelp {
+        signatures: vec![signature],
+        active_signature: Some(0),
+        active_parameter: Some(active_parameter),
+    })
+}
@@ -423,16 +423,19 @@ pub struct CallInfo {
     pub dot_call: bool,
     /// Locations of arguments
     pub arg_locs: Vec<Loc>,
+    /// Location of the whole argument list (including parentheses)
+    pub args_loc: Loc,
     /// Definition of function being called (as an Option as its computed after
     /// this struct is created)
     pub def_loc: Option<Loc>,
 }
 
 impl CallInfo {
-    pub fn new(dot_call: bool, args: &[P::Exp]) -> Self {
+    pub fn new(dot_call: bool, args: &Spanned<Vec<P::Exp>>) -> Self {
         Self {
             dot_call,
-            arg_locs: args.iter().map(|e| e.loc).collect(),
+            arg_locs: args.value.iter().map(|e| e.loc).collect(),
+            args_loc: args.loc,
             def_loc: None,
         }
     }
@@ -673,6 +676,20 @@ pub struct SymbolicatorRunner {
     mtx_cvar: Arc<(Mutex<RunnerState>, Condvar)>,
 }
 
+impl UseLoc {
+    pub fn fhash(&self) -> FileHash {
+        self.fhash
+    }
+
+    pub fn start(&self) -> Position {
+        self.start
+    }
+
+    pub fn col_end(&self) -> u32 {
+        self.col_end
+    }
+}
+
 impl ModuleDefs {
     pub fn functions(&self) -> &BTreeMap<Symbol, MemberDef> {
         &self.functions
@@ -1937,8 +1954,7 @@ pub fn get_compiled_pkg(
 
     let mut ide_diagnostics = lsp_empty_diagnostics(mapped_files.file_name_mapping());
     if let Some((compiler_diagnostics, failure)) = diagnostics {
-        let lsp_diagnostics =
-            lsp_diagnostics(&compiler_diagnostics.into_codespan_format(), &mapped_files);
+        let lsp_diagnostics = lsp_diagnostics(compiler_diagnostics, &mapped_files);
         // start with empty diagnostics for all files and replace them with actual diagnostics
         // only for files that have failures/warnings so that diagnostics for all other files
         // (that no longer have failures/warnings) are reset
@@ -0,0 +1,136 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    context::Context,
+    symbols::{def_ide_location, Symbols},
+};
+use lsp_server::Request;
+use lsp_types::{
+    OneOf, SymbolKind, WorkspaceSymbol, WorkspaceSymbolParams, WorkspaceSymbolResponse,
+};
+use move_ir_types::location::Loc;
+use std::collections::BTreeSet;
+
+/// Maximum number of symbols returned for a single query
+const MAX_WORKSPACE_SYMBOLS: usize = 256;
+
+/// Quality of a match between a query and a symbol name (better matches are smaller).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum MatchKind {
+    Exact,
+    Prefix,
+    Substring,
+    Subsequence,
+}
+
+/// Handles workspace symbol request of the language server
+pub fn on_workspace_symbol_request(context: &Context, request: &Request) {
+    let parameters = serde_json::from_value::<WorkspaceSymbolParams>(request.params.clone())
+        .expect("could not deserialize workspace symbol request");
+
+    let symbols_map = &context.symbols.lock().unwrap();
+    let symbols = workspace_symbols(symbols_map.values(), &parameters.query);
+
+    let response =
+        lsp_server::Response::new_ok(request.id.clone(), WorkspaceSymbolResponse::Nested(symbols));
+    if let Err(err) = context
+        .connection
+        .sender
+        .send(lsp_server::Message::Response(response))
+    {
+        eprintln!("could not send workspace symbol response: {:?}", err);
+    }
+}
+
+/// Finds module-level definitions (modules, datatypes, functions and constants) whose names
+/// fuzzily match the query, case-insensitively, in all packages currently open in the IDE
+/// and in their dependencies. Results are ordered by match quality and then by name.
+pub fn workspace_symbols<'a>(
+    all_symbols: impl Iterator<Item = &'a Symbols>,
+    query: &str,
+) -> Vec<WorkspaceSymbol> {
+    let query = query.to_lowercase();
+    // the same dependency may be shared by multiple packages
+    let mut seen = BTreeSet::new();
+    let mut matches = vec![];
+    for symbols in all_symbols {
+        for mod_defs in symbols.file_mods.values().flatten() {
+            let container = mod_defs.ident.to_string();
+            let mod_name = mod_defs.ident.module.to_string();
+            let members = [
+                (&mod_defs.structs, SymbolKind::STRUCT),
+                (&mod_defs.enums, SymbolKind::ENUM),
+                (&mod_defs.functions, SymbolKind::FUNCTION),
+                (&mod_defs.constants, SymbolKind::CONSTANT),
+            ]
+            .into_iter()
+            .flat_map(|(defs, kind)| {
+                defs.iter()
+                    .map(move |(name, def)| (name.to_string(), def.name_loc, kind))
+            });
+            let candidates =
+                std::iter::once((mod_name, mod_defs.name_loc, SymbolKind::MODULE)).chain(members);
+            for (name, loc, kind) in candidates {
+                let Some(match_kind) = fuzzy_match(&query, &name) else {
+                    continue;
+                };
+                if !seen.insert(loc) {
+                    continue;
+                }
+                let container_name = if kind == SymbolKind::MODULE {
+                    mod_defs.ident.address.to_string()
+                } else {
+                    container.clone()
+                };
+                matches.push((match_kind, name, container_name, kind, loc, symbols));
+            }
+        }
+    }
+    matches.sort_by(|(k1, n1, c1, ..), (k2, n2, c2, ..)| {
+        (k1, n1.len(), n1, c1).cmp(&(k2, n2.len(), n2, c2))
+    });
+    matches
+        .into_iter()
+        .take(MAX_WORKSPACE_SYMBOLS)
+        .map(|(_, name, container_name, kind, loc, symbols)| {
+            to_workspace_symbol(name, container_name, kind, &loc, symbols)
+        })
+        .collect()
+}
+
+fn to_workspace_symbol(
+    name: String,
+    container_name: String,
+    kind: SymbolKind,
+    loc: &Loc,
+    symbols: &Symbols,
+) -> WorkspaceSymbol {
+    WorkspaceSymbol {
+        name,
+        kind,
+        tags: None,
+        container_name: Some(container_name),
+        location: OneOf::Left(def_ide_location(loc, symbols)),
+        data: None,
+    }
+}
+
+/// Matches a (lowercase) query against a name. An empty query matches everything.
+fn fuzzy_match(query: &str, name: &str) -> Option<MatchKind> {
+    let name = name.to_lowercase();
+    if name == query {
+        return Some(MatchKind::Exact);
+    }
+    if name.starts_with(query) {
+        return Some(MatchKind::Prefix);
+    }
+    if name.contains(query) {
+        return Some(MatchKind::Substring);
+    }
+    let mut name_chars = name.chars();
+    query
+        .chars()
+        .all(|qc| name_chars.any(|nc| nc == qc))
+        .then_some(MatchKind::Subsequence)
+}
@@ -0,0 +1,21 @@
+module CodeActions::lints {
+    use std::option::{Self, none};
+    use std::vector::{empty, push_back};
+    use std::string;
+
+    public fun count(n: u64): u64 {
+        let mut v = empty();
+        let mut i = 0;
+        while (true) {
+            if (i == n) break;
+            push_back(&mut v, i);
+            i = i + 1;
+        };
+        ();
+        return v.length()
+    }
+
+    public fun maybe(): option::Option<u64> {
+        option::some(1)
+    }
+}
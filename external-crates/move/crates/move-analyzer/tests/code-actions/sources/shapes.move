@@ -0,0 +1,13 @@
+module CodeActions::shapes {
+    public struct Square has drop {
+        side: u64,
+    }
+
+    public fun new(side: u64): Square {
+        Square { side }
+    }
+
+    public fun area(sq: &Square): u64 {
+        sq.side * sq.side
+    }
+}
@@ -0,0 +1,12 @@
+module CodeActions::unbound {
+    use CodeActions::lints;
+
+    public fun use_square(): u64 {
+        let sq = shapes::new(2);
+        lints::count(area(&sq))
+    }
+
+    public fun make(): Square {
+        CodeActions::shapes::new(1)
+    }
+}
@@ -10,15 +10,24 @@ use std::{
 };
 
 use json_comments::StripComments;
-use lsp_types::{InlayHintKind, InlayHintLabel, InlayHintTooltip, Position};
+use lsp_types::{
+    CallHierarchyItem, Diagnostic, Documentation, InlayHintKind, InlayHintLabel, InlayHintTooltip,
+    NumberOrString, OneOf, ParameterLabel, Position, Range,
+};
 use move_analyzer::{
+    call_hierarchy::{incoming_calls, outgoing_calls, prepare_call_hierarchy},
+    code_actions::code_actions,
     completions::compute_completions_with_symbols,
     inlay_hints::inlay_hints_internal,
+    rename::rename_edits,
+    semantic_tokens::{semantic_tokens, semantic_tokens_legend},
+    signature_help::signature_help,
     symbols::{
         compute_symbols, compute_symbols_parsed_program, compute_symbols_pre_process,
         def_info_doc_string, get_compiled_pkg, maybe_convert_for_guard, CompiledPkgInfo, Symbols,
         SymbolsComputationData, UseDefMap,
     },
+    workspace_symbols::workspace_symbols,
 };
 use move_command_line_common::testing::{
     add_update_baseline_fix, format_diff, read_env_update_baseline, EXP_EXT,
@@ -49,6 +58,30 @@ enum TestSuite {
         project: String,
         file_tests: BTreeMap<String, Vec<HintTest>>,
     },
+    Rename {
+        project: String,
+        file_tests: BTreeMap<String, Vec<RenameTest>>,
+    },
+    CodeAction {
+        project: String,
+        file_tests: BTreeMap<String, Vec<CodeActionTest>>,
+    },
+    SignatureHelp {
+        project: String,
+        file_tests: BTreeMap<String, Vec<SignatureHelpTest>>,
+    },
+    SemanticTokens {
+        project: String,
+        files: Vec<String>,
+    },
+    WorkspaceSymbol {
+        project: String,
+        queries: Vec<String>,
+    },
+    CallHierarchy {
+        project: String,
+        file_tests: BTreeMap<String, Vec<CallHierarchyTest>>,
+    },
 }
 
 #[derive(Serialize, Deserialize)]
@@ -76,6 +109,30 @@ struct HintTest {
     use_col: u32,
 }
 
+#[derive(Serialize, Deserialize)]
+struct RenameTest {
+    use_line: u32,
+    use_col: u32,
+    new_name: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CodeActionTest {
+    use_line: u32,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SignatureHelpTest {
+    use_line: u32,
+    use_col: u32,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CallHierarchyTest {
+    use_line: u32,
+    use_col: u32,
+}
+
 //**************************************************************************************************
 // Test Impls
 //**************************************************************************************************
@@ -338,6 +395,221 @@ impl HintTest {
     }
 }
 
+impl RenameTest {
+    fn test(
+        &self,
+        test_idx: usize,
+        symbols: &Symbols,
+        output: &mut dyn std::io::Write,
+        use_file_path: &Path,
+    ) -> anyhow::Result<()> {
+        let RenameTest {
+            use_line,
+            use_col,
+            new_name,
+        } = self;
+        let position = Position::new(use_line - 1, use_col - 1); // 0th-based
+        writeln!(output, "-- test {test_idx} -------------------")?;
+        writeln!(
+            output,
+            "use line: {use_line}, use_col: {use_col}, new name: '{new_name}'"
+        )?;
+        let edit = match rename_edits(symbols, use_file_path, position, new_name) {
+            Ok(edit) => edit,
+            Err(msg) => {
+                writeln!(output, "ERROR: {msg}")?;
+                return Ok(());
+            }
+        };
+        let changes = edit
+            .changes
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(url, edits)| {
+                let path = url.to_file_path().unwrap();
+                (
+                    path.file_name().unwrap().to_string_lossy().to_string(),
+                    edits,
+                )
+            })
+            .collect::<BTreeMap<_, _>>();
+        for (file, edits) in changes {
+            for edit in edits {
+                let Range { start, end } = edit.range;
+                writeln!(
+                    output,
+                    "{file}:{}:{}-{} '{}'",
+                    start.line + 1,
+                    start.character + 1,
+                    end.character + 1,
+                    edit.new_text
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl CodeActionTest {
+    fn test(
+        &self,
+        test_idx: usize,
+        symbols: &Symbols,
+        diagnostics: &[Diagnostic],
+        output: &mut dyn std::io::Write,
+        use_file_path: &Path,
+    ) -> anyhow::Result<()> {
+        let lsp_use_line = self.use_line - 1; // 0th-based
+        let line_diagnostics = diagnostics
+            .iter()
+            .filter(|d| d.range.start.line == lsp_use_line)
+            .cloned()
+            .collect::<Vec<_>>();
+        writeln!(output, "-- test {test_idx} -------------------")?;
+        writeln!(output, "use line: {}", self.use_line)?;
+        for diag in &line_diagnostics {
+            let code = match &diag.code {
+                Some(NumberOrString::String(code)) => code.clone(),
+                _ => "NO CODE".to_string(),
+            };
+            writeln!(output, "DIAGNOSTIC [{code}]: {}", diag.message)?;
+        }
+        let content = fs::read_to_string(use_file_path)?;
+        let actions = code_actions(Some(symbols), use_file_path, &content, &line_diagnostics);
+        if actions.is_empty() {
+            writeln!(output, "NO CODE ACTIONS")?;
+        }
+        for action in actions {
+            writeln!(output, "ACTION: {}", action.title)?;
+            let edits = action
+                .edit
+                .and_then(|e| e.changes)
+                .unwrap_or_default()
+                .into_values()
+                .flatten();
+            for edit in edits {
+                let Range { start, end } = edit.range;
+                writeln!(
+                    output,
+                    "    {}:{}-{}:{} {:?}",
+                    start.line + 1,
+                    start.character + 1,
+                    end.line + 1,
+                    end.character + 1,
+                    edit.new_text
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl SignatureHelpTest {
+    fn test(
+        &self,
+        test_idx: usize,
+        symbols: &Symbols,
+        output: &mut dyn std::io::Write,
+        use_file_path: &Path,
+    ) -> anyhow::Result<()> {
+        let SignatureHelpTest { use_line, use_col } = self;
+        let position = Position::new(use_line - 1, use_col - 1); // 0th-based
+        writeln!(output, "-- test {test_idx} -------------------")?;
+        writeln!(output, "use line: {use_line}, use_col: {use_col}")?;
+        let Some(help) = signature_help(symbols, use_file_path, position) else {
+            writeln!(output, "NO SIGNATURE")?;
+            return Ok(());
+        };
+        for sig in help.signatures {
+            writeln!(output, "{}", sig.label)?;
+            let active = sig.active_parameter.unwrap_or_default() as usize;
+            match sig
+                .parameters
+                .as_ref()
+                .and_then(|params| params.get(active))
+            {
+                Some(param) => {
+                    let ParameterLabel::LabelOffsets([start, end]) = param.label else {
+                        writeln!(output, "ERROR: unexpected parameter label")?;
+                        continue;
+                    };
+                    let param_label = sig
+                        .label
+                        .chars()
+                        .skip(start as usize)
+                        .take((end - start) as usize)
+                        .collect::<String>();
+                    writeln!(output, "active parameter {active}: {param_label}")?;
+                }
+                None => writeln!(output, "active parameter {active}: NONE")?,
+            }
+            if let Some(Documentation::String(doc)) = sig.documentation {
+                writeln!(output, "doc: {doc}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl CallHierarchyTest {
+    fn test(
+        &self,
+        test_idx: usize,
+        symbols: &Symbols,
+        output: &mut dyn std::io::Write,
+        use_file_path: &Path,
+    ) -> anyhow::Result<()> {
+        let CallHierarchyTest { use_line, use_col } = self;
+        let position = Position::new(use_line - 1, use_col - 1); // 0th-based
+        writeln!(output, "-- test {test_idx} -------------------")?;
+        writeln!(output, "use line: {use_line}, use_col: {use_col}")?;
+        let Some(item) = prepare_call_hierarchy(symbols, use_file_path, position) else {
+            writeln!(output, "NO CALL HIERARCHY ITEM")?;
+            return Ok(());
+        };
+        writeln!(output, "item: {}", call_hierarchy_item_str(&item))?;
+        writeln!(output, "incoming calls:")?;
+        for call in incoming_calls(symbols, &item) {
+            writeln!(
+                output,
+                "  {} at {}",
+                call_hierarchy_item_str(&call.from),
+                ranges_str(&call.from_ranges)
+            )?;
+        }
+        writeln!(output, "outgoing calls:")?;
+        for call in outgoing_calls(symbols, &item) {
+            writeln!(
+                output,
+                "  {} at {}",
+                call_hierarchy_item_str(&call.to),
+                ranges_str(&call.from_ranges)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+fn call_hierarchy_item_str(item: &CallHierarchyItem) -> String {
+    let path = item.uri.to_file_path().unwrap();
+    format!(
+        "{}::{} ({}:{}:{})",
+        item.detail.clone().unwrap_or_default(),
+        item.name,
+        path.file_name().unwrap().to_string_lossy(),
+        item.selection_range.start.line + 1,
+        item.selection_range.start.character + 1,
+    )
+}
+
+fn ranges_str(ranges: &[Range]) -> String {
+    ranges
+        .iter()
+        .map(|r| format!("{}:{}", r.start.line + 1, r.start.character + 1))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
 //**************************************************************************************************
 // Test Suite Runner Code
 //**************************************************************************************************
@@ -370,6 +642,23 @@ fn check_expected(expected_path: &Path, result: &str) -> anyhow::Result<()> {
 fn initial_symbols(
     project: String,
 ) -> datatest_stable::Result<(PathBuf, CompiledPkgInfo, Symbols)> {
+    let (project_path, compiled_pkg_info, symbols, _) =
+        initial_symbols_with_diagnostics(project, LintLevel::None)?;
+    Ok((project_path, compiled_pkg_info, symbols))
+}
+
+/// Project path, compiled package, symbols and diagnostics of a test project.
+type SymbolsWithDiagnostics = (
+    PathBuf,
+    CompiledPkgInfo,
+    Symbols,
+    BTreeMap<PathBuf, Vec<Diagnostic>>,
+);
+
+fn initial_symbols_with_diagnostics(
+    project: String,
+    lint: LintLevel,
+) -> datatest_stable::Result<SymbolsWithDiagnostics> {
     let base_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
     let mut project_path = base_path.clone();
     project_path.push(project);
@@ -377,11 +666,11 @@ fn initial_symbols(
     let ide_files_root: VfsPath = MemoryFS::new().into();
     let pkg_deps = Arc::new(Mutex::new(BTreeMap::new()));
 
-    let (compiled_pkg_info_opt, _) = get_compiled_pkg(
+    let (compiled_pkg_info_opt, diagnostics) = get_compiled_pkg(
         pkg_deps.clone(),
         ide_files_root.clone(),
         project_path.as_path(),
-        LintLevel::None,
+        lint,
     )?;
 
     let compiled_pkg_info = compiled_pkg_info_opt.ok_or("PACKAGE COMPILATION FAILED")?;
@@ -391,7 +680,7 @@ fn initial_symbols(
         None,
     );
 
-    Ok((project_path, compiled_pkg_info, symbols))
+    Ok((project_path, compiled_pkg_info, symbols, diagnostics))
 }
 
 fn use_def_test_suite(
@@ -514,6 +803,229 @@ fn hint_test_suite(
     Ok(result)
 }
 
+fn rename_test_suite(
+    project: String,
+    file_tests: BTreeMap<String, Vec<RenameTest>>,
+) -> datatest_stable::Result<String> {
+    let (project_path, _, symbols) = initial_symbols(project)?;
+
+    let mut output: BufWriter<_> = BufWriter::new(Vec::new());
+    let writer: &mut dyn io::Write = output.get_mut();
+
+    for (file, tests) in file_tests {
+        writeln!(
+            writer,
+            "== {file} ========================================================"
+        )?;
+
+        let mut fpath = project_path.clone();
+
+        fpath.push(format!("sources/{file}"));
+        let cpath = dunce::canonicalize(&fpath).unwrap();
+
+        for (idx, test) in tests.iter().enumerate() {
+            test.test(idx, &symbols, writer, &cpath)?;
+            writeln!(writer)?;
+        }
+    }
+
+    let result: String = String::from_utf8(output.into_inner().unwrap()).unwrap();
+    Ok(result)
+}
+
+fn code_action_test_suite(
+    project: String,
+    file_tests: BTreeMap<String, Vec<CodeActionTest>>,
+) -> datatest_stable::Result<String> {
+    let (project_path, _, symbols, diagnostics) =
+        initial_symbols_with_diagnostics(project, LintLevel::All)?;
+
+    let mut output: BufWriter<_> = BufWriter::new(Vec::new());
+    let writer: &mut dyn io::Write = output.get_mut();
+
+    for (file, tests) in file_tests {
+        writeln!(
+            writer,
+            "== {file} ========================================================"
+        )?;
+
+        let mut fpath = project_path.clone();
+
+        fpath.push(format!("sources/{file}"));
+        let cpath = dunce::canonicalize(&fpath).unwrap();
+        let file_diagnostics = diagnostics.get(&cpath).cloned().unwrap_or_default();
+
+        for (idx, test) in tests.iter().enumerate() {
+            test.test(idx, &symbols, &file_diagnostics, writer, &cpath)?;
+            writeln!(writer)?;
+        }
+    }
+
+    let result: String = String::from_utf8(output.into_inner().unwrap()).unwrap();
+    Ok(result)
+}
+
+fn signature_help_test_suite(
+    project: String,
+    file_tests: BTreeMap<String, Vec<SignatureHelpTest>>,
+) -> datatest_stable::Result<String> {
+    let (project_path, _, symbols) = initial_symbols(project)?;
+
+    let mut output: BufWriter<_> = BufWriter::new(Vec::new());
+    let writer: &mut dyn io::Write = output.get_mut();
+
+    for (file, tests) in file_tests {
+        writeln!(
+            writer,
+            "== {file} ========================================================"
+        )?;
+
+        let mut fpath = project_path.clone();
+
+        fpath.push(format!("sources/{file}"));
+        let cpath = dunce::canonicalize(&fpath).unwrap();
+
+        for (idx, test) in tests.iter().enumerate() {
+            test.test(idx, &symbols, writer, &cpath)?;
+            writeln!(writer)?;
+        }
+    }
+
+    let result: String = String::from_utf8(output.into_inner().unwrap()).unwrap();
+    Ok(result)
+}
+
+fn semantic_tokens_test_suite(
+    project: String,
+    files: Vec<String>,
+) -> datatest_stable::Result<String> {
+    let (project_path, _, symbols) = initial_symbols(project)?;
+    let legend = semantic_tokens_legend();
+
+    let mut output: BufWriter<_> = BufWriter::new(Vec::new());
+    let writer: &mut dyn io::Write = output.get_mut();
+
+    for file in files {
+        writeln!(
+            writer,
+            "== {file} ========================================================"
+        )?;
+
+        let mut fpath = project_path.clone();
+
+        fpath.push(format!("sources/{file}"));
+        let cpath = dunce::canonicalize(&fpath).unwrap();
+        let content = fs::read_to_string(&cpath)?;
+        let lines = content.lines().collect::<Vec<_>>();
+
+        // decode relative token positions
+        let (mut line, mut col) = (0, 0);
+        for token in semantic_tokens(&symbols, &cpath) {
+            if token.delta_line == 0 {
+                col += token.delta_start;
+            } else {
+                line += token.delta_line;
+                col = token.delta_start;
+            }
+            let text = lines[line as usize]
+                .get(col as usize..(col + token.length) as usize)
+                .unwrap_or_default();
+            let token_type = legend.token_types[token.token_type as usize].as_str();
+            let modifiers = legend
+                .token_modifiers
+                .iter()
+                .enumerate()
+                .filter(|(idx, _)| token.token_modifiers_bitset & (1 << idx) != 0)
+                .map(|(_, m)| m.as_str())
+                .collect::<Vec<_>>();
+            writeln!(
+                writer,
+                "{}:{} '{text}' {token_type} {modifiers:?}",
+                line + 1,
+                col + 1
+            )?;
+        }
+        writeln!(writer)?;
+    }
+
+    let result: String = String::from_utf8(output.into_inner().unwrap()).unwrap();
+    Ok(result)
+}
+
+fn workspace_symbol_test_suite(
+    project: String,
+    queries: Vec<String>,
+) -> datatest_stable::Result<String> {
+    /// Only the best matches are included in test output as symbols from dependencies may
+    /// yield many matches
+    const MAX_PRINTED: usize = 10;
+
+    let (_, _, symbols) = initial_symbols(project)?;
+
+    let mut output: BufWriter<_> = BufWriter::new(Vec::new());
+    let writer: &mut dyn io::Write = output.get_mut();
+
+    for (idx, query) in queries.iter().enumerate() {
+        writeln!(writer, "-- test {idx} -------------------")?;
+        writeln!(writer, "query: '{query}'")?;
+        let found = workspace_symbols(std::iter::once(&symbols), query);
+        for sym in found.iter().take(MAX_PRINTED) {
+            let OneOf::Left(loc) = &sym.location else {
+                writeln!(writer, "ERROR: no location for {}", sym.name)?;
+                continue;
+            };
+            let path = loc.uri.to_file_path().unwrap();
+            writeln!(
+                writer,
+                "{} ({:?}) in {} at {}:{}:{}",
+                sym.name,
+                sym.kind,
+                sym.container_name.clone().unwrap_or_default(),
+                path.file_name().unwrap().to_string_lossy(),
+                loc.range.start.line + 1,
+                loc.range.start.character + 1,
+            )?;
+        }
+        if found.len() > MAX_PRINTED {
+            writeln!(writer, "... {} more", found.len() - MAX_PRINTED)?;
+        }
+        writeln!(writer)?;
+    }
+
+    let result: String = String::from_utf8(output.into_inner().unwrap()).unwrap();
+    Ok(result)
+}
+
+fn call_hierarchy_test_suite(
+    project: String,
+    file_tests: BTreeMap<String, Vec<CallHierarchyTest>>,
+) -> datatest_stable::Result<String> {
+    let (project_path, _, symbols) = initial_symbols(project)?;
+
+    let mut output: BufWriter<_> = BufWriter::new(Vec::new());
+    let writer: &mut dyn io::Write = output.get_mut();
+
+    for (file, tests) in file_tests {
+        writeln!(
+            writer,
+            "== {file} ========================================================"
+        )?;
+
+        let mut fpath = project_path.clone();
+
+        fpath.push(format!("sources/{file}"));
+        let cpath = dunce::canonicalize(&fpath).unwrap();
+
+        for (idx, test) in tests.iter().enumerate() {
+            test.test(idx, &symbols, writer, &cpath)?;
+            writeln!(writer)?;
+        }
+    }
+
+    let result: String = String::from_utf8(output.into_inner().unwrap()).unwrap();
+    Ok(result)
+}
+
 fn move_ide_testsuite(test_path: &Path) -> datatest_stable::Result<()> {
     let suite_file = io::BufReader::new(File::open(test_path)?);
     let stripped = StripComments::new(suite_file);
@@ -536,6 +1048,26 @@ fn move_ide_testsuite(test_path: &Path) -> datatest_stable::Result<()> {
             project,
             file_tests,
         } => hint_test_suite(project, file_tests),
+        TestSuite::Rename {
+            project,
+            file_tests,
+        } => rename_test_suite(project, file_tests),
+        TestSuite::CodeAction {
+            project,
+            file_tests,
+        } => code_action_test_suite(project, file_tests),
+        TestSuite::SignatureHelp {
+            project,
+            file_tests,
+        } => signature_help_test_suite(project, file_tests),
+        TestSuite::SemanticTokens { project, files } => semantic_tokens_test_suite(project, files),
+        TestSuite::WorkspaceSymbol { project, queries } => {
+            workspace_symbol_test_suite(project, queries)
+        }
+        TestSuite::CallHierarchy {
+            project,
+            file_tests,
+        } => call_hierarchy_test_suite(project, file_tests),
     }?;
 
     let exp_string = test_path
@@ -0,0 +1,11 @@
+module Rename::canvas {
+    use std::option;
+    use Rename::shapes::{Self, Square, new_square as make};
+
+    public fun draw(): u64 {
+        let sq: Square = make(3);
+        let other = shapes::new_square(4);
+        let _none = option::none<u64>();
+        shapes::area(&sq) + shapes::side(&other)
+    }
+}
@@ -0,0 +1,38 @@
+module Rename::shapes {
+    const MAX_SIDES: u64 = 8;
+
+    public struct Square has copy, drop {
+        side: u64,
+    }
+
+    public enum Shape has copy, drop {
+        Circle { radius: u64 },
+        Poly(u64),
+    }
+
+    public fun new_square(side: u64): Square {
+        assert!(side <= MAX_SIDES, 0);
+        Square { side }
+    }
+
+    public fun side(sq: &Square): u64 {
+        let Square { side } = *sq;
+        side
+    }
+
+    public fun area(sq: &Square): u64 {
+        let s = sq.side;
+        s * s
+    }
+
+    public fun circle(radius: u64): Shape {
+        Shape::Circle { radius }
+    }
+
+    public fun is_circle(shape: &Shape): bool {
+        match (shape) {
+            Shape::Circle { .. } => true,
+            Shape::Poly(_) => false,
+        }
+    }
+}
@@ -0,0 +1,36 @@
+module Signatures::geometry {
+    public struct Point has copy, drop { x: u64, y: u64 }
+
+    public enum Shape has drop {
+        Dot(Point),
+        Segment { from: Point, to: Point },
+    }
+
+    const ORIGIN_X: u64 = 0;
+
+    /// Creates a new point
+    public fun new(x: u64, y: u64): Point {
+        Point { x, y }
+    }
+
+    public fun translate(p: &mut Point, dx: u64, dy: u64) {
+        p.x = p.x + dx;
+        p.y = p.y + dy;
+    }
+
+    public fun pick<T: drop>(first: T, second: T, take_first: bool): T {
+        if (take_first) first else second
+    }
+
+    public macro fun apply<$T>($p: Point, $f: |Point| -> $T): $T {
+        $f($p)
+    }
+
+    public fun test(): u64 {
+        let mut p = new(ORIGIN_X, 1);
+        p.translate(2, 3);
+        let q = pick(p, new(4, 5), true);
+        let _s = Shape::Segment { from: p, to: q };
+        apply!(q, |pt| pt.x)
+    }
+}
@@ -0,0 +1,11 @@
+module Signatures::transform {
+    use Signatures::geometry::{Self, Point};
+
+    public fun translate_both(a: &mut Point, b: &mut Point, d: u64) {
+        geometry::translate(a, d, d);
+        b.translate(
+            d,
+            geometry::pick(d, 0, true),
+        );
+    }
+}
@@ -5,12 +5,30 @@ use super::reroot_path;
 use clap::*;
 use move_compiler::compiled_unit::NamedCompiledModule;
 use move_coverage::{
-    coverage_map::CoverageMap, format_csv_summary, format_human_summary,
-    source_coverage::SourceCoverageBuilder, summary::summarize_inst_cov,
+    coverage_map::CoverageMap,
+    format_csv_summary, format_human_summary,
+    line_coverage::{format_cobertura, format_lcov, ModuleLineCoverage},
+    source_coverage::SourceCoverageBuilder,
+    summary::summarize_inst_cov,
 };
 use move_disassembler::disassembler::Disassembler;
 use move_package::BuildConfig;
-use std::path::Path;
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+const SOURCE_DATE_EPOCH: &str = "SOURCE_DATE_EPOCH";
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum CoverageExportFormat {
+    /// LCOV tracefile
+    Lcov,
+    /// Cobertura XML report
+    Cobertura,
+}
 
 #[derive(Parser)]
 pub enum CoverageSummaryOptions {
@@ -36,6 +54,20 @@ pub enum CoverageSummaryOptions {
         #[clap(long = "module")]
         module_name: String,
     },
+    /// Export line and function coverage of all modules in this package to a format consumed by
+    /// standard coverage tooling
+    #[clap(name = "export")]
+    Export {
+        #[clap(long = "format", value_enum)]
+        format: CoverageExportFormat,
+        /// File to write the coverage report to (written to stdout if not specified)
+        #[clap(long = "output", short = 'o')]
+        output: Option<PathBuf>,
+        /// Timestamp (in seconds since the Unix epoch) recorded in Cobertura reports. Defaults to
+        /// the value of `SOURCE_DATE_EPOCH` if it is set, and to the current time otherwise
+        #[clap(long = "timestamp")]
+        timestamp: Option<u64>,
+    },
 }
 
 /// Inspect test coverage for this package. A previous test run with the `--coverage` flag must
@@ -95,7 +127,57 @@ impl Coverage {
                 disassembler.add_coverage_map(coverage_map.to_unified_exec_map());
                 println!("{}", disassembler.disassemble()?);
             }
+            CoverageSummaryOptions::Export {
+                format,
+                output,
+                timestamp,
+            } => {
+                let coverage_map = coverage_map.to_unified_exec_map();
+                let line_coverage = package
+                    .root_modules()
+                    .map(|unit| {
+                        ModuleLineCoverage::new(
+                            &unit.unit.module,
+                            &unit.unit.source_map,
+                            &unit.source_path,
+                            &coverage_map,
+                        )
+                    })
+                    .collect::<anyhow::Result<Vec<_>>>()?;
+                let mut writer: Box<dyn Write> = match output {
+                    Some(output) => Box::new(BufWriter::new(File::create(output)?)),
+                    None => Box::new(std::io::stdout()),
+                };
+                match format {
+                    CoverageExportFormat::Lcov => format_lcov(&line_coverage, &mut writer)?,
+                    CoverageExportFormat::Cobertura => {
+                        let timestamp = match timestamp {
+                            Some(timestamp) => timestamp,
+                            None => report_timestamp()?,
+                        };
+                        format_cobertura(
+                            package.compiled_package_info.package_name.as_str(),
+                            &path.canonicalize()?,
+                            &line_coverage,
+                            timestamp,
+                            &mut writer,
+                        )?
+                    }
+                }
+                writer.flush()?;
+            }
         }
         Ok(())
     }
 }
+
+/// The time at which a report is generated, overridden by `SOURCE_DATE_EPOCH` to make reports
+/// reproducible.
+fn report_timestamp() -> anyhow::Result<u64> {
+    if let Ok(epoch) = std::env::var(SOURCE_DATE_EPOCH) {
+        return epoch
+            .parse()
+            .map_err(|_| anyhow::anyhow!("Invalid {SOURCE_DATE_EPOCH}: {epoch}"));
+    }
+    Ok(SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs())
+}
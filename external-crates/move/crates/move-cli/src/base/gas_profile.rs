@@ -0,0 +1,364 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use super::reroot_path;
+use anyhow::{bail, Context};
+use clap::*;
+use move_binary_format::file_format::FunctionDefinitionIndex;
+use move_command_line_common::files::FileHash;
+use move_core_types::account_address::AccountAddress;
+use move_package::{compilation::compiled_package::CompiledPackage, BuildConfig};
+use serde::Deserialize;
+use similar::{ChangeTag, TextDiff};
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+/// Attribute the gas used by a transaction, as recorded in a gas profile, to the source lines of
+/// this package. The package must be built from the same source as the code that was profiled.
+#[derive(Parser)]
+#[clap(name = "gas-profile")]
+pub struct GasProfile {
+    /// Gas profile of a transaction
+    #[clap(name = "profile")]
+    pub profile: PathBuf,
+    /// Only display the source of this module
+    #[clap(long = "module")]
+    pub module_name: Option<String>,
+    /// Gas profile of the same transaction to compare against, e.g. recorded before a change to
+    /// the package
+    #[clap(long = "diff")]
+    pub base_profile: Option<PathBuf>,
+    /// Path to the version of the package the base profile was recorded with, if it is different
+    /// from this package. Lines are matched between the two versions by their content.
+    #[clap(long = "base-path", requires = "base_profile")]
+    pub base_path: Option<PathBuf>,
+}
+
+/// The part of a gas profile that this command uses
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ProfileFile {
+    /// Gas used by each instruction, keyed by function and code offset
+    #[serde(default)]
+    instruction_gas: BTreeMap<String, BTreeMap<u16, u64>>,
+}
+
+/// Gas of a profile attributed to a package
+struct PackageGas {
+    /// The source files of the package, keyed by their path relative to the package
+    files: BTreeMap<PathBuf, FileGas>,
+    /// Gas used by each function, keyed by its name as recorded in the profile, including
+    /// functions that are not in the package
+    functions: BTreeMap<String, u64>,
+}
+
+struct FileGas {
+    modules: Vec<String>,
+    source: String,
+    line_starts: Vec<usize>,
+    /// Gas used by each line, keyed by line number (starting at 1)
+    lines: BTreeMap<usize, u64>,
+}
+
+impl GasProfile {
+    pub fn execute(self, path: Option<&Path>, config: BuildConfig) -> anyhow::Result<()> {
+        // read the profiles and resolve the base package before changing the current directory to
+        // the packages
+        let profile = read_profile(&self.profile)?;
+        let base_profile = self.base_profile.as_deref().map(read_profile).transpose()?;
+        let base_path = self
+            .base_path
+            .as_deref()
+            .map(Path::canonicalize)
+            .transpose()?;
+
+        let path = reroot_path(path)?;
+        let package = config.clone().compile_package(&path, &mut Vec::new())?;
+        let gas = PackageGas::new(&package, &path, &profile)?;
+        let base = match base_profile {
+            Some(base_profile) => {
+                let base_path = reroot_path(Some(base_path.as_deref().unwrap_or(&path)))?;
+                let base_package = config.compile_package(&base_path, &mut Vec::new())?;
+                Some(PackageGas::new(&base_package, &base_path, &base_profile)?)
+            }
+            None => None,
+        };
+        if let Some(module_name) = &self.module_name {
+            if !gas.files.values().any(|f| f.modules.contains(module_name)) {
+                bail!("Unable to find module '{module_name}' in package");
+            }
+        }
+        gas.output(
+            base.as_ref(),
+            self.module_name.as_deref(),
+            &mut std::io::stdout(),
+        )?;
+        Ok(())
+    }
+}
+
+fn read_profile(path: &Path) -> anyhow::Result<BTreeMap<String, BTreeMap<u16, u64>>> {
+    let contents = fs::read_to_string(path)
+        .with_context(|| format!("Unable to read gas profile {}", path.display()))?;
+    let profile: ProfileFile = serde_json::from_str(&contents)
+        .with_context(|| format!("Unable to parse gas profile {}", path.display()))?;
+    if profile.instruction_gas.is_empty() {
+        bail!(
+            "Gas profile {} does not record the gas used by each instruction. Record it again \
+            with a version of the profiler that supports source line attribution",
+            path.display()
+        );
+    }
+    Ok(profile.instruction_gas)
+}
+
+impl PackageGas {
+    fn new(
+        package: &CompiledPackage,
+        package_path: &Path,
+        instruction_gas: &BTreeMap<String, BTreeMap<u16, u64>>,
+    ) -> anyhow::Result<Self> {
+        let mut files = BTreeMap::new();
+        let mut units = vec![];
+        for unit in package.root_modules() {
+            let file_path = unit
+                .source_path
+                .strip_prefix(package_path)
+                .unwrap_or(&unit.source_path)
+                .to_path_buf();
+            if !files.contains_key(&file_path) {
+                let source = fs::read_to_string(&unit.source_path)?;
+                files.insert(file_path.clone(), FileGas::new(source));
+            }
+            let file = files.get_mut(&file_path).unwrap();
+            file.modules.push(unit.unit.name.to_string());
+            units.push((file_path, unit));
+        }
+
+        let mut functions = BTreeMap::new();
+        for (function, offsets) in instruction_gas {
+            functions.insert(function.clone(), offsets.values().sum());
+            // functions are named `<address>::<module>::<function>`
+            let mut parts = function.rsplitn(3, "::");
+            let (Some(function_name), Some(module_name), Some(address)) =
+                (parts.next(), parts.next(), parts.next())
+            else {
+                continue;
+            };
+            let Ok(address) = AccountAddress::from_hex_literal(address) else {
+                continue;
+            };
+            // modules of the package may be profiled at the address the package is published at
+            let Some((file_path, unit)) = units.iter().find(|(_, unit)| {
+                let module = &unit.unit.module;
+                unit.unit.name.as_str() == module_name
+                    && (*module.address() == address || *module.address() == AccountAddress::ZERO)
+            }) else {
+                continue;
+            };
+            let module = &unit.unit.module;
+            let source_map = &unit.unit.source_map;
+            let Some(idx) = module.function_defs().iter().position(|def| {
+                module
+                    .identifier_at(module.function_handle_at(def.function).name)
+                    .as_str()
+                    == function_name
+            }) else {
+                continue;
+            };
+            let idx = FunctionDefinitionIndex(idx as u16);
+            let definition_loc = source_map.get_function_source_map(idx)?.definition_location;
+            let file = files.get_mut(file_path).unwrap();
+            let file_hash = FileHash::new(&file.source);
+            for (offset, gas) in offsets {
+                // code inlined from other files is attributed to the function definition
+                let loc = source_map
+                    .get_code_location(idx, *offset)
+                    .ok()
+                    .filter(|loc| loc.file_hash() == file_hash)
+                    .unwrap_or(definition_loc);
+                *file
+                    .lines
+                    .entry(file.line(loc.start() as usize))
+                    .or_default() += gas;
+            }
+        }
+        Ok(Self { files, functions })
+    }
+
+    fn total(&self) -> u64 {
+        self.functions.values().sum()
+    }
+
+    fn output(
+        &self,
+        base: Option<&PackageGas>,
+        module_name: Option<&str>,
+        w: &mut impl Write,
+    ) -> anyhow::Result<()> {
+        match base {
+            Some(base) => writeln!(
+                w,
+                "Total gas: {} ({})",
+                self.total(),
+                format_change(self.total(), base.total())
+            )?,
+            None => writeln!(w, "Total gas: {}", self.total())?,
+        }
+
+        writeln!(w)?;
+        writeln!(w, "Gas by function:")?;
+        let mut functions = self.functions.keys().collect::<Vec<_>>();
+        if let Some(base) = base {
+            functions.extend(
+                base.functions
+                    .keys()
+                    .filter(|f| !self.functions.contains_key(*f)),
+            );
+        }
+        // most expensive functions first
+        functions.sort_by_key(|f| std::cmp::Reverse(self.functions.get(*f).copied().unwrap_or(0)));
+        for function in functions {
+            let gas = self.functions.get(function).copied().unwrap_or(0);
+            match base {
+                Some(base) => {
+                    let base_gas = base.functions.get(function).copied().unwrap_or(0);
+                    writeln!(
+                        w,
+                        "{gas:>12} {:>10}  {function}",
+                        format_change(gas, base_gas)
+                    )?
+                }
+                None => writeln!(w, "{gas:>12}  {function}")?,
+            }
+        }
+
+        for (file_path, file) in &self.files {
+            if let Some(module_name) = module_name {
+                if !file.modules.iter().any(|m| m == module_name) {
+                    continue;
+                }
+            } else if file.lines.is_empty() {
+                continue;
+            }
+            let base_file = base.and_then(|base| base.files.get(file_path));
+            writeln!(w)?;
+            file.output(file_path, base.map(|_| base_file), w)?;
+        }
+        Ok(())
+    }
+}
+
+impl FileGas {
+    fn new(source: String) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(source.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+        Self {
+            modules: vec![],
+            source,
+            line_starts,
+            lines: BTreeMap::new(),
+        }
+    }
+
+    /// Returns the line number (starting at 1) of the byte offset
+    fn line(&self, offset: usize) -> usize {
+        self.line_starts.partition_point(|start| *start <= offset)
+    }
+
+    fn total(&self) -> u64 {
+        self.lines.values().sum()
+    }
+
+    /// Outputs the source of the file annotated with the gas used by each line. When comparing
+    /// against a base profile, `base` holds the file in the base version of the package, if it
+    /// exists, and lines are annotated with the change in gas since the base profile.
+    fn output(
+        &self,
+        file_path: &Path,
+        base: Option<Option<&FileGas>>,
+        w: &mut impl Write,
+    ) -> anyhow::Result<()> {
+        let Some(base) = base else {
+            writeln!(w, "{} (gas: {})", file_path.display(), self.total())?;
+            for (i, text) in self.source.lines().enumerate() {
+                match self.lines.get(&(i + 1)) {
+                    Some(gas) => writeln!(w, "{:>5} {gas:>12} | {text}", i + 1)?,
+                    None => writeln!(w, "{:>5} {:>12} | {text}", i + 1, "")?,
+                }
+            }
+            return Ok(());
+        };
+
+        let base_total = base.map(|b| b.total()).unwrap_or(0);
+        writeln!(
+            w,
+            "{} (gas: {}, {})",
+            file_path.display(),
+            self.total(),
+            format_change(self.total(), base_total)
+        )?;
+        // match the lines of the two versions of the file by their content
+        let empty = String::new();
+        let base_source = base.map(|b| &b.source).unwrap_or(&empty);
+        let diff = TextDiff::from_lines(base_source.as_str(), self.source.as_str());
+        let mut removed = vec![];
+        for change in diff.iter_all_changes() {
+            let base_gas = change
+                .old_index()
+                .and_then(|i| base.and_then(|b| b.lines.get(&(i + 1))))
+                .copied();
+            let text = change.value().trim_end_matches(['\n', '\r']);
+            match change.tag() {
+                ChangeTag::Delete => {
+                    if let Some(base_gas) = base_gas {
+                        removed.push((change.old_index().unwrap() + 1, base_gas, text));
+                    }
+                }
+                ChangeTag::Equal | ChangeTag::Insert => {
+                    let line = change.new_index().unwrap() + 1;
+                    let gas = self.lines.get(&line).copied();
+                    match (gas, base_gas) {
+                        (None, None) => writeln!(w, "{line:>5} {:>12} {:>10} | {text}", "", "")?,
+                        (gas, base_gas) => {
+                            let gas = gas.unwrap_or(0);
+                            writeln!(
+                                w,
+                                "{line:>5} {gas:>12} {:>10} | {text}",
+                                format_change(gas, base_gas.unwrap_or(0))
+                            )?
+                        }
+                    }
+                }
+            }
+        }
+        if !removed.is_empty() {
+            writeln!(
+                w,
+                "Lines of the base version that used gas and were removed:"
+            )?;
+            for (line, base_gas, text) in removed {
+                writeln!(
+                    w,
+                    "{line:>5} {:>12} {:>10} | {text}",
+                    0,
+                    format_change(0, base_gas)
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn format_change(gas: u64, base_gas: u64) -> String {
+    if gas >= base_gas {
+        format!("+{}", gas - base_gas)
+    } else {
+        format!("-{}", base_gas - gas)
+    }
+}
@@ -0,0 +1,510 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use super::{
+    reroot_path,
+    test::{run_move_unit_tests_with_vfs, UnitTestResult},
+};
+use crate::NativeFunctionRecord;
+use anyhow::{anyhow, Result};
+use clap::*;
+use move_compiler::{
+    diagnostics,
+    parser::ast as P,
+    shared::{files::MappedFiles, known_attributes::TestingAttribute},
+    PASS_PARSER,
+};
+use move_ir_types::{location::Loc, sp};
+use move_package::{compilation::build_plan::BuildPlan, BuildConfig};
+use move_symbol_pool::Symbol;
+use move_unit_test::UnitTestingConfig;
+use move_vm_test_utils::gas_schedule::CostTable;
+use rayon::prelude::*;
+use std::{fmt, io::Write, path::Path};
+use vfs::{
+    impls::{memory::MemoryFS, overlay::OverlayFS, physical::PhysicalFS},
+    VfsPath,
+};
+
+/// Run mutation testing on this package: check that the package's unit tests fail when its
+/// source code is modified.
+///
+/// Mutants of the package's non-test code are generated by flipping comparison operators,
+/// swapping arithmetic operators, removing `assert!` calls and replacing the values of constants.
+/// The unit tests are run against each mutant, and mutants for which all tests still pass
+/// (surviving mutants) are reported, as they point to behavior that is not checked by the tests.
+#[derive(Parser)]
+#[clap(name = "mutate")]
+pub struct Mutate {
+    /// Bound the amount of gas used by any one test.
+    #[clap(name = "gas-limit", short = 'i', long = "gas-limit")]
+    pub gas_limit: Option<u64>,
+    /// An optional filter string to determine which unit tests to run against each mutant. A unit
+    /// test will be run only if it contains this string in its fully qualified
+    /// (<addr>::<module_name>::<fn_name>) name.
+    #[clap(name = "filter")]
+    pub filter: Option<String>,
+    /// List the mutants without running the tests.
+    #[clap(name = "list", short = 'l', long = "list")]
+    pub list: bool,
+    /// Number of mutants to test in parallel.
+    #[clap(
+        name = "num-threads",
+        default_value = "8",
+        short = 't',
+        long = "threads"
+    )]
+    pub num_threads: usize,
+}
+
+/// The kind of modification made to the source code to obtain a mutant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MutationOperator {
+    /// Replace a comparison operator with its negation (e.g., `<` with `>=`)
+    FlipComparison,
+    /// Replace an arithmetic operator with its counterpart (e.g., `+` with `-`)
+    SwapArithmetic,
+    /// Replace an `assert!` call with `()`
+    RemoveAssert,
+    /// Replace the value of a numeric or boolean constant
+    ReplaceConstant,
+}
+
+/// A modification of a single location in the package's source code.
+#[derive(Debug, Clone)]
+pub struct Mutant {
+    pub operator: MutationOperator,
+    pub loc: Loc,
+    /// The source code at `loc`
+    pub original: String,
+    /// The source code replacing the original one in the mutant
+    pub replacement: String,
+}
+
+/// The outcome of running unit tests against a mutant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MutantStatus {
+    /// At least one test failed
+    Killed,
+    /// All tests passed
+    Survived,
+}
+
+impl Mutate {
+    pub fn execute(
+        self,
+        path: Option<&Path>,
+        config: BuildConfig,
+        natives: Vec<NativeFunctionRecord>,
+        cost_table: Option<CostTable>,
+    ) -> anyhow::Result<()> {
+        // Mutated sources are provided to the compiler through a file system overlay, which
+        // requires absolute paths.
+        let rerooted_path = reroot_path(path)?.canonicalize()?;
+        let (files, mutants) = generate_mutants(&rerooted_path, config.clone())?;
+        if self.list {
+            for mutant in &mutants {
+                println!("{}", describe_mutant(&files, &rerooted_path, mutant));
+            }
+            return Ok(());
+        }
+
+        println!("Running Move unit tests on unmodified package");
+        let result = self.run_tests(&rerooted_path, None, &config, &natives, &cost_table)?;
+        if result == UnitTestResult::Failure {
+            anyhow::bail!(
+                "Unit tests fail on the unmodified package. \
+                All tests must pass before running mutation testing"
+            );
+        }
+
+        println!("Running Move unit tests on {} mutants", mutants.len());
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(self.num_threads)
+            .build()?;
+        let statuses = pool.install(|| {
+            mutants
+                .par_iter()
+                .map(|mutant| {
+                    let vfs_root = mutant_vfs_root(&files, mutant)?;
+                    let result = self.run_tests(
+                        &rerooted_path,
+                        Some(vfs_root),
+                        &config,
+                        &natives,
+                        &cost_table,
+                    )?;
+                    Ok(match result {
+                        UnitTestResult::Success => MutantStatus::Survived,
+                        UnitTestResult::Failure => MutantStatus::Killed,
+                    })
+                })
+                .collect::<Result<Vec<_>>>()
+        })?;
+
+        for (mutant, status) in mutants.iter().zip(&statuses) {
+            let status = match status {
+                MutantStatus::Killed => "KILLED  ",
+                MutantStatus::Survived => "SURVIVED",
+            };
+            println!(
+                "[ {status} ] {}",
+                describe_mutant(&files, &rerooted_path, mutant)
+            );
+        }
+        let killed = statuses
+            .iter()
+            .filter(|status| **status == MutantStatus::Killed)
+            .count();
+        let score = if mutants.is_empty() {
+            100.0
+        } else {
+            killed as f64 / mutants.len() as f64 * 100.0
+        };
+        println!(
+            "Mutation score: {score:.2}%. Total mutants: {}; killed: {killed}; survived: {}",
+            mutants.len(),
+            mutants.len() - killed
+        );
+        Ok(())
+    }
+
+    fn run_tests(
+        &self,
+        pkg_path: &Path,
+        vfs_root: Option<VfsPath>,
+        config: &BuildConfig,
+        natives: &[NativeFunctionRecord],
+        cost_table: &Option<CostTable>,
+    ) -> Result<UnitTestResult> {
+        // Build each run in its own directory, as runs for different mutants happen concurrently.
+        let install_dir = tempfile::tempdir()?;
+        let config = BuildConfig {
+            install_dir: Some(install_dir.path().to_path_buf()),
+            skip_fetch_latest_git_deps: true,
+            silence_warnings: true,
+            ..config.clone()
+        };
+        // Mutants are tested in parallel, so tests of a single mutant are run sequentially.
+        let unit_test_config = UnitTestingConfig {
+            filter: self.filter.clone(),
+            num_threads: 1,
+            ..UnitTestingConfig::default_with_bound(self.gas_limit)
+        };
+        let (result, _) = run_move_unit_tests_with_vfs(
+            pkg_path,
+            vfs_root,
+            config,
+            unit_test_config,
+            natives.to_vec(),
+            cost_table.clone(),
+            /* compute_coverage */ false,
+            &mut std::io::sink(),
+        )?;
+        Ok(result)
+    }
+}
+
+/// Parses the package at `pkg_path` and returns the mutants of its root package, along with the
+/// package's source files.
+fn generate_mutants(
+    pkg_path: &Path,
+    mut config: BuildConfig,
+) -> Result<(MappedFiles, Vec<Mutant>)> {
+    config.test_mode = false;
+    let resolution_graph =
+        config.resolution_graph_for_package(pkg_path, None, &mut std::io::stdout())?;
+    let root_package = resolution_graph.root_package();
+    let build_plan = BuildPlan::create(resolution_graph)?;
+
+    // Intercede in the compilation to collect mutants from the parsed program before resuming
+    // the rest of the compilation, so that mutants are only generated for a valid package.
+    let mut mutants = None;
+    build_plan.compile_with_driver(&mut std::io::stdout(), |compiler| {
+        let (files, parsing_result) = compiler.run::<PASS_PARSER>()?;
+        let (_, compiler) = diagnostics::unwrap_or_report_pass_diagnostics(&files, parsing_result);
+        let (compiler, parsed_program) = compiler.into_ast();
+        mutants = Some((
+            files.clone(),
+            find_mutants(&files, &parsed_program, root_package),
+        ));
+
+        let compilation_result = compiler.at_parser(parsed_program).build();
+        let (units, warnings) =
+            diagnostics::unwrap_or_report_pass_diagnostics(&files, compilation_result);
+        diagnostics::report_warnings(&files, warnings);
+        Ok((files, units))
+    })?;
+    mutants.ok_or_else(|| anyhow!("Package {root_package} was not compiled"))
+}
+
+/// Finds the mutants of the non-test code of the package named `package`. Mutations never change
+/// the type of an expression, so all mutants of a valid package compile.
+pub fn find_mutants(files: &MappedFiles, program: &P::Program, package: Symbol) -> Vec<Mutant> {
+    let mut collector = MutantCollector {
+        files,
+        mutants: vec![],
+    };
+    for pkg_def in &program.source_definitions {
+        if pkg_def.package != Some(package) {
+            continue;
+        }
+        match &pkg_def.def {
+            P::Definition::Module(mdef) => collector.module(mdef),
+            P::Definition::Address(adef) => adef.modules.iter().for_each(|m| collector.module(m)),
+        }
+    }
+    collector.mutants
+}
+
+struct MutantCollector<'a> {
+    files: &'a MappedFiles,
+    mutants: Vec<Mutant>,
+}
+
+impl MutantCollector<'_> {
+    fn add(&mut self, operator: MutationOperator, loc: Loc, replacement: impl Into<String>) {
+        let Some(original) = self.files.source_of_loc_opt(&loc) else {
+            return;
+        };
+        self.mutants.push(Mutant {
+            operator,
+            loc,
+            original: original.to_string(),
+            replacement: replacement.into(),
+        });
+    }
+
+    fn module(&mut self, mdef: &P::ModuleDefinition) {
+        if mdef.is_spec_module || is_test_code(&mdef.attributes) {
+            return;
+        }
+        for member in &mdef.members {
+            match member {
+                P::ModuleMember::Function(fdef) if !is_test_code(&fdef.attributes) => {
+                    if let P::FunctionBody_::Defined(seq) = &fdef.body.value {
+                        self.sequence(seq);
+                    }
+                }
+                P::ModuleMember::Constant(cdef) if !is_test_code(&cdef.attributes) => {
+                    self.constant(cdef)
+                }
+                _ => (),
+            }
+        }
+    }
+
+    fn constant(&mut self, cdef: &P::Constant) {
+        // Error constants are typically referred to by name in the expected failures of tests, so
+        // changing their values does not change the outcome of tests.
+        if is_error_constant_name(cdef.name.0.value.as_str()) {
+            return;
+        }
+        let sp!(loc, P::Exp_::Value(sp!(_, value))) = &cdef.value else {
+            return;
+        };
+        let replacement = match value {
+            P::Value_::Bool(b) => (!b).to_string(),
+            P::Value_::Num(num) => replace_num(num.as_str()),
+            P::Value_::Address(_) | P::Value_::HexString(_) | P::Value_::ByteString(_) => return,
+        };
+        self.add(MutationOperator::ReplaceConstant, *loc, replacement);
+    }
+
+    fn sequence(&mut self, (_, items, _, last): &P::Sequence) {
+        for item in items {
+            match &item.value {
+                P::SequenceItem_::Seq(e) | P::SequenceItem_::Bind(_, _, e) => self.exp(e),
+                P::SequenceItem_::Declare(_, _) => (),
+            }
+        }
+        if let Some(e) = last.as_ref() {
+            self.exp(e);
+        }
+    }
+
+    fn exp(&mut self, e: &P::Exp) {
+        use P::Exp_ as E;
+        match &e.value {
+            E::BinopExp(lhs, op, rhs) => {
+                self.exp(lhs);
+                self.binop(op);
+                self.exp(rhs);
+            }
+            E::Call(name, sp!(_, args)) => {
+                if is_assert_macro(name) {
+                    self.add(MutationOperator::RemoveAssert, e.loc, "()");
+                }
+                args.iter().for_each(|arg| self.exp(arg));
+            }
+            E::Move(_, e)
+            | E::Copy(_, e)
+            | E::Loop(e)
+            | E::Labeled(_, e)
+            | E::Lambda(_, _, e)
+            | E::Parens(e)
+            | E::Dereference(e)
+            | E::UnaryExp(_, e)
+            | E::Borrow(_, e)
+            | E::Dot(e, _)
+            | E::Cast(e, _)
+            | E::Annotate(e, _)
+            | E::DotUnresolved(_, e) => self.exp(e),
+            E::Pack(_, fields) => fields.iter().for_each(|(_, e)| self.exp(e)),
+            E::Vector(_, _, sp!(_, es)) | E::ExpList(es) => es.iter().for_each(|e| self.exp(e)),
+            E::IfElse(cond, if_true, if_false) => {
+                self.exp(cond);
+                self.exp(if_true);
+                if let Some(if_false) = if_false {
+                    self.exp(if_false);
+                }
+            }
+            E::Match(subject, sp!(_, arms)) => {
+                self.exp(subject);
+                for arm in arms {
+                    if let Some(guard) = &arm.value.guard {
+                        self.exp(guard);
+                    }
+                    self.exp(&arm.value.rhs);
+                }
+            }
+            E::While(e1, e2) | E::Assign(e1, e2) => {
+                self.exp(e1);
+                self.exp(e2);
+            }
+            E::Block(seq) => self.sequence(seq),
+            E::Abort(e) | E::Return(_, e) | E::Break(_, e) => {
+                if let Some(e) = e {
+                    self.exp(e);
+                }
+            }
+            E::DotCall(e, _, _, _, sp!(_, args)) | E::Index(e, sp!(_, args)) => {
+                self.exp(e);
+                args.iter().for_each(|arg| self.exp(arg));
+            }
+            E::Value(_)
+            | E::Name(_)
+            | E::Quant(..)
+            | E::Unit
+            | E::Continue(_)
+            | E::Spec(_)
+            | E::UnresolvedError => (),
+        }
+    }
+
+    fn binop(&mut self, op: &P::BinOp) {
+        use MutationOperator as M;
+        use P::BinOp_ as B;
+        let (operator, replacement) = match op.value {
+            B::Lt => (M::FlipComparison, B::Ge),
+            B::Ge => (M::FlipComparison, B::Lt),
+            B::Gt => (M::FlipComparison, B::Le),
+            B::Le => (M::FlipComparison, B::Gt),
+            B::Eq => (M::FlipComparison, B::Neq),
+            B::Neq => (M::FlipComparison, B::Eq),
+            B::Add => (M::SwapArithmetic, B::Sub),
+            B::Sub => (M::SwapArithmetic, B::Add),
+            B::Mul => (M::SwapArithmetic, B::Div),
+            B::Div => (M::SwapArithmetic, B::Mul),
+            B::Mod => (M::SwapArithmetic, B::Div),
+            _ => return,
+        };
+        self.add(operator, op.loc, replacement.symbol());
+    }
+}
+
+fn is_test_code(attributes: &[P::Attributes]) -> bool {
+    attributes
+        .iter()
+        .flat_map(|attrs| attrs.value.iter())
+        .any(|attr| {
+            matches!(
+                attr.value.attribute_name().value.as_str(),
+                TestingAttribute::TEST | TestingAttribute::TEST_ONLY | TestingAttribute::RAND_TEST
+            )
+        })
+}
+
+fn is_assert_macro(name: &P::NameAccessChain) -> bool {
+    matches!(
+        &name.value,
+        P::NameAccessChain_::Single(entry)
+            if entry.is_macro.is_some() && entry.name.value.as_str() == "assert"
+    )
+}
+
+/// Error constants are named `E<Name>` or `E_<NAME>` by convention.
+fn is_error_constant_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next() == Some('E') && chars.next().is_some_and(|c| c.is_uppercase() || c == '_')
+}
+
+/// Replaces a (possibly suffixed) numeric literal with `1` if its value is zero, and with `0`
+/// otherwise.
+fn replace_num(num: &str) -> String {
+    const SUFFIXES: &[&str] = &["u8", "u16", "u32", "u64", "u128", "u256"];
+    let (digits, suffix) = SUFFIXES
+        .iter()
+        .find_map(|suffix| Some((num.strip_suffix(suffix)?, *suffix)))
+        .unwrap_or((num, ""));
+    let digits = digits.strip_prefix("0x").unwrap_or(digits);
+    let is_zero = digits.chars().all(|c| c == '0' || c == '_');
+    format!("{}{suffix}", if is_zero { 1 } else { 0 })
+}
+
+/// Returns a file system where the file modified by the mutant contains the mutated source code,
+/// and all other files are read from disk.
+fn mutant_vfs_root(files: &MappedFiles, mutant: &Mutant) -> Result<VfsPath> {
+    let file_hash = mutant.loc.file_hash();
+    let (file_name, source) = files
+        .get(&file_hash)
+        .ok_or_else(|| anyhow::anyhow!("Missing source file for mutant"))?;
+    let mutated_source = format!(
+        "{}{}{}",
+        &source[..mutant.loc.start() as usize],
+        mutant.replacement,
+        &source[mutant.loc.end() as usize..]
+    );
+    let vfs_root = VfsPath::new(OverlayFS::new(&[
+        VfsPath::new(MemoryFS::new()),
+        VfsPath::new(PhysicalFS::new("/")),
+    ]));
+    vfs_root
+        .join(file_name.as_str())?
+        .create_file()?
+        .write_all(mutated_source.as_bytes())?;
+    Ok(vfs_root)
+}
+
+fn describe_mutant(files: &MappedFiles, pkg_path: &Path, mutant: &Mutant) -> String {
+    let file_path = files.file_path(&mutant.loc.file_hash());
+    let file_path = file_path.strip_prefix(pkg_path).unwrap_or(file_path);
+    let position = files
+        .position_opt(&mutant.loc)
+        .map(|pos| {
+            format!(
+                ":{}:{}",
+                pos.start.line_offset() + 1,
+                pos.start.column_offset() + 1
+            )
+        })
+        .unwrap_or_default();
+    format!(
+        "{}{position} {}: replaced `{}` with `{}`",
+        file_path.display(),
+        mutant.operator,
+        mutant.original,
+        mutant.replacement
+    )
+}
+
+impl fmt::Display for MutationOperator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MutationOperator::FlipComparison => write!(f, "flip comparison"),
+            MutationOperator::SwapArithmetic => write!(f, "swap arithmetic"),
+            MutationOperator::RemoveAssert => write!(f, "remove assert"),
+            MutationOperator::ReplaceConstant => write!(f, "replace constant"),
+        }
+    }
+}
@@ -15,9 +15,10 @@ use move_compiler::{
 };
 use move_coverage::coverage_map::{output_map_to_file, CoverageMap};
 use move_package::{compilation::build_plan::BuildPlan, BuildConfig};
-use move_unit_test::UnitTestingConfig;
+use move_unit_test::{test_reporter::TestReport, UnitTestingConfig};
 use move_vm_test_utils::gas_schedule::CostTable;
 use std::{io::Write, path::Path, process::ExitStatus};
+use vfs::VfsPath;
 // if windows
 #[cfg(target_family = "windows")]
 use std::os::windows::process::ExitStatusExt;
@@ -72,6 +73,11 @@ pub struct Test {
     // Enable tracing for tests
     #[clap(long = "trace-execution", value_name = "PATH")]
     pub trace_execution: Option<Option<String>>,
+
+    /// Write a report of the test results to a file, given as `<format>=<path>` where the format
+    /// is `junit` or `json`. Can be repeated to write several reports.
+    #[clap(long = "report", value_name = "FORMAT=PATH")]
+    pub reports: Vec<TestReport>,
 }
 
 impl Test {
@@ -113,6 +119,7 @@ impl Test {
             seed,
             rand_num_iters,
             trace_execution,
+            reports,
         } = self;
         UnitTestingConfig {
             gas_limit,
@@ -124,6 +131,7 @@ impl Test {
             seed,
             rand_num_iters,
             trace_execution,
+            reports,
             ..UnitTestingConfig::default_with_bound(None)
         }
     }
@@ -138,6 +146,31 @@ pub enum UnitTestResult {
 
 pub fn run_move_unit_tests<W: Write + Send>(
     pkg_path: &Path,
+    build_config: move_package::BuildConfig,
+    unit_test_config: UnitTestingConfig,
+    natives: Vec<NativeFunctionRecord>,
+    cost_table: Option<CostTable>,
+    compute_coverage: bool,
+    writer: &mut W,
+) -> Result<(UnitTestResult, Option<Diagnostics>)> {
+    run_move_unit_tests_with_vfs(
+        pkg_path,
+        None,
+        build_config,
+        unit_test_config,
+        natives,
+        cost_table,
+        compute_coverage,
+        writer,
+    )
+}
+
+/// Same as `run_move_unit_tests`, except that if `vfs_root` is provided, package sources are read
+/// through this virtual file system rather than directly from disk.
+#[allow(clippy::too_many_arguments)]
+pub fn run_move_unit_tests_with_vfs<W: Write + Send>(
+    pkg_path: &Path,
+    vfs_root: Option<VfsPath>,
     mut build_config: move_package::BuildConfig,
     mut unit_test_config: UnitTestingConfig,
     natives: Vec<NativeFunctionRecord>,
@@ -185,7 +218,10 @@ pub fn run_move_unit_tests<W: Write + Send>(
     }
 
     let root_package = resolution_graph.root_package();
-    let build_plan = BuildPlan::create(resolution_graph)?;
+    let mut build_plan = BuildPlan::create(resolution_graph)?;
+    if let Some(vfs_root) = vfs_root {
+        build_plan = build_plan.set_compiler_vfs_root(vfs_root);
+    }
 
     // Compile the package. We need to intercede in the compilation, process being performed by the
     // Move package system, to first grab the compilation env, construct the test plan from it, and
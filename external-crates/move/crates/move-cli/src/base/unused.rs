@@ -0,0 +1,406 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use super::reroot_path;
+use clap::*;
+use move_compiler::{
+    diagnostics::{self, warning_filters::WarningFilters},
+    expansion::ast::{ModuleIdent, ModuleIdent_, TargetKind, Visibility},
+    naming::ast as N,
+    parser::ast::{ConstantName, DatatypeName, FunctionName},
+    shared::{
+        files::MappedFiles, known_attributes::TestingAttribute, program_info::FunctionInfo,
+        Identifier,
+    },
+    typing::{ast as T, visitor::TypingVisitorContext},
+    PASS_TYPING,
+};
+use move_ir_types::{location::Loc, sp};
+use move_package::{compilation::build_plan::BuildPlan, BuildConfig};
+use move_symbol_pool::Symbol;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::Path,
+};
+
+/// Report unused code in the package at `path`. If no path is provided defaults to current directory.
+///
+/// Reported are functions that cannot be reached from any of the package's public or entry
+/// functions, structs, enums and constants that are never referenced, and public functions not
+/// called (even indirectly) from any entry function of the package.
+#[derive(Parser)]
+#[clap(name = "unused")]
+pub struct Unused {
+    /// Names of functions that are invoked by the runtime rather than by other Move functions and
+    /// should therefore always be considered reachable (e.g., module initializers).
+    #[clap(long = "root", value_name = "FUNCTION")]
+    pub extra_roots: Vec<String>,
+}
+
+/// A module member that is never used.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct UnusedMember {
+    pub module: ModuleIdent_,
+    pub name: Symbol,
+    pub loc: Loc,
+}
+
+/// Unused code found in a package, grouped by the reason why it is considered unused. Each
+/// group is sorted by module and member name.
+#[derive(Debug, Default)]
+pub struct UnusedCode {
+    /// Private and `public(package)` (non-entry) functions not reachable from any public or entry
+    /// function
+    pub unreachable_functions: Vec<UnusedMember>,
+    /// Public functions not reachable from any entry function
+    pub uncalled_public_functions: Vec<UnusedMember>,
+    /// Structs and enums never referenced outside of their own definition
+    pub unused_datatypes: Vec<UnusedMember>,
+    /// Constants never referenced
+    pub unused_constants: Vec<UnusedMember>,
+}
+
+impl Unused {
+    pub fn execute(self, path: Option<&Path>, config: BuildConfig) -> anyhow::Result<()> {
+        let rerooted_path = reroot_path(path)?;
+        let resolution_graph =
+            config.resolution_graph_for_package(&rerooted_path, None, &mut std::io::stdout())?;
+        let build_plan = BuildPlan::create(resolution_graph)?;
+
+        // Intercede in the compilation to analyze the typed AST before resuming the rest of the
+        // compilation, so that the package is built as usual.
+        let mut report = None;
+        build_plan.compile_with_driver(&mut std::io::stdout(), |compiler| {
+            let (files, compilation_result) = compiler.run::<PASS_TYPING>()?;
+            let (_, compiler) =
+                diagnostics::unwrap_or_report_pass_diagnostics(&files, compilation_result);
+            let (compiler, typed_program) = compiler.into_ast();
+            let unused = find_unused_code(&typed_program, &self.extra_roots);
+            report = Some(format_unused_code(
+                compiler.compilation_env().mapped_files(),
+                &unused,
+            ));
+
+            let compilation_result = compiler.at_typing(typed_program).build();
+            let (units, warnings) =
+                diagnostics::unwrap_or_report_pass_diagnostics(&files, compilation_result);
+            diagnostics::report_warnings(&files, warnings);
+            Ok((files, units))
+        })?;
+
+        if let Some(report) = report {
+            print!("{report}");
+        }
+        Ok(())
+    }
+}
+
+/// Finds unused code in the root package of a typed program. Functions whose names are listed
+/// in `extra_roots` are considered reachable in all modules of the package. Test code and
+/// macros (which are inlined at their call sites) are never reported.
+pub fn find_unused_code(program: &T::Program, extra_roots: &[String]) -> UnusedCode {
+    let is_root_module = |mident: &ModuleIdent| {
+        program.info.modules.get(mident).is_some_and(|minfo| {
+            matches!(
+                minfo.target_kind,
+                TargetKind::Source {
+                    is_root_package: true
+                }
+            )
+        })
+    };
+
+    let mut uses = UsesCollector::default();
+    for (mident, mdef) in program.modules.key_cloned_iter() {
+        if is_root_module(&mident) {
+            uses.visit_module(mident, mdef);
+        }
+    }
+
+    let mut unused = UnusedCode::default();
+    let mut public_roots = vec![];
+    let mut entry_roots = vec![];
+    for (mident, minfo) in program.info.modules.key_cloned_iter() {
+        if !is_root_module(&mident) || is_test_only(&minfo.attributes) {
+            continue;
+        }
+        for (_, fname, finfo) in &minfo.functions {
+            let key = (mident.value, *fname);
+            let is_root =
+                extra_roots.iter().any(|r| r.as_str() == fname.as_str()) || is_test(finfo);
+            if finfo.entry.is_some() || is_root {
+                entry_roots.push(key);
+            }
+            if matches!(finfo.visibility, Visibility::Public(_)) || finfo.entry.is_some() || is_root
+            {
+                public_roots.push(key);
+            }
+        }
+    }
+    let reachable_from_public = uses.reachable(public_roots);
+    let reachable_from_entry = uses.reachable(entry_roots);
+
+    for (mident, minfo) in program.info.modules.key_cloned_iter() {
+        if !is_root_module(&mident) || is_test_only(&minfo.attributes) {
+            continue;
+        }
+        let member = |name: Symbol, loc: Loc| UnusedMember {
+            module: mident.value,
+            name,
+            loc,
+        };
+        for (loc, fname, finfo) in &minfo.functions {
+            if finfo.entry.is_some()
+                || finfo.macro_.is_some()
+                || is_test(finfo)
+                || is_test_only(&finfo.attributes)
+            {
+                continue;
+            }
+            let key = (mident.value, *fname);
+            if matches!(finfo.visibility, Visibility::Public(_)) {
+                if !reachable_from_entry.contains(&key) {
+                    unused.uncalled_public_functions.push(member(*fname, loc));
+                }
+            } else if !reachable_from_public.contains(&key) {
+                unused.unreachable_functions.push(member(*fname, loc));
+            }
+        }
+        let datatypes = minfo
+            .structs
+            .iter()
+            .map(|(loc, name, sdef)| (loc, *name, &sdef.attributes))
+            .chain(
+                minfo
+                    .enums
+                    .iter()
+                    .map(|(loc, name, edef)| (loc, *name, &edef.attributes)),
+            );
+        for (loc, name, attributes) in datatypes {
+            if !is_test_only(attributes) && !uses.datatypes.contains(&(mident.value, name)) {
+                unused.unused_datatypes.push(member(name, loc));
+            }
+        }
+        for (loc, name, cinfo) in &minfo.constants {
+            if !is_test_only(&cinfo.attributes) && !uses.constants.contains(&(mident.value, *name))
+            {
+                unused.unused_constants.push(member(*name, loc));
+            }
+        }
+    }
+
+    unused.unreachable_functions.sort();
+    unused.uncalled_public_functions.sort();
+    unused.unused_datatypes.sort();
+    unused.unused_constants.sort();
+    unused
+}
+
+/// Renders unused code as a human-readable report, with a section for each kind of unused
+/// code.
+pub fn format_unused_code(files: &MappedFiles, unused: &UnusedCode) -> String {
+    let sections = [
+        (
+            "Functions unreachable from public or entry functions",
+            &unused.unreachable_functions,
+        ),
+        (
+            "Public functions not called from any entry function",
+            &unused.uncalled_public_functions,
+        ),
+        ("Unused structs and enums", &unused.unused_datatypes),
+        ("Unused constants", &unused.unused_constants),
+    ];
+    let mut report = String::new();
+    for (title, members) in sections {
+        if members.is_empty() {
+            continue;
+        }
+        report.push_str(&format!("{title}:\n"));
+        for UnusedMember { module, name, loc } in members {
+            let position = files
+                .position_opt(loc)
+                .map(|pos| {
+                    format!(
+                        " ({}:{}:{})",
+                        files.file_path(&loc.file_hash()).display(),
+                        pos.start.line_offset() + 1,
+                        pos.start.column_offset() + 1
+                    )
+                })
+                .unwrap_or_default();
+            report.push_str(&format!("    {module}::{name}{position}\n"));
+        }
+    }
+    if report.is_empty() {
+        report.push_str("No unused code found\n");
+    }
+    report
+}
+
+fn is_test(finfo: &FunctionInfo) -> bool {
+    finfo
+        .attributes
+        .contains_key_(&TestingAttribute::Test.into())
+        || finfo
+            .attributes
+            .contains_key_(&TestingAttribute::RandTest.into())
+}
+
+fn is_test_only(attributes: &move_compiler::expansion::ast::Attributes) -> bool {
+    attributes.contains_key_(&TestingAttribute::TestOnly.into())
+}
+
+type MemberKey = (ModuleIdent_, Symbol);
+
+/// Collects the call graph of the analyzed modules along with all datatypes and constants
+/// referenced by them.
+#[derive(Default)]
+struct UsesCollector {
+    /// Function currently being visited (if any)
+    current_function: Option<MemberKey>,
+    /// Datatype currently being visited (if any)
+    current_datatype: Option<MemberKey>,
+    calls: BTreeMap<MemberKey, BTreeSet<MemberKey>>,
+    datatypes: BTreeSet<MemberKey>,
+    constants: BTreeSet<MemberKey>,
+}
+
+impl UsesCollector {
+    /// Returns all functions reachable from the given ones, including themselves.
+    fn reachable(&self, roots: Vec<MemberKey>) -> BTreeSet<MemberKey> {
+        let mut reachable = BTreeSet::new();
+        let mut worklist = roots;
+        while let Some(fun) = worklist.pop() {
+            if !reachable.insert(fun) {
+                continue;
+            }
+            if let Some(callees) = self.calls.get(&fun) {
+                worklist.extend(callees.iter().copied());
+            }
+        }
+        reachable
+    }
+
+    fn visit_pattern(&mut self, pat: &T::MatchPattern) {
+        use T::UnannotatedPat_ as P;
+        match &pat.pat.value {
+            P::Constant(m, c) => {
+                self.constants.insert((m.value, c.value()));
+            }
+            P::Variant(_, _, _, _, fields)
+            | P::BorrowVariant(_, _, _, _, _, fields)
+            | P::Struct(_, _, _, fields)
+            | P::BorrowStruct(_, _, _, _, fields) => {
+                for (_, _, (_, (_, p))) in fields {
+                    self.visit_pattern(p);
+                }
+            }
+            P::Or(p1, p2) => {
+                self.visit_pattern(p1);
+                self.visit_pattern(p2);
+            }
+            P::At(_, p) => self.visit_pattern(p),
+            P::Binder(..) | P::Literal(_) | P::Wildcard | P::ErrorPat => (),
+        }
+    }
+}
+
+impl TypingVisitorContext for UsesCollector {
+    const VISIT_TYPES: bool = true;
+    const VISIT_LVALUES: bool = true;
+
+    fn push_warning_filter_scope(&mut self, _filters: WarningFilters) {}
+
+    fn pop_warning_filter_scope(&mut self) {}
+
+    fn visit_struct_custom(
+        &mut self,
+        module: ModuleIdent,
+        struct_name: DatatypeName,
+        _sdef: &N::StructDefinition,
+    ) -> bool {
+        self.current_function = None;
+        self.current_datatype = Some((module.value, struct_name.value()));
+        false
+    }
+
+    fn visit_enum_custom(
+        &mut self,
+        module: ModuleIdent,
+        enum_name: DatatypeName,
+        _edef: &N::EnumDefinition,
+    ) -> bool {
+        self.current_function = None;
+        self.current_datatype = Some((module.value, enum_name.value()));
+        false
+    }
+
+    fn visit_constant_custom(
+        &mut self,
+        _module: ModuleIdent,
+        _constant_name: ConstantName,
+        _cdef: &T::Constant,
+    ) -> bool {
+        self.current_function = None;
+        self.current_datatype = None;
+        false
+    }
+
+    fn visit_function_custom(
+        &mut self,
+        module: ModuleIdent,
+        function_name: FunctionName,
+        _fdef: &T::Function,
+    ) -> bool {
+        let key = (module.value, function_name.value());
+        self.calls.entry(key).or_default();
+        self.current_function = Some(key);
+        self.current_datatype = None;
+        false
+    }
+
+    fn visit_type_custom(&mut self, _exp_loc: Option<Loc>, ty: &N::Type) -> bool {
+        if let N::Type_::Apply(_, sp!(_, N::TypeName_::ModuleType(m, n)), _) = &ty.value {
+            let key = (m.value, n.value());
+            // a datatype referencing itself (e.g., in a field of an enum variant) does not make
+            // it used
+            if self.current_datatype != Some(key) {
+                self.datatypes.insert(key);
+            }
+        }
+        false
+    }
+
+    fn visit_exp_custom(&mut self, exp: &T::Exp) -> bool {
+        use T::UnannotatedExp_ as E;
+        match &exp.exp.value {
+            E::ModuleCall(call) => {
+                if let Some(caller) = self.current_function {
+                    self.calls
+                        .entry(caller)
+                        .or_default()
+                        .insert((call.module.value, call.name.value()));
+                }
+            }
+            E::Constant(m, c) => {
+                self.constants.insert((m.value, c.value()));
+            }
+            E::ErrorConstant {
+                error_constant: Some(c),
+                ..
+            } => {
+                if let Some((m, _)) = self.current_function {
+                    self.constants.insert((m, c.value()));
+                }
+            }
+            E::Match(_, arms) => {
+                for sp!(_, arm) in &arms.value {
+                    self.visit_pattern(&arm.pattern);
+                }
+            }
+            _ => (),
+        }
+        false
+    }
+}
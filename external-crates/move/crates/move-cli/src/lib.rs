@@ -3,8 +3,9 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use base::{
-    build::Build, coverage::Coverage, disassemble::Disassemble, docgen::Docgen, info::Info,
-    migrate::Migrate, new::New, test::Test,
+    build::Build, coverage::Coverage, disassemble::Disassemble, docgen::Docgen,
+    gas_profile::GasProfile, info::Info, migrate::Migrate, mutate::Mutate, new::New, test::Test,
+    unused::Unused,
 };
 use move_package::BuildConfig;
 
@@ -60,10 +61,13 @@ pub enum Command {
     Coverage(Coverage),
     Disassemble(Disassemble),
     Docgen(Docgen),
+    GasProfile(GasProfile),
     Info(Info),
     Migrate(Migrate),
+    Mutate(Mutate),
     New(New),
     Test(Test),
+    Unused(Unused),
     /// Execute a sandbox command.
     #[clap(name = "sandbox")]
     Sandbox {
@@ -94,8 +98,17 @@ pub fn run_cli(
             c.execute(move_args.package_path.as_deref(), move_args.build_config)
         }
         Command::Docgen(c) => c.execute(move_args.package_path.as_deref(), move_args.build_config),
+        Command::GasProfile(c) => {
+            c.execute(move_args.package_path.as_deref(), move_args.build_config)
+        }
         Command::Info(c) => c.execute(move_args.package_path.as_deref(), move_args.build_config),
         Command::Migrate(c) => c.execute(move_args.package_path.as_deref(), move_args.build_config),
+        Command::Mutate(c) => c.execute(
+            move_args.package_path.as_deref(),
+            move_args.build_config,
+            natives,
+            Some(cost_table.clone()),
+        ),
         Command::New(c) => c.execute_with_defaults(move_args.package_path.as_deref()),
         Command::Test(c) => c.execute(
             move_args.package_path.as_deref(),
@@ -103,6 +116,7 @@ pub fn run_cli(
             natives,
             Some(cost_table.clone()),
         ),
+        Command::Unused(c) => c.execute(move_args.package_path.as_deref(), move_args.build_config),
         Command::Sandbox { storage_dir, cmd } => {
             cmd.handle_command(natives, cost_table, &move_args, &storage_dir)
         }
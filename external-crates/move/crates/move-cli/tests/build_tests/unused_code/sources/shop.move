@@ -0,0 +1,69 @@
+module 0x42::shop;
+
+const EInsufficientFunds: u64 = 0;
+const MAX_ITEMS: u64 = 10;
+#[allow(unused_const)]
+const UNUSED_LIMIT: u64 = 100;
+
+public struct Item has drop { price: u64 }
+
+#[allow(unused_type_parameter)]
+public struct Receipt<phantom T> has drop { total: u64 }
+
+#[allow(unused_field)]
+public struct Unused has drop { value: u64 }
+
+public enum Status has drop {
+    Open,
+    Closed { reason: u64 },
+}
+
+#[allow(unused_function)]
+fun init() {
+    setup();
+}
+
+fun setup() {}
+
+entry fun buy(price: u64, funds: u64) {
+    assert!(funds >= price, EInsufficientFunds);
+    let _item = new_item(price);
+}
+
+public fun new_item(price: u64): Item {
+    check(price);
+    Item { price }
+}
+
+public fun open(): Status {
+    Status::Open
+}
+
+public fun status_code(s: &Status): u64 {
+    match (s) {
+        Status::Open => MAX_ITEMS,
+        Status::Closed { reason } => *reason,
+    }
+}
+
+public(package) fun receipt_total(r: &Receipt<Item>): u64 {
+    r.total
+}
+
+fun check(price: u64) {
+    assert!(price > 0, EInsufficientFunds);
+}
+
+#[allow(unused_function)]
+fun helper(): u64 {
+    dead_helper()
+}
+
+#[allow(unused_function)]
+fun dead_helper(): u64 {
+    0
+}
+
+public macro fun double($x: u64): u64 {
+    $x * 2
+}
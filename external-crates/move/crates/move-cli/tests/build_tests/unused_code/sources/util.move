@@ -0,0 +1,5 @@
+module 0x42::util;
+
+public fun shared(): u64 {
+    0x42::shop::status_code(&0x42::shop::open())
+}
@@ -0,0 +1,44 @@
+module std::Branches {
+
+    public fun clamp(x: u64, max: u64): u64 {
+        if (x > max) {
+            max
+        } else {
+            x
+        }
+    }
+
+    public fun both_positive(a: u64, b: u64): bool {
+        if (a > 0 && b > 0) {
+            true
+        } else {
+            false
+        }
+    }
+
+    public fun sum_to(n: u64): u64 {
+        let mut i = 0;
+        let mut sum = 0;
+        while (i < n) {
+            i = i + 1;
+            sum = sum + i;
+        };
+        sum
+    }
+
+    #[test]
+    fun test_clamp_low() {
+        assert!(clamp(1, 10) == 1, 0);
+    }
+
+    #[test]
+    fun test_both_positive() {
+        assert!(!both_positive(1, 0), 0);
+        assert!(both_positive(1, 1), 1);
+    }
+
+    #[test]
+    fun test_sum_to() {
+        assert!(sum_to(3) == 6, 0);
+    }
+}
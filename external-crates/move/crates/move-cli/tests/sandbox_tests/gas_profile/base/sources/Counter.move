@@ -0,0 +1,17 @@
+module std::Counter {
+    public struct Counter has drop {
+        values: vector<u64>,
+    }
+
+    public fun new(): Counter {
+        Counter { values: vector[] }
+    }
+
+    public fun push_all(c: &mut Counter, n: u64) {
+        let mut i = 0;
+        while (i < n) {
+            c.values.push_back(i);
+            i = i + 1;
+        }
+    }
+}
@@ -0,0 +1,41 @@
+module std::Counter {
+    const EOverflow: u64 = 0;
+    const MAX: u64 = 100;
+    const START: u64 = 0;
+
+    public struct Counter has drop {
+        value: u64,
+    }
+
+    public fun new(): Counter {
+        Counter { value: START }
+    }
+
+    public fun increment(c: &mut Counter, by: u64) {
+        assert!(c.value + by <= MAX, EOverflow);
+        c.value = c.value + by;
+    }
+
+    public fun is_full(c: &Counter): bool {
+        c.value == MAX
+    }
+
+    public fun value(c: &Counter): u64 {
+        c.value
+    }
+
+    #[test]
+    fun test_increment() {
+        let mut c = new();
+        increment(&mut c, 2);
+        increment(&mut c, 3);
+        assert!(value(&c) == 5, 0);
+    }
+
+    #[test]
+    #[expected_failure(abort_code = EOverflow)]
+    fun test_overflow() {
+        let mut c = new();
+        increment(&mut c, 101);
+    }
+}
@@ -10,12 +10,12 @@ use crate::{
     diagnostics::codes::{custom, DiagnosticInfo, Severity},
     naming::ast::TypeName_,
     shared::Identifier,
-    sui_mode::linters::{FREEZE_FUN, PUBLIC_FREEZE_FUN, SUI_PKG_NAME, TRANSFER_MOD_NAME},
+    sui_mode::linters::{
+        CAPABILITY_NAME_REGEX, FREEZE_FUN, PUBLIC_FREEZE_FUN, SUI_PKG_NAME, TRANSFER_MOD_NAME,
+    },
     typing::{ast as T, core, visitor::simple_visitor},
 };
 use move_ir_types::location::*;
-use once_cell::sync::Lazy;
-use regex::Regex;
 
 const FREEZE_CAPABILITY_DIAG: DiagnosticInfo = custom(
     LINT_WARNING_PREFIX,
@@ -30,8 +30,6 @@ const FREEZE_FUNCTIONS: &[(&str, &str, &str)] = &[
     (SUI_PKG_NAME, TRANSFER_MOD_NAME, FREEZE_FUN),
 ];
 
-static REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r".*Cap(?:[A-Z0-9_]+|ability|$).*").unwrap());
-
 simple_visitor!(
     WarnFreezeCapability,
     fn visit_module_custom(
@@ -72,7 +70,7 @@ fn check_type_arguments(context: &mut Context, fun: &T::ModuleCall, loc: Loc) {
         let Some(sp!(_, TypeName_::ModuleType(_, struct_name))) = type_arg.type_name() else {
             continue;
         };
-        if REGEX.is_match(struct_name.value().as_str()) {
+        if CAPABILITY_NAME_REGEX.is_match(struct_name.value().as_str()) {
             let msg = format!(
                 "The type {} is potentially a capability based on its name",
                 core::error_format_(type_arg, &core::Subst::empty()),
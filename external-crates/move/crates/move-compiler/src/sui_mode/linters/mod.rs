@@ -13,6 +13,8 @@ use crate::{
 };
 use move_ir_types::location::Loc;
 use move_symbol_pool::Symbol;
+use once_cell::sync::Lazy;
+use regex::Regex;
 
 pub mod coin_field;
 pub mod collection_equality;
@@ -20,10 +22,14 @@ pub mod custom_state_change;
 pub mod freeze_wrapped;
 pub mod freezing_capability;
 pub mod missing_key;
+pub mod public_capability_transfer;
 pub mod public_mut_tx_context;
 pub mod public_random;
 pub mod self_transfer;
+pub mod sender_authorization;
 pub mod share_owned;
+pub mod timestamp_comparison;
+pub mod unchecked_balance_subtraction;
 
 pub const SUI_PKG_NAME: &str = "sui";
 
@@ -40,6 +46,15 @@ pub const PUBLIC_RECEIVE_FUN: &str = "public_receive";
 pub const COIN_MOD_NAME: &str = "coin";
 pub const COIN_STRUCT_NAME: &str = "Coin";
 
+pub const BALANCE_MOD_NAME: &str = "balance";
+pub const VALUE_FUN: &str = "value";
+
+pub const CLOCK_MOD_NAME: &str = "clock";
+pub const TIMESTAMP_MS_FUN: &str = "timestamp_ms";
+
+pub const TX_CONTEXT_MOD_NAME: &str = "tx_context";
+pub const SENDER_FUN: &str = "sender";
+
 pub const BAG_MOD_NAME: &str = "bag";
 pub const BAG_STRUCT_NAME: &str = "Bag";
 
@@ -74,6 +89,10 @@ pub const PUBLIC_RANDOM_FILTER_NAME: &str = "public_random";
 pub const MISSING_KEY_FILTER_NAME: &str = "missing_key";
 pub const FREEZING_CAPABILITY_FILTER_NAME: &str = "freezing_capability";
 pub const PREFER_MUTABLE_TX_CONTEXT_FILTER_NAME: &str = "prefer_mut_tx_context";
+pub const PUBLIC_CAPABILITY_TRANSFER_FILTER_NAME: &str = "public_capability_transfer";
+pub const UNCHECKED_BALANCE_SUBTRACTION_FILTER_NAME: &str = "unchecked_balance_subtraction";
+pub const TIMESTAMP_COMPARISON_FILTER_NAME: &str = "timestamp_comparison";
+pub const SENDER_AUTHORIZATION_FILTER_NAME: &str = "sender_authorization";
 
 pub const RANDOM_MOD_NAME: &str = "random";
 pub const RANDOM_STRUCT_NAME: &str = "Random";
@@ -81,6 +100,11 @@ pub const RANDOM_GENERATOR_STRUCT_NAME: &str = "RandomGenerator";
 
 pub const INVALID_LOC: Loc = Loc::invalid();
 
+/// Matches names of types that are potentially capabilities (e.g., `AdminCap`, `OwnerCapV2` or
+/// `MintCapability`).
+pub static CAPABILITY_NAME_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r".*Cap(?:[A-Z0-9_]+|ability|$).*").unwrap());
+
 #[repr(u8)]
 pub enum LinterDiagnosticCode {
     ShareOwned,
@@ -93,6 +117,10 @@ pub enum LinterDiagnosticCode {
     MissingKey,
     FreezingCapability,
     PreferMutableTxContext,
+    PublicCapabilityTransfer,
+    UncheckedBalanceSubtraction,
+    TimestampComparison,
+    SenderAuthorization,
 }
 
 pub fn known_filters() -> (Option<Symbol>, Vec<WarningFilter>) {
@@ -158,6 +186,30 @@ pub fn known_filters() -> (Option<Symbol>, Vec<WarningFilter>) {
             LinterDiagnosticCode::PreferMutableTxContext as u8,
             Some(PREFER_MUTABLE_TX_CONTEXT_FILTER_NAME),
         ),
+        WarningFilter::code(
+            Some(LINT_WARNING_PREFIX),
+            LinterDiagnosticCategory::Sui as u8,
+            LinterDiagnosticCode::PublicCapabilityTransfer as u8,
+            Some(PUBLIC_CAPABILITY_TRANSFER_FILTER_NAME),
+        ),
+        WarningFilter::code(
+            Some(LINT_WARNING_PREFIX),
+            LinterDiagnosticCategory::Sui as u8,
+            LinterDiagnosticCode::UncheckedBalanceSubtraction as u8,
+            Some(UNCHECKED_BALANCE_SUBTRACTION_FILTER_NAME),
+        ),
+        WarningFilter::code(
+            Some(LINT_WARNING_PREFIX),
+            LinterDiagnosticCategory::Sui as u8,
+            LinterDiagnosticCode::TimestampComparison as u8,
+            Some(TIMESTAMP_COMPARISON_FILTER_NAME),
+        ),
+        WarningFilter::code(
+            Some(LINT_WARNING_PREFIX),
+            LinterDiagnosticCategory::Sui as u8,
+            LinterDiagnosticCode::SenderAuthorization as u8,
+            Some(SENDER_AUTHORIZATION_FILTER_NAME),
+        ),
     ];
 
     (Some(ALLOW_ATTR_CATEGORY.into()), filters)
@@ -181,6 +233,10 @@ pub fn linter_visitors(level: LintLevel) -> Vec<Visitor> {
             visitors.extend([
                 freezing_capability::WarnFreezeCapability.visitor(),
                 public_mut_tx_context::PreferMutableTxContext.visitor(),
+                public_capability_transfer::PublicCapabilityTransfer.visitor(),
+                unchecked_balance_subtraction::UncheckedBalanceSubtraction.visitor(),
+                timestamp_comparison::TimestampComparison.visitor(),
+                sender_authorization::SenderAuthorization.visitor(),
             ]);
             visitors
         }
@@ -0,0 +1,122 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Implements lint to warn against public or entry functions that transfer capability-like objects
+//! (with `store`, via `public_transfer`) without performing any checks. Such functions allow anyone
+//! to obtain the capability. A function is considered to perform checks if it may abort (e.g., via
+//! `assert!`) or if it takes a capability-like object as a parameter.
+
+use super::{
+    base_type, LinterDiagnosticCategory, LinterDiagnosticCode, CAPABILITY_NAME_REGEX,
+    LINT_WARNING_PREFIX, PUBLIC_TRANSFER_FUN, SUI_PKG_NAME, TRANSFER_MOD_NAME,
+};
+use crate::{
+    diag,
+    diagnostics::codes::{custom, DiagnosticInfo, Severity},
+    expansion::ast::{ModuleIdent, Visibility},
+    naming::ast::{self as N, TypeName_},
+    parser::ast::FunctionName,
+    shared::Identifier,
+    typing::{
+        ast as T, core,
+        visitor::{seq_satisfies, simple_visitor},
+    },
+};
+use move_ir_types::location::*;
+
+const PUBLIC_CAPABILITY_TRANSFER_DIAG: DiagnosticInfo = custom(
+    LINT_WARNING_PREFIX,
+    Severity::Warning,
+    LinterDiagnosticCategory::Sui as u8,
+    LinterDiagnosticCode::PublicCapabilityTransfer as u8,
+    "unchecked transfer of potential capability",
+);
+
+simple_visitor!(
+    PublicCapabilityTransfer,
+    fn visit_module_custom(&mut self, _ident: ModuleIdent, mdef: &T::ModuleDefinition) -> bool {
+        // skips if true
+        mdef.attributes.is_test_or_test_only()
+    },
+    fn visit_function_custom(
+        &mut self,
+        _module: ModuleIdent,
+        _function_name: FunctionName,
+        fdef: &T::Function,
+    ) -> bool {
+        if fdef.attributes.is_test_or_test_only()
+            || !(matches!(fdef.visibility, Visibility::Public(_)) || fdef.entry.is_some())
+        {
+            return true;
+        }
+        let T::FunctionBody_::Defined(seq) = &fdef.body.value else {
+            return true;
+        };
+        // the caller is authorized through a capability or transfers a capability it already owns
+        if fdef
+            .signature
+            .parameters
+            .iter()
+            .any(|(_, _, t)| is_capability_type(t))
+        {
+            return true;
+        }
+
+        let mut may_abort = false;
+        let mut transfers = vec![];
+        seq_satisfies(seq, |e| {
+            match &e.exp.value {
+                T::UnannotatedExp_::Abort(_) => may_abort = true,
+                T::UnannotatedExp_::Builtin(builtin, _)
+                    if matches!(builtin.value, T::BuiltinFunction_::Assert(_)) =>
+                {
+                    may_abort = true
+                }
+                T::UnannotatedExp_::ModuleCall(fun) if is_public_transfer(fun) => {
+                    if let Some(t) = fun.type_arguments.first().filter(|t| is_capability_type(t)) {
+                        transfers.push((e.exp.loc, t.clone()));
+                    }
+                }
+                _ => (),
+            }
+            false
+        });
+        if may_abort {
+            return true;
+        }
+        for (loc, t) in transfers {
+            report_unchecked_transfer(self, loc, &t);
+        }
+        true
+    }
+);
+
+fn is_public_transfer(fun: &T::ModuleCall) -> bool {
+    fun.module.value.is(SUI_PKG_NAME, TRANSFER_MOD_NAME)
+        && fun.name.value().as_str() == PUBLIC_TRANSFER_FUN
+}
+
+fn is_capability_type(t: &N::Type) -> bool {
+    let Some(bt) = base_type(t) else {
+        return false;
+    };
+    matches!(
+        bt.value.type_name(),
+        Some(sp!(_, TypeName_::ModuleType(_, struct_name)))
+            if CAPABILITY_NAME_REGEX.is_match(struct_name.value().as_str())
+    )
+}
+
+fn report_unchecked_transfer(context: &mut Context, loc: Loc, t: &N::Type) {
+    let msg = format!(
+        "The type {} is potentially a capability based on its name, and is transferred without \
+        any checks",
+        core::error_format(t, &core::Subst::empty()),
+    );
+    let mut diag = diag!(PUBLIC_CAPABILITY_TRANSFER_DIAG, (loc, msg));
+    diag.add_note(
+        "Anyone can call this function and obtain the capability. Authorize the caller before \
+        the transfer, e.g. by requiring another capability or asserting a condition",
+    );
+    context.add_diag(diag);
+}
@@ -0,0 +1,138 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Implements lint to warn against using the transaction sender for authorization in functions
+//! operating on shared objects. Shared objects can be used in transactions by anyone, and
+//! comparing `tx_context::sender` against an address ties access to a single account that cannot
+//! be rotated or delegated, which is better expressed by requiring a capability object. An object
+//! is considered shared if its type is passed to `share_object` or `public_share_object` anywhere
+//! in the program.
+
+use super::{
+    base_type, LinterDiagnosticCategory, LinterDiagnosticCode, LINT_WARNING_PREFIX,
+    PUBLIC_SHARE_FUN, SENDER_FUN, SHARE_FUN, SUI_PKG_NAME, TRANSFER_MOD_NAME, TX_CONTEXT_MOD_NAME,
+};
+use crate::{
+    diag,
+    diagnostics::codes::{custom, DiagnosticInfo, Severity},
+    naming::ast::{self as N, TypeName_},
+    parser::ast::BinOp_,
+    shared::Identifier,
+    typing::{
+        ast as T, core,
+        visitor::{seq_satisfies, simple_visitor},
+    },
+};
+use move_ir_types::location::*;
+
+const SENDER_AUTHORIZATION_DIAG: DiagnosticInfo = custom(
+    LINT_WARNING_PREFIX,
+    Severity::Warning,
+    LinterDiagnosticCategory::Sui as u8,
+    LinterDiagnosticCode::SenderAuthorization as u8,
+    "transaction sender used for authorization",
+);
+
+simple_visitor!(
+    SenderAuthorization,
+    fn visit(&mut self, program: &T::Program) {
+        let shared_types = shared_types(program);
+        if shared_types.is_empty() {
+            return;
+        }
+        for (_, mdef) in program.modules.key_cloned_iter() {
+            if mdef.attributes.is_test_or_test_only() {
+                continue;
+            }
+            self.push_warning_filter_scope(mdef.warning_filter);
+            for (_, fdef) in mdef.functions.key_cloned_iter() {
+                if fdef.attributes.is_test_or_test_only() {
+                    continue;
+                }
+                self.push_warning_filter_scope(fdef.warning_filter);
+                check_function(self, &shared_types, fdef);
+                self.pop_warning_filter_scope();
+            }
+            self.pop_warning_filter_scope();
+        }
+    }
+);
+
+/// Returns the names of the types of objects shared anywhere in the program.
+fn shared_types(program: &T::Program) -> Vec<TypeName_> {
+    let mut shared_types = vec![];
+    for (_, mdef) in program.modules.key_cloned_iter() {
+        for (_, fdef) in mdef.functions.key_cloned_iter() {
+            let T::FunctionBody_::Defined(seq) = &fdef.body.value else {
+                continue;
+            };
+            seq_satisfies(seq, |e| {
+                if let T::UnannotatedExp_::ModuleCall(fun) = &e.exp.value {
+                    if is_share_function(fun) {
+                        shared_types.extend(
+                            fun.type_arguments
+                                .first()
+                                .and_then(|t| t.value.type_name())
+                                .map(|tn| tn.value.clone()),
+                        );
+                    }
+                }
+                false
+            });
+        }
+    }
+    shared_types
+}
+
+fn check_function(context: &mut Context, shared_types: &[TypeName_], fdef: &T::Function) {
+    let T::FunctionBody_::Defined(seq) = &fdef.body.value else {
+        return;
+    };
+    let Some(shared_param) = fdef.signature.parameters.iter().find_map(|(_, _, t)| {
+        let bt = base_type(t)?;
+        shared_types
+            .contains(&bt.value.type_name()?.value)
+            .then_some(bt)
+    }) else {
+        return;
+    };
+    seq_satisfies(seq, |e| {
+        if let T::UnannotatedExp_::BinopExp(lhs, op, _, rhs) = &e.exp.value {
+            if matches!(op.value, BinOp_::Eq | BinOp_::Neq) && (is_sender(lhs) || is_sender(rhs)) {
+                report_sender_authorization(context, op.loc, shared_param);
+            }
+        }
+        false
+    });
+}
+
+fn is_share_function(fun: &T::ModuleCall) -> bool {
+    fun.module.value.is(SUI_PKG_NAME, TRANSFER_MOD_NAME)
+        && [SHARE_FUN, PUBLIC_SHARE_FUN].contains(&fun.name.value().as_str())
+}
+
+fn is_sender(e: &T::Exp) -> bool {
+    match &e.exp.value {
+        T::UnannotatedExp_::Annotate(e, _) => is_sender(e),
+        T::UnannotatedExp_::ModuleCall(fun) => {
+            fun.module.value.is(SUI_PKG_NAME, TX_CONTEXT_MOD_NAME)
+                && fun.name.value().as_str() == SENDER_FUN
+        }
+        _ => false,
+    }
+}
+
+fn report_sender_authorization(context: &mut Context, loc: Loc, shared_type: &N::Type) {
+    let msg = format!(
+        "The transaction sender is compared against an address in a function operating on the \
+        shared object type {}",
+        core::error_format(shared_type, &core::Subst::empty()),
+    );
+    let mut diag = diag!(SENDER_AUTHORIZATION_DIAG, (loc, msg));
+    diag.add_note(
+        "Shared objects can be used by anyone. Authorizing by sender address ties access to a \
+        single account that cannot be rotated or delegated. Consider requiring a capability \
+        object instead",
+    );
+    context.add_diag(diag);
+}
@@ -0,0 +1,220 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Implements lint to warn against suspicious comparisons of `sui::clock::timestamp_ms` results:
+//! - (in)equality checks (`==` and `!=`), which are unlikely to behave as intended, as the exact
+//!   timestamp of a transaction cannot be controlled.
+//! - strict and non-strict comparisons that disagree on whether the same bound is reached when
+//!   the timestamp is exactly equal to it, e.g. `assert!(timestamp_ms(clock) <= offer.expiry_ms)`
+//!   in one function and `assert!(timestamp_ms(clock) >= offer.expiry_ms)` in another, which both
+//!   pass at that instant. Bounds are identified by name within a module: the name of a field, of a
+//!   local or of a constant.
+
+use super::{
+    LinterDiagnosticCategory, LinterDiagnosticCode, CLOCK_MOD_NAME, LINT_WARNING_PREFIX,
+    SUI_PKG_NAME, TIMESTAMP_MS_FUN,
+};
+use crate::{
+    diag,
+    diagnostics::codes::{custom, DiagnosticInfo, Severity},
+    parser::ast::BinOp_,
+    shared::Identifier,
+    typing::{
+        ast as T,
+        visitor::{seq_satisfies, simple_visitor},
+    },
+};
+use move_ir_types::location::*;
+use move_symbol_pool::Symbol;
+
+const TIMESTAMP_COMPARISON_DIAG: DiagnosticInfo = custom(
+    LINT_WARNING_PREFIX,
+    Severity::Warning,
+    LinterDiagnosticCategory::Sui as u8,
+    LinterDiagnosticCode::TimestampComparison as u8,
+    "suspicious timestamp comparison",
+);
+
+/// A comparison of the current timestamp against a bound, written as `timestamp op bound`
+struct BoundComparison {
+    loc: Loc,
+    op: BinOp_,
+    bound: Symbol,
+    /// Whether the comparison treats the bound as reached when the timestamp is equal to it,
+    /// i.e. for `>=` and `<`
+    reached: bool,
+}
+
+simple_visitor!(
+    TimestampComparison,
+    fn visit(&mut self, program: &T::Program) {
+        for (_, mdef) in program.modules.key_cloned_iter() {
+            if mdef.attributes.is_test_or_test_only() {
+                continue;
+            }
+            let bound_comparisons = module_bound_comparisons(mdef);
+            self.push_warning_filter_scope(mdef.warning_filter);
+            for (_, fdef) in mdef.functions.key_cloned_iter() {
+                if fdef.attributes.is_test_or_test_only() {
+                    continue;
+                }
+                self.push_warning_filter_scope(fdef.warning_filter);
+                check_function(self, &bound_comparisons, fdef);
+                self.pop_warning_filter_scope();
+            }
+            self.pop_warning_filter_scope();
+        }
+    }
+);
+
+/// Returns the comparisons of the current timestamp against a bound in the module, in the order
+/// its functions are visited.
+fn module_bound_comparisons(mdef: &T::ModuleDefinition) -> Vec<BoundComparison> {
+    let mut comparisons = vec![];
+    for (_, fdef) in mdef.functions.key_cloned_iter() {
+        if fdef.attributes.is_test_or_test_only() {
+            continue;
+        }
+        let T::FunctionBody_::Defined(seq) = &fdef.body.value else {
+            continue;
+        };
+        seq_satisfies(seq, |e| {
+            comparisons.extend(bound_comparison(e));
+            false
+        });
+    }
+    comparisons
+}
+
+fn check_function(
+    context: &mut Context,
+    module_comparisons: &[BoundComparison],
+    fdef: &T::Function,
+) {
+    let T::FunctionBody_::Defined(seq) = &fdef.body.value else {
+        return;
+    };
+    seq_satisfies(seq, |e| {
+        let T::UnannotatedExp_::BinopExp(lhs, op, _, rhs) = &e.exp.value else {
+            return false;
+        };
+        if matches!(op.value, BinOp_::Eq | BinOp_::Neq) && (is_timestamp(lhs) || is_timestamp(rhs))
+        {
+            report_equality(context, op.loc, &op.value);
+        }
+        let Some(comparison) = bound_comparison(e) else {
+            return false;
+        };
+        // only report a comparison against the first preceding one it disagrees with, so that
+        // each disagreeing pair is reported once
+        let preceding = module_comparisons
+            .iter()
+            .take_while(|other| other.loc != comparison.loc);
+        if let Some(other) = preceding
+            .filter(|other| other.bound == comparison.bound)
+            .find(|other| other.reached != comparison.reached)
+        {
+            report_inconsistent_bound(context, &comparison, other);
+        }
+        false
+    });
+}
+
+/// Returns the comparison if `e` compares the current timestamp against a named bound with `<`,
+/// `<=`, `>` or `>=`.
+fn bound_comparison(e: &T::Exp) -> Option<BoundComparison> {
+    let T::UnannotatedExp_::BinopExp(lhs, op, _, rhs) = &e.exp.value else {
+        return None;
+    };
+    let (bound, op) = if is_timestamp(lhs) {
+        (rhs, op.value)
+    } else if is_timestamp(rhs) {
+        let flipped = match op.value {
+            BinOp_::Lt => BinOp_::Gt,
+            BinOp_::Le => BinOp_::Ge,
+            BinOp_::Gt => BinOp_::Lt,
+            BinOp_::Ge => BinOp_::Le,
+            _ => return None,
+        };
+        (lhs, flipped)
+    } else {
+        return None;
+    };
+    let reached = match op {
+        BinOp_::Ge | BinOp_::Lt => true,
+        BinOp_::Gt | BinOp_::Le => false,
+        _ => return None,
+    };
+    Some(BoundComparison {
+        loc: e.exp.loc,
+        op,
+        bound: bound_name(bound)?,
+        reached,
+    })
+}
+
+/// Returns the name identifying a bound: the name of the field, local or constant it is read from
+fn bound_name(e: &T::Exp) -> Option<Symbol> {
+    use T::UnannotatedExp_ as E;
+    match &e.exp.value {
+        E::Dereference(e) | E::Annotate(e, _) | E::TempBorrow(_, e) => bound_name(e),
+        E::Borrow(_, _, field) => Some(field.value()),
+        E::Move { var, .. } | E::Copy { var, .. } | E::Use(var) | E::BorrowLocal(_, var) => {
+            Some(var.value.name)
+        }
+        E::Constant(_, c) => Some(c.value()),
+        _ => None,
+    }
+}
+
+fn is_timestamp(e: &T::Exp) -> bool {
+    match &e.exp.value {
+        T::UnannotatedExp_::Annotate(e, _) => is_timestamp(e),
+        T::UnannotatedExp_::ModuleCall(fun) => {
+            fun.module.value.is(SUI_PKG_NAME, CLOCK_MOD_NAME)
+                && fun.name.value().as_str() == TIMESTAMP_MS_FUN
+        }
+        _ => false,
+    }
+}
+
+fn report_equality(context: &mut Context, loc: Loc, op: &BinOp_) {
+    let msg = format!("Comparison '{op}' with the current timestamp");
+    let mut diag = diag!(TIMESTAMP_COMPARISON_DIAG, (loc, msg));
+    diag.add_note(
+        "The timestamp of a transaction is unlikely to be exactly equal to a given value. \
+        Consider comparing against a range of timestamps with '<', '<=', '>' or '>='",
+    );
+    context.add_diag(diag);
+}
+
+fn report_inconsistent_bound(
+    context: &mut Context,
+    comparison: &BoundComparison,
+    other: &BoundComparison,
+) {
+    let reached = |c: &BoundComparison| if c.reached { "reached" } else { "not reached" };
+    let msg = format!(
+        "Comparison 'timestamp {} {}' treats '{}' as {} when the current timestamp is equal to it",
+        comparison.op,
+        comparison.bound,
+        comparison.bound,
+        reached(comparison),
+    );
+    let other_msg = format!(
+        "But 'timestamp {} {}' treats it as {}",
+        other.op,
+        other.bound,
+        reached(other),
+    );
+    let mut diag = diag!(
+        TIMESTAMP_COMPARISON_DIAG,
+        (comparison.loc, msg),
+        (other.loc, other_msg),
+    );
+    diag.add_note(
+        "At that instant, both or neither of these conditions hold. Consider using '>=' with '<', \
+        or '>' with '<=', for all comparisons against the same bound",
+    );
+    context.add_diag(diag);
+}
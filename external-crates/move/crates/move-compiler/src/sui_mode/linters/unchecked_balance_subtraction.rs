@@ -0,0 +1,140 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Implements lint to warn against `u64` subtractions from balances that are not checked against
+//! the subtracted amount beforehand. Such subtractions abort with an arithmetic error instead of a
+//! meaningful error code when the balance is insufficient. Balances are fields whose names contain
+//! "balance", as well as values of `sui::balance::Balance` and `sui::coin::Coin` objects. A
+//! subtraction is considered checked if the function compares the same balance, read from the same
+//! path, with `<`, `<=`, `>` or `>=` before the subtraction.
+
+use super::{
+    LinterDiagnosticCategory, LinterDiagnosticCode, BALANCE_MOD_NAME, COIN_MOD_NAME,
+    LINT_WARNING_PREFIX, SUI_PKG_NAME, VALUE_FUN,
+};
+use crate::{
+    diag,
+    diagnostics::codes::{custom, DiagnosticInfo, Severity},
+    expansion::ast::ModuleIdent,
+    naming::ast::BuiltinTypeName_,
+    parser::ast::{BinOp_, FunctionName},
+    shared::Identifier,
+    typing::{
+        ast as T,
+        visitor::{seq_satisfies, simple_visitor},
+    },
+};
+use move_ir_types::location::*;
+
+const UNCHECKED_BALANCE_SUBTRACTION_DIAG: DiagnosticInfo = custom(
+    LINT_WARNING_PREFIX,
+    Severity::Warning,
+    LinterDiagnosticCategory::Sui as u8,
+    LinterDiagnosticCode::UncheckedBalanceSubtraction as u8,
+    "unchecked subtraction from balance",
+);
+
+simple_visitor!(
+    UncheckedBalanceSubtraction,
+    fn visit_module_custom(&mut self, _ident: ModuleIdent, mdef: &T::ModuleDefinition) -> bool {
+        // skips if true
+        mdef.attributes.is_test_or_test_only()
+    },
+    fn visit_function_custom(
+        &mut self,
+        _module: ModuleIdent,
+        _function_name: FunctionName,
+        fdef: &T::Function,
+    ) -> bool {
+        if fdef.attributes.is_test_or_test_only() {
+            return true;
+        }
+        let T::FunctionBody_::Defined(seq) = &fdef.body.value else {
+            return true;
+        };
+
+        // expressions are visited in source order, so only the checks preceding a subtraction
+        // have been collected when it is visited
+        let mut unchecked_subtractions = vec![];
+        let mut checked_balances = vec![];
+        seq_satisfies(seq, |e| {
+            let T::UnannotatedExp_::BinopExp(lhs, op, t, rhs) = &e.exp.value else {
+                return false;
+            };
+            match op.value {
+                BinOp_::Sub
+                    if t.value.builtin_name().map(|b| &b.value) == Some(&BuiltinTypeName_::U64) =>
+                {
+                    if let Some(balance) = balance_path(lhs) {
+                        if !checked_balances.contains(&balance) {
+                            unchecked_subtractions.push((op.loc, balance));
+                        }
+                    }
+                }
+                BinOp_::Lt | BinOp_::Le | BinOp_::Gt | BinOp_::Ge => {
+                    checked_balances.extend(balance_path(lhs));
+                    checked_balances.extend(balance_path(rhs));
+                }
+                _ => (),
+            }
+            false
+        });
+        for (loc, balance) in unchecked_subtractions {
+            report_unchecked_subtraction(self, loc, &balance);
+        }
+        true
+    }
+);
+
+/// Returns the balance read by the expression, if any, as the path it is read from: either a
+/// field whose name contains "balance" (e.g. `account.balance`), or the value of a balance or coin
+/// (e.g. `balance::value(vault.funds)`). Balances read from other expressions are not tracked.
+fn balance_path(e: &T::Exp) -> Option<String> {
+    use T::UnannotatedExp_ as E;
+    match &e.exp.value {
+        E::Dereference(e) | E::Annotate(e, _) => balance_path(e),
+        E::Borrow(_, _, field)
+            if field
+                .value()
+                .as_str()
+                .to_ascii_lowercase()
+                .contains("balance") =>
+        {
+            path(e)
+        }
+        E::ModuleCall(fun)
+            if (fun.module.value.is(SUI_PKG_NAME, BALANCE_MOD_NAME)
+                || fun.module.value.is(SUI_PKG_NAME, COIN_MOD_NAME))
+                && fun.name.value().as_str() == VALUE_FUN =>
+        {
+            let arg = path(&fun.arguments)?;
+            Some(format!("{}::{VALUE_FUN}({arg})", fun.module.value.module))
+        }
+        _ => None,
+    }
+}
+
+/// Returns the path of a local or of a (nested) field of a local, e.g. `vault.funds`
+fn path(e: &T::Exp) -> Option<String> {
+    use T::UnannotatedExp_ as E;
+    match &e.exp.value {
+        E::Move { var, .. } | E::Copy { var, .. } | E::Use(var) | E::BorrowLocal(_, var) => {
+            Some(var.value.name.to_string())
+        }
+        E::Dereference(e) | E::Annotate(e, _) | E::TempBorrow(_, e) => path(e),
+        E::Borrow(_, e, field) => Some(format!("{}.{field}", path(e)?)),
+        _ => None,
+    }
+}
+
+fn report_unchecked_subtraction(context: &mut Context, loc: Loc, balance: &str) {
+    let msg = format!(
+        "Subtraction from '{balance}' is not preceded by a check that the balance is sufficient"
+    );
+    let mut diag = diag!(UNCHECKED_BALANCE_SUBTRACTION_DIAG, (loc, msg));
+    diag.add_note(
+        "An insufficient balance makes the subtraction abort with an arithmetic error. Consider \
+        asserting that the balance is sufficient first, with a meaningful error code",
+    );
+    context.add_diag(diag);
+}
@@ -0,0 +1,41 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+module a::test_suppression {
+    use sui::object::{Self, UID};
+    use sui::transfer;
+    use sui::tx_context::TxContext;
+
+    struct AdminCap has key, store {
+       id: UID
+    }
+
+    #[allow(lint(public_capability_transfer))]
+    public fun mint_admin_cap(recipient: address, ctx: &mut TxContext) {
+        transfer::public_transfer(AdminCap { id: object::new(ctx) }, recipient);
+    }
+}
+
+module sui::object {
+    use sui::tx_context::TxContext;
+
+    struct UID has store {
+        id: address,
+    }
+
+    const ZERO: u64 = 0;
+    public fun new(_: &mut TxContext): UID {
+        abort ZERO
+    }
+}
+
+module sui::transfer {
+    const ZERO: u64 = 0;
+    public fun public_transfer<T: key + store>(_: T, _: address) {
+        abort ZERO
+    }
+}
+
+module sui::tx_context {
+    struct TxContext has drop {}
+}
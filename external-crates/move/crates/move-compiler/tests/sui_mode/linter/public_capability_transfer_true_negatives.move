@@ -0,0 +1,68 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+module a::test_true_negatives {
+    use sui::object::{Self, UID};
+    use sui::transfer;
+    use sui::tx_context::TxContext;
+
+    struct AdminCap has key, store {
+       id: UID
+    }
+
+    struct Receipt has key, store {
+       id: UID
+    }
+
+    const ENotAuthorized: u64 = 0;
+
+    // the caller must already own a capability
+    public fun mint_with_cap(_: &AdminCap, recipient: address, ctx: &mut TxContext) {
+        transfer::public_transfer(AdminCap { id: object::new(ctx) }, recipient);
+    }
+
+    // the caller transfers a capability it owns
+    public fun give_cap(cap: AdminCap, recipient: address) {
+        transfer::public_transfer(cap, recipient);
+    }
+
+    // the transfer is guarded by a check
+    public fun mint_checked(recipient: address, allowed: bool, ctx: &mut TxContext) {
+        assert!(allowed, ENotAuthorized);
+        transfer::public_transfer(AdminCap { id: object::new(ctx) }, recipient);
+    }
+
+    // not a capability
+    public fun mint_receipt(recipient: address, ctx: &mut TxContext) {
+        transfer::public_transfer(Receipt { id: object::new(ctx) }, recipient);
+    }
+
+    // not callable from outside of the module
+    fun mint_private(recipient: address, ctx: &mut TxContext) {
+        transfer::public_transfer(AdminCap { id: object::new(ctx) }, recipient);
+    }
+}
+
+module sui::object {
+    use sui::tx_context::TxContext;
+
+    struct UID has store {
+        id: address,
+    }
+
+    const ZERO: u64 = 0;
+    public fun new(_: &mut TxContext): UID {
+        abort ZERO
+    }
+}
+
+module sui::transfer {
+    const ZERO: u64 = 0;
+    public fun public_transfer<T: key + store>(_: T, _: address) {
+        abort ZERO
+    }
+}
+
+module sui::tx_context {
+    struct TxContext has drop {}
+}
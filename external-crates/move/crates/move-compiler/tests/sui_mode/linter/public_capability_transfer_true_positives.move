@@ -0,0 +1,48 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+module a::test_true_positives {
+    use sui::object::{Self, UID};
+    use sui::transfer;
+    use sui::tx_context::TxContext;
+
+    struct AdminCap has key, store {
+       id: UID
+    }
+
+    struct MintCapability has key, store {
+       id: UID
+    }
+
+    public fun mint_admin_cap(recipient: address, ctx: &mut TxContext) {
+        transfer::public_transfer(AdminCap { id: object::new(ctx) }, recipient);
+    }
+
+    entry fun mint_cap(recipient: address, ctx: &mut TxContext) {
+        transfer::public_transfer(MintCapability { id: object::new(ctx) }, recipient);
+    }
+}
+
+module sui::object {
+    use sui::tx_context::TxContext;
+
+    struct UID has store {
+        id: address,
+    }
+
+    const ZERO: u64 = 0;
+    public fun new(_: &mut TxContext): UID {
+        abort ZERO
+    }
+}
+
+module sui::transfer {
+    const ZERO: u64 = 0;
+    public fun public_transfer<T: key + store>(_: T, _: address) {
+        abort ZERO
+    }
+}
+
+module sui::tx_context {
+    struct TxContext has drop {}
+}
@@ -0,0 +1,70 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+module a::test_true_negatives {
+    use sui::object::{Self, UID};
+    use sui::transfer;
+    use sui::tx_context::{Self, TxContext};
+
+    struct Pool has key {
+        id: UID,
+        fee: u64,
+    }
+
+    struct Wallet has key {
+        id: UID,
+        owner: address,
+        amount: u64,
+    }
+
+    struct AdminCap has key {
+        id: UID,
+    }
+
+    const ENotOwner: u64 = 0;
+
+    public fun create(ctx: &mut TxContext) {
+        transfer::share_object(Pool { id: object::new(ctx), fee: 0 });
+    }
+
+    // authorized through a capability
+    public fun set_fee(_: &AdminCap, pool: &mut Pool, fee: u64) {
+        pool.fee = fee;
+    }
+
+    // not a shared object
+    public fun withdraw(wallet: &mut Wallet, ctx: &mut TxContext) {
+        assert!(tx_context::sender(ctx) == wallet.owner, ENotOwner);
+        wallet.amount = 0;
+    }
+}
+
+module sui::object {
+    use sui::tx_context::TxContext;
+
+    struct UID has store {
+        id: address,
+    }
+
+    const ZERO: u64 = 0;
+    public fun new(_: &mut TxContext): UID {
+        abort ZERO
+    }
+}
+
+module sui::transfer {
+    const ZERO: u64 = 0;
+    public fun share_object<T: key>(_: T) {
+        abort ZERO
+    }
+}
+
+module sui::tx_context {
+    struct TxContext has drop {
+        sender: address,
+    }
+
+    public fun sender(self: &TxContext): address {
+        self.sender
+    }
+}
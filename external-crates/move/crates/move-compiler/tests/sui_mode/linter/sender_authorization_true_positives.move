@@ -0,0 +1,60 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+module a::test_true_positives {
+    use sui::object::{Self, UID};
+    use sui::transfer;
+    use sui::tx_context::{Self, TxContext};
+
+    struct Pool has key {
+        id: UID,
+        admin: address,
+        fee: u64,
+    }
+
+    const ENotAdmin: u64 = 0;
+
+    public fun create(ctx: &mut TxContext) {
+        transfer::share_object(Pool { id: object::new(ctx), admin: tx_context::sender(ctx), fee: 0 });
+    }
+
+    public fun set_fee(pool: &mut Pool, fee: u64, ctx: &mut TxContext) {
+        assert!(tx_context::sender(ctx) == pool.admin, ENotAdmin);
+        pool.fee = fee;
+    }
+
+    public fun reset_fee(pool: &mut Pool, ctx: &mut TxContext) {
+        if (pool.admin != tx_context::sender(ctx)) abort ENotAdmin;
+        pool.fee = 0;
+    }
+}
+
+module sui::object {
+    use sui::tx_context::TxContext;
+
+    struct UID has store {
+        id: address,
+    }
+
+    const ZERO: u64 = 0;
+    public fun new(_: &mut TxContext): UID {
+        abort ZERO
+    }
+}
+
+module sui::transfer {
+    const ZERO: u64 = 0;
+    public fun share_object<T: key>(_: T) {
+        abort ZERO
+    }
+}
+
+module sui::tx_context {
+    struct TxContext has drop {
+        sender: address,
+    }
+
+    public fun sender(self: &TxContext): address {
+        self.sender
+    }
+}
@@ -0,0 +1,31 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+module a::test_suppression {
+    use sui::clock::{Self, Clock};
+
+    #[allow(lint(timestamp_comparison))]
+    public fun is_deadline(deadline_ms: u64, clock: &Clock): bool {
+        deadline_ms == clock::timestamp_ms(clock)
+    }
+
+    #[allow(lint(timestamp_comparison))]
+    public fun is_open(deadline_ms: u64, clock: &Clock): bool {
+        clock::timestamp_ms(clock) <= deadline_ms
+    }
+
+    #[allow(lint(timestamp_comparison))]
+    public fun is_closed(deadline_ms: u64, clock: &Clock): bool {
+        clock::timestamp_ms(clock) >= deadline_ms
+    }
+}
+
+module sui::clock {
+    struct Clock {
+        timestamp_ms: u64,
+    }
+
+    public fun timestamp_ms(clock: &Clock): u64 {
+        clock.timestamp_ms
+    }
+}
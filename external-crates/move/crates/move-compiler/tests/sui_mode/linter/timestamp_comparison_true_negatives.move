@@ -0,0 +1,59 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+module a::test_true_negatives {
+    use sui::clock::{Self, Clock};
+
+    const EExpired: u64 = 0;
+    const ENotExpired: u64 = 1;
+
+    struct Lock has drop {
+        unlock_time_ms: u64,
+        amount: u64,
+    }
+
+    struct Offer has drop {
+        expiry_ms: u64,
+    }
+
+    public fun is_unlocked(lock: &Lock, clock: &Clock): bool {
+        clock::timestamp_ms(clock) >= lock.unlock_time_ms
+    }
+
+    public fun is_expired(deadline_ms: u64, clock: &Clock): bool {
+        deadline_ms <= clock::timestamp_ms(clock)
+    }
+
+    // strict comparisons are fine as long as they agree with the others on the same bound
+    public fun is_locked(lock: &Lock, clock: &Clock): bool {
+        clock::timestamp_ms(clock) < lock.unlock_time_ms
+    }
+
+    public fun is_before(deadline_ms: u64, clock: &Clock): bool {
+        clock::timestamp_ms(clock) < deadline_ms
+    }
+
+    // exactly one of these asserts passes when the timestamp is `expiry_ms`
+    public fun accept(offer: &Offer, clock: &Clock) {
+        assert!(clock::timestamp_ms(clock) <= offer.expiry_ms, EExpired);
+    }
+
+    public fun reclaim(offer: &Offer, clock: &Clock) {
+        assert!(clock::timestamp_ms(clock) > offer.expiry_ms, ENotExpired);
+    }
+
+    // not a timestamp comparison
+    public fun is_large(lock: &Lock): bool {
+        lock.amount > 100
+    }
+}
+
+module sui::clock {
+    struct Clock {
+        timestamp_ms: u64,
+    }
+
+    public fun timestamp_ms(clock: &Clock): u64 {
+        clock.timestamp_ms
+    }
+}
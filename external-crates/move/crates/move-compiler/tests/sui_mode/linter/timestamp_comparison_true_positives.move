@@ -0,0 +1,44 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+module a::test_true_positives {
+    use sui::clock::{Self, Clock};
+
+    const EExpired: u64 = 0;
+    const ENotExpired: u64 = 1;
+
+    struct Lock has drop {
+        unlock_time_ms: u64,
+    }
+
+    struct Offer has drop {
+        expiry_ms: u64,
+    }
+
+    public fun is_unlock_time(lock: &Lock, clock: &Clock): bool {
+        clock::timestamp_ms(clock) == lock.unlock_time_ms
+    }
+
+    public fun is_not_expired(deadline_ms: u64, clock: &Clock): bool {
+        deadline_ms != clock::timestamp_ms(clock)
+    }
+
+    // both asserts pass when the timestamp is exactly `expiry_ms`
+    public fun accept(offer: &Offer, clock: &Clock) {
+        assert!(clock::timestamp_ms(clock) <= offer.expiry_ms, EExpired);
+    }
+
+    public fun reclaim(offer: &Offer, clock: &Clock) {
+        assert!(offer.expiry_ms <= clock::timestamp_ms(clock), ENotExpired);
+    }
+}
+
+module sui::clock {
+    struct Clock {
+        timestamp_ms: u64,
+    }
+
+    public fun timestamp_ms(clock: &Clock): u64 {
+        clock.timestamp_ms
+    }
+}
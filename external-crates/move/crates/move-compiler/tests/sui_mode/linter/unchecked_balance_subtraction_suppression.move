@@ -0,0 +1,13 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+#[allow(lint(unchecked_balance_subtraction))]
+module a::test_suppression {
+    struct Account has drop {
+        balance: u64,
+    }
+
+    public fun withdraw(account: &mut Account, amount: u64) {
+        account.balance = account.balance - amount;
+    }
+}
@@ -0,0 +1,53 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+module a::test_true_negatives {
+    use sui::balance::{Self, Balance};
+
+    struct Account has drop {
+        balance: u64,
+        count: u64,
+    }
+
+    struct Vault {
+        funds: Balance,
+    }
+
+    const EInsufficientBalance: u64 = 0;
+
+    public fun withdraw(account: &mut Account, amount: u64) {
+        assert!(account.balance >= amount, EInsufficientBalance);
+        account.balance = account.balance - amount;
+    }
+
+    public fun withdraw_if_possible(account: &mut Account, amount: u64) {
+        if (amount <= account.balance) {
+            account.balance = account.balance - amount;
+        }
+    }
+
+    public fun remaining(vault: &Vault, amount: u64): u64 {
+        assert!(balance::value(&vault.funds) > amount, EInsufficientBalance);
+        balance::value(&vault.funds) - amount
+    }
+
+    // not a balance
+    public fun decrement(account: &mut Account) {
+        account.count = account.count - 1;
+    }
+
+    // not subtracting from the balance
+    public fun deficit(account: &Account, amount: u64): u64 {
+        amount - account.balance
+    }
+}
+
+module sui::balance {
+    struct Balance has store {
+        value: u64,
+    }
+
+    public fun value(self: &Balance): u64 {
+        self.value
+    }
+}
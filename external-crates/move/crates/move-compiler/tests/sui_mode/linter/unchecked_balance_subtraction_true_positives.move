@@ -0,0 +1,65 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+module a::test_true_positives {
+    use sui::balance::{Self, Balance};
+
+    struct Account has drop {
+        balance: u64,
+    }
+
+    struct Vault {
+        funds: Balance,
+        locked_balance: u64,
+    }
+
+    const EInsufficientBalance: u64 = 0;
+
+    public fun withdraw(account: &mut Account, amount: u64) {
+        account.balance = account.balance - amount;
+    }
+
+    public fun remaining(vault: &Vault, amount: u64): u64 {
+        balance::value(&vault.funds) - amount
+    }
+
+    public fun unlock(vault: &mut Vault, amount: u64) {
+        // checks a different balance
+        assert!(account_balance(vault) >= amount, EInsufficientBalance);
+        vault.locked_balance = vault.locked_balance - amount;
+    }
+
+    public fun remaining_of(vault: &Vault, other: &Vault, amount: u64): u64 {
+        // checks the value of a different balance
+        assert!(balance::value(&other.funds) >= amount, EInsufficientBalance);
+        balance::value(&vault.funds) - amount
+    }
+
+    public fun transfer(from: &mut Account, to: &mut Account, amount: u64) {
+        // checks the balance of a different account
+        assert!(to.balance >= amount, EInsufficientBalance);
+        from.balance = from.balance - amount;
+        to.balance = to.balance + amount;
+    }
+
+    public fun withdraw_then_check(account: &mut Account, amount: u64) {
+        // checks the balance after subtracting from it
+        let remaining = account.balance - amount;
+        assert!(account.balance >= amount, EInsufficientBalance);
+        account.balance = remaining;
+    }
+
+    fun account_balance(vault: &Vault): u64 {
+        balance::value(&vault.funds)
+    }
+}
+
+module sui::balance {
+    struct Balance has store {
+        value: u64,
+    }
+
+    public fun value(self: &Balance): u64 {
+        self.value
+    }
+}
@@ -20,6 +20,19 @@ use std::{
 
 pub type FunctionCoverage = BTreeMap<u64, u64>;
 
+/// Outcomes of a conditional branch (`BrTrue` or `BrFalse`) instruction.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BranchCoverage {
+    /// Number of times the branch jumped to its target
+    pub taken: u64,
+    /// Number of times the branch fell through to the next instruction
+    pub not_taken: u64,
+}
+
+/// Outcomes of the conditional branches of a function, keyed by the offset of the branch
+/// instruction.
+pub type FunctionBranchCoverage = BTreeMap<u64, BranchCoverage>;
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CoverageMap {
     pub exec_maps: BTreeMap<String, ExecCoverageMap>,
@@ -30,6 +43,7 @@ pub struct ModuleCoverageMap {
     pub module_addr: AccountAddress,
     pub module_name: Identifier,
     pub function_maps: BTreeMap<Identifier, FunctionCoverage>,
+    pub branch_maps: BTreeMap<Identifier, FunctionBranchCoverage>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -38,6 +52,26 @@ pub struct ExecCoverageMap {
     pub module_maps: BTreeMap<(AccountAddress, Identifier), ModuleCoverageMap>,
 }
 
+/// Layout of coverage maps serialized before branch coverage was tracked, i.e. without
+/// `branch_maps`, so that their files can still be read.
+#[derive(Serialize, Deserialize)]
+struct LegacyCoverageMap {
+    exec_maps: BTreeMap<String, LegacyExecCoverageMap>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct LegacyExecCoverageMap {
+    exec_id: String,
+    module_maps: BTreeMap<(AccountAddress, Identifier), LegacyModuleCoverageMap>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct LegacyModuleCoverageMap {
+    module_addr: AccountAddress,
+    module_name: Identifier,
+    function_maps: BTreeMap<Identifier, FunctionCoverage>,
+}
+
 #[derive(Debug)]
 pub struct ExecCoverageMapWithModules {
     pub module_maps: BTreeMap<(String, AccountAddress, Identifier), ModuleCoverageMap>,
@@ -65,12 +99,25 @@ impl CoverageMap {
     ) -> Self {
         let file = File::open(&filename)
             .unwrap_or_else(|_| panic!("Unable to open coverage trace file '{:?}'", filename));
+        // conditional branch last executed in each execution whose outcome is not yet known, as
+        // (function context, branch offset, branch target)
+        let mut pending_branches: BTreeMap<String, (String, u64, u64)> = BTreeMap::new();
         for line in BufReader::new(file).lines() {
             let line = line.unwrap();
             let mut splits = line.split(',');
             let exec_id = splits.next().unwrap();
             let context = splits.next().unwrap();
             let pc = splits.next().unwrap().parse::<u64>().unwrap();
+            let branch_target = splits.next().and_then(parse_branch_target);
+
+            // the instruction executed right after a branch determines its outcome
+            let branch_outcome = pending_branches
+                .remove(exec_id)
+                .filter(|(branch_context, _, _)| branch_context == context)
+                .map(|(_, branch_pc, target)| (branch_pc, pc == target));
+            if let Some(target) = branch_target {
+                pending_branches.insert(exec_id.to_owned(), (context.to_owned(), pc, target));
+            }
 
             let mut context_segs: Vec<_> = context.split("::").collect();
             let is_script = context_segs.len() == 2;
@@ -79,6 +126,16 @@ impl CoverageMap {
                 let module_name = Identifier::new(context_segs.pop().unwrap()).unwrap();
                 let module_addr =
                     AccountAddress::from_hex_literal(context_segs.pop().unwrap()).unwrap();
+                if let Some((branch_pc, taken)) = branch_outcome {
+                    self.insert_branch(
+                        exec_id,
+                        module_addr,
+                        module_name.clone(),
+                        func_name.clone(),
+                        branch_pc,
+                        taken,
+                    );
+                }
                 self.insert(exec_id, module_addr, module_name, func_name, pc);
             } else {
                 // Don't count scripts (for now)
@@ -105,7 +162,18 @@ impl CoverageMap {
             .read_to_end(&mut bytes)
             .ok()
             .ok_or_else(|| format_err!("Unable to read coverage map"))?;
-        bcs::from_bytes(&bytes).map_err(|_| format_err!("Error deserializing coverage map"))
+        Self::from_bytes(&bytes)
+    }
+
+    /// Deserializes a coverage map, falling back to the layout used before branch coverage was
+    /// tracked. Coverage maps in that layout have no branch coverage.
+    fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        if let Ok(coverage_map) = bcs::from_bytes(bytes) {
+            return Ok(coverage_map);
+        }
+        let legacy: LegacyCoverageMap =
+            bcs::from_bytes(bytes).map_err(|_| format_err!("Error deserializing coverage map"))?;
+        Ok(legacy.into())
     }
 
     // add entries in a cascading manner
@@ -124,6 +192,33 @@ impl CoverageMap {
         exec_entry.insert(module_addr, module_name, func_name, pc);
     }
 
+    pub fn insert_branch(
+        &mut self,
+        exec_id: &str,
+        module_addr: AccountAddress,
+        module_name: Identifier,
+        func_name: Identifier,
+        pc: u64,
+        taken: bool,
+    ) {
+        let exec_entry = self
+            .exec_maps
+            .entry(exec_id.to_owned())
+            .or_insert_with(|| ExecCoverageMap::new(exec_id.to_owned()));
+        let branch_cov = if taken {
+            BranchCoverage {
+                taken: 1,
+                not_taken: 0,
+            }
+        } else {
+            BranchCoverage {
+                taken: 0,
+                not_taken: 1,
+            }
+        };
+        exec_entry.insert_branch_multi(module_addr, module_name, func_name, pc, branch_cov);
+    }
+
     pub fn to_unified_exec_map(&self) -> ExecCoverageMap {
         let mut unified_map = ExecCoverageMap::new(String::new());
         for (_, exec_map) in self.exec_maps.iter() {
@@ -139,6 +234,17 @@ impl CoverageMap {
                         );
                     }
                 }
+                for (func_name, branch_map) in module_map.branch_maps.iter() {
+                    for (pc, branch_cov) in branch_map.iter() {
+                        unified_map.insert_branch_multi(
+                            *module_addr,
+                            module_name.clone(),
+                            func_name.clone(),
+                            *pc,
+                            *branch_cov,
+                        );
+                    }
+                }
             }
         }
         unified_map
@@ -151,6 +257,7 @@ impl ModuleCoverageMap {
             module_addr,
             module_name,
             function_maps: BTreeMap::new(),
+            branch_maps: BTreeMap::new(),
         }
     }
 
@@ -164,15 +271,42 @@ impl ModuleCoverageMap {
         self.insert_multi(func_name, pc, 1);
     }
 
+    pub fn insert_branch_multi(
+        &mut self,
+        func_name: Identifier,
+        pc: u64,
+        branch_cov: BranchCoverage,
+    ) {
+        let func_entry = self.branch_maps.entry(func_name).or_default();
+        let pc_entry = func_entry.entry(pc).or_default();
+        pc_entry.taken += branch_cov.taken;
+        pc_entry.not_taken += branch_cov.not_taken;
+    }
+
+    /// Adds the hit counts and branch outcomes of `another` to those of this map.
     pub fn merge(&mut self, another: ModuleCoverageMap) {
-        for (key, val) in another.function_maps {
-            self.function_maps.entry(key).or_default().extend(val);
+        for (func_name, func_map) in another.function_maps {
+            for (pc, count) in func_map {
+                self.insert_multi(func_name.clone(), pc, count);
+            }
+        }
+        for (func_name, branch_map) in another.branch_maps {
+            for (pc, branch_cov) in branch_map {
+                self.insert_branch_multi(func_name.clone(), pc, branch_cov);
+            }
         }
     }
 
     pub fn get_function_coverage(&self, func_name: &IdentStr) -> Option<&FunctionCoverage> {
         self.function_maps.get(func_name)
     }
+
+    pub fn get_function_branch_coverage(
+        &self,
+        func_name: &IdentStr,
+    ) -> Option<&FunctionBranchCoverage> {
+        self.branch_maps.get(func_name)
+    }
 }
 
 impl ExecCoverageMap {
@@ -198,6 +332,21 @@ impl ExecCoverageMap {
         module_entry.insert_multi(func_name, pc, count);
     }
 
+    pub fn insert_branch_multi(
+        &mut self,
+        module_addr: AccountAddress,
+        module_name: Identifier,
+        func_name: Identifier,
+        pc: u64,
+        branch_cov: BranchCoverage,
+    ) {
+        let module_entry = self
+            .module_maps
+            .entry((module_addr, module_name.clone()))
+            .or_insert_with(|| ModuleCoverageMap::new(module_addr, module_name));
+        module_entry.insert_branch_multi(func_name, pc, branch_cov);
+    }
+
     pub fn insert(
         &mut self,
         module_addr: AccountAddress,
@@ -264,6 +413,36 @@ impl ExecCoverageMapWithModules {
     }
 }
 
+impl From<LegacyCoverageMap> for CoverageMap {
+    fn from(legacy: LegacyCoverageMap) -> Self {
+        let exec_maps = legacy
+            .exec_maps
+            .into_iter()
+            .map(|(exec_id, exec_map)| {
+                let module_maps = exec_map
+                    .module_maps
+                    .into_iter()
+                    .map(|(key, module_map)| {
+                        let module_map = ModuleCoverageMap {
+                            module_addr: module_map.module_addr,
+                            module_name: module_map.module_name,
+                            function_maps: module_map.function_maps,
+                            branch_maps: BTreeMap::new(),
+                        };
+                        (key, module_map)
+                    })
+                    .collect();
+                let exec_map = ExecCoverageMap {
+                    exec_id: exec_map.exec_id,
+                    module_maps,
+                };
+                (exec_id, exec_map)
+            })
+            .collect();
+        CoverageMap { exec_maps }
+    }
+}
+
 impl TraceMap {
     /// Takes in a file containing a raw VM trace, and returns an updated coverage map.
     pub fn update_from_trace_file<P: AsRef<Path>>(mut self, filename: P) -> Self {
@@ -332,9 +511,111 @@ impl TraceMap {
     }
 }
 
+/// Returns the target of a conditional branch from its rendering in a VM trace (e.g.,
+/// `BrTrue(5)`), or `None` for other instructions.
+fn parse_branch_target(instr: &str) -> Option<u64> {
+    instr
+        .strip_prefix("BrTrue(")
+        .or_else(|| instr.strip_prefix("BrFalse("))?
+        .strip_suffix(')')?
+        .parse()
+        .ok()
+}
+
 pub fn output_map_to_file<M: Serialize, P: AsRef<Path>>(file_name: P, data: &M) -> Result<()> {
     let bytes = bcs::to_bytes(data)?;
     let mut file = File::create(file_name)?;
     file.write_all(&bytes)?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ident(name: &str) -> Identifier {
+        Identifier::new(name).unwrap()
+    }
+
+    #[test]
+    fn deserialize_legacy_coverage_map() {
+        let module_key = (AccountAddress::ONE, ident("M"));
+        let legacy = LegacyCoverageMap {
+            exec_maps: BTreeMap::from([(
+                "exec".to_string(),
+                LegacyExecCoverageMap {
+                    exec_id: "exec".to_string(),
+                    module_maps: BTreeMap::from([(
+                        module_key.clone(),
+                        LegacyModuleCoverageMap {
+                            module_addr: AccountAddress::ONE,
+                            module_name: ident("M"),
+                            function_maps: BTreeMap::from([(
+                                ident("f"),
+                                BTreeMap::from([(0, 2), (3, 1)]),
+                            )]),
+                        },
+                    )]),
+                },
+            )]),
+        };
+        let bytes = bcs::to_bytes(&legacy).unwrap();
+
+        let coverage_map = CoverageMap::from_bytes(&bytes).unwrap();
+        let module_map = &coverage_map.exec_maps["exec"].module_maps[&module_key];
+        assert_eq!(
+            module_map.get_function_coverage(&ident("f")),
+            Some(&BTreeMap::from([(0, 2), (3, 1)]))
+        );
+        assert!(module_map.branch_maps.is_empty());
+
+        // the current layout still round-trips
+        let bytes = bcs::to_bytes(&coverage_map).unwrap();
+        let coverage_map = CoverageMap::from_bytes(&bytes).unwrap();
+        assert!(coverage_map.exec_maps["exec"]
+            .module_maps
+            .contains_key(&module_key));
+    }
+
+    #[test]
+    fn merge_sums_counts() {
+        let mut module_map = ModuleCoverageMap::new(AccountAddress::ONE, ident("M"));
+        module_map.insert_multi(ident("f"), 0, 2);
+        module_map.insert_branch_multi(
+            ident("f"),
+            1,
+            BranchCoverage {
+                taken: 1,
+                not_taken: 0,
+            },
+        );
+
+        let mut another = ModuleCoverageMap::new(AccountAddress::ONE, ident("M"));
+        another.insert_multi(ident("f"), 0, 3);
+        another.insert_multi(ident("f"), 2, 1);
+        another.insert_branch_multi(
+            ident("f"),
+            1,
+            BranchCoverage {
+                taken: 0,
+                not_taken: 4,
+            },
+        );
+
+        module_map.merge(another);
+        assert_eq!(
+            module_map.get_function_coverage(&ident("f")),
+            Some(&BTreeMap::from([(0, 5), (2, 1)]))
+        );
+        assert_eq!(
+            module_map.get_function_branch_coverage(&ident("f")),
+            Some(&BTreeMap::from([(
+                1,
+                BranchCoverage {
+                    taken: 1,
+                    not_taken: 4,
+                }
+            )]))
+        );
+    }
+}
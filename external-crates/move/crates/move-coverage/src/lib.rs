@@ -7,6 +7,7 @@ use move_binary_format::CompiledModule;
 use std::io::Write;
 
 pub mod coverage_map;
+pub mod line_coverage;
 pub mod source_coverage;
 pub mod summary;
 
@@ -53,7 +54,11 @@ pub fn format_csv_summary<'a, M, F, W: Write>(
 ) where
     F: Fn(&CompiledModule, &M) -> ModuleSummary,
 {
-    writeln!(summary_writer, "ModuleName,FunctionName,Covered,Uncovered").unwrap();
+    writeln!(
+        summary_writer,
+        "ModuleName,FunctionName,Covered,Uncovered,BranchesCovered,BranchesTotal"
+    )
+    .unwrap();
 
     for module in modules {
         let coverage_summary = summary_func(module, coverage_map);
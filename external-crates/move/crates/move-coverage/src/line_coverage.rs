@@ -0,0 +1,355 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Line and function coverage of Move source files, computed by mapping the coverage of
+//! bytecode instructions back to the source through the modules' source maps, and its export to
+//! formats consumed by standard coverage tooling (LCOV and Cobertura).
+
+#![forbid(unsafe_code)]
+
+use crate::coverage_map::{BranchCoverage, ExecCoverageMap};
+use anyhow::{bail, Result};
+use codespan::Files;
+use move_binary_format::{
+    file_format::{Bytecode, CodeOffset, FunctionDefinitionIndex},
+    CompiledModule,
+};
+use move_bytecode_source_map::source_map::SourceMap;
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+/// Coverage of a single function.
+#[derive(Debug, Clone)]
+pub struct FunctionLineCoverage {
+    pub name: String,
+    /// Line (1-based) where the function is defined
+    pub line: u32,
+    /// Number of times the function was called
+    pub hits: u64,
+    /// Number of times each (1-based) line containing the function's code was executed
+    pub lines: BTreeMap<u32, u64>,
+    /// Outcomes of the conditional branches starting on each (1-based) line
+    pub branches: BTreeMap<u32, Vec<BranchCoverage>>,
+}
+
+/// Coverage of all (non-native) functions of a module.
+#[derive(Debug, Clone)]
+pub struct ModuleLineCoverage {
+    /// Name of the module qualified with its (shortened) address
+    pub name: String,
+    pub source_path: PathBuf,
+    pub functions: Vec<FunctionLineCoverage>,
+}
+
+impl ModuleLineCoverage {
+    pub fn new(
+        module: &CompiledModule,
+        source_map: &SourceMap,
+        source_path: &Path,
+        coverage_map: &ExecCoverageMap,
+    ) -> Result<Self> {
+        let file_contents = fs::read_to_string(source_path)?;
+        if !source_map.check(&file_contents) {
+            bail!(
+                "File contents of {} out of sync with source map",
+                source_path.display()
+            );
+        }
+        let mut files = Files::new();
+        let file_id = files.add(source_path.as_os_str().to_os_string(), file_contents);
+        let line_of = |byte_index: u32| -> Result<u32> {
+            Ok(files.location(file_id, byte_index)?.line.0 + 1)
+        };
+
+        let module_id = module.self_id();
+        let module_map = coverage_map
+            .module_maps
+            .get(&(*module_id.address(), module_id.name().to_owned()));
+
+        let mut functions = vec![];
+        for (idx, function_def) in module.function_defs().iter().enumerate() {
+            let Some(code_unit) = &function_def.code else {
+                continue;
+            };
+            let fn_handle = module.function_handle_at(function_def.function);
+            let fn_name = module.identifier_at(fn_handle.name);
+            let fdef_idx = FunctionDefinitionIndex(idx as u16);
+            let function_map = source_map.get_function_source_map(fdef_idx)?;
+            let fn_coverage = module_map.and_then(|m| m.get_function_coverage(fn_name));
+            let fn_branch_coverage =
+                module_map.and_then(|m| m.get_function_branch_coverage(fn_name));
+            let hits_at = |code_offset: u64| {
+                fn_coverage
+                    .and_then(|cov| cov.get(&code_offset))
+                    .copied()
+                    .unwrap_or(0)
+            };
+
+            let mut lines = BTreeMap::new();
+            let mut branches: BTreeMap<_, Vec<_>> = BTreeMap::new();
+            for (code_offset, instr) in code_unit.code.iter().enumerate() {
+                let loc = source_map.get_code_location(fdef_idx, code_offset as CodeOffset)?;
+                let line = line_of(loc.start())?;
+                // instructions on the same line are attributed the largest hit count among them
+                let line_hits = lines.entry(line).or_insert(0);
+                *line_hits = (*line_hits).max(hits_at(code_offset as u64));
+
+                let (Bytecode::BrTrue(target) | Bytecode::BrFalse(target)) = instr else {
+                    continue;
+                };
+                let branch_cov = if *target as usize == code_offset + 1 {
+                    // both outcomes of a branch to the next instruction are the same
+                    let hits = hits_at(code_offset as u64);
+                    BranchCoverage {
+                        taken: hits,
+                        not_taken: hits,
+                    }
+                } else {
+                    fn_branch_coverage
+                        .and_then(|cov| cov.get(&(code_offset as u64)))
+                        .copied()
+                        .unwrap_or_default()
+                };
+                branches.entry(line).or_default().push(branch_cov);
+            }
+            functions.push(FunctionLineCoverage {
+                name: fn_name.to_string(),
+                line: line_of(function_map.definition_location.start())?,
+                hits: hits_at(0),
+                lines,
+                branches,
+            });
+        }
+
+        Ok(Self {
+            name: format!(
+                "0x{}::{}",
+                module_id.address().short_str_lossless(),
+                module_id.name()
+            ),
+            source_path: source_path.to_path_buf(),
+            functions,
+        })
+    }
+
+    /// Number of times each (1-based) line containing the module's code was executed.
+    pub fn lines(&self) -> BTreeMap<u32, u64> {
+        let mut lines = BTreeMap::new();
+        for function in &self.functions {
+            for (line, hits) in &function.lines {
+                let line_hits = lines.entry(*line).or_insert(0);
+                *line_hits = (*line_hits).max(*hits);
+            }
+        }
+        lines
+    }
+
+    /// Outcomes of the conditional branches starting on each (1-based) line of the module.
+    pub fn branches(&self) -> BTreeMap<u32, Vec<BranchCoverage>> {
+        let mut branches: BTreeMap<_, Vec<_>> = BTreeMap::new();
+        for function in &self.functions {
+            for (line, line_branches) in &function.branches {
+                branches.entry(*line).or_default().extend(line_branches);
+            }
+        }
+        branches
+    }
+}
+
+/// Outputs coverage in the LCOV tracefile format, with a record for each module.
+pub fn format_lcov<W: Write>(modules: &[ModuleLineCoverage], writer: &mut W) -> io::Result<()> {
+    writeln!(writer, "TN:")?;
+    for module in modules {
+        writeln!(writer, "SF:{}", module.source_path.display())?;
+        for function in &module.functions {
+            writeln!(writer, "FN:{},{}", function.line, function.name)?;
+        }
+        for function in &module.functions {
+            writeln!(writer, "FNDA:{},{}", function.hits, function.name)?;
+        }
+        writeln!(writer, "FNF:{}", module.functions.len())?;
+        writeln!(
+            writer,
+            "FNH:{}",
+            module.functions.iter().filter(|f| f.hits > 0).count()
+        )?;
+        let branches = module.branches();
+        let mut block = 0;
+        for (line, line_branches) in &branches {
+            for branch_cov in line_branches {
+                // outcomes of branches that were never executed are reported as unknown
+                let executed = branch_cov.taken + branch_cov.not_taken > 0;
+                for (outcome, count) in [branch_cov.taken, branch_cov.not_taken].iter().enumerate()
+                {
+                    if executed {
+                        writeln!(writer, "BRDA:{line},{block},{outcome},{count}")?;
+                    } else {
+                        writeln!(writer, "BRDA:{line},{block},{outcome},-")?;
+                    }
+                }
+                block += 1;
+            }
+        }
+        let (branches_valid, branches_covered) = count_branch_outcomes(branches.values());
+        writeln!(writer, "BRF:{branches_valid}")?;
+        writeln!(writer, "BRH:{branches_covered}")?;
+        let lines = module.lines();
+        for (line, hits) in &lines {
+            writeln!(writer, "DA:{line},{hits}")?;
+        }
+        writeln!(writer, "LF:{}", lines.len())?;
+        writeln!(writer, "LH:{}", covered_count(&lines))?;
+        writeln!(writer, "end_of_record")?;
+    }
+    Ok(())
+}
+
+/// Outputs coverage in the Cobertura XML format, with the package represented as a Cobertura
+/// package, each module as a class and each function as a method. Source file names are
+/// relative to `source_root`.
+pub fn format_cobertura<W: Write>(
+    package_name: &str,
+    source_root: &Path,
+    modules: &[ModuleLineCoverage],
+    timestamp: u64,
+    writer: &mut W,
+) -> io::Result<()> {
+    let all_lines: Vec<_> = modules.iter().map(|m| m.lines()).collect();
+    let lines_valid: usize = all_lines.iter().map(|l| l.len()).sum();
+    let lines_covered: usize = all_lines.iter().map(covered_count).sum();
+    let line_rate = rate(lines_covered, lines_valid);
+    let all_branches: Vec<_> = modules.iter().map(|m| m.branches()).collect();
+    let (branches_valid, branches_covered) =
+        count_branch_outcomes(all_branches.iter().flat_map(|b| b.values()));
+    let branch_rate = rate(branches_covered, branches_valid);
+
+    writeln!(writer, r#"<?xml version="1.0" ?>"#)?;
+    writeln!(
+        writer,
+        r#"<!DOCTYPE coverage SYSTEM "http://cobertura.sourceforge.net/xml/coverage-04.dtd">"#
+    )?;
+    writeln!(
+        writer,
+        r#"<coverage line-rate="{line_rate}" branch-rate="{branch_rate}" lines-covered="{lines_covered}" lines-valid="{lines_valid}" branches-covered="{branches_covered}" branches-valid="{branches_valid}" complexity="0" version="0.1" timestamp="{timestamp}">"#
+    )?;
+    writeln!(writer, "  <sources>")?;
+    writeln!(
+        writer,
+        "    <source>{}</source>",
+        xml_escape(&source_root.display().to_string())
+    )?;
+    writeln!(writer, "  </sources>")?;
+    writeln!(writer, "  <packages>")?;
+    writeln!(
+        writer,
+        r#"    <package name="{}" line-rate="{line_rate}" branch-rate="{branch_rate}" complexity="0">"#,
+        xml_escape(package_name)
+    )?;
+    writeln!(writer, "      <classes>")?;
+    for ((module, lines), branches) in modules.iter().zip(&all_lines).zip(&all_branches) {
+        let filename = module
+            .source_path
+            .strip_prefix(source_root)
+            .unwrap_or(&module.source_path);
+        writeln!(
+            writer,
+            r#"        <class name="{}" filename="{}" line-rate="{}" branch-rate="{}" complexity="0">"#,
+            xml_escape(&module.name),
+            xml_escape(&filename.display().to_string()),
+            rate(covered_count(lines), lines.len()),
+            branch_rate_of(branches),
+        )?;
+        writeln!(writer, "          <methods>")?;
+        for function in &module.functions {
+            writeln!(
+                writer,
+                r#"            <method name="{}" signature="" line-rate="{}" branch-rate="{}" complexity="0">"#,
+                xml_escape(&function.name),
+                rate(covered_count(&function.lines), function.lines.len()),
+                branch_rate_of(&function.branches),
+            )?;
+            write_cobertura_lines(
+                writer,
+                "              ",
+                &function.lines,
+                &function.branches,
+            )?;
+            writeln!(writer, "            </method>")?;
+        }
+        writeln!(writer, "          </methods>")?;
+        write_cobertura_lines(writer, "          ", lines, branches)?;
+        writeln!(writer, "        </class>")?;
+    }
+    writeln!(writer, "      </classes>")?;
+    writeln!(writer, "    </package>")?;
+    writeln!(writer, "  </packages>")?;
+    writeln!(writer, "</coverage>")?;
+    Ok(())
+}
+
+fn write_cobertura_lines<W: Write>(
+    writer: &mut W,
+    indent: &str,
+    lines: &BTreeMap<u32, u64>,
+    branches: &BTreeMap<u32, Vec<BranchCoverage>>,
+) -> io::Result<()> {
+    writeln!(writer, "{indent}<lines>")?;
+    for (line, hits) in lines {
+        match branches.get(line) {
+            None => writeln!(
+                writer,
+                r#"{indent}  <line number="{line}" hits="{hits}" branch="false"/>"#
+            )?,
+            Some(line_branches) => {
+                let (valid, covered) = count_branch_outcomes([line_branches]);
+                writeln!(
+                    writer,
+                    r#"{indent}  <line number="{line}" hits="{hits}" branch="true" condition-coverage="{}% ({covered}/{valid})"/>"#,
+                    covered * 100 / valid
+                )?
+            }
+        }
+    }
+    writeln!(writer, "{indent}</lines>")
+}
+
+/// Returns the number of possible outcomes of the branches and the number of those that occurred.
+fn count_branch_outcomes<'a>(
+    branches: impl IntoIterator<Item = &'a Vec<BranchCoverage>>,
+) -> (usize, usize) {
+    let mut valid = 0;
+    let mut covered = 0;
+    for branch_cov in branches.into_iter().flatten() {
+        valid += 2;
+        covered += (branch_cov.taken > 0) as usize + (branch_cov.not_taken > 0) as usize;
+    }
+    (valid, covered)
+}
+
+fn branch_rate_of(branches: &BTreeMap<u32, Vec<BranchCoverage>>) -> String {
+    let (valid, covered) = count_branch_outcomes(branches.values());
+    rate(covered, valid)
+}
+
+fn covered_count(lines: &BTreeMap<u32, u64>) -> usize {
+    lines.values().filter(|hits| **hits > 0).count()
+}
+
+fn rate(covered: usize, total: usize) -> String {
+    if total == 0 {
+        return "1".to_string();
+    }
+    format!("{:.4}", covered as f64 / total as f64)
+}
+
+fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
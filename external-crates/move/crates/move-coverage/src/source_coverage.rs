@@ -4,12 +4,13 @@
 
 #![forbid(unsafe_code)]
 
-use crate::coverage_map::CoverageMap;
+use crate::coverage_map::{CoverageMap, FunctionBranchCoverage};
 use codespan::{Files, Span};
 use colored::*;
 use indexmap::IndexSet;
+use move_abstract_interpreter::control_flow_graph::{ControlFlowGraph, VMControlFlowGraph};
 use move_binary_format::{
-    file_format::{CodeOffset, FunctionDefinitionIndex},
+    file_format::{Bytecode, CodeOffset, CodeUnit, FunctionDefinitionIndex},
     CompiledModule,
 };
 use move_bytecode_source_map::source_map::SourceMap;
@@ -17,7 +18,7 @@ use move_core_types::identifier::Identifier;
 use move_ir_types::location::Loc;
 use serde::Serialize;
 use std::{
-    collections::BTreeMap,
+    collections::{BTreeMap, BTreeSet},
     fs,
     io::{self, Write},
     path::Path,
@@ -27,6 +28,8 @@ use std::{
 pub struct FunctionSourceCoverage {
     pub fn_is_native: bool,
     pub uncovered_locations: Vec<Loc>,
+    /// Locations of conditional branches that were executed but only ever had one outcome
+    pub partial_branch_locations: Vec<Loc>,
 }
 
 #[derive(Debug, Serialize)]
@@ -46,6 +49,13 @@ pub enum AbstractSegment {
 pub enum StringSegment {
     Covered(String),
     Uncovered(String),
+    PartiallyCovered(String),
+}
+
+#[derive(Debug, Serialize, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash)]
+enum SegmentKind {
+    Uncovered,
+    PartiallyCovered,
 }
 
 pub type AnnotatedLine = Vec<StringSegment>;
@@ -55,6 +65,26 @@ pub struct SourceCoverage {
     pub annotated_lines: Vec<AnnotatedLine>,
 }
 
+impl AbstractSegment {
+    fn start(&self) -> u32 {
+        match self {
+            AbstractSegment::Bounded { start, .. } | AbstractSegment::BoundedLeft { start } => {
+                *start
+            }
+            AbstractSegment::BoundedRight { .. } => 0,
+        }
+    }
+}
+
+impl SegmentKind {
+    fn string_segment(self, s: &str) -> StringSegment {
+        match self {
+            SegmentKind::Uncovered => StringSegment::Uncovered(s.to_string()),
+            SegmentKind::PartiallyCovered => StringSegment::PartiallyCovered(s.to_string()),
+        }
+    }
+}
+
 impl<'a> SourceCoverageBuilder<'a> {
     pub fn new(
         module: &CompiledModule,
@@ -81,6 +111,7 @@ impl<'a> SourceCoverageBuilder<'a> {
                     None => Some(FunctionSourceCoverage {
                         fn_is_native: true,
                         uncovered_locations: Vec::new(),
+                        partial_branch_locations: Vec::new(),
                     }),
                     Some(code_unit) => {
                         module_map.map(|fn_map| match fn_map.function_maps.get(&fn_name) {
@@ -95,6 +126,7 @@ impl<'a> SourceCoverageBuilder<'a> {
                                 FunctionSourceCoverage {
                                     fn_is_native: false,
                                     uncovered_locations,
+                                    partial_branch_locations: Vec::new(),
                                 }
                             }
                             Some(function_coverage) => {
@@ -114,9 +146,16 @@ impl<'a> SourceCoverageBuilder<'a> {
                                         }
                                     })
                                     .collect();
+                                let partial_branch_locations = partial_branch_locations(
+                                    source_map,
+                                    function_def_idx,
+                                    code_unit,
+                                    fn_map.get_function_branch_coverage(&fn_name),
+                                );
                                 FunctionSourceCoverage {
                                     fn_is_native: false,
                                     uncovered_locations,
+                                    partial_branch_locations,
                                 }
                             }
                         })
@@ -141,75 +180,100 @@ impl<'a> SourceCoverageBuilder<'a> {
         let mut files = Files::new();
         let file_id = files.add(file_path.as_os_str().to_os_string(), file_contents.clone());
 
-        let mut uncovered_segments = BTreeMap::new();
+        let partial_spans: Vec<_> = self
+            .uncovered_locations
+            .values()
+            .flat_map(|fn_cov| merge_spans(&fn_cov.partial_branch_locations))
+            .collect();
+        // conditions of partially covered branches are reported as such even if they are part of
+        // a larger expression that is otherwise uncovered
+        let uncovered_spans: Vec<_> = self
+            .uncovered_locations
+            .values()
+            .flat_map(|fn_cov| merge_spans(&fn_cov.uncovered_locations))
+            .flat_map(|span| subtract_spans(span, &partial_spans))
+            .collect();
+        let annotated_spans = uncovered_spans
+            .into_iter()
+            .map(|span| (span, SegmentKind::Uncovered))
+            .chain(
+                partial_spans
+                    .into_iter()
+                    .map(|span| (span, SegmentKind::PartiallyCovered)),
+            );
 
-        for (_, fn_cov) in self.uncovered_locations.iter() {
-            for span in merge_spans(fn_cov.clone()).into_iter() {
-                let start_loc = files.location(file_id, span.start()).unwrap();
-                let end_loc = files.location(file_id, span.end()).unwrap();
-                let start_line = start_loc.line.0;
-                let end_line = end_loc.line.0;
-                let segments = uncovered_segments
-                    .entry(start_line)
-                    .or_insert_with(IndexSet::new);
-                if start_line == end_line {
-                    let segment = AbstractSegment::Bounded {
-                        start: start_loc.column.0,
-                        end: end_loc.column.0,
-                    };
-                    // TODO: There is some issue with the source map where we have multiple spans
-                    // from different functions. This can be seen in the source map for `Roles.move`
-                    if !segments.contains(&segment) {
-                        segments.insert(segment);
-                    }
-                } else {
-                    segments.insert(AbstractSegment::BoundedLeft {
+        let mut annotated_segments = BTreeMap::new();
+
+        for (span, kind) in annotated_spans {
+            let start_loc = files.location(file_id, span.start()).unwrap();
+            let end_loc = files.location(file_id, span.end()).unwrap();
+            let start_line = start_loc.line.0;
+            let end_line = end_loc.line.0;
+            let segments = annotated_segments
+                .entry(start_line)
+                .or_insert_with(IndexSet::new);
+            if start_line == end_line {
+                let segment = AbstractSegment::Bounded {
+                    start: start_loc.column.0,
+                    end: end_loc.column.0,
+                };
+                // TODO: There is some issue with the source map where we have multiple spans
+                // from different functions. This can be seen in the source map for `Roles.move`
+                segments.insert((segment, kind));
+            } else {
+                segments.insert((
+                    AbstractSegment::BoundedLeft {
                         start: start_loc.column.0,
-                    });
-                    for i in start_line + 1..end_line {
-                        let segment = uncovered_segments.entry(i).or_insert_with(IndexSet::new);
-                        segment.insert(AbstractSegment::BoundedLeft { start: 0 });
-                    }
-                    let last_segment = uncovered_segments
-                        .entry(end_line)
-                        .or_insert_with(IndexSet::new);
-                    last_segment.insert(AbstractSegment::BoundedRight {
-                        end: end_loc.column.0,
-                    });
+                    },
+                    kind,
+                ));
+                for i in start_line + 1..end_line {
+                    let segment = annotated_segments.entry(i).or_insert_with(IndexSet::new);
+                    segment.insert((AbstractSegment::BoundedLeft { start: 0 }, kind));
                 }
+                let last_segment = annotated_segments
+                    .entry(end_line)
+                    .or_insert_with(IndexSet::new);
+                last_segment.insert((
+                    AbstractSegment::BoundedRight {
+                        end: end_loc.column.0,
+                    },
+                    kind,
+                ));
             }
         }
 
         let mut annotated_lines = Vec::new();
         for (line_number, mut line) in file_contents.lines().map(|x| x.to_owned()).enumerate() {
-            match uncovered_segments.get(&(line_number as u32)) {
+            match annotated_segments.get(&(line_number as u32)) {
                 None => annotated_lines.push(vec![StringSegment::Covered(line)]),
                 Some(segments) => {
-                    // Note: segments are already pre-sorted by construction so don't need to be
-                    // resorted.
+                    // segments of different kinds are not ordered with respect to one another
+                    let mut segments: Vec<_> = segments.iter().collect();
+                    segments.sort_by_key(|(segment, _)| segment.start());
                     let mut line_acc = Vec::new();
                     let mut cursor = 0;
-                    for segment in segments {
+                    for (segment, kind) in segments {
                         match segment {
                             AbstractSegment::Bounded { start, end } => {
                                 let length = end - start;
                                 let (before, after) = line.split_at((start - cursor) as usize);
-                                let (uncovered, rest) = after.split_at(length as usize);
+                                let (annotated, rest) = after.split_at(length as usize);
                                 line_acc.push(StringSegment::Covered(before.to_string()));
-                                line_acc.push(StringSegment::Uncovered(uncovered.to_string()));
+                                line_acc.push(kind.string_segment(annotated));
                                 line = rest.to_string();
                                 cursor = *end;
                             }
                             AbstractSegment::BoundedRight { end } => {
-                                let (uncovered, rest) = line.split_at((end - cursor) as usize);
-                                line_acc.push(StringSegment::Uncovered(uncovered.to_string()));
+                                let (annotated, rest) = line.split_at((end - cursor) as usize);
+                                line_acc.push(kind.string_segment(annotated));
                                 line = rest.to_string();
                                 cursor = *end;
                             }
                             AbstractSegment::BoundedLeft { start } => {
                                 let (before, after) = line.split_at((start - cursor) as usize);
                                 line_acc.push(StringSegment::Covered(before.to_string()));
-                                line_acc.push(StringSegment::Uncovered(after.to_string()));
+                                line_acc.push(kind.string_segment(after));
                                 line = "".to_string();
                                 cursor = 0;
                             }
@@ -222,7 +286,6 @@ impl<'a> SourceCoverageBuilder<'a> {
                 }
             }
         }
-
         SourceCoverage { annotated_lines }
     }
 }
@@ -234,6 +297,9 @@ impl SourceCoverage {
                 match string_segment {
                     StringSegment::Covered(s) => write!(output_writer, "{}", s.green())?,
                     StringSegment::Uncovered(s) => write!(output_writer, "{}", s.bold().red())?,
+                    StringSegment::PartiallyCovered(s) => {
+                        write!(output_writer, "{}", s.bold().yellow())?
+                    }
                 }
             }
             writeln!(output_writer)?;
@@ -242,13 +308,98 @@ impl SourceCoverage {
     }
 }
 
-fn merge_spans(cov: FunctionSourceCoverage) -> Vec<Span> {
-    if cov.uncovered_locations.is_empty() {
+/// Returns the locations of the conditions of branches that were executed but only ever had one
+/// outcome.
+fn partial_branch_locations(
+    source_map: &SourceMap,
+    function_def_idx: FunctionDefinitionIndex,
+    code_unit: &CodeUnit,
+    branch_coverage: Option<&FunctionBranchCoverage>,
+) -> Vec<Loc> {
+    let partial_branches: Vec<_> = code_unit
+        .code
+        .iter()
+        .enumerate()
+        .filter(|(code_offset, instr)| {
+            let (Bytecode::BrTrue(target) | Bytecode::BrFalse(target)) = instr else {
+                return false;
+            };
+            // both outcomes of a branch to the next instruction are the same
+            if *target as usize == code_offset + 1 {
+                return false;
+            }
+            branch_coverage
+                .and_then(|m| m.get(&(*code_offset as u64)))
+                .is_some_and(|cov| (cov.taken == 0) != (cov.not_taken == 0))
+        })
+        .map(|(code_offset, _)| code_offset as CodeOffset)
+        .collect();
+    if partial_branches.is_empty() {
         return vec![];
     }
 
-    let mut covs: Vec<_> = cov
-        .uncovered_locations
+    let cfg = VMControlFlowGraph::new(&code_unit.code, &code_unit.jump_tables);
+    let block_starts: BTreeSet<_> = cfg
+        .blocks()
+        .into_iter()
+        .map(|block_id| cfg.block_start(block_id))
+        .collect();
+    partial_branches
+        .into_iter()
+        .map(|code_offset| {
+            let block_start = block_starts.range(..=code_offset).next_back().copied();
+            branch_condition_location(
+                source_map,
+                function_def_idx,
+                block_start.unwrap_or(code_offset),
+                code_offset,
+            )
+        })
+        .collect()
+}
+
+/// Returns the location of the condition of the branch at `code_offset`, that is of the
+/// instructions of its basic block computing the value the branch is on, as the location of the
+/// branch itself usually spans the whole conditional expression.
+fn branch_condition_location(
+    source_map: &SourceMap,
+    function_def_idx: FunctionDefinitionIndex,
+    block_start: CodeOffset,
+    code_offset: CodeOffset,
+) -> Loc {
+    let branch_loc = source_map
+        .get_code_location(function_def_idx, code_offset)
+        .unwrap();
+    let mut cond_loc: Option<Loc> = None;
+    for offset in (block_start..code_offset).rev() {
+        let Ok(loc) = source_map.get_code_location(function_def_idx, offset) else {
+            break;
+        };
+        // instructions outside of the conditional expression precede the condition
+        if loc.file_hash() != branch_loc.file_hash()
+            || loc.start() < branch_loc.start()
+            || loc.end() > branch_loc.end()
+        {
+            break;
+        }
+        cond_loc = Some(match cond_loc {
+            None => loc,
+            Some(cond_loc) => Loc::new(
+                loc.file_hash(),
+                loc.start().min(cond_loc.start()),
+                loc.end().max(cond_loc.end()),
+            ),
+        });
+    }
+    cond_loc.unwrap_or(branch_loc)
+}
+
+fn merge_spans(locations: &[Loc]) -> Vec<Span> {
+    if locations.is_empty() {
+        return vec![];
+    }
+
+    let mut covs: Vec<_> = locations
         .iter()
         .map(|loc| Span::new(loc.start(), loc.end()))
         .collect();
@@ -269,3 +420,27 @@ fn merge_spans(cov: FunctionSourceCoverage) -> Vec<Span> {
     unioned.push(curr);
     unioned
 }
+
+/// Returns the parts of `span` not overlapping with any of `holes`.
+fn subtract_spans(span: Span, holes: &[Span]) -> Vec<Span> {
+    let mut remaining = vec![span];
+    for hole in holes {
+        remaining = remaining
+            .into_iter()
+            .flat_map(|span| {
+                if span.disjoint(*hole) {
+                    return vec![span];
+                }
+                let mut parts = vec![];
+                if span.start() < hole.start() {
+                    parts.push(Span::new(span.start(), hole.start()));
+                }
+                if hole.end() < span.end() {
+                    parts.push(Span::new(hole.end(), span.end()));
+                }
+                parts
+            })
+            .collect();
+    }
+    remaining
+}
@@ -5,7 +5,8 @@
 #![forbid(unsafe_code)]
 
 use crate::coverage_map::{
-    ExecCoverageMap, ExecCoverageMapWithModules, ModuleCoverageMap, TraceMap,
+    ExecCoverageMap, ExecCoverageMapWithModules, FunctionBranchCoverage, ModuleCoverageMap,
+    TraceMap,
 };
 use move_abstract_interpreter::control_flow_graph::{
     BlockId, ControlFlowGraph, VMControlFlowGraph,
@@ -33,6 +34,10 @@ pub struct FunctionSummary {
     pub fn_is_native: bool,
     pub total: u64,
     pub covered: u64,
+    /// Number of possible outcomes of conditional branches (two per branch)
+    pub branches_total: u64,
+    /// Number of outcomes of conditional branches that occurred
+    pub branches_covered: u64,
 }
 
 pub struct FunctionInfo {
@@ -52,11 +57,11 @@ impl ModuleSummary {
             self.module_name.name()
         );
 
-        let mut format_line = |fn_name, covered, uncovered| {
+        let mut format_line = |fn_name, covered, uncovered, branches_covered, branches_total| {
             writeln!(
                 summary_writer,
-                "{},{},{},{}",
-                module, fn_name, covered, uncovered
+                "{},{},{},{},{},{}",
+                module, fn_name, covered, uncovered, branches_covered, branches_total
             )
         };
 
@@ -65,7 +70,13 @@ impl ModuleSummary {
             .iter()
             .filter(|(_, summary)| !summary.fn_is_native)
         {
-            format_line(fn_name, fn_summary.covered, fn_summary.total)?;
+            format_line(
+                fn_name,
+                fn_summary.covered,
+                fn_summary.total,
+                fn_summary.branches_covered,
+                fn_summary.branches_total,
+            )?;
         }
 
         Ok(())
@@ -80,6 +91,8 @@ impl ModuleSummary {
     ) -> io::Result<(u64, u64)> {
         let mut all_total = 0;
         let mut all_covered = 0;
+        let mut all_branches_total = 0;
+        let mut all_branches_covered = 0;
 
         writeln!(
             summary_writer,
@@ -91,6 +104,8 @@ impl ModuleSummary {
         for (fn_name, fn_summary) in self.function_summaries.iter() {
             all_total += fn_summary.total;
             all_covered += fn_summary.covered;
+            all_branches_total += fn_summary.branches_total;
+            all_branches_covered += fn_summary.branches_covered;
 
             if summarize_function_coverage {
                 let native = if fn_summary.fn_is_native {
@@ -106,6 +121,23 @@ impl ModuleSummary {
                     "\t\t% coverage: {:.2}",
                     fn_summary.percent_coverage()
                 )?;
+                if fn_summary.branches_total > 0 {
+                    writeln!(
+                        summary_writer,
+                        "\t\tbranches total: {}",
+                        fn_summary.branches_total
+                    )?;
+                    writeln!(
+                        summary_writer,
+                        "\t\tbranches covered: {}",
+                        fn_summary.branches_covered
+                    )?;
+                    writeln!(
+                        summary_writer,
+                        "\t\t% branch coverage: {:.2}",
+                        fn_summary.percent_branch_coverage()
+                    )?;
+                }
             }
         }
 
@@ -115,6 +147,13 @@ impl ModuleSummary {
             ">>> % Module coverage: {:.2}",
             covered_percentage
         )?;
+        if all_branches_total > 0 {
+            writeln!(
+                summary_writer,
+                ">>> % Module branch coverage: {:.2}",
+                (all_branches_covered as f64) / (all_branches_total as f64) * 100f64
+            )?;
+        }
         Ok((all_total, all_covered))
     }
 }
@@ -123,6 +162,38 @@ impl FunctionSummary {
     pub fn percent_coverage(&self) -> f64 {
         (self.covered as f64) / (self.total as f64) * 100f64
     }
+
+    pub fn percent_branch_coverage(&self) -> f64 {
+        (self.branches_covered as f64) / (self.branches_total as f64) * 100f64
+    }
+}
+
+/// Returns the number of possible outcomes of the conditional branches in `code` and the number
+/// of those that occurred according to `branch_map`. If the target of a branch is the next
+/// instruction, both of its outcomes are considered to have occurred when it was executed.
+pub fn summarize_branch_cov(
+    code: &[Bytecode],
+    branch_map: Option<&FunctionBranchCoverage>,
+) -> (u64, u64) {
+    let mut total = 0;
+    let mut covered = 0;
+    for (pc, instr) in code.iter().enumerate() {
+        let (Bytecode::BrTrue(target) | Bytecode::BrFalse(target)) = instr else {
+            continue;
+        };
+        total += 2;
+        let Some(branch_cov) = branch_map.and_then(|m| m.get(&(pc as u64))) else {
+            continue;
+        };
+        if *target as usize == pc + 1 {
+            if branch_cov.taken + branch_cov.not_taken > 0 {
+                covered += 2;
+            }
+        } else {
+            covered += (branch_cov.taken > 0) as u64 + (branch_cov.not_taken > 0) as u64;
+        }
+    }
+    (total, covered)
 }
 
 pub fn summarize_inst_cov_by_module(
@@ -142,6 +213,8 @@ pub fn summarize_inst_cov_by_module(
                     fn_is_native: true,
                     total: 0,
                     covered: 0,
+                    branches_total: 0,
+                    branches_covered: 0,
                 },
                 Some(code_unit) => {
                     let total_number_of_instructions = code_unit.code.len() as u64;
@@ -153,10 +226,16 @@ pub fn summarize_inst_cov_by_module(
                                 .map(|function_map| function_map.len())
                         })
                         .unwrap_or(0) as u64;
+                    let (branches_total, branches_covered) = summarize_branch_cov(
+                        &code_unit.code,
+                        module_map.and_then(|fn_map| fn_map.get_function_branch_coverage(&fn_name)),
+                    );
                     FunctionSummary {
                         fn_is_native: false,
                         total: total_number_of_instructions,
                         covered: covered_instructions,
+                        branches_total,
+                        branches_covered,
                     }
                 }
             };
@@ -426,6 +505,8 @@ pub fn summarize_path_cov(module: &CompiledModule, trace_map: &TraceMap) -> Modu
                     fn_is_native: true,
                     total: 0,
                     covered: 0,
+                    branches_total: 0,
+                    branches_covered: 0,
                 },
                 Some(_) => FunctionSummary {
                     fn_is_native: false,
@@ -434,6 +515,8 @@ pub fn summarize_path_cov(module: &CompiledModule, trace_map: &TraceMap) -> Modu
                         None => 0,
                         Some(pathset) => pathset.len() as u64,
                     },
+                    branches_total: 0,
+                    branches_covered: 0,
                 },
             };
 
@@ -7,7 +7,7 @@ pub mod extensions;
 pub mod test_reporter;
 pub mod test_runner;
 
-use crate::test_runner::TestRunner;
+use crate::{test_reporter::TestReport, test_runner::TestRunner};
 use anyhow::{bail, Result};
 use clap::*;
 use move_binary_format::CompiledModule;
@@ -126,6 +126,11 @@ pub struct UnitTestingConfig {
     // Enable tracing for tests
     #[clap(long = TRACE_FLAG, value_name = "PATH")]
     pub trace_execution: Option<Option<String>>,
+
+    /// Write a report of the test results to a file, given as `<format>=<path>` where the format
+    /// is `junit` or `json`. Can be repeated to write several reports
+    #[clap(long = "report", value_name = "FORMAT=PATH")]
+    pub reports: Vec<TestReport>,
 }
 
 fn format_module_id(
@@ -158,6 +163,7 @@ impl UnitTestingConfig {
             seed: None,
             deterministic_generation: false,
             trace_execution: None,
+            reports: vec![],
         }
     }
 
@@ -296,6 +302,9 @@ impl UnitTestingConfig {
         if let Some(report_type) = &self.report_statistics {
             test_results.report_statistics(&shared_writer, report_type)?;
         }
+        for report in &self.reports {
+            test_results.write_report(report)?;
+        }
 
         let ok = test_results.summarize(&shared_writer)?;
 
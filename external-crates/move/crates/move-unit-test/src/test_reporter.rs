@@ -16,10 +16,13 @@ use move_core_types::{
 };
 use move_ir_types::location::Loc;
 use move_trace_format::format::MoveTrace;
+use serde::Serialize;
 use std::{
     collections::{BTreeMap, BTreeSet},
+    fmt,
     io::{Result, Write},
-    path::Path,
+    path::{Path, PathBuf},
+    str::FromStr,
     sync::Mutex,
     time::Duration,
 };
@@ -59,6 +62,9 @@ pub struct TestRunInfo {
 
 type TestRuns<T> = BTreeMap<String, Vec<T>>;
 
+/// A run of a test, given by the name of the test, along with its failure if it failed
+type TestRunRecord<'a> = (&'a str, &'a TestRunInfo, Option<&'a TestFailure>);
+
 #[derive(Debug, Clone)]
 pub struct TestStatistics {
     passed: BTreeMap<ModuleId, TestRuns<TestRunInfo>>,
@@ -71,6 +77,51 @@ pub struct TestResults {
     test_plan: TestPlan,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TestReportFormat {
+    Junit,
+    Json,
+}
+
+/// A machine-readable report of test results to be written to a file, specified on the command
+/// line as `<format>=<path>`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TestReport {
+    pub format: TestReportFormat,
+    pub path: PathBuf,
+}
+
+/// Result of a single test run, as included in a JSON report.
+#[derive(Serialize)]
+struct JsonTestRun {
+    module: String,
+    name: String,
+    status: &'static str,
+    wall_time_secs: f64,
+    instructions_executed: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    seed: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    failure: Option<JsonTestFailure>,
+}
+
+#[derive(Serialize)]
+struct JsonTestFailure {
+    reason: String,
+    message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    abort_code: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    location: Option<String>,
+}
+
+#[derive(Serialize)]
+struct JsonTestReport {
+    passed: usize,
+    failed: usize,
+    tests: Vec<JsonTestRun>,
+}
+
 fn write_string_to_file(filepath: &str, content: &str) -> std::io::Result<()> {
     let path = Path::new(filepath);
     if let Some(parent) = path.parent() {
@@ -103,6 +154,41 @@ impl TestRunInfo {
     }
 }
 
+impl FromStr for TestReport {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let (format, path) = s
+            .split_once('=')
+            .ok_or_else(|| format!("Invalid test report '{s}', expected <format>=<path>"))?;
+        let format = match format {
+            "junit" => TestReportFormat::Junit,
+            "json" => TestReportFormat::Json,
+            _ => {
+                return Err(format!(
+                    "Unknown test report format '{format}', expected 'junit' or 'json'"
+                ))
+            }
+        };
+        if path.is_empty() {
+            return Err(format!("Missing path for {format} test report"));
+        }
+        Ok(Self {
+            format,
+            path: PathBuf::from(path),
+        })
+    }
+}
+
+impl fmt::Display for TestReportFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TestReportFormat::Junit => write!(f, "junit"),
+            TestReportFormat::Json => write!(f, "json"),
+        }
+    }
+}
+
 impl FailureReason {
     pub fn no_error() -> Self {
         FailureReason::NoError("Test did not error as expected".to_string())
@@ -135,6 +221,18 @@ impl FailureReason {
     pub fn property(details: String) -> Self {
         FailureReason::Property(details)
     }
+
+    /// A short description of the failure, without any details about the error
+    pub fn message(&self) -> &str {
+        match self {
+            FailureReason::NoError(message)
+            | FailureReason::WrongError(message, _, _)
+            | FailureReason::WrongAbortDEPRECATED(message, _, _)
+            | FailureReason::UnexpectedError(message, _)
+            | FailureReason::Timeout(message)
+            | FailureReason::Property(message) => message,
+        }
+    }
 }
 
 fn clever_error_line_number_to_loc(test_plan: &TestPlan, vm_error: &VMError) -> Option<Loc> {
@@ -178,6 +276,44 @@ impl TestFailure {
     }
 
     pub fn render_error(&self, test_plan: &TestPlan) -> String {
+        self.render_error_with_color(test_plan, control::SHOULD_COLORIZE.should_colorize())
+    }
+
+    /// Returns the abort code of the test if it aborted.
+    pub fn abort_code(&self) -> Option<u64> {
+        let vm_error = self.vm_error.as_ref()?;
+        match (vm_error.major_status(), vm_error.sub_status()) {
+            (StatusCode::ABORTED, Some(abort_code)) => Some(abort_code),
+            _ => None,
+        }
+    }
+
+    /// Returns the function and source location where the error of the test was raised, as
+    /// `<module>::<function>(<file>:<line>)`.
+    pub fn error_location(&self, test_plan: &TestPlan) -> Option<String> {
+        let vm_error = self.vm_error.as_ref()?;
+        let Location::Module(module_id) = vm_error.location() else {
+            return None;
+        };
+        let (fdef_idx, offset) = vm_error.offsets().first()?;
+        let named_module = test_plan.module_info.get(module_id)?;
+        let loc = named_module
+            .source_map
+            .get_function_source_map(*fdef_idx)
+            .ok()?
+            .get_code_location(*offset)?;
+        let fn_handle_idx = named_module.module.function_def_at(*fdef_idx).function;
+        let fn_id_idx = named_module.module.function_handle_at(fn_handle_idx).name;
+        Some(format!(
+            "{}::{}({}:{})",
+            format_module_id(&test_plan.module_info, module_id),
+            named_module.module.identifier_at(fn_id_idx),
+            test_plan.mapped_files.filename(&loc.file_hash()),
+            test_plan.mapped_files.position(&loc).start.user_line(),
+        ))
+    }
+
+    fn render_error_with_color(&self, test_plan: &TestPlan, color: bool) -> String {
         match &self.failure_reason {
             FailureReason::NoError(message) => message.to_string(),
             FailureReason::Timeout(message) => message.to_string(),
@@ -189,7 +325,7 @@ impl TestFailure {
                         .present_tense(),
                     actual.with_context(&test_plan.module_info).past_tense(),
                 );
-                Self::report_error_with_location(test_plan, base_message, &self.vm_error)
+                Self::report_error_with_location(test_plan, base_message, &self.vm_error, color)
             }
             FailureReason::WrongAbortDEPRECATED(message, expected_code, actual) => {
                 let base_message = format!(
@@ -199,7 +335,7 @@ impl TestFailure {
                     expected_code,
                     actual.with_context(&test_plan.module_info).past_tense(),
                 );
-                Self::report_error_with_location(test_plan, base_message, &self.vm_error)
+                Self::report_error_with_location(test_plan, base_message, &self.vm_error, color)
             }
             FailureReason::UnexpectedError(message, error) => {
                 let prefix = match error.0.status_type() {
@@ -223,7 +359,7 @@ impl TestFailure {
                     message,
                     error.with_context(&test_plan.module_info).past_tense(),
                 );
-                Self::report_error_with_location(test_plan, base_message, &self.vm_error)
+                Self::report_error_with_location(test_plan, base_message, &self.vm_error, color)
             }
             FailureReason::Property(message) => message.clone(),
         }
@@ -281,13 +417,10 @@ impl TestFailure {
         test_plan: &TestPlan,
         base_message: String,
         vm_error: &Option<VMError>,
+        color: bool,
     ) -> String {
         let report_diagnostics = |mapped_files, diags| {
-            diagnostics::report_diagnostics_to_buffer_with_mapped_files(
-                mapped_files,
-                diags,
-                control::SHOULD_COLORIZE.should_colorize(),
-            )
+            diagnostics::report_diagnostics_to_buffer_with_mapped_files(mapped_files, diags, color)
         };
 
         let vm_error = match vm_error {
@@ -567,6 +700,162 @@ impl TestResults {
         writeln!(writer.lock().unwrap())
     }
 
+    /// Writes a machine-readable report of the results of all test runs to a file.
+    pub fn write_report(&self, report: &TestReport) -> Result<()> {
+        let content = match report.format {
+            TestReportFormat::Junit => self.junit_report(),
+            TestReportFormat::Json => self.json_report()?,
+        };
+        write_string_to_file(&report.path.to_string_lossy(), &content)
+    }
+
+    /// Returns the runs of every test, grouped by module, along with the failure of each failed
+    /// run.
+    fn test_runs(&self) -> BTreeMap<String, Vec<TestRunRecord>> {
+        let mut runs: BTreeMap<_, Vec<_>> = BTreeMap::new();
+        for (module_id, test_results) in &self.final_statistics.passed {
+            let module_runs = runs
+                .entry(format_module_id(&self.test_plan.module_info, module_id))
+                .or_default();
+            for (test_name, test_results) in test_results {
+                for test_run_info in test_results {
+                    module_runs.push((test_name.as_str(), test_run_info, None));
+                }
+            }
+        }
+        for (module_id, test_failures) in &self.final_statistics.failed {
+            let module_runs = runs
+                .entry(format_module_id(&self.test_plan.module_info, module_id))
+                .or_default();
+            for (test_name, test_failures) in test_failures {
+                for test_failure in test_failures {
+                    module_runs.push((
+                        test_name.as_str(),
+                        &test_failure.test_run_info,
+                        Some(test_failure),
+                    ));
+                }
+            }
+        }
+        for module_runs in runs.values_mut() {
+            module_runs.sort_by_key(|(test_name, _, failure)| (*test_name, failure.is_some()));
+        }
+        runs
+    }
+
+    fn json_report(&self) -> Result<String> {
+        let mut passed = 0;
+        let mut failed = 0;
+        let mut tests = vec![];
+        for (module, module_runs) in self.test_runs() {
+            for (test_name, test_run_info, test_failure) in module_runs {
+                if test_failure.is_some() {
+                    failed += 1;
+                } else {
+                    passed += 1;
+                }
+                tests.push(JsonTestRun {
+                    module: module.clone(),
+                    name: test_name.to_string(),
+                    status: if test_failure.is_some() {
+                        "failed"
+                    } else {
+                        "passed"
+                    },
+                    wall_time_secs: test_run_info.elapsed_time.as_secs_f64(),
+                    instructions_executed: test_run_info.instructions_executed,
+                    seed: test_failure.and_then(|f| f.prng_seed),
+                    failure: test_failure.map(|f| JsonTestFailure {
+                        reason: f.failure_reason.message().to_string(),
+                        message: f.render_error_with_color(&self.test_plan, false),
+                        abort_code: f.abort_code(),
+                        location: f.error_location(&self.test_plan),
+                    }),
+                });
+            }
+        }
+        let report = JsonTestReport {
+            passed,
+            failed,
+            tests,
+        };
+        Ok(serde_json::to_string_pretty(&report)?)
+    }
+
+    fn junit_report(&self) -> String {
+        let runs = self.test_runs();
+        let count = |module_runs: &[TestRunRecord]| {
+            let failures = module_runs.iter().filter(|(_, _, f)| f.is_some()).count();
+            let (time, _) = calculate_run_statistics(module_runs.iter().map(|(_, info, _)| *info));
+            (module_runs.len(), failures, time)
+        };
+        let (tests, failures, time) = runs.values().fold(
+            (0, 0, Duration::new(0, 0)),
+            |(acc_tests, acc_failures, acc_time), module_runs| {
+                let (tests, failures, time) = count(module_runs);
+                (acc_tests + tests, acc_failures + failures, acc_time + time)
+            },
+        );
+
+        let mut xml = String::new();
+        xml.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        xml.push_str(&format!(
+            "<testsuites name=\"Move unit tests\" tests=\"{tests}\" failures=\"{failures}\" errors=\"0\" time=\"{:.3}\">\n",
+            time.as_secs_f64()
+        ));
+        for (module, module_runs) in &runs {
+            let (tests, failures, time) = count(module_runs);
+            xml.push_str(&format!(
+                "  <testsuite name=\"{}\" tests=\"{tests}\" failures=\"{failures}\" errors=\"0\" skipped=\"0\" time=\"{:.3}\">\n",
+                xml_escape(module),
+                time.as_secs_f64()
+            ));
+            for (test_name, test_run_info, test_failure) in module_runs {
+                xml.push_str(&format!(
+                    "    <testcase name=\"{}\" classname=\"{}\" time=\"{:.3}\">\n",
+                    xml_escape(test_name),
+                    xml_escape(module),
+                    test_run_info.elapsed_time.as_secs_f64()
+                ));
+                xml.push_str("      <properties>\n");
+                xml.push_str(&format!(
+                    "        <property name=\"instructions_executed\" value=\"{}\"/>\n",
+                    test_run_info.instructions_executed
+                ));
+                if let Some(test_failure) = test_failure {
+                    if let Some(abort_code) = test_failure.abort_code() {
+                        xml.push_str(&format!(
+                            "        <property name=\"abort_code\" value=\"{abort_code}\"/>\n"
+                        ));
+                    }
+                    if let Some(location) = test_failure.error_location(&self.test_plan) {
+                        xml.push_str(&format!(
+                            "        <property name=\"location\" value=\"{}\"/>\n",
+                            xml_escape(&location)
+                        ));
+                    }
+                    if let Some(seed) = test_failure.prng_seed {
+                        xml.push_str(&format!(
+                            "        <property name=\"seed\" value=\"{seed}\"/>\n"
+                        ));
+                    }
+                }
+                xml.push_str("      </properties>\n");
+                if let Some(test_failure) = test_failure {
+                    xml.push_str(&format!(
+                        "      <failure message=\"{}\">{}</failure>\n",
+                        xml_escape(test_failure.failure_reason.message()),
+                        xml_escape(&test_failure.render_error_with_color(&self.test_plan, false))
+                    ));
+                }
+                xml.push_str("    </testcase>\n");
+            }
+            xml.push_str("  </testsuite>\n");
+        }
+        xml.push_str("</testsuites>\n");
+        xml
+    }
+
     /// Returns `true` if all tests passed, `false` if there was a test failure/timeout
     pub fn summarize<W: Write>(self, writer: &Mutex<W>) -> Result<bool> {
         let num_failed_tests = self
@@ -639,3 +928,11 @@ impl TestResults {
         Ok(num_failed_tests == 0)
     }
 }
+
+fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
@@ -5,13 +5,20 @@
 use move_command_line_common::testing::{
     add_update_baseline_fix, format_diff, read_env_update_baseline, EXP_EXT,
 };
-use move_unit_test::{self, UnitTestingConfig};
+use move_unit_test::{
+    self,
+    test_reporter::{TestReport, TestReportFormat},
+    UnitTestingConfig,
+};
 use regex::RegexBuilder;
 use std::{
     fs,
     path::{Path, PathBuf},
 };
 
+/// Test sources for which JUnit and JSON reports of the test results are checked as well
+const REPORT_TESTS: &[&str] = &["unexpected_abort"];
+
 fn run_test_with_modifiers(
     unit_test_config: UnitTestingConfig,
     path: &Path,
@@ -37,6 +44,18 @@ fn run_test_impl(path: &Path) -> anyhow::Result<()> {
     std::env::set_var("NO_COLOR", "1");
     let update_baseline = read_env_update_baseline();
     let source_files = vec![path.to_str().unwrap().to_owned()];
+    let report_dir = tempfile::tempdir()?;
+    let reports = if REPORT_TESTS.contains(&path.file_stem().unwrap().to_str().unwrap()) {
+        [TestReportFormat::Junit, TestReportFormat::Json]
+            .into_iter()
+            .map(|format| TestReport {
+                format,
+                path: report_dir.path().join(format.to_string()),
+            })
+            .collect()
+    } else {
+        vec![]
+    };
     let unit_test_config = UnitTestingConfig {
         num_threads: 1,
         gas_limit: Some(1000),
@@ -47,6 +66,7 @@ fn run_test_impl(path: &Path) -> anyhow::Result<()> {
             .collect(),
         report_stacktrace_on_abort: true,
         deterministic_generation: true,
+        reports: reports.clone(),
 
         ..UnitTestingConfig::default_with_bound(None)
     };
@@ -56,9 +76,31 @@ fn run_test_impl(path: &Path) -> anyhow::Result<()> {
         .build()
         .unwrap();
 
+    let mut outputs = vec![];
     for ((buffer, _), exp_path) in run_test_with_modifiers(unit_test_config, path)? {
         let base_output = String::from_utf8(buffer)?;
-        let cleaned_output = regex.replacen(&base_output, 0, r"$1$2");
+        outputs.push((
+            regex.replacen(&base_output, 0, r"$1$2").into_owned(),
+            exp_path,
+        ));
+    }
+    // timings differ between runs
+    let time_regex = RegexBuilder::new(r#"(time="|"wall_time_secs": )[0-9.e-]+"#)
+        .build()
+        .unwrap();
+    for report in reports {
+        // paths of dependencies are absolute
+        let crates_dir = Path::new(env!("CARGO_MANIFEST_DIR")).parent().unwrap();
+        let base_output =
+            fs::read_to_string(&report.path)?.replace(&format!("{}/", crates_dir.display()), "");
+        let cleaned_output = time_regex.replace_all(&base_output, "${1}0");
+        outputs.push((
+            cleaned_output.into_owned(),
+            path.with_extension(format!("{}.{EXP_EXT}", report.format)),
+        ));
+    }
+
+    for (cleaned_output, exp_path) in outputs {
         if update_baseline {
             fs::write(&exp_path, &*cleaned_output)?
         }
@@ -13,4 +13,6 @@ mod invariant_violation_tests;
 mod leak_tests;
 mod loader_tests;
 mod nested_loop_tests;
+#[cfg(feature = "tracing")]
+mod profiler_tests;
 mod return_value_tests;
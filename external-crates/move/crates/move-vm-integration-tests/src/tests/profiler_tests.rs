@@ -0,0 +1,111 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::compiler::{as_module, compile_units, serialize_module_at_max_version};
+use move_core_types::{
+    account_address::AccountAddress, identifier::Identifier, language_storage::ModuleId,
+};
+use move_vm_config::runtime::VMProfilerConfig;
+use move_vm_profiler::GasProfiler;
+use move_vm_runtime::move_vm::MoveVM;
+use move_vm_test_utils::{
+    gas_schedule::{unit_cost_schedule, Gas, GasStatus},
+    InMemoryStorage,
+};
+use move_vm_types::gas::GasMeter;
+use std::collections::BTreeMap;
+
+const TEST_ADDR: AccountAddress = AccountAddress::new([42; AccountAddress::LENGTH]);
+const GAS_BUDGET: u64 = 1_000;
+
+/// Runs `M::run` with profiling enabled and returns the gas used by each instruction, along with
+/// the total gas used.
+fn profile_run(track_bytecode_instructions: bool) -> (BTreeMap<String, BTreeMap<u16, u64>>, u64) {
+    let code = format!(
+        r#"
+        module 0x{}::M {{
+            fun add(a: u64, b: u64): u64 {{
+                a + b
+            }}
+
+            fun run(): u64 {{
+                add(1, 2)
+            }}
+        }}
+    "#,
+        TEST_ADDR
+    );
+
+    let mut units = compile_units(&code).unwrap();
+    let m = as_module(units.pop().unwrap());
+    let mut blob = vec![];
+    serialize_module_at_max_version(&m, &mut blob).unwrap();
+
+    let mut storage = InMemoryStorage::new();
+    let module_id = ModuleId::new(TEST_ADDR, Identifier::new("M").unwrap());
+    storage.publish_or_overwrite_module(module_id.clone(), blob);
+
+    let vm = MoveVM::new(vec![]).unwrap();
+    let mut sess = vm.new_session(&storage);
+
+    let output = tempfile::tempdir().unwrap();
+    let config = VMProfilerConfig {
+        full_path: output.path().join("gas_profile.json"),
+        track_bytecode_instructions,
+        use_long_function_name: false,
+    };
+    let cost_table = unit_cost_schedule();
+    let mut gas = GasStatus::new(&cost_table, Gas::new(GAS_BUDGET));
+    let start_gas: u64 = GasMeter::remaining_gas(&gas).into();
+    gas.set_profiler(GasProfiler::init(
+        &Some(config),
+        "run".to_string(),
+        start_gas,
+    ));
+
+    sess.execute_function_bypass_visibility(
+        &module_id,
+        &Identifier::new("run").unwrap(),
+        vec![],
+        Vec::<Vec<u8>>::new(),
+        &mut gas,
+    )
+    .unwrap();
+
+    let gas_used = start_gas - u64::from(GasMeter::remaining_gas(&gas));
+    let profiler = gas.get_profiler_mut().unwrap();
+    profiler.finish();
+    (profiler.instruction_gas().clone(), gas_used)
+}
+
+fn check_instruction_gas(track_bytecode_instructions: bool) {
+    let (instruction_gas, gas_used) = profile_run(track_bytecode_instructions);
+    let run = &instruction_gas[&format!("0x{}::M::run", TEST_ADDR)];
+    let add = &instruction_gas[&format!("0x{}::M::add", TEST_ADDR)];
+
+    // LdU64, LdU64, Call, Ret
+    assert_eq!(run.keys().copied().collect::<Vec<_>>(), vec![0, 1, 2, 3]);
+    // MoveLoc, MoveLoc, Add, Ret
+    assert_eq!(add.keys().copied().collect::<Vec<_>>(), vec![0, 1, 2, 3]);
+    // each instruction costs 2 units (1 for the instruction and 1 for memory), and the call
+    // is charged for its 2 arguments and the callee
+    assert_eq!(run[&0], 2);
+    assert_eq!(run[&2], 6);
+    assert_eq!(add[&2], 2);
+
+    // all gas used is attributed to an instruction
+    let attributed: u64 = instruction_gas.values().flat_map(|gas| gas.values()).sum();
+    assert_eq!(attributed, gas_used);
+}
+
+#[test]
+fn instruction_gas() {
+    check_instruction_gas(false);
+}
+
+#[test]
+fn instruction_gas_with_bytecode_frames() {
+    // closing the frame of each instruction must not lose the gas charged between that and the
+    // next instruction starting, e.g. for a call
+    check_instruction_gas(true);
+}
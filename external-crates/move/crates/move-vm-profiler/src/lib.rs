@@ -53,6 +53,9 @@ pub struct GasProfiler {
     schema: String,
     shared: Shared,
     profiles: Vec<Profile>,
+    /// Gas used by each instruction, keyed by the long name of its function and its code offset
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    instruction_gas: BTreeMap<String, BTreeMap<u16, u64>>,
 
     #[serde(skip)]
     pub start_gas: u64,
@@ -60,6 +63,9 @@ pub struct GasProfiler {
     pub config: Option<VMProfilerConfig>,
     #[serde(skip)]
     finished: bool,
+    /// The instruction being executed: its function, code offset and the gas remaining before it
+    #[serde(skip)]
+    current_instruction: Option<(String, u16, u64)>,
 }
 
 #[cfg(feature = "tracing")]
@@ -89,9 +95,11 @@ impl GasProfiler {
                 end_value: 0,
                 events: vec![],
             }],
+            instruction_gas: BTreeMap::new(),
             start_gas,
             config: config.clone(),
             finished: false,
+            current_instruction: None,
         };
         profile_open_frame_impl!(
             Some(&mut prof),
@@ -167,6 +175,7 @@ impl GasProfiler {
         if self.config.is_none() || self.start_gas == 0 {
             return;
         }
+        self.charge_instruction(gas_end);
         let frame_idx = self.add_frame(metadata.clone(), frame_name, metadata);
         let start = self.start_gas();
 
@@ -178,6 +187,41 @@ impl GasProfiler {
         self.profiles[0].end_value = start - gas_end;
     }
 
+    /// Starts attributing gas to the instruction at `pc` in `function`, attributing the gas used
+    /// since the previous instruction started to that instruction. The gas used by an instruction
+    /// includes that of the native functions it calls.
+    #[cfg(feature = "tracing")]
+    pub fn start_instruction(&mut self, function: String, pc: u16, gas_start: u64) {
+        if self.config.is_none() || self.start_gas == 0 {
+            return;
+        }
+        self.charge_instruction(gas_start);
+        self.current_instruction = Some((function, pc, gas_start));
+    }
+
+    /// Attributes the gas used since the current instruction started, or was last charged, to it.
+    /// The instruction stays current until the next one starts, so that gas charged after a frame
+    /// closes, e.g. for the call itself or for dropping the frame, is attributed to it as well.
+    #[cfg(feature = "tracing")]
+    fn charge_instruction(&mut self, gas_end: u64) {
+        if let Some((function, pc, gas_start)) = &mut self.current_instruction {
+            *self
+                .instruction_gas
+                .entry(function.clone())
+                .or_default()
+                .entry(*pc)
+                .or_default() += gas_start.saturating_sub(gas_end);
+            *gas_start = gas_end;
+        }
+    }
+
+    /// Gas used by each instruction so far, keyed by the long name of its function and its code
+    /// offset
+    #[cfg(feature = "tracing")]
+    pub fn instruction_gas(&self) -> &BTreeMap<String, BTreeMap<u16, u64>> {
+        &self.instruction_gas
+    }
+
     #[cfg(feature = "tracing")]
     pub fn to_file(&self) {
         use std::ffi::{OsStr, OsString};
@@ -340,6 +384,21 @@ macro_rules! profile_close_instr {
     };
 }
 
+#[macro_export]
+macro_rules! profile_instr_gas {
+    ($gas_meter:expr, $function_name:expr, $pc:expr) => {
+        #[cfg(feature = "tracing")]
+        {
+            let gas_rem = $gas_meter.remaining_gas().into();
+            if let Some(profiler) = $gas_meter.get_profiler_mut() {
+                if profiler.config.is_some() {
+                    profiler.start_instruction($function_name, $pc, gas_rem)
+                }
+            }
+        }
+    };
+}
+
 #[macro_export]
 macro_rules! profile_dump_file {
     ($profiler:expr) => {
@@ -84,6 +84,11 @@ macro_rules! open_instruction {
             $tracer.as_mut().map(|tracer| {
                 tracer.open_instruction($frame, $interp, $loader, $gas_meter.remaining_gas().into())
             });
+            move_vm_profiler::profile_instr_gas!(
+                $gas_meter,
+                $frame.function.pretty_string(),
+                $frame.pc
+            );
             move_vm_profiler::profile_open_instr!($gas_meter, format!("{:?}", $instruction));
         }
     };
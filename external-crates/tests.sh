@@ -5,3 +5,4 @@ echo "Excluding prover Move tests"
 cargo nextest run -E '!package(move-prover) and !test(prove) and !test(run_all::simple_build_with_docs/args.txt) and !test(run_test::nested_deps_bad_parent/Move.toml)' --workspace --no-fail-fast
 echo "Running tracing-specific tests"
 cargo nextest run -p move-cli --features tracing
+cargo nextest run -p move-vm-integration-tests --features tracing
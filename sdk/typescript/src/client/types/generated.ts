@@ -163,6 +163,8 @@ export interface DryRunTransactionBlockResponse {
 	events: SuiEvent[];
 	input: TransactionBlockData;
 	objectChanges: SuiObjectChange[];
+	/** The BCS serialized objects that the transaction would create, mutate or unwrap. */
+	rawWrittenObjects?: number[][];
 }
 export interface DynamicFieldInfo {
 	bcsName: string;